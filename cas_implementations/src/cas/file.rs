@@ -6,7 +6,7 @@ use holochain_core_types::{
     error::HolochainError,
 };
 use std::{
-    fs::{create_dir_all, read_to_string, write},
+    fs::{create_dir_all, read_to_string, rename, write},
     path::{Path, MAIN_SEPARATOR},
     sync::{Arc, RwLock},
 };
@@ -51,10 +51,12 @@ impl ContentAddressableStorage for FilesystemStorage {
         // @see https://github.com/holochain/holochain-rust/issues/248
         create_dir_all(&self.dir_path)?;
 
-        write(
-            self.address_to_path(&content.address()),
-            content.content().to_string(),
-        )?;
+        // Write to a temporary file first so that content which gets overwritten,
+        // like persisted state snapshots, is never left half written
+        let path = self.address_to_path(&content.address());
+        let tmp_path = format!("{}.tmp", path);
+        write(&tmp_path, content.content().to_string())?;
+        rename(tmp_path, path)?;
 
         Ok(())
     }
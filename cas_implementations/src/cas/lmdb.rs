@@ -0,0 +1,181 @@
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    error::HolochainError,
+};
+use lmdb::{
+    Database, DatabaseFlags, Environment, EnvironmentFlags, Error as LmdbError, Transaction,
+    WriteFlags,
+};
+use std::{path::Path, sync::Arc};
+use uuid::Uuid;
+
+const CAS_DATABASE: &str = "cas";
+
+/// LMDB reserves the whole map as address space up front but the file on disk only grows
+/// as content gets added. 16 GiB should leave plenty of headroom for a single source chain.
+pub const DEFAULT_MAP_SIZE: usize = 16 * 1024 * 1024 * 1024;
+
+/// ContentAddressableStorage backed by an embedded LMDB database.
+/// All content lives in a single memory mapped file. Writes happen in transactions so
+/// the store is crash-safe and any number of readers can fetch concurrently without
+/// blocking each other or the (single) writer.
+#[derive(Clone, Debug)]
+pub struct LmdbStorage {
+    /// path to the database file content will be saved to
+    file_path: String,
+    id: Uuid,
+    env: Arc<Environment>,
+    db: Database,
+}
+
+impl PartialEq for LmdbStorage {
+    fn eq(&self, other: &LmdbStorage) -> bool {
+        self.id == other.id
+    }
+}
+
+/// lmdb::Error is foreign to this crate as is HolochainError, so we can't implement From
+pub(crate) fn lmdb_to_holochain_error(error: LmdbError) -> HolochainError {
+    HolochainError::IoError(format!("LMDB error: {}", error))
+}
+
+/// Opens (or creates) the LMDB environment living in the single file at the given path.
+/// LMDB environments must not be opened twice in the same process, so clones of the
+/// storage share the returned environment instead of opening it again.
+pub(crate) fn open_environment(
+    file_path: &str,
+    max_dbs: u32,
+    map_size: usize,
+) -> Result<Environment, HolochainError> {
+    Environment::new()
+        .set_flags(EnvironmentFlags::NO_SUB_DIR)
+        .set_max_dbs(max_dbs)
+        .set_map_size(map_size)
+        .open(Path::new(file_path))
+        .map_err(lmdb_to_holochain_error)
+}
+
+impl LmdbStorage {
+    pub fn new(file_path: &str) -> Result<LmdbStorage, HolochainError> {
+        LmdbStorage::with_map_size(file_path, DEFAULT_MAP_SIZE)
+    }
+
+    /// Same as new() but with an explicit upper bound for the size of the database file.
+    pub fn with_map_size(file_path: &str, map_size: usize) -> Result<LmdbStorage, HolochainError> {
+        let env = open_environment(file_path, 1, map_size)?;
+        let db = env
+            .create_db(Some(CAS_DATABASE), DatabaseFlags::empty())
+            .map_err(lmdb_to_holochain_error)?;
+        Ok(LmdbStorage {
+            file_path: String::from(file_path),
+            id: Uuid::new_v4(),
+            env: Arc::new(env),
+            db,
+        })
+    }
+
+    pub fn file_path(&self) -> String {
+        self.file_path.clone()
+    }
+}
+
+impl ContentAddressableStorage for LmdbStorage {
+    fn add(&mut self, content: &AddressableContent) -> Result<(), HolochainError> {
+        let mut txn = self.env.begin_rw_txn().map_err(lmdb_to_holochain_error)?;
+        txn.put(
+            self.db,
+            &content.address().to_string(),
+            &content.content().to_string(),
+            WriteFlags::empty(),
+        )
+        .map_err(lmdb_to_holochain_error)?;
+        txn.commit().map_err(lmdb_to_holochain_error)
+    }
+
+    fn contains(&self, address: &Address) -> Result<bool, HolochainError> {
+        Ok(self.fetch(address)?.is_some())
+    }
+
+    fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
+        let txn = self.env.begin_ro_txn().map_err(lmdb_to_holochain_error)?;
+        match txn.get(self.db, &address.to_string()) {
+            Ok(bytes) => {
+                let content = String::from_utf8(bytes.to_vec()).map_err(|_| {
+                    HolochainError::SerializationError(format!(
+                        "Content stored for {} is not valid UTF-8",
+                        address
+                    ))
+                })?;
+                Ok(Some(content.into()))
+            }
+            Err(LmdbError::NotFound) => Ok(None),
+            Err(error) => Err(lmdb_to_holochain_error(error)),
+        }
+    }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::{tempdir, TempDir};
+    use crate::cas::lmdb::LmdbStorage;
+    use holochain_core_types::{
+        cas::{
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
+        },
+        json::RawString,
+    };
+
+    /// small map size so tests don't reserve gigabytes of address space
+    const TEST_MAP_SIZE: usize = 10 * 1024 * 1024;
+
+    pub fn test_lmdb_cas() -> (LmdbStorage, TempDir) {
+        let dir = tempdir().expect("Could not create a tempdir for CAS testing");
+        let file_path = dir.path().join("cas.lmdb");
+        (
+            LmdbStorage::with_map_size(&file_path.to_string_lossy(), TEST_MAP_SIZE).unwrap(),
+            dir,
+        )
+    }
+
+    #[test]
+    /// show that content of different types can round trip through the same storage
+    fn lmdb_content_round_trip_test() {
+        let (cas, _dir) = test_lmdb_cas();
+        let test_suite = StorageTestSuite::new(cas);
+        test_suite.round_trip_test::<ExampleAddressableContent, OtherExampleAddressableContent>(
+            RawString::from("foo").into(),
+            RawString::from("bar").into(),
+        );
+    }
+
+    #[test]
+    /// show that content survives closing and reopening the database file
+    fn lmdb_content_persists_test() {
+        let (mut cas, dir) = test_lmdb_cas();
+        let file_path = cas.file_path();
+        let content = ExampleAddressableContent::try_from_content(&RawString::from("foo").into())
+            .expect("could not create AddressableContent");
+        cas.add(&content).expect("could not add content");
+        drop(cas);
+
+        let reopened = LmdbStorage::with_map_size(&file_path, TEST_MAP_SIZE).unwrap();
+        assert_eq!(Ok(true), reopened.contains(&content.address()));
+        assert_eq!(
+            Ok(Some(content.content())),
+            reopened.fetch(&content.address())
+        );
+        drop(dir);
+    }
+}
@@ -226,4 +226,14 @@ pub mod tests {
         EavTestSuite::test_many_to_one::<ExampleAddressableContent, EavFileStorage>(eav_storage)
     }
 
+    #[test]
+    fn file_eav_attribute_prefix() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let temp_path = String::from(temp.path().to_str().expect("temp dir could not be string"));
+        let eav_storage = EavFileStorage::new(temp_path).unwrap();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavFileStorage>(
+            eav_storage,
+        )
+    }
+
 }
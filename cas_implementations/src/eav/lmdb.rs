@@ -0,0 +1,294 @@
+use crate::cas::lmdb::{lmdb_to_holochain_error, open_environment, DEFAULT_MAP_SIZE};
+use holochain_core_types::{
+    eav::{Attribute, Entity, EntityAttributeValue, EntityAttributeValueStorage, Value},
+    error::{HcResult, HolochainError},
+};
+use lmdb::{Cursor, Database, DatabaseFlags, Environment, Transaction, WriteFlags};
+use std::{collections::HashSet, str, sync::Arc};
+use uuid::Uuid;
+
+const ENTITY_INDEX: &str = "eav_by_entity";
+const ATTRIBUTE_INDEX: &str = "eav_by_attribute";
+const VALUE_INDEX: &str = "eav_by_value";
+
+/// Separates the parts of an index key.
+/// Addresses never contain it, so keys can always be split unambiguously
+/// from the address ends, even if an attribute contained it.
+const SEPARATOR: char = '\u{0}';
+
+/// The three orderings the triples are indexed in.
+/// Every index key holds the full triple so that a fetch never has to touch more than
+/// the one index whose ordering matches the given constraints.
+#[derive(Clone, Copy, Debug)]
+enum Index {
+    /// entity, attribute, value
+    Entity,
+    /// attribute, entity, value
+    Attribute,
+    /// value, attribute, entity
+    Value,
+}
+
+impl Index {
+    fn key(&self, eav: &EntityAttributeValue) -> String {
+        let (first, second, third) = match self {
+            Index::Entity => (
+                eav.entity().to_string(),
+                eav.attribute(),
+                eav.value().to_string(),
+            ),
+            Index::Attribute => (
+                eav.attribute(),
+                eav.entity().to_string(),
+                eav.value().to_string(),
+            ),
+            Index::Value => (
+                eav.value().to_string(),
+                eav.attribute(),
+                eav.entity().to_string(),
+            ),
+        };
+        format!("{}{}{}{}{}", first, SEPARATOR, second, SEPARATOR, third)
+    }
+
+    fn eav_from_key(&self, key: &[u8]) -> HcResult<EntityAttributeValue> {
+        let key = str::from_utf8(key).map_err(|_| {
+            HolochainError::SerializationError("EAV index key is not valid UTF-8".to_string())
+        })?;
+        let malformed =
+            || HolochainError::SerializationError(format!("Malformed EAV index key: {:?}", key));
+        let (entity, attribute, value) = match self {
+            Index::Entity => {
+                let (entity, rest) = split_first(key).ok_or_else(malformed)?;
+                let (attribute, value) = split_last(rest).ok_or_else(malformed)?;
+                (entity, attribute, value)
+            }
+            Index::Attribute => {
+                let (rest, value) = split_last(key).ok_or_else(malformed)?;
+                let (attribute, entity) = split_last(rest).ok_or_else(malformed)?;
+                (entity, attribute, value)
+            }
+            Index::Value => {
+                let (value, rest) = split_first(key).ok_or_else(malformed)?;
+                let (attribute, entity) = split_last(rest).ok_or_else(malformed)?;
+                (entity, attribute, value)
+            }
+        };
+        EntityAttributeValue::new(
+            &Entity::from(entity),
+            &attribute.to_string(),
+            &Value::from(value),
+        )
+    }
+}
+
+fn split_first(key: &str) -> Option<(&str, &str)> {
+    key.find(SEPARATOR)
+        .map(|index| (&key[..index], &key[index + 1..]))
+}
+
+fn split_last(key: &str) -> Option<(&str, &str)> {
+    key.rfind(SEPARATOR)
+        .map(|index| (&key[..index], &key[index + 1..]))
+}
+
+/// EntityAttributeValueStorage backed by an embedded LMDB database.
+/// Every triple is stored in three ordered indexes (E-A-V, A-E-V and V-A-E) so that
+/// any combination of constraints, as well as attribute prefixes, can be answered with
+/// a single range scan instead of reading all stored triples.
+#[derive(Clone, Debug)]
+pub struct EavLmdbStorage {
+    /// path to the database file the indexes will be saved to
+    file_path: String,
+    id: Uuid,
+    env: Arc<Environment>,
+    entity_index: Database,
+    attribute_index: Database,
+    value_index: Database,
+}
+
+impl PartialEq for EavLmdbStorage {
+    fn eq(&self, other: &EavLmdbStorage) -> bool {
+        self.id == other.id
+    }
+}
+
+impl EavLmdbStorage {
+    pub fn new(file_path: &str) -> HcResult<EavLmdbStorage> {
+        EavLmdbStorage::with_map_size(file_path, DEFAULT_MAP_SIZE)
+    }
+
+    /// Same as new() but with an explicit upper bound for the size of the database file.
+    pub fn with_map_size(file_path: &str, map_size: usize) -> HcResult<EavLmdbStorage> {
+        let env = open_environment(file_path, 3, map_size)?;
+        let create_index = |name| {
+            env.create_db(Some(name), DatabaseFlags::empty())
+                .map_err(lmdb_to_holochain_error)
+        };
+        let entity_index = create_index(ENTITY_INDEX)?;
+        let attribute_index = create_index(ATTRIBUTE_INDEX)?;
+        let value_index = create_index(VALUE_INDEX)?;
+        Ok(EavLmdbStorage {
+            file_path: String::from(file_path),
+            id: Uuid::new_v4(),
+            env: Arc::new(env),
+            entity_index,
+            attribute_index,
+            value_index,
+        })
+    }
+
+    pub fn file_path(&self) -> String {
+        self.file_path.clone()
+    }
+
+    fn database(&self, index: Index) -> Database {
+        match index {
+            Index::Entity => self.entity_index,
+            Index::Attribute => self.attribute_index,
+            Index::Value => self.value_index,
+        }
+    }
+
+    /// Returns all triples of the given index whose key starts with prefix.
+    fn scan(&self, index: Index, prefix: &str) -> HcResult<HashSet<EntityAttributeValue>> {
+        let txn = self.env.begin_ro_txn().map_err(lmdb_to_holochain_error)?;
+        let mut cursor = txn
+            .open_ro_cursor(self.database(index))
+            .map_err(lmdb_to_holochain_error)?;
+        let items = if prefix.is_empty() {
+            cursor.iter_start()
+        } else {
+            cursor.iter_from(prefix)
+        };
+        let mut result = HashSet::new();
+        for item in items {
+            let (key, _) = item.map_err(lmdb_to_holochain_error)?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            result.insert(index.eav_from_key(key)?);
+        }
+        Ok(result)
+    }
+}
+
+/// Builds the key prefix that selects all triples starting with the given (complete) parts.
+fn exact_prefix(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| format!("{}{}", part, SEPARATOR))
+        .collect()
+}
+
+impl EntityAttributeValueStorage for EavLmdbStorage {
+    fn add_eav(&mut self, eav: &EntityAttributeValue) -> Result<(), HolochainError> {
+        let mut txn = self.env.begin_rw_txn().map_err(lmdb_to_holochain_error)?;
+        for index in &[Index::Entity, Index::Attribute, Index::Value] {
+            txn.put(
+                self.database(*index),
+                &index.key(eav),
+                &"",
+                WriteFlags::empty(),
+            )
+            .map_err(lmdb_to_holochain_error)?;
+        }
+        txn.commit().map_err(lmdb_to_holochain_error)
+    }
+
+    fn fetch_eav(
+        &self,
+        entity: Option<Entity>,
+        attribute: Option<Attribute>,
+        value: Option<Value>,
+    ) -> Result<HashSet<EntityAttributeValue>, HolochainError> {
+        let (index, prefix) = match (&entity, &attribute, &value) {
+            (Some(e), Some(a), _) => (Index::Entity, exact_prefix(&[e.to_string(), a.clone()])),
+            (Some(e), None, _) => (Index::Entity, exact_prefix(&[e.to_string()])),
+            (None, Some(a), Some(v)) => (Index::Value, exact_prefix(&[v.to_string(), a.clone()])),
+            (None, None, Some(v)) => (Index::Value, exact_prefix(&[v.to_string()])),
+            (None, Some(a), None) => (Index::Attribute, exact_prefix(&[a.clone()])),
+            (None, None, None) => (Index::Entity, String::new()),
+        };
+        Ok(self
+            .scan(index, &prefix)?
+            .into_iter()
+            .filter(|e| EntityAttributeValue::filter_on_eav(&e.value(), value.as_ref()))
+            .collect())
+    }
+
+    fn fetch_eav_with_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        value: Option<Value>,
+    ) -> Result<HashSet<EntityAttributeValue>, HolochainError> {
+        let (index, prefix) = match (&entity, &value) {
+            (Some(e), _) => (Index::Entity, exact_prefix(&[e.to_string()])),
+            (None, Some(v)) => (Index::Value, exact_prefix(&[v.to_string()])),
+            (None, None) => (Index::Attribute, String::new()),
+        };
+        Ok(self
+            .scan(index, &format!("{}{}", prefix, attribute_prefix))?
+            .into_iter()
+            .filter(|e| EntityAttributeValue::filter_on_eav(&e.value(), value.as_ref()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    use self::tempfile::{tempdir, TempDir};
+    use eav::lmdb::EavLmdbStorage;
+    use holochain_core_types::{
+        cas::{
+            content::{AddressableContent, ExampleAddressableContent},
+            storage::EavTestSuite,
+        },
+        json::RawString,
+    };
+
+    /// small map size so tests don't reserve gigabytes of address space
+    const TEST_MAP_SIZE: usize = 10 * 1024 * 1024;
+
+    fn test_lmdb_eav() -> (EavLmdbStorage, TempDir) {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let file_path = temp.path().join("eav.lmdb");
+        (
+            EavLmdbStorage::with_map_size(&file_path.to_string_lossy(), TEST_MAP_SIZE).unwrap(),
+            temp,
+        )
+    }
+
+    #[test]
+    fn lmdb_eav_round_trip() {
+        let (eav_storage, _temp) = test_lmdb_eav();
+        let entity_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let attribute = "favourite-color".to_string();
+        let value_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("blue").into()).unwrap();
+        EavTestSuite::test_round_trip(eav_storage, entity_content, attribute, value_content)
+    }
+
+    #[test]
+    fn lmdb_eav_one_to_many() {
+        let (eav_storage, _temp) = test_lmdb_eav();
+        EavTestSuite::test_one_to_many::<ExampleAddressableContent, EavLmdbStorage>(eav_storage)
+    }
+
+    #[test]
+    fn lmdb_eav_many_to_one() {
+        let (eav_storage, _temp) = test_lmdb_eav();
+        EavTestSuite::test_many_to_one::<ExampleAddressableContent, EavLmdbStorage>(eav_storage)
+    }
+
+    #[test]
+    fn lmdb_eav_attribute_prefix() {
+        let (eav_storage, _temp) = test_lmdb_eav();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavLmdbStorage>(
+            eav_storage,
+        )
+    }
+}
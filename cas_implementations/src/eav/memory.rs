@@ -91,4 +91,12 @@ pub mod tests {
         EavTestSuite::test_many_to_one::<ExampleAddressableContent, EavMemoryStorage>(eav_storage)
     }
 
+    #[test]
+    fn memory_eav_attribute_prefix() {
+        let eav_storage = EavMemoryStorage::new();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavMemoryStorage>(
+            eav_storage,
+        )
+    }
+
 }
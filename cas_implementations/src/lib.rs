@@ -6,6 +6,7 @@
 //! which are defined but not implemented in the core_types crate.
 
 extern crate holochain_core_types;
+extern crate lmdb;
 extern crate snowflake;
 extern crate walkdir;
 
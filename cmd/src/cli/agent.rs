@@ -1,5 +1,259 @@
 use crate::error::DefaultResult;
+use colored::*;
+use holochain_container_api::container::PASSPHRASE_ENV_VAR;
+use holochain_core::agent::keystore::{Keystore, KeystoreFile};
+use holochain_core_types::error::HcResult;
+use rpassword;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
 
-pub fn agent() -> DefaultResult<()> {
-    unimplemented!()
+pub const KEYSTORE_FILE_EXTENSION: &str = "keystore";
+
+#[derive(StructOpt)]
+pub enum AgentCommand {
+    #[structopt(
+        name = "generate",
+        alias = "g",
+        about = "Generates a new agent keystore file and prints its public address"
+    )]
+    Generate {
+        #[structopt(
+            long,
+            short,
+            default_value = "agent.keystore",
+            help = "The file the new keystore gets written to",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+    },
+    #[structopt(
+        name = "list",
+        alias = "l",
+        about = "Lists the keystore files in a directory together with their public addresses"
+    )]
+    List {
+        #[structopt(default_value = ".", parse(from_os_str))]
+        dir: PathBuf,
+    },
+    #[structopt(
+        name = "inspect",
+        about = "Decrypts a keystore file and checks that it holds the keys of its public address"
+    )]
+    Inspect {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    #[structopt(
+        name = "rotate",
+        about = "Replaces the keys of a keystore file with new ones, keeping a backup of the old file"
+    )]
+    Rotate {
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+}
+
+pub fn agent(command: AgentCommand) -> DefaultResult<()> {
+    match command {
+        AgentCommand::Generate { path } => keygen(&path),
+        AgentCommand::List { dir } => {
+            for (path, file) in list_keystores(&dir)? {
+                match file {
+                    Ok(file) => println!("{}\t{}", file.public_address.green(), path.display()),
+                    Err(error) => println!(
+                        "{}\t{}: {}",
+                        "Could not be read".red(),
+                        path.display(),
+                        error
+                    ),
+                }
+            }
+            Ok(())
+        }
+        AgentCommand::Inspect { path } => {
+            let file = KeystoreFile::load(&path)?;
+            println!("Keystore file format version: {}", file.version);
+            println!("Public address: {}", file.public_address.green());
+            let passphrase = read_passphrase("Passphrase: ")?;
+            Keystore::decrypt(&file, &passphrase)?;
+            println!("Passphrase is correct and the keys match the public address.");
+            Ok(())
+        }
+        AgentCommand::Rotate { path } => {
+            let passphrase = read_passphrase("Passphrase: ")?;
+            let (old_address, backup_path) = rotate_keystore(&path, &passphrase)?;
+            println!(
+                "Old keys of {} have been moved to {}",
+                old_address,
+                backup_path.display()
+            );
+            println!(
+                "New public address: {}",
+                KeystoreFile::load(&path)?.public_address.green()
+            );
+            println!("Update the public_address of this agent in your container configuration.");
+            Ok(())
+        }
+    }
+}
+
+/// Generates a new agent keystore file at the given path and prints its public address
+pub fn keygen(path: &Path) -> DefaultResult<()> {
+    let passphrase = read_new_passphrase()?;
+    let address = generate_keystore(path, &passphrase)?;
+    println!("Wrote agent keystore file to {}", path.display());
+    println!("Public address: {}", address.green());
+    Ok(())
+}
+
+/// Creates a keystore with new random keys and writes it to path, never overwriting
+/// an existing file. Returns the public address of the new keys.
+fn generate_keystore(path: &Path, passphrase: &str) -> DefaultResult<String> {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    let mut keystore = Keystore::new_random();
+    keystore.save(path, passphrase)?;
+    Ok(keystore.address().to_string())
+}
+
+/// Reads all keystore files (files with the KEYSTORE_FILE_EXTENSION) in the given directory.
+/// Files that can't be read are listed together with the error instead.
+fn list_keystores(dir: &Path) -> DefaultResult<Vec<(PathBuf, HcResult<KeystoreFile>)>> {
+    let mut keystores = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path.extension().and_then(|extension| extension.to_str())
+                == Some(KEYSTORE_FILE_EXTENSION)
+        {
+            let file = KeystoreFile::load(&path);
+            keystores.push((path, file));
+        }
+    }
+    keystores.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(keystores)
+}
+
+/// Replaces the keystore at path with new random keys encrypted with the same passphrase.
+/// The old file is only moved aside after it got decrypted successfully, so nobody who
+/// doesn't know the passphrase can rotate the keys, and after the new keystore got written
+/// next to it, so there is a usable keystore at path whenever rotating fails.
+/// Returns the old public address and the path of the backup.
+fn rotate_keystore(path: &Path, passphrase: &str) -> DefaultResult<(String, PathBuf)> {
+    let old_address = Keystore::load(path, passphrase)?.address().to_string();
+    let backup_path = PathBuf::from(format!("{}.{}.old", path.display(), old_address));
+    if backup_path.exists() {
+        bail!("backup file {} already exists", backup_path.display());
+    }
+    let new_path = PathBuf::from(format!("{}.new", path.display()));
+    generate_keystore(&new_path, passphrase)?;
+    fs::rename(path, &backup_path).map_err(|error| {
+        fs::remove_file(&new_path).unwrap_or(());
+        error
+    })?;
+    fs::rename(&new_path, path).map_err(|error| {
+        fs::rename(&backup_path, path).unwrap_or(());
+        error
+    })?;
+    Ok((old_address, backup_path))
+}
+
+/// Takes the passphrase from the same environment variable the container reads it from
+/// and asks for it otherwise
+fn read_passphrase(prompt: &str) -> DefaultResult<String> {
+    match env::var(PASSPHRASE_ENV_VAR) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => Ok(rpassword::prompt_password_stdout(prompt)?),
+    }
+}
+
+fn read_new_passphrase() -> DefaultResult<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+    let passphrase = rpassword::prompt_password_stdout("New passphrase: ")?;
+    let repeated = rpassword::prompt_password_stdout("Repeat passphrase: ")?;
+    ensure!(passphrase == repeated, "passphrases don't match");
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn generate_and_list_keystores_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("agent.keystore");
+        let address = generate_keystore(&path, "passphrase").unwrap();
+
+        assert!(generate_keystore(&path, "passphrase").is_err());
+        assert_eq!(
+            Keystore::load(&path, "passphrase")
+                .unwrap()
+                .address()
+                .to_string(),
+            address
+        );
+
+        fs::write(dir.path().join("README.md"), "not a keystore").unwrap();
+        let corrupt_path = dir.path().join("corrupt.keystore");
+        fs::write(&corrupt_path, "{").unwrap();
+        let keystores = list_keystores(dir.path()).unwrap();
+        assert_eq!(keystores.len(), 2);
+        assert_eq!(keystores[0].0, path);
+        assert_eq!(keystores[0].1.as_ref().unwrap().public_address, address);
+        assert_eq!(keystores[1].0, corrupt_path);
+        assert!(keystores[1].1.is_err());
+    }
+
+    #[test]
+    fn rotate_keystore_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("agent.keystore");
+        let address = generate_keystore(&path, "passphrase").unwrap();
+
+        assert!(rotate_keystore(&path, "wrong passphrase").is_err());
+
+        let (old_address, backup_path) = rotate_keystore(&path, "passphrase").unwrap();
+        assert_eq!(old_address, address);
+        assert_eq!(
+            Keystore::load(&backup_path, "passphrase")
+                .unwrap()
+                .address()
+                .to_string(),
+            address
+        );
+        assert_ne!(
+            Keystore::load(&path, "passphrase")
+                .unwrap()
+                .address()
+                .to_string(),
+            address
+        );
+    }
+
+    #[test]
+    fn rotate_keystore_keeps_old_keys_if_new_ones_can_not_be_written_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("agent.keystore");
+        let address = generate_keystore(&path, "passphrase").unwrap();
+        // something is in the way of the new keystore
+        fs::create_dir(dir.path().join("agent.keystore.new")).unwrap();
+
+        assert!(rotate_keystore(&path, "passphrase").is_err());
+        assert_eq!(
+            Keystore::load(&path, "passphrase")
+                .unwrap()
+                .address()
+                .to_string(),
+            address
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
 }
@@ -0,0 +1,313 @@
+use crate::error::DefaultResult;
+use colored::*;
+use holochain_cas_implementations::cas::{file::FilesystemStorage, lmdb::LmdbStorage};
+use holochain_core::{
+    agent::{chain_archive::ChainArchive, chain_store::ChainStore},
+    persister::SimplePersister,
+};
+use holochain_core_types::{
+    cas::{content::AddressableContent, storage::ContentAddressableStorage},
+    entry::{entry_type::EntryType, Entry},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub enum ChainCommand {
+    #[structopt(
+        name = "verify",
+        about = "Checks the source chain of an instance's storage for missing or corrupt headers and entries"
+    )]
+    Verify {
+        #[structopt(
+            help = "The storage path of the instance, as given in its storage configuration",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+        #[structopt(
+            long,
+            default_value = "file",
+            help = "The storage type of the instance: file or lmdb"
+        )]
+        storage: String,
+        #[structopt(
+            long,
+            help = "Rewrites the chain headers so that every header links to the previous one of its type"
+        )]
+        repair: bool,
+    },
+    #[structopt(
+        name = "export",
+        about = "Writes the source chain of an instance's storage to an archive file"
+    )]
+    Export {
+        #[structopt(
+            help = "The storage path of the instance, as given in its storage configuration",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+        #[structopt(help = "The archive file to write", parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(
+            long,
+            default_value = "file",
+            help = "The storage type of the instance: file or lmdb"
+        )]
+        storage: String,
+    },
+    #[structopt(
+        name = "import",
+        about = "Verifies an archive file and makes its source chain the one of an instance's storage"
+    )]
+    Import {
+        #[structopt(help = "The archive file to read", parse(from_os_str))]
+        file: PathBuf,
+        #[structopt(
+            help = "The storage path of the instance, as given in its storage configuration",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+        #[structopt(
+            long,
+            default_value = "file",
+            help = "The storage type of the instance: file or lmdb"
+        )]
+        storage: String,
+    },
+}
+
+pub fn chain(command: ChainCommand) -> DefaultResult<()> {
+    match command {
+        ChainCommand::Verify {
+            path,
+            storage,
+            repair,
+        } => verify(&open_storage(&path, &storage)?, repair),
+        ChainCommand::Export {
+            path,
+            file,
+            storage,
+        } => export(&open_storage(&path, &storage)?, &file),
+        ChainCommand::Import {
+            file,
+            path,
+            storage,
+        } => import(&file, &open_storage(&path, &storage)?),
+    }
+}
+
+/// Opens the CAS of an instance the same way the container does for its storage configuration
+fn open_storage(
+    path: &Path,
+    storage: &str,
+) -> DefaultResult<Arc<RwLock<ContentAddressableStorage>>> {
+    Ok(match storage {
+        "file" => Arc::new(RwLock::new(FilesystemStorage::new(
+            &path.join("cas").to_string_lossy(),
+        )?)),
+        "lmdb" => {
+            fs::create_dir_all(path)?;
+            Arc::new(RwLock::new(LmdbStorage::new(
+                &path.join("cas.lmdb").to_string_lossy(),
+            )?))
+        }
+        _ => bail!("unknown storage type {}, use file or lmdb", storage),
+    })
+}
+
+/// Prints every inconsistency of the chain the persisted state points to.
+/// With repair, the same-type links get rebuilt first. Fails if inconsistencies remain.
+fn verify(storage: &Arc<RwLock<ContentAddressableStorage>>, repair: bool) -> DefaultResult<()> {
+    let mut persister = SimplePersister::new(storage.clone());
+    let chain_store = ChainStore::new(storage.clone());
+    let mut top_chain_header = persister.load_top_chain_header()?;
+    if top_chain_header.is_none() {
+        bail!("no persisted source chain found in this storage");
+    }
+
+    if repair {
+        let rebuilt_top = chain_store.rebuild_type_links(&top_chain_header)?;
+        if rebuilt_top != top_chain_header {
+            if let Some(ref header) = rebuilt_top {
+                persister.save_top_chain_header(header.clone())?;
+                println!("Rebuilt the chain, its new top is {}", header.address());
+            }
+            top_chain_header = rebuilt_top;
+        }
+    }
+
+    let inconsistencies = chain_store.verify(&top_chain_header)?;
+    for inconsistency in inconsistencies.iter() {
+        println!("{}", inconsistency.to_string().red());
+    }
+    ensure!(
+        inconsistencies.is_empty(),
+        "found {} inconsistencies in the source chain",
+        inconsistencies.len()
+    );
+    println!("{}", "Source chain is intact.".green());
+    Ok(())
+}
+
+fn export(storage: &Arc<RwLock<ContentAddressableStorage>>, file: &Path) -> DefaultResult<()> {
+    let top_chain_header = SimplePersister::new(storage.clone()).load_top_chain_header()?;
+    ensure!(
+        top_chain_header.is_some(),
+        "no persisted source chain found in this storage"
+    );
+    let archive = ChainArchive::export(&ChainStore::new(storage.clone()), &top_chain_header)?;
+    archive.save(file)?;
+    println!(
+        "Exported {} chain headers to {}",
+        archive.items().len(),
+        file.display()
+    );
+    Ok(())
+}
+
+/// Imports the chain of the archive and points the persisted state to it.
+/// A chain that is already in the storage can only be replaced by one of the same agent and DNA.
+fn import(file: &Path, storage: &Arc<RwLock<ContentAddressableStorage>>) -> DefaultResult<()> {
+    let archive = ChainArchive::load(file)?;
+    let mut persister = SimplePersister::new(storage.clone());
+    let existing_top = persister.load_top_chain_header()?;
+    if existing_top.is_some() {
+        let existing = ChainArchive::export(&ChainStore::new(storage.clone()), &existing_top)?;
+        let own_entries: Vec<Entry> = vec![EntryType::Dna, EntryType::AgentId]
+            .into_iter()
+            .filter_map(|entry_type| {
+                existing
+                    .find_entry(|entry| entry.entry_type() == entry_type)
+                    .cloned()
+            })
+            .collect();
+        archive.check_owner(&own_entries)?;
+    }
+    match archive.import(storage)? {
+        Some(top_chain_header) => {
+            persister.save_top_chain_header(top_chain_header.clone())?;
+            println!(
+                "Imported {} chain headers, the top is now {}",
+                archive.items().len(),
+                top_chain_header.address()
+            );
+        }
+        None => println!("The archive holds an empty chain, nothing to import."),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core::agent::keystore::Keystore;
+    use holochain_core_types::{
+        chain_header::{test_chain_header, ChainHeader},
+        entry::{test_entry, test_entry_b},
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn verify_and_repair_test() {
+        let dir = tempdir().unwrap();
+        let storage = open_storage(dir.path(), "file").unwrap();
+        assert!(verify(&storage, false).is_err());
+
+        let header_a = test_chain_header();
+        let entry_b = test_entry_b();
+        let header_b = ChainHeader::new(
+            &entry_b.entry_type(),
+            &entry_b.address(),
+            header_a.sources(),
+            header_a.entry_signatures(),
+            &Some(header_a.address()),
+            &None,
+            &None,
+            header_a.timestamp(),
+        );
+        // same type as header_a but without a link to it
+        let header_c = ChainHeader::new(
+            header_a.entry_type(),
+            header_a.entry_address(),
+            header_a.sources(),
+            header_a.entry_signatures(),
+            &Some(header_b.address()),
+            &None,
+            &None,
+            header_a.timestamp(),
+        );
+        {
+            let mut cas = storage.write().unwrap();
+            cas.add(&test_entry()).unwrap();
+            cas.add(&entry_b).unwrap();
+            for header in vec![&header_a, &header_b, &header_c] {
+                cas.add(header).unwrap();
+            }
+        }
+        let mut persister = SimplePersister::new(storage.clone());
+        persister.save_top_chain_header(header_c.clone()).unwrap();
+
+        assert!(verify(&storage, false).is_err());
+        verify(&storage, true).unwrap();
+        verify(&storage, false).unwrap();
+
+        let top_chain_header = persister.load_top_chain_header().unwrap().unwrap();
+        assert_ne!(top_chain_header, header_c);
+        assert_eq!(top_chain_header.entry_address(), header_c.entry_address());
+        assert_eq!(top_chain_header.link_same_type(), Some(header_a.address()));
+    }
+
+    #[test]
+    fn export_and_import_test() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("chain.json");
+        let storage = open_storage(&dir.path().join("from"), "file").unwrap();
+        assert!(export(&storage, &file).is_err());
+
+        // imports only accept signed headers
+        let mut keystore = Keystore::new_random();
+        let entry = test_entry();
+        let header = ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &vec![keystore.address()],
+            &vec![keystore.sign(&entry.address().to_string())],
+            &None,
+            &None,
+            &None,
+            test_chain_header().timestamp(),
+        );
+        {
+            let mut cas = storage.write().unwrap();
+            cas.add(&entry).unwrap();
+            cas.add(&header).unwrap();
+        }
+        SimplePersister::new(storage.clone())
+            .save_top_chain_header(header.clone())
+            .unwrap();
+        export(&storage, &file).unwrap();
+
+        let other_storage = open_storage(&dir.path().join("to"), "lmdb").unwrap();
+        import(&file, &other_storage).unwrap();
+        assert_eq!(
+            SimplePersister::new(other_storage.clone())
+                .load_top_chain_header()
+                .unwrap(),
+            Some(header)
+        );
+        verify(&other_storage, false).unwrap();
+        // importing the same chain again is fine
+        import(&file, &other_storage).unwrap();
+    }
+
+    #[test]
+    fn open_storage_test() {
+        let dir = tempdir().unwrap();
+        assert!(open_storage(dir.path(), "lmdb").is_ok());
+        assert!(open_storage(dir.path(), "memory").is_err());
+    }
+}
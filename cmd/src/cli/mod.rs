@@ -1,4 +1,5 @@
 mod agent;
+mod chain;
 mod generate;
 mod init;
 pub mod package;
@@ -8,7 +9,8 @@ pub mod test;
 mod test_context;
 
 pub use self::{
-    agent::agent,
+    agent::{agent, keygen, AgentCommand},
+    chain::{chain, ChainCommand},
     generate::generate,
     init::init,
     package::{package, unpack},
@@ -2,9 +2,10 @@ use cli::{self, package};
 use colored::*;
 use error::DefaultResult;
 use holochain_container_api::{config::*, container::Container};
-use holochain_core_types::agent::AgentId;
+use holochain_core::agent::keystore::Keystore;
+use holochain_core_types::{cas::content::AddressableContent, dna::Dna, json::JsonString};
 use holochain_net::p2p_config::P2pConfig;
-use std::fs;
+use std::{convert::TryFrom, fs};
 
 const LOCAL_STORAGE_PATH: &str = ".hc";
 
@@ -19,18 +20,25 @@ pub fn run(package: bool, port: u16, persist: bool) -> DefaultResult<()> {
         cli::package(true, Some(package::DEFAULT_BUNDLE_FILE_NAME.into()))?;
     }
 
-    let agent = AgentId::generate_fake("testAgent");
+    let agent_name = "testAgent";
     let agent_config = AgentConfiguration {
         id: AGENT_CONFIG_ID.into(),
-        name: agent.nick,
-        public_address: agent.key,
+        name: agent_name.into(),
+        public_address: Keystore::generate_fake(AGENT_CONFIG_ID)
+            .address()
+            .to_string(),
         key_file: "hc_run.key".into(),
+        test_agent: Some(true),
     };
 
+    let dna_file = package::DEFAULT_BUNDLE_FILE_NAME;
+    let dna = Dna::try_from(JsonString::from(fs::read_to_string(dna_file)?))
+        .map_err(|err| format_err!("{}", err))?;
     let dna_config = DnaConfiguration {
         id: DNA_CONFIG_ID.into(),
-        file: package::DEFAULT_BUNDLE_FILE_NAME.into(),
-        hash: "Qm328wyq38924ybogus".into(),
+        file: dna_file.into(),
+        hash: dna.address().to_string(),
+        skip_hash_check: None,
     };
 
     let storage = if persist {
@@ -50,6 +58,9 @@ pub fn run(package: bool, port: u16, persist: bool) -> DefaultResult<()> {
         logger: Default::default(),
         storage,
         network: Some(P2pConfig::default_mock().as_str()),
+        network_timeouts: Default::default(),
+        result_retention: Default::default(),
+        dna_properties: None,
     };
 
     let interface_config = InterfaceConfiguration {
@@ -75,8 +86,12 @@ pub fn run(package: bool, port: u16, persist: bool) -> DefaultResult<()> {
         .load_config()
         .map_err(|err| format_err!("{}", err))?;
 
-    container.start_all_interfaces();
-    container.start_all_instances()?;
+    let container = container.into_handle();
+    {
+        let mut container = container.lock().unwrap();
+        container.start_all_interfaces();
+        container.start_all_instances()?;
+    }
 
     println!(
         "Holochain development container started. Running websocket server on port {}",
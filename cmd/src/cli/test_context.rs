@@ -1,6 +1,9 @@
 use holochain_container_api::context_builder::ContextBuilder;
-use holochain_core::{context::Context, logger::Logger};
-use holochain_core_types::agent::AgentId;
+use holochain_core::{
+    agent::keystore::Keystore,
+    context::Context,
+    logger::{LogLevel, Logger},
+};
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Debug)]
@@ -9,7 +12,7 @@ pub struct TestLogger {
 }
 
 impl Logger for TestLogger {
-    fn log(&mut self, msg: String) {
+    fn log(&mut self, _level: LogLevel, msg: String) {
         self.log.push(msg);
     }
     fn dump(&self) -> String {
@@ -25,10 +28,12 @@ pub fn test_logger() -> Arc<Mutex<TestLogger>> {
 /// create a test context and TestLogger pair so we can use the logger in assertions
 #[cfg_attr(tarpaulin, skip)]
 pub fn test_context(agent_name: &str) -> Arc<Context> {
-    let agent = AgentId::generate_fake(agent_name);
+    let keystore = Keystore::new_random();
+    let agent = keystore.agent_id(agent_name);
     Arc::new(
         ContextBuilder::new()
-            .with_agent(agent)
+            .with_agent(agent, keystore)
+            .expect("The agent of a keystore has its keys")
             .with_logger(test_logger())
             .with_memory_storage()
             .spawn(),
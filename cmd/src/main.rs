@@ -19,6 +19,7 @@ extern crate toml;
 #[macro_use]
 extern crate serde_json;
 extern crate ignore;
+extern crate rpassword;
 extern crate rustyline;
 extern crate tempfile;
 extern crate uuid;
@@ -38,9 +39,34 @@ enum Cli {
     #[structopt(
         name = "agent",
         alias = "a",
-        about = "Starts a Holochain node as an agent"
+        about = "Manages the keystore files holding the keys of agents"
     )]
-    Agent,
+    Agent {
+        #[structopt(subcommand)]
+        command: cli::AgentCommand,
+    },
+    #[structopt(
+        name = "chain",
+        about = "Inspects and repairs the persisted source chain of an instance"
+    )]
+    Chain {
+        #[structopt(subcommand)]
+        command: cli::ChainCommand,
+    },
+    #[structopt(
+        name = "keygen",
+        about = "Generates a new agent keystore file and prints its public address"
+    )]
+    Keygen {
+        #[structopt(
+            long,
+            short,
+            default_value = "agent.keystore",
+            help = "The file the new keystore gets written to",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+    },
     #[structopt(
         name = "package",
         alias = "p",
@@ -147,7 +173,9 @@ fn run() -> HolochainResult<()> {
     let args = Cli::from_args();
 
     match args {
-        Cli::Agent => cli::agent().map_err(HolochainError::Default)?,
+        Cli::Agent { command } => cli::agent(command).map_err(HolochainError::Default)?,
+        Cli::Chain { command } => cli::chain(command).map_err(HolochainError::Default)?,
+        Cli::Keygen { path } => cli::keygen(&path).map_err(HolochainError::Default)?,
         Cli::Package { strip_meta, output } => {
             cli::package(strip_meta, output).map_err(HolochainError::Default)?
         }
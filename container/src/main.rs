@@ -44,18 +44,29 @@ fn main() {
     let config_path_str = config_path.to_str().unwrap();
     println!("Using config path: {}", config_path_str);
     match bootstrap_from_config(config_path_str) {
-        Ok(mut container) => {
-            if container.instances().len() > 0 {
+        Ok(container) => {
+            let has_admin_interface = container
+                .config()
+                .interfaces
+                .iter()
+                .any(|interface| interface.admin);
+            // Containers with admin interfaces can start empty and get instances added
+            if container.instances().len() > 0 || has_admin_interface {
                 println!(
                     "Successfully loaded {} instance configurations",
                     container.instances().len()
                 );
-                println!("Starting all of them...");
-                container
-                    .start_all_instances()
-                    .expect("Could not start instances!");
-                println!("Starting interfaces...");
-                container.start_all_interfaces();
+                // Admin interfaces change the container while it runs
+                let container = container.into_handle();
+                {
+                    let mut container = container.lock().unwrap();
+                    println!("Starting all of them...");
+                    container
+                        .start_all_instances()
+                        .expect("Could not start instances!");
+                    println!("Starting interfaces...");
+                    container.start_all_interfaces();
+                }
                 println!("Done.");
                 loop {}
             } else {
@@ -72,7 +83,7 @@ fn bootstrap_from_config(path: &str) -> Result<Container, HolochainError> {
     config
         .check_consistency()
         .map_err(|string| HolochainError::ConfigError(string))?;
-    let mut container = Container::from_config(config);
+    let mut container = Container::from_config(config).with_config_path(PathBuf::from(path));
     container.load_config()?;
     Ok(container)
 }
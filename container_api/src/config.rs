@@ -10,14 +10,21 @@
 ///   the container
 /// * bridges, which are
 use boolinator::*;
+use holochain_core::{
+    logger::{LogFormat, LogLevel},
+    network::NetworkTimeouts,
+    retention::ResultRetention,
+};
 use holochain_core_types::{
     agent::AgentId,
+    cas::content::{Address, AddressableContent},
     dna::Dna,
     error::{HcResult, HolochainError},
     json::JsonString,
 };
 use petgraph::{algo::toposort, graph::DiGraph, prelude::NodeIndex};
 use serde::Deserialize;
+use serde_json::Value;
 use std::{collections::HashMap, convert::TryFrom, fs::File, io::prelude::*};
 use toml;
 
@@ -75,6 +82,24 @@ impl Configuration {
                     )
                 })?;
             }
+            if let InterfaceDriver::Http {
+                ref cors_allowed_origins,
+                ..
+            } = interface.driver
+            {
+                for origin in cors_allowed_origins.iter() {
+                    // origins get sent back in a header, which can only hold visible ASCII
+                    origin
+                        .bytes()
+                        .all(|byte| byte.is_ascii_graphic())
+                        .ok_or_else(|| {
+                            format!(
+                                "Invalid CORS origin \"{}\" in interface \"{}\"",
+                                origin, interface.id
+                            )
+                        })?;
+                }
+            }
         }
 
         for ref bridge in self.bridges.iter() {
@@ -203,13 +228,23 @@ impl Configuration {
     }
 }
 
-/// An agent has a name/ID and is defined by a private key that resides in a file
+/// An agent has a name/ID and is defined by a private key that resides in a file.
+/// The key file is an encrypted keystore (see `Keystore::save()`) that has to hold the keys
+/// of the given public address.
 #[derive(Deserialize, Serialize, Clone)]
 pub struct AgentConfiguration {
     pub id: String,
     pub name: String,
+    /// Optional for test agents, required for all others
+    #[serde(default)]
     pub public_address: String,
     pub key_file: String,
+    /// If set to true, key_file is ignored and the agent's keys get derived from
+    /// the agent's id instead. A configured public_address then has to be the one
+    /// of those keys.
+    /// Anybody can sign as such an agent, so this is meant for tests and local
+    /// development only.
+    pub test_agent: Option<bool>,
 }
 
 impl From<AgentConfiguration> for AgentId {
@@ -225,16 +260,41 @@ impl From<AgentConfiguration> for AgentId {
 pub struct DnaConfiguration {
     pub id: String,
     pub file: String,
+    /// The address of the DNA in the file. Instances don't get created for DNA files
+    /// with another address.
     pub hash: String,
+    /// If set to true, the DNA file doesn't get checked against the hash.
+    /// Any DNA put in the file runs then, so this is meant for local development only.
+    pub skip_hash_check: Option<bool>,
+}
+
+impl DnaConfiguration {
+    /// Makes sure the DNA loaded from this configuration's file has the configured hash,
+    /// unless the check is skipped.
+    pub fn check_hash(&self, dna: &Dna) -> Result<(), HolochainError> {
+        if self.skip_hash_check.unwrap_or(false) {
+            return Ok(());
+        }
+        let address = dna.address();
+        if address != Address::from(self.hash.clone()) {
+            return Err(HolochainError::ConfigError(format!(
+                "DNA file \"{}\" holds a DNA with hash {} but DNA \"{}\" is configured with hash {}",
+                self.file, address, self.id, self.hash
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl TryFrom<DnaConfiguration> for Dna {
     type Error = HolochainError;
     fn try_from(dna_config: DnaConfiguration) -> Result<Self, Self::Error> {
-        let mut f = File::open(dna_config.file)?;
+        let mut f = File::open(&dna_config.file)?;
         let mut contents = String::new();
         f.read_to_string(&mut contents)?;
-        Dna::try_from(JsonString::from(contents))
+        let dna = Dna::try_from(JsonString::from(contents))?;
+        dna_config.check_hash(&dna)?;
+        Ok(dna)
     }
 }
 
@@ -245,26 +305,79 @@ pub struct InstanceConfiguration {
     pub id: String,
     pub dna: String,
     pub agent: String,
+    #[serde(default)]
     pub logger: LoggerConfiguration,
     pub storage: StorageConfiguration,
     pub network: Option<String>,
+    /// How long the instance's requests to the network wait for an answer,
+    /// defaults to NetworkTimeouts::DEFAULT_TIMEOUT_MS for each kind of request
+    #[serde(default)]
+    pub network_timeouts: NetworkTimeouts,
+    /// How long and how many unconsumed results the instance's state keeps
+    /// (results of actions only expire), defaults to ResultRetention::DEFAULT_TTL_MS
+    /// and ResultRetention::DEFAULT_MAX_RESULTS
+    #[serde(default)]
+    pub result_retention: ResultRetention,
+    /// Overrides of the DNA's properties for this instance, given as a table that gets merged
+    /// into the DNA's properties when zomes read them. The DNA and its hash stay untouched.
+    /// They are local settings of this node: validation callbacks (and the definitions of
+    /// what validation needs) only see the DNA's own properties, so that all nodes sharing
+    /// a DHT still validate alike.
+    #[serde(default)]
+    pub dna_properties: Option<Value>,
 }
 
-/// There might be different kinds of loggers in the future.
-/// Currently there is no logger at all.
-/// TODO: make this an enum when it's actually in use
-#[derive(Deserialize, Serialize, Clone, Default)]
+/// Configures where the log messages of an instance go. The type is one of:
+/// * simple: plain lines on stdout (the default)
+/// * stdout: lines on stdout with the level in colour
+/// * file: lines appended to `file`, which gets rotated once it reaches max_file_size
+/// * null: no logging at all
+///
+/// Only messages of at least `level` get logged. If there are `include` patterns,
+/// a message has to match one of these regular expressions, and it must not match any
+/// of the `exclude` ones.
+#[derive(Deserialize, Serialize, Clone)]
 pub struct LoggerConfiguration {
     #[serde(rename = "type")]
     pub logger_type: String,
     pub file: Option<String>,
+    /// text or json, which writes every message as a JSON object on its own line
+    #[serde(default)]
+    pub format: LogFormat,
+    #[serde(default)]
+    pub level: LogLevel,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Size in bytes a log file may reach before it gets rotated,
+    /// defaults to FileLogger::DEFAULT_MAX_FILE_SIZE
+    pub max_file_size: Option<u64>,
+    /// Number of rotated log files that get kept, defaults to FileLogger::DEFAULT_MAX_FILES
+    pub max_files: Option<usize>,
+}
+
+impl Default for LoggerConfiguration {
+    fn default() -> Self {
+        LoggerConfiguration {
+            logger_type: String::from("simple"),
+            file: None,
+            format: LogFormat::default(),
+            level: LogLevel::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_file_size: None,
+            max_files: None,
+        }
+    }
 }
 
 /// This configures the Content Addressable Storage (CAS) that
 /// the instance uses to store source chain and DHT shard in.
-/// There are two storage implementations in cas_implementations so far:
+/// There are three storage implementations in cas_implementations so far:
 /// * memory
 /// * file
+/// * lmdb (embedded transactional key-value store with indexed EAV meta data)
 ///
 /// Projected are various DB adapters.
 #[derive(Deserialize, Serialize, Clone)]
@@ -274,15 +387,16 @@ pub enum StorageConfiguration {
     Memory,
     #[serde(rename = "file")]
     File { path: String },
+    #[serde(rename = "lmdb")]
+    Lmdb { path: String },
 }
 
 /// Here, interfaces are user facing and make available zome functions to
 /// GUIs, browser based web UIs, local native UIs, other local applications and scripts.
-/// None is implemented yet, but we will have:
+/// Implemented are:
 /// * websockets
-/// * HTTP REST
-/// * Unix domain sockets
-/// very soon.
+/// * HTTP (JSON-RPC requests POSTed to the interface's port)
+/// * Unix domain sockets (newline delimited JSON-RPC, Unix only)
 ///
 /// Every interface lists the instances that are made available here.
 /// An admin flag will enable container functions for programmatically changing the configuration
@@ -299,9 +413,20 @@ pub struct InterfaceConfiguration {
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum InterfaceDriver {
-    Websocket { port: u16 },
-    Http { port: u16 },
-    DomainSocket { file: String },
+    Websocket {
+        port: u16,
+    },
+    Http {
+        port: u16,
+        /// Origins browsers may send requests from, "*" for any.
+        /// Requests with an Origin header are rejected if it's not in this list.
+        #[serde(default)]
+        cors_allowed_origins: Vec<String>,
+    },
+    DomainSocket {
+        /// Path of the socket file, which only the user running the container can access.
+        file: String,
+    },
     Custom(toml::value::Value),
 }
 
@@ -358,10 +483,29 @@ where
     })
 }
 
+/// Use this function to turn a `Configuration` back into a string `load_configuration()` reads.
+pub fn serialize_configuration(config: &Configuration) -> HcResult<String> {
+    // Going through toml::Value puts plain values before tables, as TOML requires
+    toml::Value::try_from(config)
+        .and_then(|value| toml::to_string(&value))
+        .map_err(|e| {
+            HolochainError::IoError(format!("Could not serialize toml: {}", e.to_string()))
+        })
+}
+
 #[cfg(test)]
 pub mod tests {
-    use crate::config::{load_configuration, Configuration, NetworkConfig};
-    use holochain_core::context::mock_network_config;
+    use crate::config::{
+        load_configuration, serialize_configuration, Configuration, DnaConfiguration,
+        InterfaceDriver, NetworkConfig, StorageConfiguration,
+    };
+    use holochain_core::{
+        context::mock_network_config,
+        logger::{LogFormat, LogLevel},
+        network::NetworkTimeouts,
+        retention::ResultRetention,
+    };
+    use holochain_core_types::{cas::content::AddressableContent, dna::Dna, error::HolochainError};
 
     pub fn example_serialized_network_config() -> String {
         String::from(mock_network_config())
@@ -381,6 +525,7 @@ pub mod tests {
     name = "Holo Tester 1"
     public_address = "HoloTester1-------------------------------------------------------------------------AHi1"
     key_file="another/file"
+    test_agent=true
 
     [[dnas]]
     id="dna"
@@ -401,6 +546,8 @@ pub mod tests {
             agents.get(1).expect("expected at least 2 agents").id,
             "alex"
         );
+        assert_eq!(agents.get(0).unwrap().test_agent, None);
+        assert_eq!(agents.get(1).unwrap().test_agent, Some(true));
     }
 
     #[test]
@@ -422,6 +569,31 @@ pub mod tests {
         assert_eq!(dna_config.id, "app spec rust");
         assert_eq!(dna_config.file, "app_spec.hcpkg");
         assert_eq!(dna_config.hash, "Qm328wyq38924y");
+        assert_eq!(dna_config.skip_hash_check, None);
+    }
+
+    #[test]
+    fn test_dna_check_hash() {
+        let dna = Dna::new();
+        let mut dna_config = DnaConfiguration {
+            id: "dna".to_string(),
+            file: "file.dna.json".to_string(),
+            hash: dna.address().to_string(),
+            skip_hash_check: None,
+        };
+        assert!(dna_config.check_hash(&dna).is_ok());
+
+        dna_config.hash = "Qm328wyq38924y".to_string();
+        match dna_config.check_hash(&dna) {
+            Err(HolochainError::ConfigError(message)) => {
+                assert!(message.contains("Qm328wyq38924y"));
+                assert!(message.contains(&dna.address().to_string()));
+            }
+            _ => panic!("Expected a ConfigError"),
+        }
+
+        dna_config.skip_hash_check = Some(true);
+        assert!(dna_config.check_hash(&dna).is_ok());
     }
 
     #[test]
@@ -445,11 +617,24 @@ pub mod tests {
     agent = "test agent"
     network = "{}"
     [instances.logger]
-    type = "simple"
+    type = "file"
     file = "app_spec.log"
+    format = "json"
+    level = "debug"
+    exclude = ["^HANDLE"]
+    max_files = 3
     [instances.storage]
     type = "file"
     path = "app_spec_storage"
+    [instances.network_timeouts]
+    get_entry = 5000
+    send = 10000
+    [instances.result_retention]
+    max_results = 500
+    [instances.dna_properties]
+    max_posts = 20
+    [instances.dna_properties.limits]
+    likes = 3
 
     [[interfaces]]
     id = "app spec websocket interface"
@@ -490,6 +675,33 @@ pub mod tests {
             instance_config.network,
             Some("{\"backend_kind\":\"special\"}".to_string())
         );
+        assert_eq!(
+            instance_config.network_timeouts,
+            NetworkTimeouts {
+                get_entry: 5000,
+                get_links: NetworkTimeouts::DEFAULT_TIMEOUT_MS,
+                send: 10000,
+            }
+        );
+        assert_eq!(
+            instance_config.result_retention,
+            ResultRetention {
+                ttl: ResultRetention::DEFAULT_TTL_MS,
+                max_results: 500,
+            }
+        );
+        assert_eq!(
+            instance_config.dna_properties,
+            Some(json!({"max_posts": 20, "limits": {"likes": 3}}))
+        );
+        let logger_config = &instance_config.logger;
+        assert_eq!(logger_config.logger_type, "file");
+        assert_eq!(logger_config.format, LogFormat::Json);
+        assert_eq!(logger_config.level, LogLevel::Debug);
+        assert!(logger_config.include.is_empty());
+        assert_eq!(logger_config.exclude, vec![String::from("^HANDLE")]);
+        assert_eq!(logger_config.max_file_size, None);
+        assert_eq!(logger_config.max_files, Some(3));
         assert_eq!(
             config.network.unwrap(),
             NetworkConfig {
@@ -503,6 +715,58 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_serialize_configuration() {
+        let toml = r#"
+    [[agents]]
+    id = "test agent"
+    name = "Holo Tester 1"
+    public_address = "HoloTester1-------------------------------------------------------------------------AHi1"
+    key_file = "holo_tester.key"
+
+    [[dnas]]
+    id = "app spec rust"
+    file = "app_spec.hcpkg"
+    hash = "Qm328wyq38924y"
+
+    [[instances]]
+    id = "app spec instance"
+    dna = "app spec rust"
+    agent = "test agent"
+    network = "{}"
+    [instances.logger]
+    type = "simple"
+    file = "app_spec.log"
+    [instances.storage]
+    type = "file"
+    path = "app_spec_storage"
+    [instances.dna_properties]
+    language = "de"
+
+    [[interfaces]]
+    id = "app spec websocket interface"
+    admin = true
+    [interfaces.driver]
+    type = "websocket"
+    port = 8888
+    [[interfaces.instances]]
+    id = "app spec instance"
+    "#;
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        let serialized = serialize_configuration(&config).unwrap();
+        let reloaded = load_configuration::<Configuration>(&serialized).unwrap();
+
+        assert_eq!(reloaded.check_consistency(), Ok(()));
+        assert_eq!(serialize_configuration(&reloaded).unwrap(), serialized);
+        let instance_config = reloaded.instance_by_id("app spec instance").unwrap();
+        assert_eq!(instance_config.network, Some("{}".to_string()));
+        assert_eq!(
+            instance_config.dna_properties,
+            Some(json!({"language": "de"}))
+        );
+        assert!(reloaded.interfaces[0].admin);
+    }
+
     #[test]
     fn test_load_complete_config_default_network() {
         let toml = r#"
@@ -560,6 +824,87 @@ pub mod tests {
         assert_eq!(instance_config.dna, "app spec rust");
         assert_eq!(instance_config.agent, "test agent");
         assert_eq!(instance_config.network, None);
+        assert_eq!(instance_config.network_timeouts, NetworkTimeouts::default());
+        assert_eq!(instance_config.result_retention, ResultRetention::default());
+        assert_eq!(instance_config.dna_properties, None);
+    }
+
+    #[test]
+    fn test_load_lmdb_storage_config() {
+        let toml = r#"
+    [[agents]]
+    id = "test agent"
+    name = "Holo Tester 1"
+    public_address = "HoloTester1-------------------------------------------------------------------------AHi1"
+    key_file = "holo_tester.key"
+
+    [[dnas]]
+    id = "app spec rust"
+    file = "app_spec.hcpkg"
+    hash = "Qm328wyq38924y"
+
+    [[instances]]
+    id = "app spec instance"
+    dna = "app spec rust"
+    agent = "test agent"
+    [instances.logger]
+    type = "simple"
+    file = "app_spec.log"
+    [instances.storage]
+    type = "lmdb"
+    path = "app_spec_storage"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        assert_eq!(config.check_consistency(), Ok(()));
+        match config.instances[0].storage {
+            StorageConfiguration::Lmdb { ref path } => assert_eq!(path, "app_spec_storage"),
+            _ => panic!("Expected LMDB storage configuration"),
+        }
+    }
+
+    #[test]
+    fn test_load_http_interface_config() {
+        let toml = r#"
+    [[interfaces]]
+    id = "http interface"
+    [interfaces.driver]
+    type = "http"
+    port = 4000
+    cors_allowed_origins = ["http://localhost:8080"]
+    [[interfaces.instances]]
+    id = "app spec instance"
+
+    [[interfaces]]
+    id = "http interface without cors"
+    [interfaces.driver]
+    type = "http"
+    port = 4001
+    [[interfaces.instances]]
+    id = "app spec instance"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        match config.interfaces[0].driver {
+            InterfaceDriver::Http {
+                port,
+                ref cors_allowed_origins,
+            } => {
+                assert_eq!(port, 4000);
+                assert_eq!(
+                    cors_allowed_origins,
+                    &vec!["http://localhost:8080".to_string()]
+                );
+            }
+            _ => panic!("Expected HTTP interface configuration"),
+        }
+        match config.interfaces[1].driver {
+            InterfaceDriver::Http {
+                ref cors_allowed_origins,
+                ..
+            } => assert!(cors_allowed_origins.is_empty()),
+            _ => panic!("Expected HTTP interface configuration"),
+        }
     }
 
     #[test]
@@ -595,6 +940,29 @@ pub mod tests {
         assert_eq!(config.check_consistency(), Err("DNA configuration \"WRONG DNA ID\" not found, mentioned in instance \"app spec instance\"".to_string()));
     }
 
+    #[test]
+    fn test_invalid_cors_origin() {
+        let toml = r#"
+    [[interfaces]]
+    id = "http interface"
+    [interfaces.driver]
+    type = "http"
+    port = 4000
+    cors_allowed_origins = ["http://bücher.example"]
+    "#;
+
+        let config: Configuration =
+            load_configuration(toml).expect("Failed to load config from toml string");
+
+        assert_eq!(
+            config.check_consistency(),
+            Err(
+                "Invalid CORS origin \"http://bücher.example\" in interface \"http interface\""
+                    .to_string()
+            )
+        );
+    }
+
     #[test]
     fn test_inconsistent_config_interface_1() {
         let toml = r#"
@@ -735,7 +1103,9 @@ pub mod tests {
     path = "app_spec_storage"
 
     {}
-    "#, bridges)
+    "#,
+            bridges
+        )
     }
 
     #[test]
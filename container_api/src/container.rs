@@ -1,15 +1,22 @@
 use crate::{
     config::{
-        Configuration, InterfaceConfiguration, InterfaceDriver, NetworkConfig, StorageConfiguration,
+        serialize_configuration, AgentConfiguration, Bridge, Configuration, DnaConfiguration,
+        InstanceConfiguration, InterfaceConfiguration, InterfaceDriver, LoggerConfiguration,
+        NetworkConfig, StorageConfiguration,
     },
     context_builder::ContextBuilder,
     error::HolochainInstanceError,
     Holochain,
 };
-use holochain_core::{logger::Logger, signal::Signal};
+use holochain_core::{
+    agent::keystore::Keystore,
+    logger::{FileLogger, LogFilter, LogFormat, LogLevel, Logger, StdoutLogger},
+    signal::{signal_channel, Signal, SignalReceiver},
+};
 use holochain_core_types::{
-    agent::{AgentId, KeyBuffer},
-    dna::Dna,
+    agent::KeyBuffer,
+    cas::content::AddressableContent,
+    dna::{bridges::BridgePresence, Dna},
     error::HolochainError,
     json::JsonString,
 };
@@ -19,16 +26,24 @@ use std::{
     clone::Clone,
     collections::HashMap,
     convert::TryFrom,
-    fs::File,
+    env,
+    fs::{self, File},
     io::prelude::*,
-    sync::{mpsc::SyncSender, Arc, RwLock},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Sender, SyncSender},
+        Arc, Mutex, RwLock, RwLockReadGuard, Weak,
+    },
     thread,
 };
 
 use holochain_net::p2p_config::P2pConfig;
 use holochain_net_connection::net_connection::NetShutdown;
 use holochain_net_ipc::spawn::{ipc_spawn, SpawnResult};
-use interface::{ContainerApiBuilder, InstanceMap, Interface};
+use interface::{
+    add_zome_function_methods, signal_notification, ContainerApiBuilder, InstanceMap, Interface,
+};
+use tempfile::NamedTempFile;
 /// Main representation of the container.
 /// Holds a `HashMap` of Holochain instances referenced by ID.
 
@@ -39,14 +54,26 @@ use interface::{ContainerApiBuilder, InstanceMap, Interface};
 /// In order to not bind this code to the assumption that there is a filesystem
 /// and also enable easier testing, a DnaLoader ()which is a closure that returns a
 /// Dna object for a given path string) has to be injected on creation.
+/// The same goes for agent key files which get read by a KeyLoader.
 pub struct Container {
-    instances: InstanceMap,
+    /// Shared with the admin interfaces, so they see instances that get added later on
+    instances: Arc<RwLock<InstanceMap>>,
     config: Configuration,
+    /// The bridges the instances got wired up with: the configured ones together with
+    /// the ones resolved from the bridges their DNAs declare
+    resolved_bridges: Vec<Bridge>,
     interface_threads: HashMap<String, InterfaceThreadHandle>,
     dna_loader: DnaLoader,
+    /// The DNAs loaded by the DnaLoader, by the id of their configuration
+    dnas: HashMap<String, Dna>,
+    key_loader: KeyLoader,
     signal_tx: Option<SignalSender>,
+    /// By the ID of the interface they go to
+    signal_subscriptions: Arc<Mutex<HashMap<String, SignalSubscription>>>,
     network_ipc_uri: Option<String>,
     network_child_process: NetShutdown,
+    config_path: Option<PathBuf>,
+    handle: Option<Weak<Mutex<Container>>>,
 }
 
 impl Drop for Container {
@@ -58,27 +85,60 @@ impl Drop for Container {
 }
 
 type SignalSender = SyncSender<Signal>;
+/// The instances an interface exposes and the channel to the interface
+/// the notifications of their app signals go to
+type SignalSubscription = (Vec<String>, Sender<String>);
 type InterfaceThreadHandle = thread::JoinHandle<Result<(), String>>;
-type DnaLoader = Arc<Box<FnMut(&String) -> Result<Dna, HolochainError> + Send>>;
+type DnaLoader = Arc<Box<FnMut(&String) -> Result<Dna, HolochainError> + Send + Sync>>;
+type KeyLoader = Arc<Box<FnMut(&String) -> Result<Keystore, HolochainError> + Send + Sync>>;
+
+/// A container shared with the admin interfaces that change it while it runs,
+/// see `Container::into_handle()`
+pub type ContainerHandle = Arc<Mutex<Container>>;
+
+/// The environment variable the default KeyLoader reads the passphrase of agent key files from
+pub const PASSPHRASE_ENV_VAR: &str = "HC_AGENT_PASSPHRASE";
 
 pub static DEFAULT_NETWORK_CONFIG: &'static str = P2pConfig::DEFAULT_MOCK_CONFIG;
 
 impl Container {
-    /// Creates a new instance with the default DnaLoader that actually loads files.
+    /// Creates a new instance with the default DnaLoader and KeyLoader that actually load files.
     pub fn from_config(config: Configuration) -> Self {
         Container {
-            instances: HashMap::new(),
+            instances: Arc::new(RwLock::new(HashMap::new())),
             interface_threads: HashMap::new(),
             config,
+            resolved_bridges: Vec::new(),
             dna_loader: Arc::new(Box::new(Self::load_dna)),
+            dnas: HashMap::new(),
+            key_loader: Arc::new(Box::new(Self::load_key_file)),
             signal_tx: None,
+            signal_subscriptions: Arc::new(Mutex::new(HashMap::new())),
             network_ipc_uri: None,
             network_child_process: None,
+            config_path: None,
+            handle: None,
         }
     }
 
+    /// Sets the file the configuration got loaded from.
+    /// Changes made through the admin API get written back to it.
+    pub fn with_config_path(mut self, config_path: PathBuf) -> Self {
+        self.config_path = Some(config_path);
+        self
+    }
+
+    /// Moves the container behind a lock so that the admin API of its interfaces
+    /// can change it while it runs. Interfaces have to be started after this
+    /// to get the admin methods that need the container.
+    pub fn into_handle(self) -> ContainerHandle {
+        let handle = Arc::new(Mutex::new(self));
+        handle.lock().unwrap().handle = Some(Arc::downgrade(&handle));
+        handle
+    }
+
     pub fn with_signal_channel(mut self, signal_tx: SyncSender<Signal>) -> Self {
-        if !self.instances.is_empty() {
+        if !self.instances.read().unwrap().is_empty() {
             panic!("Cannot set a signal channel after having run load_config()");
         }
         self.signal_tx = Some(signal_tx);
@@ -108,7 +168,9 @@ impl Container {
     /// Starts all instances
     pub fn start_all_instances(&mut self) -> Result<(), HolochainInstanceError> {
         self.instances
-            .iter_mut()
+            .read()
+            .unwrap()
+            .iter()
             .map(|(id, hc)| {
                 println!("Starting instance \"{}\"...", id);
                 hc.write().unwrap().start()
@@ -120,7 +182,9 @@ impl Container {
     /// Stops all instances
     pub fn stop_all_instances(&mut self) -> Result<(), HolochainInstanceError> {
         self.instances
-            .iter_mut()
+            .read()
+            .unwrap()
+            .iter()
             .map(|(id, hc)| {
                 println!("Stopping instance \"{}\"...", id);
                 hc.write().unwrap().stop()
@@ -129,18 +193,40 @@ impl Container {
             .map(|_| ())
     }
 
-    pub fn instances(&self) -> &InstanceMap {
-        &self.instances
+    pub fn instances(&self) -> RwLockReadGuard<InstanceMap> {
+        self.instances.read().unwrap()
     }
 
     /// Stop and clear all instances
     pub fn shutdown(&mut self) -> Result<(), HolochainInstanceError> {
         self.stop_all_instances()?;
         // @TODO: also stop all interfaces
-        self.instances = HashMap::new();
+        self.remove_socket_files();
+        self.instances.write().unwrap().clear();
         Ok(())
     }
 
+    /// Removes the socket files of the started domain socket interfaces so that
+    /// clients can't connect anymore and the next container can bind them
+    #[cfg(unix)]
+    fn remove_socket_files(&self) {
+        for id in self.interface_threads.keys() {
+            if let Some(InterfaceConfiguration {
+                driver: InterfaceDriver::DomainSocket { ref file },
+                ..
+            }) = self.config.interface_by_id(id)
+            {
+                crate::interface_impls::domain_socket::remove_socket_file(
+                    Path::new(file),
+                    &make_interface_logger(id),
+                );
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn remove_socket_files(&self) {}
+
     pub fn spawn_network(&mut self) -> Result<String, HolochainError> {
         let network_config = self
             .config
@@ -226,9 +312,12 @@ impl Container {
             }
         }
 
+        // DNA files might have changed since the last time
+        self.dnas.clear();
         let config = self.config.clone();
+        let config = self.resolve_dna_bridges(&config)?;
         self.shutdown().map_err(|e| e.to_string())?;
-        self.instances = HashMap::new();
+        self.resolved_bridges = config.bridges.clone();
 
         for id in config.instance_ids_sorted_by_bridge_dependencies()? {
             let instance = self
@@ -241,11 +330,142 @@ impl Container {
                 })?;
 
             self.instances
+                .write()
+                .unwrap()
                 .insert(id.clone(), Arc::new(RwLock::new(instance)));
         }
+        self.open_migrated_chains()
+            .map_err(|error| format!("Error while opening migrated chains: {}", error))
+    }
+
+    /// Wires up the instances that continue chains closed by a Close migration:
+    /// the instance of the same agent that runs the DNA the migration names gets the
+    /// matching Open migration committed, unless it holds it already.
+    /// A successor that can't be opened (e.g. because its chain already has entries)
+    /// gets reported in its log and does not keep the other instances from starting.
+    fn open_migrated_chains(&self) -> Result<(), HolochainInstanceError> {
+        let instances = self.instances();
+        for instance_config in self.config.instances.iter() {
+            let old_hc = match instances.get(&instance_config.id) {
+                Some(hc) => hc.read().unwrap(),
+                None => continue,
+            };
+            let (closing_header, migration) = match old_hc.chain_closure()? {
+                Some(closure) => closure,
+                None => continue,
+            };
+            let successor = self
+                .config
+                .instances
+                .iter()
+                .filter(|other| other.agent == instance_config.agent)
+                .filter_map(|other| instances.get(&other.id).map(|hc| (&other.id, hc)))
+                .find(|(_, hc)| {
+                    hc.read().unwrap().dna_address().as_ref() == Some(migration.dna_address())
+                });
+            match successor {
+                Some((successor_id, successor_hc)) => {
+                    let old_dna_address = old_hc.dna_address().ok_or(HolochainError::DnaMissing)?;
+                    let mut successor_hc = successor_hc.write().unwrap();
+                    let (level, message) =
+                        match successor_hc.open_migrated_chain(old_dna_address, closing_header) {
+                            Ok(false) => continue,
+                            Ok(true) => (LogLevel::Info, format!(
+                                "Opened the chain of instance \"{}\" to continue instance \"{}\"",
+                                successor_id, instance_config.id
+                            )),
+                            Err(error) => (LogLevel::Error, format!(
+                                "Could not open the chain of instance \"{}\" to continue instance \"{}\": {}",
+                                successor_id, instance_config.id, error
+                            )),
+                        };
+                    successor_hc.context().log(level, message);
+                }
+                None => old_hc.context().log(LogLevel::Warn, format!(
+                    "Instance \"{}\" is closed in favour of DNA {}, but no instance of its agent runs it",
+                    instance_config.id,
+                    migration.dna_address()
+                )),
+            }
+        }
         Ok(())
     }
 
+    /// Returns the given configuration completed with the bridges the DNAs declare.
+    /// Each declared bridge that is not configured explicitly gets bound under its handle
+    /// to an instance whose DNA has the referenced address or implements all referenced
+    /// capabilities, preferring instances of the caller's agent.
+    /// Explicitly configured bridges with a declared handle have to point to such an
+    /// instance, and required bridges that no instance satisfies are an error.
+    fn resolve_dna_bridges(&mut self, config: &Configuration) -> Result<Configuration, String> {
+        // DNA files that can't be loaded are left to instantiate_from_config() to report
+        let mut dnas = HashMap::new();
+        for dna_config in config.dnas.iter() {
+            if let Ok(dna) = self.dna(dna_config) {
+                dnas.insert(dna_config.id.clone(), dna);
+            }
+        }
+
+        let mut resolved = config.clone();
+        for caller in config.instances.iter() {
+            let declared_bridges = match dnas.get(&caller.dna) {
+                Some(dna) => dna.get_bridges(),
+                None => continue,
+            };
+            for declared in declared_bridges {
+                let configured = config
+                    .bridge_dependencies(caller.id.clone())
+                    .into_iter()
+                    .find(|bridge| bridge.handle == declared.handle);
+                if let Some(bridge) = configured {
+                    let satisfied = config
+                        .instance_by_id(&bridge.callee_id)
+                        .and_then(|callee| dnas.get(&callee.dna))
+                        .map_or(true, |dna| {
+                            dna.satisfies_bridge_reference(&declared.reference)
+                        });
+                    if !satisfied {
+                        return Err(format!(
+                            "Bridge \"{}\" of instance \"{}\" calls instance \"{}\", which does not match the DNA's bridge reference",
+                            bridge.handle, bridge.caller_id, bridge.callee_id
+                        ));
+                    }
+                    continue;
+                }
+
+                let candidates: Vec<&InstanceConfiguration> = config
+                    .instances
+                    .iter()
+                    .filter(|callee| callee.id != caller.id)
+                    .filter(|callee| {
+                        dnas.get(&callee.dna).map_or(false, |dna| {
+                            dna.satisfies_bridge_reference(&declared.reference)
+                        })
+                    })
+                    .collect();
+                let callee = candidates
+                    .iter()
+                    .find(|callee| callee.agent == caller.agent)
+                    .or_else(|| candidates.first());
+                match callee {
+                    Some(callee) => resolved.bridges.push(Bridge {
+                        caller_id: caller.id.clone(),
+                        callee_id: callee.id.clone(),
+                        handle: declared.handle.clone(),
+                    }),
+                    None if declared.presence == BridgePresence::Required => {
+                        return Err(format!(
+                            "No instance satisfies the required bridge \"{}\" of instance \"{}\"",
+                            declared.handle, caller.id
+                        ));
+                    }
+                    None => (),
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
     /// Creates one specific Holochain instance from a given Configuration,
     /// id string and DnaLoader.
     pub fn instantiate_from_config(
@@ -264,21 +484,41 @@ impl Container {
 
                 // Agent:
                 let agent_config = config.agent_by_id(&instance_config.agent).unwrap();
-                let pub_key = KeyBuffer::with_corrected(&agent_config.public_address)?;
+                let keystore = self.load_agent_keystore(&agent_config)?;
                 context_builder =
-                    context_builder.with_agent(AgentId::new(&agent_config.name, &pub_key));
+                    context_builder.with_agent(keystore.agent_id(&agent_config.name), keystore)?;
 
                 // Network config (if it exists)
                 if let Some(network_config) = config.clone().network {
                     context_builder = context_builder
                         .with_network_config(self.instance_network_config(&network_config)?);
                 }
+                context_builder =
+                    context_builder.with_network_timeouts(instance_config.network_timeouts.clone());
+                context_builder =
+                    context_builder.with_result_retention(instance_config.result_retention.clone());
+                if let Some(ref dna_properties) = instance_config.dna_properties {
+                    context_builder =
+                        context_builder.with_dna_property_overrides(dna_properties.clone());
+                }
+
+                // Logger:
+                context_builder = context_builder
+                    .with_logger(make_logger(&instance_config.id, &instance_config.logger)?);
 
                 // Storage:
-                if let StorageConfiguration::File { path } = instance_config.storage {
-                    context_builder = context_builder.with_file_storage(path).map_err(|hc_err| {
-                        format!("Error creating context: {}", hc_err.to_string())
-                    })?
+                context_builder = match instance_config.storage {
+                    StorageConfiguration::Memory => context_builder,
+                    StorageConfiguration::File { path } => {
+                        context_builder.with_file_storage(path).map_err(|hc_err| {
+                            format!("Error creating context: {}", hc_err.to_string())
+                        })?
+                    }
+                    StorageConfiguration::Lmdb { path } => {
+                        context_builder.with_lmdb_storage(path).map_err(|hc_err| {
+                            format!("Error creating context: {}", hc_err.to_string())
+                        })?
+                    }
                 };
 
                 // Container API
@@ -290,7 +530,7 @@ impl Container {
                     let callee_config = config
                         .instance_by_id(&bridge.callee_id)
                         .expect("config.check_consistency()? jumps out if config is broken");
-                    let callee_instance = self.instances.get(&bridge.callee_id).expect(
+                    let callee_instance = self.instance_by_id(&bridge.callee_id).expect(
                         r#"
                             We have to create instances ordered by bridge dependencies such that we
                             can expect the callee to be present here because we need it to create
@@ -303,23 +543,18 @@ impl Container {
                         .with_named_instance_config(bridge.handle.clone(), callee_config);
                 }
                 context_builder = context_builder.with_container_api(api_builder.spawn());
-                if let Some(signal_tx) = self.signal_tx.clone() {
-                    context_builder = context_builder.with_signals(signal_tx);
-                }
+
+                // Signals:
+                let (signal_tx, signal_rx) = signal_channel();
+                context_builder = context_builder.with_signals(signal_tx);
+                self.spawn_signal_forwarder(id.clone(), signal_rx);
 
                 // Spawn context
                 let context = context_builder.spawn();
 
                 // Get DNA
                 let dna_config = config.dna_by_id(&instance_config.dna).unwrap();
-                let dna = Arc::get_mut(&mut self.dna_loader).unwrap()(&dna_config.file).map_err(
-                    |_| {
-                        HolochainError::ConfigError(format!(
-                            "Could not load DNA file \"{}\"",
-                            dna_config.file
-                        ))
-                    },
-                )?;
+                let dna = self.dna(&dna_config)?;
 
                 Holochain::new(dna, Arc::new(context)).map_err(|hc_err| hc_err.to_string())
             })
@@ -334,6 +569,33 @@ impl Container {
         Ok(())
     }
 
+    /// Returns the DNA of the given configuration, which gets loaded only the first time
+    /// and has to have the configured hash.
+    fn dna(&mut self, dna_config: &DnaConfiguration) -> Result<Dna, HolochainError> {
+        if let Some(dna) = self.dnas.get(&dna_config.id) {
+            return Ok(dna.clone());
+        }
+        let dna = Arc::get_mut(&mut self.dna_loader).unwrap()(&dna_config.file).map_err(|_| {
+            HolochainError::ConfigError(format!("Could not load DNA file \"{}\"", dna_config.file))
+        })?;
+        dna_config.check_hash(&dna)?;
+        self.dnas.insert(dna_config.id.clone(), dna.clone());
+        Ok(dna)
+    }
+
+    /// Tells if the DNA of the bridge's caller declares it as required
+    fn is_required_bridge(&self, bridge: &Bridge) -> bool {
+        self.config
+            .instance_by_id(&bridge.caller_id)
+            .and_then(|caller| self.dnas.get(&caller.dna))
+            .map_or(false, |dna| {
+                dna.get_bridges().iter().any(|declared| {
+                    declared.handle == bridge.handle
+                        && declared.presence == BridgePresence::Required
+                })
+            })
+    }
+
     /// Default DnaLoader that actually reads files from the filesystem
     fn load_dna(file: &String) -> Result<Dna, HolochainError> {
         let mut f = File::open(file)?;
@@ -342,6 +604,62 @@ impl Container {
         Dna::try_from(JsonString::from(contents))
     }
 
+    /// Default KeyLoader that decrypts keystore files from the filesystem
+    /// with the passphrase found in PASSPHRASE_ENV_VAR
+    fn load_key_file(file: &String) -> Result<Keystore, HolochainError> {
+        let passphrase = env::var(PASSPHRASE_ENV_VAR).map_err(|_| {
+            HolochainError::ConfigError(format!(
+                "Set {} to the passphrase of the agent key files",
+                PASSPHRASE_ENV_VAR
+            ))
+        })?;
+        Keystore::load(Path::new(file), &passphrase)
+    }
+
+    /// Loads the keystore of the given agent and makes sure it holds the keys
+    /// of the configured public address.
+    /// Test agents don't have a key file, their keys get derived from their id,
+    /// so different agents never share keys. Their public address can be left out,
+    /// but if it is configured it has to be the one of those keys.
+    fn load_agent_keystore(
+        &mut self,
+        agent_config: &AgentConfiguration,
+    ) -> Result<Keystore, HolochainError> {
+        if agent_config.test_agent.unwrap_or(false) {
+            let keystore = Keystore::generate_fake(&agent_config.id);
+            if !agent_config.public_address.is_empty()
+                && KeyBuffer::with_corrected(&agent_config.public_address)?.render()
+                    != keystore.address().to_string()
+            {
+                return Err(HolochainError::ConfigError(format!(
+                    "Test agent \"{}\" gets the keys of {} derived from its id but is configured as {}",
+                    agent_config.id,
+                    keystore.address(),
+                    agent_config.public_address
+                )));
+            }
+            return Ok(keystore);
+        }
+        let keystore = Arc::get_mut(&mut self.key_loader).unwrap()(&agent_config.key_file)
+            .map_err(|error| {
+                HolochainError::ConfigError(format!(
+                    "Could not load key file \"{}\": {}",
+                    agent_config.key_file, error
+                ))
+            })?;
+        let public_address = KeyBuffer::with_corrected(&agent_config.public_address)?.render();
+        if keystore.address().to_string() != public_address {
+            return Err(HolochainError::ConfigError(format!(
+                "Key file \"{}\" holds the keys of {} but agent \"{}\" is configured as {}",
+                agent_config.key_file,
+                keystore.address(),
+                agent_config.id,
+                public_address
+            )));
+        }
+        Ok(keystore)
+    }
+
     fn make_interface_handler(&self, interface_config: &InterfaceConfiguration) -> IoHandler {
         let instance_ids: Vec<String> = interface_config
             .instances
@@ -350,16 +668,22 @@ impl Container {
             .collect();
 
         let instance_subset: InstanceMap = self
-            .instances
+            .instances()
             .iter()
             .filter(|(id, _)| instance_ids.contains(&id))
             .map(|(id, val)| (id.clone(), val.clone()))
             .collect();
 
-        ContainerApiBuilder::new()
+        let mut api_builder = ContainerApiBuilder::new()
             .with_instances(instance_subset)
-            .with_instance_configs(self.config.instances.clone())
-            .spawn()
+            .with_instance_configs(self.config.instances.clone());
+        if interface_config.admin {
+            api_builder = api_builder.with_admin_chain_api(self.instances.clone());
+            if let Some(ref handle) = self.handle {
+                api_builder = api_builder.with_admin_container_api(handle.clone());
+            }
+        }
+        api_builder.spawn()
     }
 
     fn spawn_interface_thread(
@@ -367,9 +691,276 @@ impl Container {
         interface_config: InterfaceConfiguration,
     ) -> InterfaceThreadHandle {
         let dispatcher = self.make_interface_handler(&interface_config);
+        let (signal_tx, signal_rx) = channel();
+        self.signal_subscriptions.lock().unwrap().insert(
+            interface_config.id.clone(),
+            (interface_instance_ids(&interface_config), signal_tx),
+        );
         thread::spawn(move || {
             let iface = make_interface(&interface_config);
-            iface.run(dispatcher)
+            iface.run_with_signals(dispatcher, signal_rx)
+        })
+    }
+
+    /// Passes on the signals of an instance: its app signals as notifications to the
+    /// interfaces that expose it, and all of its signals to the signal channel
+    /// of the container, if there is one.
+    fn spawn_signal_forwarder(&self, instance_id: String, signal_rx: SignalReceiver) {
+        let subscriptions = self.signal_subscriptions.clone();
+        let container_signal_tx = self.signal_tx.clone();
+        thread::spawn(move || {
+            for signal in signal_rx.iter() {
+                if let Signal::User(ref user_signal) = signal {
+                    let notification = signal_notification(&instance_id, user_signal);
+                    // interfaces that stopped or drop notifications get unsubscribed
+                    subscriptions
+                        .lock()
+                        .unwrap()
+                        .retain(|_, (instance_ids, signal_tx)| {
+                            !instance_ids.contains(&instance_id)
+                                || signal_tx.send(notification.clone()).is_ok()
+                        });
+                }
+                if let Some(ref container_signal_tx) = container_signal_tx {
+                    container_signal_tx.send(signal).unwrap_or(());
+                }
+            }
+        });
+    }
+
+    /// Makes the signal subscriptions of the interfaces match their current configuration:
+    /// they get the signals of the instances they expose now, and interfaces that are
+    /// no longer configured don't get any.
+    fn update_signal_subscriptions(&self) {
+        self.signal_subscriptions
+            .lock()
+            .unwrap()
+            .retain(|interface_id, (instance_ids, _)| {
+                match self.config.interface_by_id(interface_id) {
+                    Some(interface_config) => {
+                        *instance_ids = interface_instance_ids(&interface_config);
+                        true
+                    }
+                    None => false,
+                }
+            });
+    }
+}
+
+/// Admin functionality, exposed through the `admin/...` methods of interfaces with the
+/// admin flag set. Every change gets checked for consistency with the rest of the configuration
+/// before it gets applied, and the changed configuration gets written to the configuration file.
+impl Container {
+    /// Adds a DNA that instances can then be created for. Its file has to be loadable.
+    pub fn install_dna(&mut self, dna_config: DnaConfiguration) -> Result<(), HolochainError> {
+        if self.config.dna_by_id(&dna_config.id).is_some() {
+            return Err(HolochainError::ConfigError(format!(
+                "DNA \"{}\" already exists",
+                dna_config.id
+            )));
+        }
+        self.dna(&dna_config)?;
+        let id = dna_config.id.clone();
+        let mut new_config = self.config.clone();
+        new_config.dnas.push(dna_config);
+        self.replace_config(new_config).map_err(|error| {
+            self.dnas.remove(&id);
+            error
+        })
+    }
+
+    /// Adds an agent whose key file has to hold the keys of its public address.
+    pub fn add_agent(&mut self, agent_config: AgentConfiguration) -> Result<(), HolochainError> {
+        if self.config.agent_by_id(&agent_config.id).is_some() {
+            return Err(HolochainError::ConfigError(format!(
+                "Agent \"{}\" already exists",
+                agent_config.id
+            )));
+        }
+        self.load_agent_keystore(&agent_config)?;
+        let mut new_config = self.config.clone();
+        new_config.agents.push(agent_config);
+        self.replace_config(new_config)
+    }
+
+    /// Adds and creates an instance, which then has to be started with `start_instance()`.
+    pub fn add_instance(
+        &mut self,
+        instance_config: InstanceConfiguration,
+    ) -> Result<(), HolochainError> {
+        let id = instance_config.id.clone();
+        if self.config.instance_by_id(&id).is_some() {
+            return Err(HolochainError::ConfigError(format!(
+                "Instance \"{}\" already exists",
+                id
+            )));
+        }
+        let mut new_config = self.config.clone();
+        new_config.instances.push(instance_config);
+        new_config
+            .check_consistency()
+            .map_err(HolochainError::ConfigError)?;
+        let resolved_config = self
+            .resolve_dna_bridges(&new_config)
+            .map_err(HolochainError::ConfigError)?;
+        let instance = self
+            .instantiate_from_config(&id, &resolved_config)
+            .map_err(|error| {
+                HolochainError::ConfigError(format!(
+                    "Error while trying to create instance \"{}\": {}",
+                    id, error
+                ))
+            })?;
+        self.instances
+            .write()
+            .unwrap()
+            .insert(id, Arc::new(RwLock::new(instance)));
+        self.resolved_bridges = resolved_config.bridges;
+        self.replace_config(new_config)
+    }
+
+    /// Stops and removes an instance together with its bridges and its references
+    /// in interfaces. Its storage is left as it is.
+    /// Instances that are the callee of a bridge another instance's DNA requires
+    /// can't be removed.
+    pub fn remove_instance(&mut self, id: &str) -> Result<(), HolochainError> {
+        let instance = self.instance_by_id(id)?;
+        if let Some(bridge) = self
+            .resolved_bridges
+            .iter()
+            .find(|bridge| bridge.callee_id == id && self.is_required_bridge(bridge))
+        {
+            return Err(HolochainError::ConfigError(format!(
+                "Instance \"{}\" can't be removed, instance \"{}\" requires it as bridge \"{}\"",
+                id, bridge.caller_id, bridge.handle
+            )));
+        }
+        let mut new_config = self.config.clone();
+        new_config.instances.retain(|instance| instance.id != id);
+        for interface in new_config.interfaces.iter_mut() {
+            interface.instances.retain(|instance| instance.id != id);
+        }
+        new_config
+            .bridges
+            .retain(|bridge| bridge.caller_id != id && bridge.callee_id != id);
+        new_config
+            .check_consistency()
+            .map_err(HolochainError::ConfigError)?;
+        {
+            let mut hc = instance.write().unwrap();
+            if hc.active() {
+                hc.stop()?;
+            }
+        }
+        self.instances.write().unwrap().remove(id);
+        self.resolved_bridges
+            .retain(|bridge| bridge.caller_id != id && bridge.callee_id != id);
+        self.replace_config(new_config)
+    }
+
+    pub fn start_instance(&mut self, id: &str) -> Result<(), HolochainError> {
+        Ok(self.instance_by_id(id)?.write().unwrap().start()?)
+    }
+
+    pub fn stop_instance(&mut self, id: &str) -> Result<(), HolochainError> {
+        Ok(self.instance_by_id(id)?.write().unwrap().stop()?)
+    }
+
+    /// Adds an interface and starts it.
+    pub fn add_interface(
+        &mut self,
+        interface_config: InterfaceConfiguration,
+    ) -> Result<(), HolochainError> {
+        if self.config.interface_by_id(&interface_config.id).is_some() {
+            return Err(HolochainError::ConfigError(format!(
+                "Interface \"{}\" already exists",
+                interface_config.id
+            )));
+        }
+        let mut new_config = self.config.clone();
+        new_config.interfaces.push(interface_config.clone());
+        new_config
+            .check_consistency()
+            .map_err(HolochainError::ConfigError)?;
+        // A running interface can't be taken back, so the configuration
+        // only gets changed once the interface got started.
+        self.start_interface(&interface_config)
+            .map_err(HolochainError::ConfigError)?;
+        self.replace_config(new_config)
+    }
+
+    /// Adds a bridge and makes the functions of the running callee available to the
+    /// running caller under the bridge's handle.
+    /// Handles already taken by a bridge resolved from the caller's DNA can't be reused,
+    /// and a callee has to match the caller DNA's bridge reference of that handle.
+    pub fn add_bridge(&mut self, bridge: Bridge) -> Result<(), HolochainError> {
+        if self.resolved_bridges.iter().any(|existing| {
+            existing.caller_id == bridge.caller_id && existing.handle == bridge.handle
+        }) {
+            return Err(HolochainError::ConfigError(format!(
+                "Instance \"{}\" already has a bridge called \"{}\"",
+                bridge.caller_id, bridge.handle
+            )));
+        }
+        let mut new_config = self.config.clone();
+        new_config.bridges.push(bridge.clone());
+        new_config
+            .check_consistency()
+            .map_err(HolochainError::ConfigError)?;
+        let resolved_config = self
+            .resolve_dna_bridges(&new_config)
+            .map_err(HolochainError::ConfigError)?;
+        let caller = self.instance_by_id(&bridge.caller_id)?;
+        let callee = self.instance_by_id(&bridge.callee_id)?;
+        let container_api = caller
+            .read()
+            .unwrap()
+            .context()
+            .container_api
+            .clone()
+            .ok_or_else(|| {
+                HolochainError::ErrorGeneric(format!(
+                    "Instance \"{}\" has no container API to add the bridge to",
+                    bridge.caller_id
+                ))
+            })?;
+        self.resolved_bridges = resolved_config.bridges;
+        add_zome_function_methods(&mut container_api.write().unwrap(), &bridge.handle, callee);
+        self.replace_config(new_config)
+    }
+
+    /// Writes the configuration to the file it got loaded from, if there is one.
+    /// It gets written to a temporary file in the same directory first, which then replaces
+    /// the old file, so a failing write never leaves a truncated configuration behind.
+    pub fn save_config(&self) -> Result<(), HolochainError> {
+        if let Some(ref config_path) = self.config_path {
+            let dir = match config_path.parent() {
+                Some(parent) if parent != Path::new("") => parent,
+                _ => Path::new("."),
+            };
+            let mut file = NamedTempFile::new_in(dir)?;
+            file.write_all(serialize_configuration(&self.config)?.as_bytes())?;
+            if let Ok(metadata) = fs::metadata(config_path) {
+                file.as_file().set_permissions(metadata.permissions())?;
+            }
+            file.persist(config_path).map_err(|error| error.error)?;
+        }
+        Ok(())
+    }
+
+    /// Makes the given configuration the current one, if it is consistent, and saves it.
+    fn replace_config(&mut self, config: Configuration) -> Result<(), HolochainError> {
+        config
+            .check_consistency()
+            .map_err(HolochainError::ConfigError)?;
+        self.config = config;
+        self.update_signal_subscriptions();
+        self.save_config()
+    }
+
+    fn instance_by_id(&self, id: &str) -> Result<Arc<RwLock<Holochain>>, HolochainError> {
+        self.instances().get(id).cloned().ok_or_else(|| {
+            HolochainError::ConfigError(format!("Instance \"{}\" does not exist", id))
         })
     }
 }
@@ -385,30 +976,123 @@ impl<'a> TryFrom<&'a Configuration> for Container {
     }
 }
 
+fn interface_instance_ids(interface_config: &InterfaceConfiguration) -> Vec<String> {
+    interface_config
+        .instances
+        .iter()
+        .map(|instance| instance.id.clone())
+        .collect()
+}
+
 /// This can eventually be dependency injected for third party Interface definitions
 fn make_interface(interface_config: &InterfaceConfiguration) -> Box<Interface> {
-    use interface_impls::websocket::WebsocketInterface;
+    use interface_impls::{http::HttpInterface, websocket::WebsocketInterface};
     match interface_config.driver {
-        InterfaceDriver::Websocket { port } => Box::new(WebsocketInterface::new(port)),
+        InterfaceDriver::Websocket { port } => Box::new(WebsocketInterface::new(
+            port,
+            make_interface_logger(&interface_config.id),
+        )),
+        InterfaceDriver::Http {
+            port,
+            ref cors_allowed_origins,
+        } => Box::new(HttpInterface::new(
+            port,
+            cors_allowed_origins.clone(),
+            make_interface_logger(&interface_config.id),
+        )),
+        #[cfg(unix)]
+        InterfaceDriver::DomainSocket { ref file } => Box::new(
+            crate::interface_impls::domain_socket::DomainSocketInterface::new(
+                file,
+                make_interface_logger(&interface_config.id),
+            ),
+        ),
         _ => unimplemented!(),
     }
 }
 
+/// Creates the logger of an interface, which prints to stdout named after the interface
+fn make_interface_logger(interface_id: &str) -> Arc<Mutex<Logger>> {
+    Arc::new(Mutex::new(StdoutLogger::new(
+        format!("interface {}", interface_id),
+        LogFilter::default(),
+        LogFormat::default(),
+        true,
+    )))
+}
+
+/// Creates the logger of an instance as configured, named after the instance
+fn make_logger(
+    instance_id: &str,
+    logger_config: &LoggerConfiguration,
+) -> Result<Arc<Mutex<Logger>>, HolochainError> {
+    let filter = LogFilter::new(
+        logger_config.level,
+        &logger_config.include,
+        &logger_config.exclude,
+    )?;
+    let name = instance_id.to_string();
+    let format = logger_config.format;
+    let logger: Arc<Mutex<Logger>> = match logger_config.logger_type.as_ref() {
+        "simple" => Arc::new(Mutex::new(StdoutLogger::new(name, filter, format, false))),
+        "stdout" => Arc::new(Mutex::new(StdoutLogger::new(name, filter, format, true))),
+        "file" => {
+            let file = logger_config.file.as_ref().ok_or_else(|| {
+                HolochainError::ConfigError(format!(
+                    "The file logger of instance \"{}\" needs a file",
+                    instance_id
+                ))
+            })?;
+            Arc::new(Mutex::new(FileLogger::new(
+                name,
+                Path::new(file),
+                filter,
+                format,
+                logger_config
+                    .max_file_size
+                    .unwrap_or(FileLogger::DEFAULT_MAX_FILE_SIZE),
+                logger_config
+                    .max_files
+                    .unwrap_or(FileLogger::DEFAULT_MAX_FILES),
+            )?))
+        }
+        "null" => Arc::new(Mutex::new(NullLogger {})),
+        other => {
+            return Err(HolochainError::ConfigError(format!(
+                "Unknown logger type \"{}\" for instance \"{}\"",
+                other, instance_id
+            )));
+        }
+    };
+    Ok(logger)
+}
+
 #[derive(Clone, Debug)]
 struct NullLogger {}
 
 impl Logger for NullLogger {
-    fn log(&mut self, _msg: String) {}
+    fn log(&mut self, _level: LogLevel, _msg: String) {}
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::config::load_configuration;
-    use holochain_core::{action::Action, signal::signal_channel};
-    use holochain_core_types::{cas::content::Address, dna, json::RawString};
+    use futures::executor::block_on;
+    use holochain_core::{
+        action::Action, nucleus::ribosome::api::emit_signal::emit_signal, signal::signal_channel,
+        workflows::author_entry::author_entry,
+    };
+    use holochain_core_types::{
+        cas::content::Address,
+        chain_migrate::ChainMigrate,
+        dna::{self, capabilities::CapabilityType},
+        entry::{cap_entries::CapTokenGrant, Entry},
+        json::RawString,
+    };
     use holochain_wasm_utils::wasm_target_dir;
-    use std::{fs::File, io::Write};
+    use serde_json::{self, Value};
+    use std::{fs::File, io::Write, time::Duration};
     use tempfile::tempdir;
     use test_utils::*;
 
@@ -417,9 +1101,11 @@ pub mod tests {
             Ok(match path.as_ref() {
                 "bridge/callee.dna" => callee_dna(),
                 "bridge/caller.dna" => caller_dna(),
+                "bridge/dynamic_caller.dna" => dynamic_caller_dna(BridgePresence::Required),
+                "bridge/optional_caller.dna" => dynamic_caller_dna(BridgePresence::Optional),
                 _ => Dna::try_from(JsonString::from(example_dna_string())).unwrap(),
             })
-        }) as Box<FnMut(&String) -> Result<Dna, HolochainError> + Send>;
+        }) as Box<FnMut(&String) -> Result<Dna, HolochainError> + Send + Sync>;
         Arc::new(loader)
     }
 
@@ -428,35 +1114,38 @@ pub mod tests {
     [[agents]]
     id = "test-agent-1"
     name = "Holo Tester 1"
-    public_address = "HoloTester1-----------------------------------------------------------------------AAACZp4xHB"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[agents]]
     id = "test-agent-2"
     name = "Holo Tester 2"
-    public_address = "HoloTester2-----------------------------------------------------------------------AAAGy4WW9e"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[agents]]
     id = "test-agent-3"
     name = "Holo Tester 3"
-    public_address = "HoloTester2-----------------------------------------------------------------------AAAGy4WW9e"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[dnas]]
     id = "test-dna"
     file = "app_spec.hcpkg"
     hash = "Qm328wyq38924y"
+    skip_hash_check = true
 
     [[dnas]]
     id = "bridge-callee"
     file = "bridge/callee.dna"
     hash = "Qm328wyq38924y"
+    skip_hash_check = true
 
     [[dnas]]
     id = "bridge-caller"
     file = "bridge/caller.dna"
     hash = "Qm328wyq38924y"
+    skip_hash_check = true
 
     [[instances]]
     id = "test-instance-1"
@@ -477,6 +1166,8 @@ pub mod tests {
     file = "app_spec.log"
     [instances.storage]
     type = "memory"
+    [instances.dna_properties]
+    language = "de"
 
     [[instances]]
     id = "bridge-caller"
@@ -600,13 +1291,57 @@ pub mod tests {
     #[test]
     fn test_container_load_config() {
         let mut container = test_container();
-        assert_eq!(container.instances.len(), 3);
+        assert_eq!(container.instances().len(), 3);
+        assert_eq!(
+            container.instances()["test-instance-2"]
+                .read()
+                .unwrap()
+                .context()
+                .dna_property_overrides,
+            Some(json!({"language": "de"}))
+        );
+        assert_eq!(
+            container.instances()["test-instance-1"]
+                .read()
+                .unwrap()
+                .context()
+                .dna_property_overrides,
+            None
+        );
 
         container.start_all_instances().unwrap();
         container.start_all_interfaces();
         container.stop_all_instances().unwrap();
     }
 
+    #[test]
+    fn test_container_checks_dna_hashes() {
+        let mut config = load_configuration::<Configuration>(&test_toml()).unwrap();
+        config
+            .dnas
+            .iter_mut()
+            .find(|dna_config| dna_config.id == "test-dna")
+            .unwrap()
+            .skip_hash_check = None;
+
+        let mut container = Container::from_config(config.clone());
+        container.dna_loader = test_dna_loader();
+        let error = container.load_config().unwrap_err();
+        assert!(error.contains("app_spec.hcpkg"));
+        assert!(error.contains("Qm328wyq38924y"));
+
+        let dna = Dna::try_from(JsonString::from(example_dna_string())).unwrap();
+        for dna_config in config.dnas.iter_mut() {
+            if dna_config.id == "test-dna" {
+                dna_config.hash = dna.address().to_string();
+            }
+        }
+        let mut container = Container::from_config(config);
+        container.dna_loader = test_dna_loader();
+        container.load_config().unwrap();
+        assert_eq!(container.instances().len(), 3);
+    }
+
     #[test]
     fn test_container_try_from_configuration() {
         let config = load_configuration::<Configuration>(&test_toml()).unwrap();
@@ -636,6 +1371,430 @@ pub mod tests {
         assert!(response.contains("test-instance-2"));
     }
 
+    fn migration_test_container() -> Container {
+        let toml = r#"
+    [[agents]]
+    id = "test-agent-1"
+    name = "Holo Tester 1"
+    key_file = "holo_tester.key"
+    test_agent = true
+
+    [[dnas]]
+    id = "old-dna"
+    file = "bridge/callee.dna"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[dnas]]
+    id = "new-dna"
+    file = "bridge/caller.dna"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[instances]]
+    id = "old-instance"
+    dna = "old-dna"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+
+    [[instances]]
+    id = "new-instance"
+    dna = "new-dna"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+    "#;
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        let mut container = Container::from_config(config);
+        container.dna_loader = test_dna_loader();
+        container.load_config().unwrap();
+        container
+    }
+
+    #[test]
+    fn test_container_opens_migrated_chains() {
+        let container = migration_test_container();
+        let old_hc = container.instances()["old-instance"].clone();
+        let new_hc = container.instances()["new-instance"].clone();
+        let new_dna_address = new_hc.read().unwrap().dna_address().unwrap();
+
+        let close = Entry::ChainMigrate(ChainMigrate::close(new_dna_address));
+        block_on(author_entry(&close, None, old_hc.read().unwrap().context())).unwrap();
+        let (closing_header, _) = old_hc.read().unwrap().chain_closure().unwrap().unwrap();
+        container.open_migrated_chains().unwrap();
+
+        let open = Entry::ChainMigrate(ChainMigrate::open(
+            old_hc.read().unwrap().dna_address().unwrap(),
+            closing_header,
+        ));
+        let top_chain_header = new_hc
+            .read()
+            .unwrap()
+            .state()
+            .unwrap()
+            .agent()
+            .top_chain_header()
+            .unwrap();
+        assert_eq!(top_chain_header.entry_address(), &open.address());
+
+        // wiring up again does not open the chain twice
+        container.open_migrated_chains().unwrap();
+        assert_eq!(
+            new_hc
+                .read()
+                .unwrap()
+                .state()
+                .unwrap()
+                .agent()
+                .top_chain_header(),
+            Some(top_chain_header)
+        );
+    }
+
+    #[test]
+    fn test_container_starts_if_a_migrated_chain_can_not_be_opened() {
+        let container = migration_test_container();
+        let old_hc = container.instances()["old-instance"].clone();
+        let new_hc = container.instances()["new-instance"].clone();
+        let new_dna_address = new_hc.read().unwrap().dna_address().unwrap();
+
+        // the successor chain already has entries, so it can't get opened anymore
+        let grant = Entry::CapTokenGrant(
+            CapTokenGrant::create("test", CapabilityType::Public, None, "nonce").unwrap(),
+        );
+        block_on(author_entry(&grant, None, new_hc.read().unwrap().context())).unwrap();
+        let close = Entry::ChainMigrate(ChainMigrate::close(new_dna_address));
+        block_on(author_entry(&close, None, old_hc.read().unwrap().context())).unwrap();
+
+        assert!(container.open_migrated_chains().is_ok());
+        let top_chain_header = new_hc
+            .read()
+            .unwrap()
+            .state()
+            .unwrap()
+            .agent()
+            .top_chain_header()
+            .unwrap();
+        assert_eq!(top_chain_header.entry_address(), &grant.address());
+    }
+
+    #[test]
+    fn test_admin_api_only_on_admin_interfaces() {
+        let container = test_container();
+        let mut interface_config = container.config.interfaces[0].clone();
+        interface_config.admin = false;
+        let io = container.make_interface_handler(&interface_config);
+        assert!(!format!("{:?}", io).contains("admin/chain/export"));
+
+        interface_config.admin = true;
+        let io = container.make_interface_handler(&interface_config);
+        assert!(format!("{:?}", io).contains("admin/chain/export"));
+        assert!(format!("{:?}", io).contains("admin/chain/import"));
+    }
+
+    #[test]
+    fn test_admin_add_start_stop_and_remove_instance() {
+        let mut container = test_container();
+        let mut instance_config = container.config.instance_by_id("test-instance-1").unwrap();
+        instance_config.id = "test-instance-4".to_string();
+
+        container.add_instance(instance_config.clone()).unwrap();
+        assert!(container.instances().contains_key("test-instance-4"));
+        assert!(container.config.instance_by_id("test-instance-4").is_some());
+        assert!(container.add_instance(instance_config.clone()).is_err());
+
+        container.start_instance("test-instance-4").unwrap();
+        assert!(container.instances()["test-instance-4"]
+            .read()
+            .unwrap()
+            .active());
+        container.stop_instance("test-instance-4").unwrap();
+        assert!(!container.instances()["test-instance-4"]
+            .read()
+            .unwrap()
+            .active());
+
+        container.remove_instance("test-instance-4").unwrap();
+        assert!(!container.instances().contains_key("test-instance-4"));
+        assert!(container.config.instance_by_id("test-instance-4").is_none());
+        assert!(container.start_instance("test-instance-4").is_err());
+    }
+
+    #[test]
+    fn test_admin_remove_instance_removes_its_bridges_and_interface_references() {
+        let mut container = test_container();
+        container.remove_instance("test-instance-1").unwrap();
+        assert!(container
+            .config
+            .bridges
+            .iter()
+            .all(|bridge| bridge.callee_id != "test-instance-1"));
+        assert!(container.config.interfaces[0]
+            .instances
+            .iter()
+            .all(|instance| instance.id != "test-instance-1"));
+    }
+
+    #[test]
+    fn test_admin_rejects_inconsistent_changes() {
+        let mut container = test_container();
+
+        let mut instance_config = container.config.instance_by_id("test-instance-1").unwrap();
+        instance_config.id = "test-instance-4".to_string();
+        instance_config.agent = "unknown-agent".to_string();
+        assert!(container.add_instance(instance_config).is_err());
+        assert!(container.config.instance_by_id("test-instance-4").is_none());
+
+        let mut agent_config = container.config.agent_by_id("test-agent-1").unwrap();
+        assert!(container.add_agent(agent_config.clone()).is_err());
+        agent_config.id = "test-agent-4".to_string();
+        container.add_agent(agent_config).unwrap();
+        assert!(container.config.agent_by_id("test-agent-4").is_some());
+
+        let mut dna_config = container.config.dna_by_id("test-dna").unwrap();
+        assert!(container.install_dna(dna_config.clone()).is_err());
+        dna_config.id = "test-dna-2".to_string();
+        container.install_dna(dna_config).unwrap();
+        assert!(container.config.dna_by_id("test-dna-2").is_some());
+
+        let interface_config = container.config.interfaces[0].clone();
+        assert!(container.add_interface(interface_config.clone()).is_err());
+
+        // interfaces that can't be started don't end up in the configuration
+        let interface_config = InterfaceConfiguration {
+            id: "busy-interface".to_string(),
+            ..interface_config
+        };
+        container
+            .interface_threads
+            .insert(interface_config.id.clone(), thread::spawn(|| Ok(())));
+        assert!(container.add_interface(interface_config).is_err());
+        assert!(container.config.interface_by_id("busy-interface").is_none());
+    }
+
+    #[test]
+    fn test_admin_add_bridge() {
+        let mut container = test_container();
+        let bridge = Bridge {
+            caller_id: "test-instance-2".to_string(),
+            callee_id: "test-instance-1".to_string(),
+            handle: "DPKI".to_string(),
+        };
+        // handles have to be unique per caller
+        assert!(container.add_bridge(bridge.clone()).is_err());
+
+        // bridges must not form cycles
+        let cyclic_bridge = Bridge {
+            caller_id: "test-instance-1".to_string(),
+            callee_id: "bridge-caller".to_string(),
+            handle: "caller".to_string(),
+        };
+        assert!(container.add_bridge(cyclic_bridge).is_err());
+
+        let bridge = Bridge {
+            handle: "callee".to_string(),
+            ..bridge
+        };
+        container.add_bridge(bridge.clone()).unwrap();
+        assert!(container
+            .config
+            .bridge_dependencies("test-instance-2".to_string())
+            .contains(&bridge));
+        assert!(container.resolved_bridges.contains(&bridge));
+        let container_api = container.instances()["test-instance-2"]
+            .read()
+            .unwrap()
+            .context()
+            .container_api
+            .clone()
+            .unwrap();
+        assert!(format!("{:?}", *container_api.read().unwrap()).contains("callee/greeter"));
+    }
+
+    #[test]
+    fn test_admin_changes_get_saved() {
+        let tempdir = tempdir().unwrap();
+        let config_path = tempdir.path().join("container-config.toml");
+        let config = load_configuration::<Configuration>(&test_toml()).unwrap();
+        let mut container = Container::from_config(config).with_config_path(config_path.clone());
+        container.dna_loader = test_dna_loader();
+        container.load_config().unwrap();
+
+        container.remove_instance("bridge-caller").unwrap();
+
+        let mut toml = String::new();
+        File::open(&config_path)
+            .unwrap()
+            .read_to_string(&mut toml)
+            .unwrap();
+        let saved_config = load_configuration::<Configuration>(&toml).unwrap();
+        // the temporary file the configuration got written to replaced the old one
+        assert_eq!(fs::read_dir(tempdir.path()).unwrap().count(), 1);
+        assert!(saved_config.instance_by_id("bridge-caller").is_none());
+        assert!(saved_config.instance_by_id("test-instance-1").is_some());
+        assert_eq!(saved_config.bridges.len(), 1);
+    }
+
+    #[test]
+    fn test_admin_rpc() {
+        let handle = test_container().into_handle();
+        let io = {
+            let container = handle.lock().unwrap();
+            let mut interface_config = container.config.interfaces[0].clone();
+            interface_config.admin = true;
+            container.make_interface_handler(&interface_config)
+        };
+        let request = |method: &str, id: &str| {
+            format!(
+                r#"{{"jsonrpc": "2.0", "method": "{}", "params": {{"id": "{}"}}, "id": 1}}"#,
+                method, id
+            )
+        };
+
+        assert_eq!(
+            io.handle_request_sync(&request("admin/instance/start", "test-instance-1")),
+            Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_string())
+        );
+        assert!(handle.lock().unwrap().instances()["test-instance-1"]
+            .read()
+            .unwrap()
+            .active());
+
+        let response = io
+            .handle_request_sync(&request("admin/instance/start", "unknown-instance"))
+            .unwrap();
+        assert!(response.contains("error"));
+
+        io.handle_request_sync(&request("admin/instance/remove", "bridge-caller"))
+            .unwrap();
+        assert!(!handle
+            .lock()
+            .unwrap()
+            .instances()
+            .contains_key("bridge-caller"));
+    }
+
+    #[test]
+    fn test_make_logger() {
+        let tempdir = tempdir().unwrap();
+        let log_file = tempdir.path().join("instance.log");
+        let mut logger_config = LoggerConfiguration {
+            logger_type: "file".to_string(),
+            file: Some(log_file.to_str().unwrap().to_string()),
+            exclude: vec!["^Zome Function".to_string()],
+            ..Default::default()
+        };
+        {
+            let logger = make_logger("test-instance-1", &logger_config).unwrap();
+            let mut logger = logger.lock().unwrap();
+            logger.log(
+                LogLevel::Info,
+                "Authoring entry Qm...: committed".to_string(),
+            );
+            logger.log(
+                LogLevel::Info,
+                "Zome Function 'main' returned: 1".to_string(),
+            );
+            logger.log(LogLevel::Trace, "REDUCE: Commit".to_string());
+        }
+
+        let mut log = String::new();
+        File::open(&log_file)
+            .unwrap()
+            .read_to_string(&mut log)
+            .unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("INFO  [test-instance-1] Authoring entry Qm...: committed"));
+
+        logger_config.file = None;
+        assert!(make_logger("test-instance-1", &logger_config).is_err());
+        logger_config.logger_type = "syslog".to_string();
+        assert!(make_logger("test-instance-1", &logger_config).is_err());
+        logger_config.logger_type = "stdout".to_string();
+        logger_config.include = vec!["(".to_string()];
+        assert!(make_logger("test-instance-1", &logger_config).is_err());
+    }
+
+    #[test]
+    fn test_container_passes_app_signals_to_interfaces() {
+        let container = test_container();
+        let (signal_tx, signal_rx) = channel();
+        let (other_signal_tx, other_signal_rx) = channel();
+        {
+            let mut subscriptions = container.signal_subscriptions.lock().unwrap();
+            subscriptions.insert(
+                "interface-1".to_string(),
+                (vec!["test-instance-1".to_string()], signal_tx),
+            );
+            subscriptions.insert(
+                "interface-2".to_string(),
+                (vec!["test-instance-2".to_string()], other_signal_tx),
+            );
+        }
+
+        let context = container.instances()["test-instance-1"]
+            .read()
+            .unwrap()
+            .context()
+            .clone();
+        emit_signal(
+            &context,
+            "new_post".to_string(),
+            JsonString::from(json!({"title": "hello"})),
+        )
+        .unwrap();
+
+        let notification = signal_rx.recv_timeout(Duration::from_millis(1000)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&notification).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "method": "signal",
+                "params": {
+                    "instance_id": "test-instance-1",
+                    "name": "new_post",
+                    "arguments": {"title": "hello"},
+                },
+            })
+        );
+        assert!(other_signal_rx
+            .recv_timeout(Duration::from_millis(100))
+            .is_err());
+    }
+
+    #[test]
+    fn test_container_updates_signal_subscriptions_of_changed_interfaces() {
+        let mut container = test_container();
+        let (signal_tx, _signal_rx) = channel();
+        {
+            let mut subscriptions = container.signal_subscriptions.lock().unwrap();
+            subscriptions.insert(
+                "test-interface".to_string(),
+                (
+                    vec!["test-instance-1".to_string(), "test-instance-2".to_string()],
+                    signal_tx.clone(),
+                ),
+            );
+            subscriptions.insert(
+                "removed-interface".to_string(),
+                (vec!["test-instance-1".to_string()], signal_tx),
+            );
+        }
+
+        container.remove_instance("test-instance-2").unwrap();
+
+        let subscriptions = container.signal_subscriptions.lock().unwrap();
+        assert_eq!(
+            subscriptions.keys().collect::<Vec<_>>(),
+            vec![&"test-interface".to_string()]
+        );
+        assert_eq!(
+            subscriptions["test-interface"].0,
+            vec!["test-instance-1".to_string()]
+        );
+    }
+
     #[test]
     fn test_container_signal_handler() {
         let (signal_tx, signal_rx) = signal_channel();
@@ -662,6 +1821,91 @@ pub mod tests {
         .unwrap();
     }
 
+    fn test_container_with_key_file(public_address: Option<String>) -> Result<Container, String> {
+        let mut keystore = Keystore::new_random();
+        let tempdir = tempdir().unwrap();
+        let key_file = tempdir.path().join("agent.keystore");
+        keystore.save(&key_file, "test passphrase").unwrap();
+
+        let mut config = load_configuration::<Configuration>(&test_toml()).unwrap();
+        config.agents[0].test_agent = None;
+        config.agents[0].key_file = key_file.to_string_lossy().to_string();
+        config.agents[0].public_address =
+            public_address.unwrap_or_else(|| keystore.address().to_string());
+
+        let mut container = Container::from_config(config);
+        container.dna_loader = test_dna_loader();
+        container.key_loader = Arc::new(Box::new(|file: &String| {
+            Keystore::load(Path::new(file), "test passphrase")
+        }));
+        container.load_config().map(|_| container)
+    }
+
+    #[test]
+    fn test_container_loads_key_files() {
+        let container = test_container_with_key_file(None).unwrap();
+        let agent_config = container.config().agent_by_id("test-agent-1").unwrap();
+        let context = container.instances()["test-instance-1"]
+            .read()
+            .unwrap()
+            .context()
+            .clone();
+        assert_eq!(context.agent_id.key, agent_config.public_address);
+        assert_eq!(context.agent_id.nick, "Holo Tester 1");
+    }
+
+    #[test]
+    fn test_container_derives_test_agent_keys_from_their_id() {
+        let container = test_container();
+        let agent_key = |instance_id: &str| {
+            container.instances()[instance_id]
+                .read()
+                .unwrap()
+                .context()
+                .agent_id
+                .key
+                .clone()
+        };
+        for (instance_id, agent_id) in vec![
+            ("test-instance-1", "test-agent-1"),
+            ("test-instance-2", "test-agent-2"),
+        ] {
+            assert_eq!(
+                agent_key(instance_id),
+                Keystore::generate_fake(agent_id).address().to_string()
+            );
+        }
+        assert_ne!(agent_key("test-instance-1"), agent_key("test-instance-2"));
+    }
+
+    #[test]
+    fn test_container_checks_the_public_address_of_test_agents() {
+        let mut config = load_configuration::<Configuration>(&test_toml()).unwrap();
+        config.agents[0].public_address = Keystore::generate_fake("test-agent-1")
+            .address()
+            .to_string();
+        let mut container = Container::from_config(config.clone());
+        container.dna_loader = test_dna_loader();
+        container.load_config().unwrap();
+
+        config.agents[0].public_address = Keystore::generate_fake("test-agent-2")
+            .address()
+            .to_string();
+        let mut container = Container::from_config(config);
+        container.dna_loader = test_dna_loader();
+        let error = container.load_config().err().unwrap();
+        assert!(error.contains("derived from its id"));
+        assert!(error.contains("test-agent-1"));
+    }
+
+    #[test]
+    fn test_container_rejects_key_files_of_other_agents() {
+        let other_address = Keystore::new_random().address().to_string();
+        let result = test_container_with_key_file(Some(other_address));
+        assert!(result.is_err());
+        assert!(result.err().unwrap().contains("holds the keys of"));
+    }
+
     pub fn callee_wat() -> String {
         r#"
 (module
@@ -785,6 +2029,250 @@ pub mod tests {
         dna
     }
 
+    /// A caller that declares a bridge to any DNA that can say hello like the callee
+    fn dynamic_caller_dna(presence: BridgePresence) -> Dna {
+        let mut dna = caller_dna();
+        let greeter_capability = callee_dna().zomes["greeter"].capabilities["public"].clone();
+        dna.zomes
+            .get_mut("main")
+            .unwrap()
+            .bridges
+            .push(dna::bridges::Bridge {
+                presence,
+                handle: String::from("test-callee"),
+                reference: dna::bridges::BridgeReference::Capability {
+                    capabilities: btreemap! { String::from("public") => greeter_capability },
+                },
+            });
+        dna
+    }
+
+    fn dynamic_bridge_toml(caller_dna_file: &str, with_callee: bool) -> String {
+        let callee = if with_callee {
+            r#"
+    [[instances]]
+    id = "greeter"
+    dna = "bridge-callee"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+    "#
+        } else {
+            ""
+        };
+        format!(
+            r#"
+    [[agents]]
+    id = "test-agent-1"
+    name = "Holo Tester 1"
+    key_file = "holo_tester.key"
+    test_agent = true
+
+    [[dnas]]
+    id = "bridge-callee"
+    file = "bridge/callee.dna"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[dnas]]
+    id = "bridge-caller"
+    file = "{}"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[instances]]
+    id = "bridge-caller"
+    dna = "bridge-caller"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+    {}
+    "#,
+            caller_dna_file, callee
+        )
+    }
+
+    fn load_dynamic_bridge_container(toml: &str) -> Result<Container, String> {
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        let mut container = Container::from_config(config);
+        container.dna_loader = test_dna_loader();
+        container.load_config().map(|_| container)
+    }
+
+    fn call_bridge(container: &Container) -> Result<JsonString, HolochainError> {
+        let caller_instance = container.instances()["bridge-caller"].clone();
+        let mut caller = caller_instance.write().unwrap();
+        caller.call(
+            "main",
+            Some(dna::capabilities::CapabilityCall::new(
+                String::from("main"),
+                Address::from("fake_token"),
+                None,
+            )),
+            "call_bridge",
+            "{}",
+        )
+    }
+
+    #[test]
+    fn test_container_resolves_dna_bridges() {
+        let mut container =
+            load_dynamic_bridge_container(&dynamic_bridge_toml("bridge/dynamic_caller.dna", true))
+                .unwrap();
+        container.start_all_instances().unwrap();
+        assert_eq!(
+            call_bridge(&container).unwrap(),
+            JsonString::from(RawString::from("Holo World"))
+        );
+        // resolved bridges don't end up in the configuration file
+        assert!(container.config().bridges.is_empty());
+    }
+
+    #[test]
+    fn test_container_refuses_to_remove_callees_of_required_dna_bridges() {
+        let mut container =
+            load_dynamic_bridge_container(&dynamic_bridge_toml("bridge/dynamic_caller.dna", true))
+                .unwrap();
+        let error = container.remove_instance("greeter").err().unwrap();
+        assert!(format!("{}", error).contains("test-callee"));
+        assert!(container.config().instance_by_id("greeter").is_some());
+        container.start_all_instances().unwrap();
+        assert_eq!(
+            call_bridge(&container).unwrap(),
+            JsonString::from(RawString::from("Holo World"))
+        );
+
+        let mut container =
+            load_dynamic_bridge_container(&dynamic_bridge_toml("bridge/optional_caller.dna", true))
+                .unwrap();
+        container.remove_instance("greeter").unwrap();
+        assert!(container.resolved_bridges.is_empty());
+        // the remaining configuration still resolves
+        let mut instance_config = container.config().instance_by_id("bridge-caller").unwrap();
+        instance_config.id = String::from("another-caller");
+        container.add_instance(instance_config).unwrap();
+        assert_eq!(container.instances().len(), 2);
+    }
+
+    #[test]
+    fn test_container_loads_each_dna_once() {
+        let config = load_configuration::<Configuration>(&dynamic_bridge_toml(
+            "bridge/dynamic_caller.dna",
+            true,
+        ))
+        .unwrap();
+        let mut container = Container::from_config(config);
+        let loaded = Arc::new(Mutex::new(Vec::new()));
+        let loaded_files = loaded.clone();
+        let mut loader = test_dna_loader();
+        container.dna_loader = Arc::new(Box::new(move |file: &String| {
+            loaded_files.lock().unwrap().push(file.clone());
+            Arc::get_mut(&mut loader).unwrap()(file)
+        })
+            as Box<FnMut(&String) -> Result<Dna, HolochainError> + Send + Sync>);
+        container.load_config().unwrap();
+        let mut instance_config = container.config().instance_by_id("bridge-caller").unwrap();
+        instance_config.id = String::from("another-caller");
+        container.add_instance(instance_config).unwrap();
+        assert_eq!(
+            *loaded.lock().unwrap(),
+            vec![
+                String::from("bridge/callee.dna"),
+                String::from("bridge/dynamic_caller.dna")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_container_requires_required_dna_bridges() {
+        let error =
+            load_dynamic_bridge_container(&dynamic_bridge_toml("bridge/dynamic_caller.dna", false))
+                .err()
+                .unwrap();
+        assert!(error.contains("test-callee"));
+        assert!(error.contains("bridge-caller"));
+
+        let container = load_dynamic_bridge_container(&dynamic_bridge_toml(
+            "bridge/optional_caller.dna",
+            false,
+        ))
+        .unwrap();
+        assert_eq!(container.instances().len(), 1);
+    }
+
+    #[test]
+    fn test_container_checks_configured_dna_bridges() {
+        let mut toml = dynamic_bridge_toml("bridge/dynamic_caller.dna", true);
+        toml.push_str(
+            r#"
+    [[dnas]]
+    id = "other"
+    file = "app_spec.hcpkg"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[instances]]
+    id = "other"
+    dna = "other"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+
+    [[bridges]]
+    caller_id = "bridge-caller"
+    callee_id = "other"
+    handle = "test-callee"
+    "#,
+        );
+        let error = load_dynamic_bridge_container(&toml).err().unwrap();
+        assert!(error.contains("test-callee"));
+        assert!(error.contains("other"));
+    }
+
+    #[test]
+    fn test_admin_add_bridge_checks_dna_bridges() {
+        let mut container =
+            load_dynamic_bridge_container(&dynamic_bridge_toml("bridge/dynamic_caller.dna", true))
+                .unwrap();
+        // the handle is taken by the bridge resolved from the caller's DNA
+        let bridge = Bridge {
+            caller_id: "bridge-caller".to_string(),
+            callee_id: "greeter".to_string(),
+            handle: "test-callee".to_string(),
+        };
+        assert!(container.add_bridge(bridge).is_err());
+        assert!(container.config().bridges.is_empty());
+
+        let mut toml = dynamic_bridge_toml("bridge/optional_caller.dna", false);
+        toml.push_str(
+            r#"
+    [[dnas]]
+    id = "other"
+    file = "app_spec.hcpkg"
+    hash = "Qm328wyq38924y"
+    skip_hash_check = true
+
+    [[instances]]
+    id = "other"
+    dna = "other"
+    agent = "test-agent-1"
+    [instances.storage]
+    type = "memory"
+    "#,
+        );
+        let mut container = load_dynamic_bridge_container(&toml).unwrap();
+        // the callee has to match the caller DNA's bridge reference
+        let bridge = Bridge {
+            caller_id: "bridge-caller".to_string(),
+            callee_id: "other".to_string(),
+            handle: "test-callee".to_string(),
+        };
+        let error = container.add_bridge(bridge).err().unwrap();
+        assert!(format!("{}", error).contains("does not match"));
+        assert!(container.config().bridges.is_empty());
+        assert!(container.resolved_bridges.is_empty());
+    }
+
     #[test]
     fn basic_bridge_call_roundtrip() {
         let config = load_configuration::<Configuration>(&test_toml()).unwrap();
@@ -794,7 +2282,7 @@ pub mod tests {
         container
             .start_all_instances()
             .expect("Instances must be spawnable");
-        let caller_instance = container.instances["bridge-caller"].clone();
+        let caller_instance = container.instances()["bridge-caller"].clone();
         let result = caller_instance
             .write()
             .unwrap()
@@ -813,5 +2301,4 @@ pub mod tests {
         // "Holo World" comes for the callee_wat above which runs in the callee instance
         assert_eq!(result, JsonString::from(RawString::from("Holo World")));
     }
-
 }
@@ -1,21 +1,28 @@
 use holochain_cas_implementations::{
-    cas::{file::FilesystemStorage, memory::MemoryStorage},
-    eav::{file::EavFileStorage, memory::EavMemoryStorage},
+    cas::{file::FilesystemStorage, lmdb::LmdbStorage, memory::MemoryStorage},
+    eav::{file::EavFileStorage, lmdb::EavLmdbStorage, memory::EavMemoryStorage},
     path::create_path_if_not_exists,
 };
 
 use holochain_core::{
+    agent::keystore::Keystore,
     context::Context,
     logger::{Logger, SimpleLogger},
+    network::NetworkTimeouts,
     persister::SimplePersister,
+    retention::ResultRetention,
     signal::SignalSender,
 };
 use holochain_core_types::{
-    agent::AgentId, cas::storage::ContentAddressableStorage, eav::EntityAttributeValueStorage,
-    error::HolochainError, json::JsonString,
+    agent::AgentId,
+    cas::{content::AddressableContent, storage::ContentAddressableStorage},
+    eav::EntityAttributeValueStorage,
+    error::HolochainError,
+    json::JsonString,
 };
 use holochain_net::p2p_config::P2pConfig;
 use jsonrpc_ws_server::jsonrpc_core::IoHandler;
+use serde_json::Value;
 use std::sync::{Arc, Mutex, RwLock};
 
 /// This type helps building [context objects](struct.Context.html) that need to be
@@ -28,14 +35,19 @@ use std::sync::{Arc, Mutex, RwLock};
 /// `spawn()` to retrieve the context.
 pub struct ContextBuilder {
     agent_id: Option<AgentId>,
+    keystore: Option<Keystore>,
     logger: Option<Arc<Mutex<Logger>>>,
     // Persister is currently set to a reasonable default in spawn().
     // TODO: add with_persister() function to ContextBuilder.
     //persister: Option<Arc<Mutex<Persister>>>,
     chain_storage: Option<Arc<RwLock<ContentAddressableStorage>>>,
     dht_storage: Option<Arc<RwLock<ContentAddressableStorage>>>,
+    encrypted_storage: Option<Arc<RwLock<ContentAddressableStorage>>>,
     eav_storage: Option<Arc<RwLock<EntityAttributeValueStorage>>>,
     network_config: Option<JsonString>,
+    network_timeouts: Option<NetworkTimeouts>,
+    result_retention: Option<ResultRetention>,
+    dna_property_overrides: Option<Value>,
     container_api: Option<Arc<RwLock<IoHandler>>>,
     signal_tx: Option<SignalSender>,
 }
@@ -44,47 +56,101 @@ impl ContextBuilder {
     pub fn new() -> Self {
         ContextBuilder {
             agent_id: None,
+            keystore: None,
             logger: None,
             chain_storage: None,
             dht_storage: None,
+            encrypted_storage: None,
             eav_storage: None,
             network_config: None,
+            network_timeouts: None,
+            result_retention: None,
+            dna_property_overrides: None,
             container_api: None,
             signal_tx: None,
         }
     }
 
-    /// Sets the agent of the context that gets built.
-    pub fn with_agent(mut self, agent_id: AgentId) -> Self {
+    /// Sets the agent of the context that gets built together with the keystore holding
+    /// the keys it signs its chain headers with, as returned by `keystore.agent_id()`.
+    /// Fails if the agent's address is not the one of the keystore's keys, as other nodes
+    /// would reject its signatures.
+    pub fn with_agent(
+        mut self,
+        agent_id: AgentId,
+        keystore: Keystore,
+    ) -> Result<Self, HolochainError> {
+        if agent_id.address() != keystore.address() {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Agent {} does not have the keys of the keystore of {}",
+                agent_id.address(),
+                keystore.address()
+            )));
+        }
         self.agent_id = Some(agent_id);
+        self.keystore = Some(keystore);
+        Ok(self)
+    }
+
+    /// Sets the keystore holding the keys the agent signs its chain headers with,
+    /// for an agent called "alice" unless `with_agent()` sets another one.
+    pub fn with_keystore(mut self, keystore: Keystore) -> Self {
+        self.agent_id = None;
+        self.keystore = Some(keystore);
         self
     }
 
-    /// Sets all three storages, chain, DHT and EAV storage, to transient memory implementations.
-    /// Chain and DHT storages get set to the same memory CAS.
+    /// Sets all storages, chain, DHT, encrypted entry and EAV storage, to transient memory
+    /// implementations. Chain and DHT storages get set to the same memory CAS.
     pub fn with_memory_storage(mut self) -> Self {
         let cas = Arc::new(RwLock::new(MemoryStorage::new()));
         let eav = Arc::new(RwLock::new(EavMemoryStorage::new()));
         self.chain_storage = Some(cas.clone());
         self.dht_storage = Some(cas);
+        self.encrypted_storage = Some(Arc::new(RwLock::new(MemoryStorage::new())));
         self.eav_storage = Some(eav);
         self
     }
 
-    /// Sets all three storages, chain, DHT and EAV storage, to persistent file based implementations.
-    /// Chain and DHT storages get set to the same file CAS.
+    /// Sets all storages, chain, DHT, encrypted entry and EAV storage, to persistent file based
+    /// implementations. Chain and DHT storages get set to the same file CAS.
     /// Returns an error if no file storage could be spawned on the given path.
     pub fn with_file_storage<T: Into<String>>(mut self, path: T) -> Result<Self, HolochainError> {
         let path: String = path.into();
         let cas_path = format!("{}/cas", path);
+        let encrypted_path = format!("{}/encrypted", path);
         let eav_path = format!("{}/eav", path);
         create_path_if_not_exists(&cas_path)?;
+        create_path_if_not_exists(&encrypted_path)?;
         create_path_if_not_exists(&eav_path)?;
 
         let file_storage = Arc::new(RwLock::new(FilesystemStorage::new(&cas_path)?));
         let eav_storage = Arc::new(RwLock::new(EavFileStorage::new(eav_path)?));
         self.chain_storage = Some(file_storage.clone());
         self.dht_storage = Some(file_storage);
+        self.encrypted_storage = Some(Arc::new(RwLock::new(FilesystemStorage::new(
+            &encrypted_path,
+        )?)));
+        self.eav_storage = Some(eav_storage);
+        Ok(self)
+    }
+
+    /// Sets all storages, chain, DHT, encrypted entry and EAV storage, to persistent LMDB based
+    /// implementations. Content, encrypted entries and EAV indexes each live in a single
+    /// database file. Chain and DHT storages get set to the same LMDB CAS.
+    /// Returns an error if the databases could not be opened on the given path.
+    pub fn with_lmdb_storage<T: Into<String>>(mut self, path: T) -> Result<Self, HolochainError> {
+        let path: String = path.into();
+        let cas_path = format!("{}/cas.lmdb", path);
+        let encrypted_path = format!("{}/encrypted.lmdb", path);
+        let eav_path = format!("{}/eav.lmdb", path);
+        create_path_if_not_exists(&path)?;
+
+        let lmdb_storage = Arc::new(RwLock::new(LmdbStorage::new(&cas_path)?));
+        let eav_storage = Arc::new(RwLock::new(EavLmdbStorage::new(&eav_path)?));
+        self.chain_storage = Some(lmdb_storage.clone());
+        self.dht_storage = Some(lmdb_storage);
+        self.encrypted_storage = Some(Arc::new(RwLock::new(LmdbStorage::new(&encrypted_path)?)));
         self.eav_storage = Some(eav_storage);
         Ok(self)
     }
@@ -95,6 +161,25 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets how long network requests wait for an answer.
+    pub fn with_network_timeouts(mut self, network_timeouts: NetworkTimeouts) -> Self {
+        self.network_timeouts = Some(network_timeouts);
+        self
+    }
+
+    /// Sets how long and how many unconsumed results the instance's state keeps.
+    pub fn with_result_retention(mut self, result_retention: ResultRetention) -> Self {
+        self.result_retention = Some(result_retention);
+        self
+    }
+
+    /// Sets overrides of the DNA's properties that zomes of this instance see
+    /// instead of the DNA's own values.
+    pub fn with_dna_property_overrides(mut self, overrides: Value) -> Self {
+        self.dna_property_overrides = Some(overrides);
+        self
+    }
+
     pub fn with_container_api(mut self, api_handler: IoHandler) -> Self {
         self.container_api = Some(Arc::new(RwLock::new(api_handler)));
         self
@@ -111,7 +196,8 @@ impl ContextBuilder {
     }
 
     /// Actually creates the context.
-    /// Defaults to memory storages, a mock network config and a fake agent called "alice".
+    /// Defaults to memory storages, a mock network config with default timeouts
+    /// and an agent called "alice" with a freshly generated keystore.
     /// The logger gets set to SimpleLogger.
     /// The persister gets set to SimplePersister based on the chain storage.
     pub fn spawn(self) -> Context {
@@ -124,8 +210,11 @@ impl ContextBuilder {
         let eav_storage = self
             .eav_storage
             .unwrap_or(Arc::new(RwLock::new(EavMemoryStorage::new())));
-        Context::new(
-            self.agent_id.unwrap_or(AgentId::generate_fake("alice")),
+        let keystore = self.keystore.unwrap_or_else(Keystore::new_random);
+        let agent_id = self.agent_id.unwrap_or_else(|| keystore.agent_id("alice"));
+        let mut context = Context::new(
+            agent_id,
+            Arc::new(Mutex::new(keystore)),
             self.logger.unwrap_or(Arc::new(Mutex::new(SimpleLogger {}))),
             Arc::new(Mutex::new(SimplePersister::new(chain_storage.clone()))),
             chain_storage,
@@ -136,7 +225,14 @@ impl ContextBuilder {
             ))),
             self.container_api,
             self.signal_tx,
-        )
+        );
+        if let Some(encrypted_storage) = self.encrypted_storage {
+            context.encrypted_storage = encrypted_storage;
+        }
+        context.network_timeouts = self.network_timeouts.unwrap_or_default();
+        context.result_retention = self.result_retention.unwrap_or_default();
+        context.dna_property_overrides = self.dna_property_overrides;
+        context
     }
 }
 
@@ -148,7 +244,11 @@ mod tests {
     #[test]
     fn vanilla() {
         let context = ContextBuilder::new().spawn();
-        assert_eq!(context.agent_id, AgentId::generate_fake("alice"));
+        assert_eq!(context.agent_id.nick, "alice");
+        assert_eq!(
+            context.agent_id.address(),
+            context.keystore.lock().unwrap().address()
+        );
         assert_eq!(
             context.network_config,
             JsonString::from(String::from(P2pConfig::DEFAULT_MOCK_CONFIG))
@@ -157,9 +257,31 @@ mod tests {
 
     #[test]
     fn with_agent() {
-        let agent = AgentId::generate_fake("alice");
-        let context = ContextBuilder::new().with_agent(agent.clone()).spawn();
+        let keystore = Keystore::new_random();
+        let agent = keystore.agent_id("bob");
+        let context = ContextBuilder::new()
+            .with_agent(agent.clone(), keystore)
+            .unwrap()
+            .spawn();
         assert_eq!(context.agent_id, agent);
+        assert_eq!(
+            context.agent_id.address(),
+            context.keystore.lock().unwrap().address()
+        );
+
+        // there are no keys for the fake agent in another keystore
+        assert!(ContextBuilder::new()
+            .with_agent(AgentId::generate_fake("alice"), Keystore::new_random())
+            .is_err());
+    }
+
+    #[test]
+    fn with_keystore() {
+        let keystore = Keystore::new_random();
+        let address = keystore.address();
+        let context = ContextBuilder::new().with_keystore(keystore).spawn();
+        assert_eq!(context.agent_id.nick, "alice");
+        assert_eq!(context.agent_id.address(), address);
     }
 
     #[test]
@@ -171,14 +293,62 @@ mod tests {
         assert_eq!(context.network_config, net);
     }
 
+    #[test]
+    fn with_network_timeouts() {
+        let context = ContextBuilder::new().spawn();
+        assert_eq!(context.network_timeouts, NetworkTimeouts::default());
+
+        let timeouts = NetworkTimeouts {
+            get_entry: 1000,
+            get_links: 2000,
+            send: 3000,
+        };
+        let context = ContextBuilder::new()
+            .with_network_timeouts(timeouts.clone())
+            .spawn();
+        assert_eq!(context.network_timeouts, timeouts);
+    }
+
+    #[test]
+    fn with_result_retention() {
+        let context = ContextBuilder::new().spawn();
+        assert_eq!(context.result_retention, ResultRetention::default());
+
+        let retention = ResultRetention {
+            ttl: 1000,
+            max_results: 10,
+        };
+        let context = ContextBuilder::new()
+            .with_result_retention(retention.clone())
+            .spawn();
+        assert_eq!(context.result_retention, retention);
+    }
+
+    #[test]
+    fn with_dna_property_overrides() {
+        let context = ContextBuilder::new().spawn();
+        assert_eq!(context.dna_property_overrides, None);
+
+        let overrides = json!({"max_posts": 20});
+        let context = ContextBuilder::new()
+            .with_dna_property_overrides(overrides.clone())
+            .spawn();
+        assert_eq!(context.dna_property_overrides, Some(overrides));
+    }
+
     #[test]
     fn smoke_tests() {
         let _ = ContextBuilder::new().with_memory_storage().spawn();
         let temp = tempdir().expect("test was supposed to create temp dir");
         let temp_path = String::from(temp.path().to_str().expect("temp dir could not be string"));
         let _ = ContextBuilder::new()
-            .with_file_storage(temp_path)
+            .with_file_storage(temp_path.clone())
             .expect("Filestorage should get instantiated with tempdir")
             .spawn();
+        let lmdb_path = format!("{}/lmdb", temp_path);
+        let _ = ContextBuilder::new()
+            .with_lmdb_storage(lmdb_path)
+            .expect("LMDB storage should get instantiated with tempdir")
+            .spawn();
     }
 }
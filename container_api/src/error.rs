@@ -45,6 +45,15 @@ impl From<HolochainError> for HolochainInstanceError {
     }
 }
 
+impl From<HolochainInstanceError> for HolochainError {
+    fn from(error: HolochainInstanceError) -> Self {
+        match error {
+            HolochainInstanceError::InternalFailure(error) => error,
+            other => HolochainError::ErrorGeneric(other.description().to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -106,4 +115,19 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// show From<HolochainInstanceError> for HolochainError
+    fn holochain_error_from_holochain_instance_error_test() {
+        assert_eq!(
+            HolochainError::from(HolochainInstanceError::InternalFailure(
+                HolochainError::DnaMissing
+            )),
+            HolochainError::DnaMissing,
+        );
+        assert_eq!(
+            HolochainError::from(HolochainInstanceError::InstanceNotActiveYet),
+            HolochainError::ErrorGeneric("Holochain instance is not active yet.".to_string()),
+        );
+    }
+
 }
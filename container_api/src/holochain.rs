@@ -13,9 +13,9 @@
 //! extern crate holochain_cas_implementations;
 //! extern crate tempfile;
 //! use holochain_container_api::{*, context_builder::ContextBuilder};
+//! use holochain_core::agent::keystore::Keystore;
 //! use holochain_core_types::{
 //!     cas::content::Address,
-//!     agent::AgentId,
 //!     dna::{Dna, capabilities::CapabilityCall},
 //!     json::JsonString};
 //! use std::sync::Arc;
@@ -30,9 +30,10 @@
 //! let dna = Dna::new();
 //! let dir = tempdir().unwrap();
 //! let storage_directory_path = dir.path().to_str().unwrap();
-//! let agent = AgentId::generate_fake("bob");
+//! let keystore = Keystore::generate_fake("bob");
 //! let context = ContextBuilder::new()
-//!     .with_agent(agent)
+//!     .with_agent(keystore.agent_id("bob"), keystore)
+//!     .expect("The agent of a keystore has its keys")
 //!     .with_file_storage(storage_directory_path)
 //!     .expect("Tempdir should be accessible")
 //!     .spawn();
@@ -60,15 +61,22 @@
 use crate::error::{HolochainInstanceError, HolochainResult};
 use futures::executor::block_on;
 use holochain_core::{
+    action::{Action, ActionWrapper},
+    agent::chain_archive::ChainArchive,
     context::Context,
     instance::Instance,
+    logger::LogLevel,
     nucleus::{call_and_wait_for_result, ZomeFnCall},
-    persister::{Persister, SimplePersister},
-    state::State,
-    workflows::application,
+    persister::Persister,
+    state::{State, StateSnapshot},
+    workflows::{application, author_entry::author_entry},
 };
 use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    chain_migrate::ChainMigrate,
     dna::{capabilities::CapabilityCall, Dna},
+    entry::Entry,
     error::HolochainError,
     json::JsonString,
 };
@@ -84,11 +92,56 @@ pub struct Holochain {
 
 impl Holochain {
     /// create a new Holochain instance
+    /// If the context's storage holds the persisted state of an instance that ran before,
+    /// that state gets restored and genesis does not run again.
+    /// Fails if that instance ran another DNA than the given one or for another agent
+    /// than the context's.
     pub fn new(dna: Dna, context: Arc<Context>) -> HolochainResult<Self> {
-        let instance = Instance::new(context.clone());
+        let instance = match Self::load_state(&context)? {
+            Some(state) => {
+                Self::check_agent(&state, &context)?;
+                if let Some(persisted_dna) = state.nucleus().dna.as_ref() {
+                    let persisted_address = persisted_dna.address();
+                    if persisted_address != dna.address() {
+                        return Err(HolochainInstanceError::InternalFailure(
+                            HolochainError::ErrorGeneric(format!(
+                                "The storage holds an instance of DNA {}, not of the given DNA {}",
+                                persisted_address,
+                                dna.address()
+                            )),
+                        ));
+                    }
+                }
+                Instance::from_state(state)
+            }
+            None => Instance::new(context.clone()),
+        };
         Self::from_dna_and_context_and_instance(dna, context, instance)
     }
 
+    fn load_state(context: &Arc<Context>) -> Result<Option<State>, HolochainError> {
+        context.persister.lock()?.load(context.clone())
+    }
+
+    /// Makes sure a persisted state holds the chain of the context's agent, so no agent
+    /// continues (and signs headers onto) the chain of another one
+    fn check_agent(state: &State, context: &Arc<Context>) -> Result<(), HolochainError> {
+        match state.agent().get_agent_address() {
+            Ok(agent_address) => {
+                let own_address = Entry::AgentId(context.agent_id.clone()).address();
+                if agent_address != own_address {
+                    return Err(HolochainError::ErrorGeneric(format!(
+                        "The storage holds the chain of agent entry {}, not of agent {}",
+                        agent_address, context.agent_id.key
+                    )));
+                }
+                Ok(())
+            }
+            // genesis did not get to commit the agent
+            Err(_) => Ok(()),
+        }
+    }
+
     fn from_dna_and_context_and_instance(
         dna: Dna,
         context: Arc<Context>,
@@ -103,7 +156,7 @@ impl Holochain {
         ));
         match result {
             Ok(new_context) => {
-                context.log(format!("{} instantiated", name));
+                context.log(LogLevel::Info, format!("{} instantiated", name));
                 let hc = Holochain {
                     instance,
                     context: new_context.clone(),
@@ -115,11 +168,12 @@ impl Holochain {
         }
     }
 
+    /// Restores a Holochain instance from the state persisted in the context's storage.
+    /// Fails if it holds the chain of another agent than the context's.
     pub fn load(_path: String, context: Arc<Context>) -> Result<Self, HolochainError> {
-        let persister = SimplePersister::new(context.dht_storage.clone());
-        let loaded_state = persister
-            .load(context.clone())?
-            .unwrap_or(State::new(context.clone()));
+        let loaded_state =
+            Self::load_state(&context)?.unwrap_or_else(|| State::new(context.clone()));
+        Self::check_agent(&loaded_state, &context)?;
         let mut instance = Instance::from_state(loaded_state.clone());
         instance.start_action_loop(context.clone());
         let new_context = block_on(application::initialize(&instance, None, context.clone()))?;
@@ -139,13 +193,13 @@ impl Holochain {
         Ok(())
     }
 
-    /// deactivate the Holochain instance
+    /// deactivate the Holochain instance and persist its state
     pub fn stop(&mut self) -> Result<(), HolochainInstanceError> {
         if !self.active {
             return Err(HolochainInstanceError::InstanceNotActiveYet);
         }
         self.active = false;
-        Ok(())
+        Ok(self.instance.save_state(&self.context)?)
     }
 
     /// call a function in a zome
@@ -163,6 +217,30 @@ impl Holochain {
         Ok(call_and_wait_for_result(zome_call, &mut self.instance)?)
     }
 
+    /// call a function in a zome as the instance's agent, who may call any function
+    /// with its address as token.
+    /// This is for hosts that embed the instance and act as its agent, like test runners,
+    /// never for calls coming in from the outside, which have to go through `call()`.
+    pub fn call_as_agent(
+        &mut self,
+        zome: &str,
+        cap_name: &str,
+        fn_name: &str,
+        params: &str,
+    ) -> HolochainResult<JsonString> {
+        if !self.active {
+            return Err(HolochainInstanceError::InstanceNotActiveYet);
+        }
+        let agent_id = self.context.agent_id.clone();
+        let cap = CapabilityCall::new(
+            cap_name.to_string(),
+            Address::from(agent_id.key.clone()),
+            Some(agent_id.address()),
+        );
+        let zome_call = ZomeFnCall::new_internal(&zome, Some(cap), &fn_name, String::from(params));
+        Ok(call_and_wait_for_result(zome_call, &mut self.instance)?)
+    }
+
     /// checks to see if an instance is active
     pub fn active(&self) -> bool {
         self.active
@@ -176,6 +254,85 @@ impl Holochain {
     pub fn context(&self) -> &Arc<Context> {
         &self.context
     }
+
+    /// The address of the DNA this instance runs
+    pub fn dna_address(&self) -> Option<Address> {
+        self.instance
+            .state()
+            .nucleus()
+            .dna()
+            .map(|dna| dna.address())
+    }
+
+    /// The Close migration the chain of this instance got closed with, with its header
+    pub fn chain_closure(&self) -> HolochainResult<Option<(ChainHeader, ChainMigrate)>> {
+        Ok(self.instance.state().agent().chain_closure()?)
+    }
+
+    /// Commits the Open migration that continues the chain of the given DNA, which got closed
+    /// by the Close migration with the given header, unless the chain holds it already.
+    /// Returns whether it got committed.
+    pub fn open_migrated_chain(
+        &mut self,
+        old_dna_address: Address,
+        closing_header: ChainHeader,
+    ) -> HolochainResult<bool> {
+        let entry = Entry::ChainMigrate(ChainMigrate::open(old_dna_address, closing_header));
+        if self
+            .instance
+            .state()
+            .agent()
+            .get_header_for_entry(&entry)
+            .is_some()
+        {
+            return Ok(false);
+        }
+        block_on(author_entry(&entry, None, &self.context))?;
+        Ok(true)
+    }
+
+    /// Collects the whole source chain of this instance into an archive
+    pub fn export_chain(&self) -> HolochainResult<ChainArchive> {
+        let agent = self.instance.state().agent();
+        Ok(ChainArchive::export(
+            &agent.chain(),
+            &agent.top_chain_header(),
+        )?)
+    }
+
+    /// Replaces the source chain of this instance with the one of the archive, which has
+    /// to be a chain of the same DNA and agent. The instance must not be running.
+    pub fn import_chain(&mut self, archive: &ChainArchive) -> HolochainResult<()> {
+        if self.active {
+            return Err(HolochainInstanceError::InstanceAlreadyActive);
+        }
+        let mut own_entries = vec![Entry::AgentId(self.context.agent_id.clone())];
+        if let Some(dna) = self.instance.state().nucleus().dna() {
+            own_entries.push(Entry::Dna(dna));
+        }
+        archive.check_owner(&own_entries)?;
+
+        let top_chain_header = archive.import(&self.context.chain_storage)?;
+        // The old instance stops talking to the network, which the new one joins again,
+        // and can't persist its state over the imported one.
+        self.instance
+            .dispatch_and_wait(ActionWrapper::new(Action::ShutdownNetwork));
+        self.instance.stop_action_loop();
+        let mut snapshot = StateSnapshot::from(&*self.instance.state());
+        snapshot.top_chain_header = top_chain_header;
+        let mut instance =
+            Instance::from_state(State::try_from_snapshot(self.context.clone(), snapshot)?);
+        instance.start_action_loop(self.context.clone());
+        let new_context = block_on(application::initialize(
+            &instance,
+            None,
+            self.context.clone(),
+        ))?;
+        instance.save_state(&new_context)?;
+        self.instance = instance;
+        self.context = new_context;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -186,11 +343,15 @@ mod tests {
     use context_builder::ContextBuilder;
     use holochain_core::{
         action::Action,
+        agent::keystore::Keystore,
         context::Context,
         nucleus::ribosome::{callback::Callback, Defn},
         signal::{signal_channel, SignalReceiver},
     };
-    use holochain_core_types::{agent::AgentId, cas::content::Address, dna::Dna};
+    use holochain_core_types::{
+        cas::content::Address,
+        dna::{capabilities::CapabilityType, Dna},
+    };
     use holochain_wasm_utils::wasm_target_dir;
     use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
@@ -210,16 +371,30 @@ mod tests {
         Arc<Mutex<test_utils::TestLogger>>,
         SignalReceiver,
     ) {
-        let agent = AgentId::generate_fake(agent_name);
+        test_context_with_storage(agent_name, tempdir().unwrap().path().to_str().unwrap())
+    }
+
+    fn test_context_with_storage(
+        agent_name: &str,
+        storage_path: &str,
+    ) -> (
+        Arc<Context>,
+        Arc<Mutex<test_utils::TestLogger>>,
+        SignalReceiver,
+    ) {
+        // the same agent every time, so it can restore its persisted chain
+        let keystore = Keystore::generate_fake(agent_name);
+        let agent = keystore.agent_id(agent_name);
         let (signal_tx, signal_rx) = signal_channel();
         let logger = test_utils::test_logger();
         (
             Arc::new(
                 ContextBuilder::new()
-                    .with_agent(agent)
+                    .with_agent(agent, keystore)
+                    .unwrap()
                     .with_logger(logger.clone())
                     .with_signals(signal_tx)
-                    .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
+                    .with_file_storage(storage_path)
                     .unwrap()
                     .spawn(),
             ),
@@ -228,8 +403,6 @@ mod tests {
         )
     }
 
-    use std::{fs::File, io::prelude::*, path::MAIN_SEPARATOR};
-
     fn example_api_wasm_path() -> String {
         format!(
             "{}/wasm32-unknown-unknown/release/example_api_wasm.wasm",
@@ -268,18 +441,20 @@ mod tests {
         assert!(format!("{:?}", *test_logger).contains("\"TestApp instantiated\""));
     }
 
-    fn write_agent_state_to_file() -> String {
-        let tempdir = tempdir().unwrap();
-        let path = tempdir.path().to_str().unwrap();
-        let tempfile = vec![path, "Agentstate.txt"].join(&*MAIN_SEPARATOR.to_string());
-        let mut file = File::create(tempfile).unwrap();
-        file.write_all(b"{\"top_chain_header\":{\"entry_type\":\"AgentId\",\"entry_address\":\"Qma6RfzvZRL127UCEVEktPhQ7YSS1inxEFw7SjEsfMJcrq\",\"sources\":[\"sandwich--------------------------------------------------------------------------AAAEqzh28L\"],\"entry_signatures\":[\"fake-signature\"],\"link\":null,\"link_same_type\":null,\"timestamp\":\"2018-10-11T03:23:38+00:00\"}}").unwrap();
-        path.to_string()
-    }
     #[test]
     fn can_load() {
-        let path = write_agent_state_to_file();
-        let (context, _, _) = test_context("bob");
+        let storage = tempdir().unwrap();
+        let path = storage.path().to_str().unwrap().to_string();
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        let top_chain_header = hc.state().unwrap().agent().top_chain_header();
+        assert!(top_chain_header.is_some());
+        hc.start().unwrap();
+        hc.stop().unwrap();
+
+        let (context, _, _) = test_context_with_storage("bob", &path);
         let result = Holochain::load(path, context.clone());
         assert!(result.is_ok());
         let loaded_holo = result.unwrap();
@@ -289,6 +464,145 @@ mod tests {
         assert_eq!(network_state.agent_id.is_some(), true);
         assert_eq!(network_state.dna_address.is_some(), true);
         assert!(loaded_holo.instance.state().nucleus().has_initialized());
+        assert_eq!(loaded_holo.instance.state().nucleus().dna(), Some(dna));
+        // genesis did not commit the DNA and agent again
+        assert_eq!(
+            loaded_holo.instance.state().agent().top_chain_header(),
+            top_chain_header
+        );
+    }
+
+    #[test]
+    fn new_restores_persisted_state() {
+        let storage = tempdir().unwrap();
+        let path = storage.path().to_str().unwrap().to_string();
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        let hc = Holochain::new(dna.clone(), context).unwrap();
+        let top_chain_header = hc.state().unwrap().agent().top_chain_header();
+
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        let restored_holo = Holochain::new(dna, context).unwrap();
+        assert!(restored_holo.instance.state().nucleus().has_initialized());
+        assert_eq!(
+            restored_holo.instance.state().agent().top_chain_header(),
+            top_chain_header
+        );
+    }
+
+    #[test]
+    fn new_fails_for_another_dna_than_the_persisted_one() {
+        let storage = tempdir().unwrap();
+        let path = storage.path().to_str().unwrap().to_string();
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        Holochain::new(dna.clone(), context).unwrap();
+
+        let mut other_dna = dna.clone();
+        other_dna.name = "OtherApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        let result = Holochain::new(other_dna.clone(), context);
+        assert_eq!(
+            result.err(),
+            Some(HolochainInstanceError::InternalFailure(
+                HolochainError::ErrorGeneric(format!(
+                    "The storage holds an instance of DNA {}, not of the given DNA {}",
+                    dna.address(),
+                    other_dna.address()
+                ))
+            ))
+        );
+    }
+
+    #[test]
+    fn restoring_fails_for_another_agent() {
+        let storage = tempdir().unwrap();
+        let path = storage.path().to_str().unwrap().to_string();
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", &path);
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        hc.start().unwrap();
+        hc.stop().unwrap();
+
+        let (context, _, _) = test_context_with_storage("alice", &path);
+        let agent_address =
+            Entry::AgentId(Keystore::generate_fake("bob").agent_id("bob")).address();
+        let error = HolochainError::ErrorGeneric(format!(
+            "The storage holds the chain of agent entry {}, not of agent {}",
+            agent_address, context.agent_id.key
+        ));
+        assert_eq!(
+            Holochain::new(dna, context.clone()).err(),
+            Some(HolochainInstanceError::InternalFailure(error.clone()))
+        );
+        assert_eq!(Holochain::load(path, context).err(), Some(error));
+    }
+
+    fn test_context_with_keystore(keystore: Keystore, storage_path: &str) -> Arc<Context> {
+        Arc::new(
+            ContextBuilder::new()
+                .with_agent(keystore.agent_id("bob"), keystore)
+                .unwrap()
+                .with_file_storage(storage_path)
+                .unwrap()
+                .spawn(),
+        )
+    }
+
+    #[test]
+    fn can_export_and_import_chain() {
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let storage = tempdir().unwrap();
+        let context = test_context_with_keystore(
+            Keystore::generate_fake("bob"),
+            storage.path().to_str().unwrap(),
+        );
+        let hc = Holochain::new(dna.clone(), context).unwrap();
+        let archive = hc.export_chain().unwrap();
+        assert_eq!(archive.items().len(), 2);
+        assert_eq!(
+            archive.top_chain_header(),
+            hc.state().unwrap().agent().top_chain_header()
+        );
+
+        // another machine running the same DNA for the same agent
+        let other_storage = tempdir().unwrap();
+        let path = other_storage.path().to_str().unwrap().to_string();
+        let context = test_context_with_keystore(Keystore::generate_fake("bob"), &path);
+        let mut other_hc = Holochain::new(dna.clone(), context).unwrap();
+        other_hc.start().unwrap();
+        assert_eq!(
+            other_hc.import_chain(&archive),
+            Err(HolochainInstanceError::InstanceAlreadyActive)
+        );
+        other_hc.stop().unwrap();
+        other_hc.import_chain(&archive).unwrap();
+        assert_eq!(
+            other_hc.state().unwrap().agent().top_chain_header(),
+            archive.top_chain_header()
+        );
+        assert_eq!(other_hc.export_chain().unwrap(), archive);
+        assert!(other_hc.state().unwrap().network().initialized().is_ok());
+
+        // the imported chain survives a restart
+        let context = test_context_with_keystore(Keystore::generate_fake("bob"), &path);
+        let restored_hc = Holochain::new(dna.clone(), context).unwrap();
+        assert_eq!(
+            restored_hc.state().unwrap().agent().top_chain_header(),
+            archive.top_chain_header()
+        );
+
+        // but another agent can't take it over
+        let context = test_context_with_keystore(
+            Keystore::generate_fake("alice"),
+            tempdir().unwrap().path().to_str().unwrap(),
+        );
+        let mut alice_hc = Holochain::new(dna, context).unwrap();
+        assert!(alice_hc.import_chain(&archive).is_err());
     }
 
     #[test]
@@ -382,9 +696,8 @@ mod tests {
         assert!(!hc.active());
     }
 
-    #[test]
-    fn can_call() {
-        let wat = r#"
+    fn holo_world_wat() -> &'static str {
+        r#"
 (module
  (memory 1)
  (export "memory" (memory 0))
@@ -396,8 +709,12 @@ mod tests {
        "{\"holo\":\"world\"}"
        )
  )
-"#;
-        let dna = create_test_dna_with_wat("test_zome", "test_cap", Some(wat));
+"#
+    }
+
+    #[test]
+    fn can_call() {
+        let dna = create_test_dna_with_wat("test_zome", "test_cap", Some(holo_world_wat()));
         let (context, _, _) = test_context("bob");
         let mut hc = Holochain::new(dna.clone(), context).unwrap();
 
@@ -419,6 +736,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_call_as_agent() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", Some(holo_world_wat()));
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .capabilities
+            .get_mut("test_cap")
+            .unwrap()
+            .cap_type = CapabilityType::Assigned;
+        let (context, _, _) = test_context("bob");
+        let mut hc = Holochain::new(dna.clone(), context.clone()).unwrap();
+        hc.start().expect("couldn't start");
+
+        // calls from the outside can't use the agent's address as token
+        let agent_call = CapabilityCall::new(
+            "test_cap".to_string(),
+            Address::from(context.agent_id.key.clone()),
+            Some(context.agent_id.address()),
+        );
+        assert_eq!(
+            hc.call("test_zome", Some(agent_call), "main", ""),
+            Err(HolochainInstanceError::InternalFailure(
+                HolochainError::CapabilityCheckFailed
+            ))
+        );
+
+        assert_eq!(
+            hc.call_as_agent("test_zome", "test_cap", "main", ""),
+            Ok(JsonString::from("{\"holo\":\"world\"}"))
+        );
+    }
+
     #[test]
     fn can_get_state() {
         let dna = Dna::new();
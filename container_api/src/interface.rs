@@ -1,15 +1,23 @@
-use holochain_core::state::State;
-use holochain_core_types::{cas::content::Address, dna::capabilities::CapabilityCall};
+use holochain_core::{agent::chain_archive::ChainArchive, signal::UserSignal, state::State};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    dna::capabilities::{CapabilityCall, CapabilityRequestParams, CAP_REQUEST_PARAMS_KEY},
+    error::HolochainError,
+};
 use Holochain;
 
-use jsonrpc_ws_server::jsonrpc_core::{self, IoHandler, Value};
+use jsonrpc_ws_server::jsonrpc_core::{self, ErrorCode, IoHandler, Params, Value};
 use serde_json;
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    path::Path,
+    sync::{mpsc::Receiver, Arc, Mutex, RwLock, Weak},
 };
 
-use config::InstanceConfiguration;
+use config::{
+    AgentConfiguration, Bridge, DnaConfiguration, InstanceConfiguration, InterfaceConfiguration,
+};
+use container::Container;
 
 pub type InterfaceError = String;
 pub type InstanceMap = HashMap<String, Arc<RwLock<Holochain>>>;
@@ -25,7 +33,16 @@ pub trait DispatchRpc {
 /// Examples for method names are:
 /// {instance_id}/{zome}/{cap}/{func} -> a zome call
 /// info/list_instances               -> Map of InstanceConfigs, keyed by ID
-/// admin/...                         -> TODO
+/// admin/chain/export                -> Writes the source chain of an instance to an archive file
+/// admin/chain/import                -> Replaces the source chain of a stopped instance
+/// admin/dna/install                 -> Adds a DNA configuration
+/// admin/agent/add                   -> Adds an agent configuration
+/// admin/instance/add                -> Adds and creates an instance
+/// admin/instance/remove             -> Stops and removes an instance
+/// admin/instance/start              -> Starts an instance
+/// admin/instance/stop               -> Stops an instance
+/// admin/interface/add               -> Adds and starts an interface
+/// admin/bridge/add                  -> Adds a bridge between two running instances
 ///
 /// Each interface has their own handler, and each may be configured differently.
 /// This builder makes it convenient to create handlers with different configurations.
@@ -73,6 +90,105 @@ impl ContainerApiBuilder {
         });
     }
 
+    /// Adds "admin/chain/export" and "admin/chain/import" methods that move the source chain
+    /// of any of the given instances to and from a ChainArchive file.
+    /// Both take the params `instance_id` and `path` and return the address of the top
+    /// chain header, or null if the chain is empty.
+    /// The instances are shared with the container, so instances it adds later on are
+    /// available as well.
+    pub fn with_admin_chain_api(mut self, instances: Arc<RwLock<InstanceMap>>) -> Self {
+        let export_instances = instances.clone();
+        self.io.add_method("admin/chain/export", move |params| {
+            let (hc_lock, path) = instance_and_path(&export_instances.read().unwrap(), params)?;
+            let archive = hc_lock
+                .read()
+                .unwrap()
+                .export_chain()
+                .map_err(|e| internal_error(e.to_string()))?;
+            archive
+                .save(Path::new(&path))
+                .map_err(|e| internal_error(e.to_string()))?;
+            Ok(top_chain_header_value(&archive))
+        });
+
+        self.io.add_method("admin/chain/import", move |params| {
+            let (hc_lock, path) = instance_and_path(&instances.read().unwrap(), params)?;
+            let archive = ChainArchive::load(Path::new(&path))
+                .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+            hc_lock
+                .write()
+                .unwrap()
+                .import_chain(&archive)
+                .map_err(|e| internal_error(e.to_string()))?;
+            Ok(top_chain_header_value(&archive))
+        });
+        self
+    }
+
+    /// Adds the "admin/..." methods that change the running container and its configuration.
+    /// The methods that add something take the configuration of what they add as params,
+    /// in the same form as in the container's configuration file. The instance methods
+    /// that don't add an instance take its `id`. All of them return true on success.
+    pub fn with_admin_container_api(mut self, container: Weak<Mutex<Container>>) -> Self {
+        let dna_container = container.clone();
+        self.io.add_method("admin/dna/install", move |params| {
+            let dna_config: DnaConfiguration = params.parse()?;
+            admin_call(&dna_container, |container| {
+                container.install_dna(dna_config)
+            })
+        });
+
+        let agent_container = container.clone();
+        self.io.add_method("admin/agent/add", move |params| {
+            let agent_config: AgentConfiguration = params.parse()?;
+            admin_call(&agent_container, |container| {
+                container.add_agent(agent_config)
+            })
+        });
+
+        let add_container = container.clone();
+        self.io.add_method("admin/instance/add", move |params| {
+            let instance_config: InstanceConfiguration = params.parse()?;
+            admin_call(&add_container, |container| {
+                container.add_instance(instance_config)
+            })
+        });
+
+        let remove_container = container.clone();
+        self.io.add_method("admin/instance/remove", move |params| {
+            let id = id_param(params)?;
+            admin_call(&remove_container, |container| {
+                container.remove_instance(&id)
+            })
+        });
+
+        let start_container = container.clone();
+        self.io.add_method("admin/instance/start", move |params| {
+            let id = id_param(params)?;
+            admin_call(&start_container, |container| container.start_instance(&id))
+        });
+
+        let stop_container = container.clone();
+        self.io.add_method("admin/instance/stop", move |params| {
+            let id = id_param(params)?;
+            admin_call(&stop_container, |container| container.stop_instance(&id))
+        });
+
+        let interface_container = container.clone();
+        self.io.add_method("admin/interface/add", move |params| {
+            let interface_config: InterfaceConfiguration = params.parse()?;
+            admin_call(&interface_container, |container| {
+                container.add_interface(interface_config)
+            })
+        });
+
+        self.io.add_method("admin/bridge/add", move |params| {
+            let bridge: Bridge = params.parse()?;
+            admin_call(&container, |container| container.add_bridge(bridge))
+        });
+        self
+    }
+
     /// Add a [InstanceConfig](struct.InstanceConfig.html) for a custom named instance
     pub fn with_named_instance_config(
         mut self,
@@ -106,66 +222,185 @@ impl ContainerApiBuilder {
         instance_name: String,
         instance: Arc<RwLock<Holochain>>,
     ) -> Self {
-        let hc_lock = instance.clone();
-        let hc = hc_lock.read().unwrap();
-        let state: State = hc.state().unwrap();
-        let nucleus = state.nucleus();
-        let dna = nucleus.dna();
-        match dna {
-            Some(dna) => {
-                for (zome_name, zome) in dna.zomes {
-                    for (cap_name, cap) in zome.capabilities {
-                        for func in cap.functions {
-                            let func_name = func.name;
-                            let zome_name = zome_name.clone();
-                            let cap_name = cap_name.clone();
-                            let method_name = format!(
-                                "{}/{}/{}/{}",
-                                instance_name, zome_name, cap_name, func_name
-                            );
-                            let hc_lock_inner = hc_lock.clone();
-                            self.io.add_method(&method_name, move |params| {
-                                let mut hc = hc_lock_inner.write().unwrap();
-                                let params_string =
-                                    serde_json::to_string(&params).map_err(|e| {
-                                        jsonrpc_core::Error::invalid_params(e.to_string())
-                                    })?;
-                                let response = hc
-                                    .call(
-                                        &zome_name,
-                                        Some(CapabilityCall::new(
-                                            cap_name.clone(),
-                                            Address::from("fake_token"),
-                                            None,
-                                        )),
-                                        &func_name,
-                                        &params_string,
-                                    )
-                                    .map_err(|e| {
-                                        jsonrpc_core::Error::invalid_params(e.to_string())
-                                    })?;
-                                Ok(Value::String(response.to_string()))
-                            })
-                        }
+        add_zome_function_methods(&mut self.io, &instance_name, instance.clone());
+        self.instances.insert(instance_name, instance);
+        self
+    }
+}
+
+/// Adds a method named "{instance_name}/{zome}/{cap}/{func}" for every function of the
+/// given instance's DNA to the handler.
+pub fn add_zome_function_methods(
+    io: &mut IoHandler,
+    instance_name: &str,
+    instance: Arc<RwLock<Holochain>>,
+) {
+    let hc_lock = instance.clone();
+    let hc = hc_lock.read().unwrap();
+    let state: State = hc.state().unwrap();
+    let nucleus = state.nucleus();
+    let dna = nucleus.dna();
+    match dna {
+        Some(dna) => {
+            for (zome_name, zome) in dna.zomes {
+                for (cap_name, cap) in zome.capabilities {
+                    for func in cap.functions {
+                        let func_name = func.name;
+                        let zome_name = zome_name.clone();
+                        let cap_name = cap_name.clone();
+                        let method_name =
+                            format!("{}/{}/{}/{}", instance_name, zome_name, cap_name, func_name);
+                        let hc_lock_inner = hc_lock.clone();
+                        io.add_method(&method_name, move |params| {
+                            let mut hc = hc_lock_inner.write().unwrap();
+                            let params = serde_json::to_value(&params)
+                                .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+                            let (cap_call, params_string) =
+                                split_cap_request_params(&cap_name, params)?;
+                            let response = hc
+                                .call(&zome_name, Some(cap_call), &func_name, &params_string)
+                                .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+                            Ok(Value::String(response.to_string()))
+                        })
                     }
                 }
             }
-            None => unreachable!(),
-        };
-        self.instances
-            .insert(instance_name.clone(), instance.clone());
-        self
+        }
+        None => unreachable!(),
+    };
+}
+
+/// Takes the capability token, caller and signature with its expiry (see CAP_REQUEST_PARAMS_KEY)
+/// out of the params
+/// of a zome call and returns them as CapabilityCall together with the remaining arguments.
+/// Calls without them can only reach public capabilities.
+fn split_cap_request_params(
+    cap_name: &str,
+    mut params: Value,
+) -> Result<(CapabilityCall, String), jsonrpc_core::Error> {
+    let maybe_cap_params = params
+        .as_object_mut()
+        .and_then(|object| object.remove(CAP_REQUEST_PARAMS_KEY));
+    let cap_call = match maybe_cap_params {
+        Some(cap_params) => {
+            let cap_params: CapabilityRequestParams =
+                serde_json::from_value(cap_params).map_err(|e| {
+                    jsonrpc_core::Error::invalid_params(format!(
+                        "Invalid {} parameter: {}",
+                        CAP_REQUEST_PARAMS_KEY, e
+                    ))
+                })?;
+            let cap_call =
+                CapabilityCall::new(cap_name.to_string(), cap_params.token, cap_params.caller);
+            match (cap_params.signature, cap_params.expires) {
+                (Some(signature), Some(expires)) => cap_call.with_signature(signature, expires),
+                // signatures that never expire don't get accepted
+                _ => cap_call,
+            }
+        }
+        None => CapabilityCall::new(cap_name.to_string(), Address::from(""), None),
+    };
+    Ok((cap_call, params.to_string()))
+}
+
+/// Looks up the instance and the file path named by the `instance_id` and `path` params
+fn instance_and_path(
+    instances: &InstanceMap,
+    params: Params,
+) -> Result<(Arc<RwLock<Holochain>>, String), jsonrpc_core::Error> {
+    let params: Value = params.parse()?;
+    let string_param = |key: &str| {
+        params
+            .get(key)
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| {
+                jsonrpc_core::Error::invalid_params(format!("Missing string parameter {}", key))
+            })
+    };
+    let instance_id = string_param("instance_id")?;
+    let instance = instances.get(&instance_id).cloned().ok_or_else(|| {
+        jsonrpc_core::Error::invalid_params(format!("Unknown instance {}", instance_id))
+    })?;
+    Ok((instance, string_param("path")?))
+}
+
+/// Reads the `id` param of the admin methods that act on an existing instance
+fn id_param(params: Params) -> Result<String, jsonrpc_core::Error> {
+    let params: Value = params.parse()?;
+    params
+        .get("id")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| jsonrpc_core::Error::invalid_params("Missing string parameter id"))
+}
+
+/// Runs an admin function on the container, if it still exists
+fn admin_call<F>(container: &Weak<Mutex<Container>>, call: F) -> Result<Value, jsonrpc_core::Error>
+where
+    F: FnOnce(&mut Container) -> Result<(), HolochainError>,
+{
+    let container = container
+        .upgrade()
+        .ok_or_else(|| internal_error("The container is gone".to_string()))?;
+    let mut container = container.lock().unwrap();
+    call(&mut container).map_err(|e| internal_error(e.to_string()))?;
+    Ok(Value::Bool(true))
+}
+
+fn top_chain_header_value(archive: &ChainArchive) -> Value {
+    archive
+        .top_chain_header()
+        .map(|header| Value::String(header.address().to_string()))
+        .unwrap_or(Value::Null)
+}
+
+fn internal_error(message: String) -> jsonrpc_core::Error {
+    jsonrpc_core::Error {
+        code: ErrorCode::InternalError,
+        message,
+        data: None,
     }
 }
 
 pub trait Interface {
     fn run(&self, handler: IoHandler) -> Result<(), String>;
+
+    /// Runs the interface like `run()` and also pushes the notifications received
+    /// on the given channel to its clients, see `signal_notification()`.
+    /// Interfaces that can't push messages to their clients drop the notifications.
+    fn run_with_signals(
+        &self,
+        handler: IoHandler,
+        _signals: Receiver<String>,
+    ) -> Result<(), String> {
+        self.run(handler)
+    }
+}
+
+/// JSON-RPC notification of a signal the given instance emitted for its clients.
+/// The method is "signal" and the params hold the instance ID, the signal's name
+/// and its arguments.
+pub fn signal_notification(instance_id: &str, signal: &UserSignal) -> String {
+    let arguments: Value =
+        serde_json::from_str(&String::from(signal.arguments.clone())).unwrap_or(Value::Null);
+    json!({
+        "jsonrpc": "2.0",
+        "method": "signal",
+        "params": {
+            "instance_id": instance_id,
+            "name": signal.name,
+            "arguments": arguments,
+        },
+    })
+    .to_string()
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::{config::Configuration, container::tests::test_container};
+    use holochain_core_types::{signature::Signature, time::test_iso_8601};
 
     fn example_config_and_instances() -> (Configuration, InstanceMap) {
         let container = test_container();
@@ -193,6 +428,60 @@ pub mod tests {
         assert!(!result.contains(r#""test-instance-2//test/test""#));
     }
 
+    #[test]
+    fn test_split_cap_request_params() {
+        let params: Value = serde_json::from_str(
+            r#"{"to":"world","__cap":{"token":"Qmtoken","caller":"HcScaller"}}"#,
+        )
+        .unwrap();
+        let (cap_call, args) = split_cap_request_params("test_cap", params).unwrap();
+        assert_eq!(
+            cap_call,
+            CapabilityCall::new(
+                "test_cap".to_string(),
+                Address::from("Qmtoken"),
+                Some(Address::from("HcScaller"))
+            )
+        );
+        assert_eq!(args, r#"{"to":"world"}"#);
+
+        let params: Value = serde_json::from_str(&format!(
+            r#"{{"__cap":{{"token":"Qmtoken","caller":"HcScaller","signature":"sig","expires":"{}"}}}}"#,
+            test_iso_8601()
+        ))
+        .unwrap();
+        let (cap_call, _) = split_cap_request_params("test_cap", params).unwrap();
+        let unsigned_call = CapabilityCall::new(
+            "test_cap".to_string(),
+            Address::from("Qmtoken"),
+            Some(Address::from("HcScaller")),
+        );
+        assert_eq!(
+            cap_call,
+            unsigned_call
+                .clone()
+                .with_signature(Signature::from("sig"), test_iso_8601())
+        );
+
+        let params: Value = serde_json::from_str(
+            r#"{"__cap":{"token":"Qmtoken","caller":"HcScaller","signature":"sig"}}"#,
+        )
+        .unwrap();
+        let (cap_call, _) = split_cap_request_params("test_cap", params).unwrap();
+        assert_eq!(cap_call, unsigned_call);
+
+        let params: Value = serde_json::from_str(r#"{"to":"world"}"#).unwrap();
+        let (cap_call, args) = split_cap_request_params("test_cap", params).unwrap();
+        assert_eq!(
+            cap_call,
+            CapabilityCall::new("test_cap".to_string(), Address::from(""), None)
+        );
+        assert_eq!(args, r#"{"to":"world"}"#);
+
+        let params: Value = serde_json::from_str(r#"{"__cap":"Qmtoken"}"#).unwrap();
+        assert!(split_cap_request_params("test_cap", params).is_err());
+    }
+
     #[test]
     fn test_named_instances() {
         let (config, instances) = example_config_and_instances();
@@ -212,4 +501,58 @@ pub mod tests {
         assert!(result.contains(r#""happ-store/greeter/public/hello""#));
         assert!(!result.contains(r#""test-instance-1//test/test""#));
     }
+
+    #[test]
+    fn test_admin_chain_api() {
+        let (_, instances) = example_config_and_instances();
+        let shared_instances = Arc::new(RwLock::new(instances.clone()));
+        let handler = ContainerApiBuilder::new()
+            .with_admin_chain_api(shared_instances.clone())
+            .spawn();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.json");
+        let path = path.to_str().unwrap();
+        let top_chain_header = instances["test-instance-1"]
+            .read()
+            .unwrap()
+            .state()
+            .unwrap()
+            .agent()
+            .top_chain_header()
+            .unwrap();
+        let expected = format!(
+            r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+            top_chain_header.address()
+        );
+
+        let request = |method: &str, instance_id: &str| {
+            format!(
+                r#"{{"jsonrpc":"2.0","method":"{}","params":{{"instance_id":"{}","path":"{}"}},"id":1}}"#,
+                method, instance_id, path
+            )
+        };
+        assert_eq!(
+            handler.handle_request_sync(&request("admin/chain/export", "test-instance-1")),
+            Some(expected.clone())
+        );
+        assert!(ChainArchive::load(Path::new(path)).is_ok());
+        assert!(handler
+            .handle_request_sync(&request("admin/chain/export", "unknown-instance"))
+            .unwrap()
+            .contains("Unknown instance"));
+        assert_eq!(
+            handler.handle_request_sync(&request("admin/chain/import", "test-instance-1")),
+            Some(expected.clone())
+        );
+
+        // instances added after the handler got built are available too
+        shared_instances.write().unwrap().insert(
+            "added-instance".to_string(),
+            instances["test-instance-1"].clone(),
+        );
+        assert_eq!(
+            handler.handle_request_sync(&request("admin/chain/export", "added-instance")),
+            Some(expected)
+        );
+    }
 }
@@ -0,0 +1,272 @@
+use holochain_core::logger::{LogLevel, Logger};
+use interface::Interface;
+use jsonrpc_ws_server::jsonrpc_core::IoHandler;
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{FileTypeExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+use tempfile;
+
+/// Only the user running the container can connect to its sockets.
+/// Other local users can be given access by changing the permissions of the socket file
+/// (or of the directory it is in).
+pub const SOCKET_FILE_MODE: u32 = 0o600;
+
+/// Serves newline delimited JSON-RPC 2.0 over a Unix domain socket: every line a client
+/// sends is a request (or batch) that gets handled by the IoHandler, and every response is
+/// written back as a single line. Notifications don't get a response line.
+pub struct DomainSocketInterface {
+    file: PathBuf,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl DomainSocketInterface {
+    pub fn new<P: AsRef<Path>>(file: P, logger: Arc<Mutex<Logger>>) -> Self {
+        DomainSocketInterface {
+            file: file.as_ref().to_path_buf(),
+            logger,
+        }
+    }
+}
+
+impl Interface for DomainSocketInterface {
+    fn run(&self, handler: IoHandler) -> Result<(), String> {
+        remove_stale_socket(&self.file)?;
+        let listener = bind_private(&self.file)?;
+        let _socket_file = SocketFile(self.file.clone(), self.logger.clone());
+
+        let handler = Arc::new(handler);
+        for stream in listener.incoming() {
+            let stream = stream.map_err(|error| error.to_string())?;
+            let handler = handler.clone();
+            let logger = self.logger.clone();
+            thread::spawn(move || {
+                if let Err(error) = serve_connection(&handler, stream) {
+                    logger.lock().unwrap().log(
+                        LogLevel::Warn,
+                        format!("Domain socket connection closed with error: {}", error),
+                    );
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Binds the socket inside a fresh directory only the container's user can enter, restricts
+/// its permissions there and only then moves it to the given path. That way no other user
+/// can connect in between binding the socket and setting its permissions.
+fn bind_private(file: &Path) -> Result<UnixListener, String> {
+    let bind_error =
+        |error: io::Error| format!("Could not bind socket {}: {}", file.display(), error);
+    let parent = match file.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    // tempfile creates directories with mode 0700
+    let private_dir = tempfile::Builder::new()
+        .prefix(".holochain-socket")
+        .tempdir_in(parent)
+        .map_err(bind_error)?;
+    let private_file = private_dir.path().join("socket");
+    let listener = UnixListener::bind(&private_file).map_err(bind_error)?;
+    fs::set_permissions(&private_file, fs::Permissions::from_mode(SOCKET_FILE_MODE))
+        .map_err(bind_error)?;
+    fs::rename(&private_file, file).map_err(bind_error)?;
+    Ok(listener)
+}
+
+/// Answers the requests of a single client until it hangs up
+fn serve_connection(handler: &IoHandler, stream: UnixStream) -> Result<(), String> {
+    let mut writer = stream.try_clone().map_err(|error| error.to_string())?;
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|error| error.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handler.handle_request_sync(&line) {
+            writeln!(writer, "{}", response).map_err(|error| error.to_string())?;
+            writer.flush().map_err(|error| error.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the socket file a previous container left behind when it didn't shut down
+/// cleanly. Refuses to remove sockets something is still listening on, so a second
+/// container can't take over the socket of a running one, and anything that isn't a socket.
+fn remove_stale_socket(file: &Path) -> Result<(), String> {
+    if !is_socket(file)? {
+        return Ok(());
+    }
+    match UnixStream::connect(file) {
+        Ok(_) => Err(format!(
+            "{} is in use by a running container, refusing to replace it",
+            file.display()
+        )),
+        Err(ref error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+            fs::remove_file(file).map_err(|error| error.to_string())
+        }
+        Err(error) => Err(format!(
+            "Could not check whether {} is in use: {}",
+            file.display(),
+            error
+        )),
+    }
+}
+
+/// Whether there is a socket at the given path. Errors if there is something else.
+fn is_socket(file: &Path) -> Result<bool, String> {
+    match fs::symlink_metadata(file) {
+        Err(_) => Ok(false),
+        Ok(ref metadata) if metadata.file_type().is_socket() => Ok(true),
+        Ok(_) => Err(format!(
+            "{} exists and is not a socket, refusing to replace it",
+            file.display()
+        )),
+    }
+}
+
+/// Removes the socket file of a container's own interface again when it stops,
+/// so clients don't find a dead socket
+pub(crate) fn remove_socket_file(file: &Path, logger: &Arc<Mutex<Logger>>) {
+    let result = is_socket(file).and_then(|is_socket| {
+        if is_socket {
+            fs::remove_file(file).map_err(|error| error.to_string())
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(error) = result {
+        logger.lock().unwrap().log(
+            LogLevel::Warn,
+            format!("Could not remove socket file: {}", error),
+        );
+    }
+}
+
+struct SocketFile(PathBuf, Arc<Mutex<Logger>>);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        remove_socket_file(&self.0, &self.1);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core::logger::SimpleLogger;
+    use jsonrpc_ws_server::jsonrpc_core::Value;
+    use std::{io::Read, thread::sleep, time::Duration};
+    use tempfile::tempdir;
+
+    fn test_handler() -> IoHandler {
+        let mut handler = IoHandler::new();
+        handler.add_method("hello", |_| Ok(Value::String("world".to_string())));
+        handler
+    }
+
+    fn test_interface(file: &Path) -> DomainSocketInterface {
+        DomainSocketInterface::new(file, Arc::new(Mutex::new(SimpleLogger {})))
+    }
+
+    fn start_interface(file: &Path) -> UnixStream {
+        let interface = test_interface(file);
+        thread::spawn(move || interface.run(test_handler()));
+        for _ in 0..100 {
+            if let Ok(stream) = UnixStream::connect(file) {
+                return stream;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        panic!("domain socket interface did not start");
+    }
+
+    #[test]
+    fn test_domain_socket_requests() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("holochain.sock");
+        let mut stream = start_interface(&file);
+
+        stream
+            .write_all(
+                concat!(
+                    r#"{"jsonrpc": "2.0", "method": "hello"}"#,
+                    "\n\n",
+                    r#"{"jsonrpc": "2.0", "method": "hello", "id": 1}"#,
+                    "\n",
+                    r#"[{"jsonrpc": "2.0", "method": "hello", "id": 2}]"#,
+                    "\n"
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"jsonrpc":"2.0","result":"world","id":1}"#
+        );
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"[{"jsonrpc":"2.0","result":"world","id":2}]"#
+        );
+        // the socket only shows up at its path with its permissions already set,
+        // and the directory it got bound in is gone
+        assert_eq!(
+            fs::metadata(&file).unwrap().permissions().mode() & 0o777,
+            SOCKET_FILE_MODE
+        );
+        let entries: Vec<PathBuf> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(entries, vec![file.clone()]);
+    }
+
+    #[test]
+    fn test_domain_socket_replaces_stale_sockets_only() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("stale.sock");
+        drop(UnixListener::bind(&file).unwrap());
+        assert!(file.exists());
+        let mut stream = start_interface(&file);
+        stream
+            .write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"hello\", \"id\": 1}\n")
+            .unwrap();
+        let mut response = [0; 1];
+        stream.read_exact(&mut response).unwrap();
+
+        let not_a_socket = dir.path().join("not_a_socket");
+        fs::write(&not_a_socket, "important").unwrap();
+        assert!(test_interface(&not_a_socket).run(test_handler()).is_err());
+        assert_eq!(fs::read_to_string(&not_a_socket).unwrap(), "important");
+    }
+
+    #[test]
+    fn test_domain_socket_refuses_sockets_in_use() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("holochain.sock");
+        let mut stream = start_interface(&file);
+
+        let error = test_interface(&file).run(test_handler()).err().unwrap();
+        assert!(error.contains("in use"));
+        // the running interface still serves on its socket
+        stream
+            .write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"hello\", \"id\": 1}\n")
+            .unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"{"jsonrpc":"2.0","result":"world","id":1}"#
+        );
+        assert!(UnixStream::connect(&file).is_ok());
+    }
+}
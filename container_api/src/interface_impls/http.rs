@@ -1,31 +1,268 @@
-// use holochain_core_types::json::JsonString;
+use holochain_core::logger::{LogLevel, Logger};
+use interface::Interface;
+use jsonrpc_ws_server::jsonrpc_core::IoHandler;
+use std::{
+    io::{Cursor, Read},
+    sync::{Arc, Mutex},
+};
+use tiny_http::{Header, Method, Request, Response, Server};
 
-use std::sync::Arc;
-use tiny_http::{Response, Server};
-use jsonrpc_ws_server::jsonrpc_core::{self, IoHandler, Value};
-use interface::{DispatchRpc, Interface};
+/// Allows cross-origin requests from any origin if listed in cors_allowed_origins
+pub const CORS_ANY_ORIGIN: &str = "*";
 
+/// Serves JSON-RPC 2.0 over HTTP: every POST request's body gets handled by the IoHandler
+/// (single calls as well as batches) and the JSON-RPC response is sent back as the body of
+/// the HTTP response.
+///
+/// Requests from browsers (i.e. with an Origin header) are only accepted from the origins
+/// listed in cors_allowed_origins, which is also what preflight (OPTIONS) requests get told.
+/// Those origins have to be valid header values, as `Configuration::check_consistency()`
+/// makes sure they are.
 pub struct HttpInterface {
     port: u16,
+    cors_allowed_origins: Vec<String>,
+    logger: Arc<Mutex<Logger>>,
 }
 
-/// TODO: this is a stub, to be implemented later
 impl HttpInterface {
-    pub fn new(port: u16) -> Self {
-        Self { port }
+    pub fn new(port: u16, cors_allowed_origins: Vec<String>, logger: Arc<Mutex<Logger>>) -> Self {
+        HttpInterface {
+            port,
+            cors_allowed_origins,
+            logger,
+        }
+    }
+
+    fn respond(&self, handler: &IoHandler, mut request: Request) -> Result<(), String> {
+        let origin = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Origin"))
+            .map(|header| header.value.as_str().to_string());
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => self.handle(handler, request.method(), origin.as_ref(), &body),
+            Err(error) => HttpResponse::new(400, format!("Could not read request: {}", error)),
+        };
+        request
+            .respond(response.into_response())
+            .map_err(|error| error.to_string())
+    }
+
+    fn handle(
+        &self,
+        handler: &IoHandler,
+        method: &Method,
+        origin: Option<&String>,
+        body: &str,
+    ) -> HttpResponse {
+        let allowed_origin = match origin {
+            None => None,
+            Some(origin) => match self.allowed_origin(origin) {
+                Some(allowed_origin) => Some(allowed_origin),
+                None => {
+                    return HttpResponse::new(
+                        403,
+                        format!("Origin {} is not allowed to access this interface", origin),
+                    );
+                }
+            },
+        };
+        let response = match method {
+            Method::Post => match handler.handle_request_sync(body) {
+                Some(json) => {
+                    HttpResponse::new(200, json).with_header("Content-Type", "application/json")
+                }
+                // Requests that only hold notifications don't get a response
+                None => HttpResponse::new(204, String::new()),
+            },
+            Method::Options => HttpResponse::new(204, String::new())
+                .with_header("Access-Control-Allow-Methods", "POST, OPTIONS")
+                .with_header("Access-Control-Allow-Headers", "Content-Type"),
+            _ => HttpResponse::new(405, String::from("JSON-RPC requests have to be POSTed"))
+                .with_header("Allow", "POST, OPTIONS"),
+        };
+        match allowed_origin {
+            Some(allowed_origin) => response
+                .with_header("Access-Control-Allow-Origin", &allowed_origin)
+                .with_header("Vary", "Origin"),
+            None => response,
+        }
+    }
+
+    fn allowed_origin(&self, origin: &str) -> Option<String> {
+        self.cors_allowed_origins
+            .iter()
+            .find(|allowed| *allowed == CORS_ANY_ORIGIN || *allowed == origin)
+            .cloned()
     }
 }
 
 impl Interface for HttpInterface {
-    fn run(&self, _handlerr: IoHandler) -> Result<(), String> {
+    fn run(&self, handler: IoHandler) -> Result<(), String> {
         let server_url = format!("0.0.0.0:{}", self.port);
-        let server = Server::http(server_url.as_str()).unwrap();
+        let server = Server::http(server_url.as_str()).map_err(|error| error.to_string())?;
         for request in server.incoming_requests() {
-            let method = request.url().to_string();
-            println!("{}", method);
-            let response = Response::from_string(method);
-            request.respond(response).unwrap();
+            if let Err(error) = self.respond(&handler, request) {
+                self.logger.lock().unwrap().log(
+                    LogLevel::Warn,
+                    format!("Could not answer HTTP request: {}", error),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Status, headers and body of an answer, kept apart from tiny_http so requests can be
+/// handled (and tested) without a running server
+#[derive(Debug, PartialEq)]
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpResponse {
+    fn new(status: u16, body: String) -> Self {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body,
         }
-        unimplemented!();
+    }
+
+    fn with_header(mut self, field: &str, value: &str) -> Self {
+        self.headers.push((field.to_string(), value.to_string()));
+        self
+    }
+
+    fn into_response(self) -> Response<Cursor<Vec<u8>>> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = self;
+        headers.into_iter().fold(
+            Response::from_string(body).with_status_code(status),
+            |response, (field, value)| {
+                response.with_header(
+                    Header::from_bytes(field.as_bytes(), value.as_bytes())
+                        .expect("header fields and values are valid ASCII, origins got checked"),
+                )
+            },
+        )
+    }
+
+    #[cfg(test)]
+    fn header(&self, field: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_field, _)| header_field == field)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core::logger::SimpleLogger;
+    use jsonrpc_ws_server::jsonrpc_core::Value;
+    use serde_json;
+
+    fn test_handler() -> IoHandler {
+        let mut handler = IoHandler::new();
+        handler.add_method("hello", |_| Ok(Value::String("world".to_string())));
+        handler
+    }
+
+    fn test_interface(cors_allowed_origins: Vec<&str>) -> HttpInterface {
+        HttpInterface::new(
+            0,
+            cors_allowed_origins
+                .into_iter()
+                .map(|origin| origin.to_string())
+                .collect(),
+            Arc::new(Mutex::new(SimpleLogger {})),
+        )
+    }
+
+    #[test]
+    fn test_post_single_and_batch_requests() {
+        let interface = test_interface(Vec::new());
+        let response = interface.handle(
+            &test_handler(),
+            &Method::Post,
+            None,
+            r#"{"jsonrpc": "2.0", "method": "hello", "id": 1}"#,
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("Content-Type"), Some("application/json"));
+        assert_eq!(
+            response.body,
+            r#"{"jsonrpc":"2.0","result":"world","id":1}"#
+        );
+
+        let response = interface.handle(
+            &test_handler(),
+            &Method::Post,
+            None,
+            r#"[{"jsonrpc": "2.0", "method": "hello", "id": 1},
+                {"jsonrpc": "2.0", "method": "unknown", "id": 2}]"#,
+        );
+        assert_eq!(response.status, 200);
+        let responses: Vec<Value> = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"], "world");
+        assert_eq!(responses[1]["error"]["code"], -32601);
+
+        let response = interface.handle(
+            &test_handler(),
+            &Method::Post,
+            None,
+            r#"{"jsonrpc": "2.0", "method": "hello"}"#,
+        );
+        assert_eq!(response.status, 204);
+
+        let response = interface.handle(&test_handler(), &Method::Get, None, "");
+        assert_eq!(response.status, 405);
+    }
+
+    #[test]
+    fn test_cors() {
+        let origin = String::from("http://localhost:8080");
+        let request = r#"{"jsonrpc": "2.0", "method": "hello", "id": 1}"#;
+
+        let response = test_interface(Vec::new()).handle(
+            &test_handler(),
+            &Method::Post,
+            Some(&origin),
+            request,
+        );
+        assert_eq!(response.status, 403);
+
+        let interface = test_interface(vec!["http://localhost:8080"]);
+        let response = interface.handle(&test_handler(), &Method::Post, Some(&origin), request);
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.header("Access-Control-Allow-Origin"),
+            Some("http://localhost:8080")
+        );
+        let response = interface.handle(
+            &test_handler(),
+            &Method::Options,
+            Some(&String::from("http://example.com")),
+            "",
+        );
+        assert_eq!(response.status, 403);
+
+        let interface = test_interface(vec![CORS_ANY_ORIGIN]);
+        let response = interface.handle(&test_handler(), &Method::Options, Some(&origin), "");
+        assert_eq!(response.status, 204);
+        assert_eq!(response.header("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(
+            response.header("Access-Control-Allow-Methods"),
+            Some("POST, OPTIONS")
+        );
     }
 }
@@ -1,3 +1,6 @@
+#[cfg(unix)]
+pub mod domain_socket;
+pub mod http;
 pub mod websocket;
 
 pub use self::websocket::*;
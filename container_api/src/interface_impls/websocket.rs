@@ -1,22 +1,53 @@
+use holochain_core::logger::{LogLevel, Logger};
 use interface::Interface;
 use jsonrpc_ws_server::{jsonrpc_core::IoHandler, ServerBuilder};
+use std::{
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+};
 
 pub struct WebsocketInterface {
     port: u16,
+    logger: Arc<Mutex<Logger>>,
 }
 
 impl WebsocketInterface {
-    pub fn new(port: u16) -> Self {
-        WebsocketInterface { port }
+    pub fn new(port: u16, logger: Arc<Mutex<Logger>>) -> Self {
+        WebsocketInterface { port, logger }
     }
 }
 
 impl Interface for WebsocketInterface {
     fn run(&self, handler: IoHandler) -> Result<(), String> {
+        let (_, signals) = channel();
+        self.run_with_signals(handler, signals)
+    }
+
+    /// Sends every signal notification to all connected clients
+    fn run_with_signals(
+        &self,
+        handler: IoHandler,
+        signals: Receiver<String>,
+    ) -> Result<(), String> {
         let url = format!("0.0.0.0:{}", self.port);
         let server = ServerBuilder::new(handler)
             .start(&url.parse().expect("Invalid URL!"))
             .map_err(|e| e.to_string())?;
+        let broadcaster = server.broadcaster();
+        let logger = self.logger.clone();
+        thread::spawn(move || {
+            for notification in signals.iter() {
+                if let Err(error) = broadcaster.send(notification) {
+                    logger.lock().unwrap().log(
+                        LogLevel::Warn,
+                        format!("Could not push signal to websocket clients: {}", error),
+                    );
+                }
+            }
+        });
         server.wait().map_err(|e| e.to_string())?;
         Ok(())
     }
@@ -6,6 +6,7 @@ use crate::{
         state::{NucleusState, ValidationResult},
         ExecuteZomeFnResponse, ZomeFnCall,
     },
+    wakers::WakerKey,
 };
 use holochain_core_types::{
     cas::content::Address,
@@ -104,6 +105,10 @@ pub enum Action {
     /// Create a network proxy instance from the given [NetworkSettings](struct.NetworkSettings.html)
     InitNetwork(NetworkSettings),
 
+    /// Stops the network proxy instance, e.g. before another instance takes over
+    /// the storage of this one. Network actions fail as uninitialized afterwards.
+    ShutdownNetwork,
+
     /// Makes the network PUT the given entry to the DHT.
     /// Distinguishes between different entry types and does
     /// the right thing respectively.
@@ -111,7 +116,7 @@ pub enum Action {
     Publish(Address),
 
     /// GetEntry by address
-    GetEntry(Address),
+    GetEntry(GetEntryKey),
 
     /// Lets the network module respond to a GET request.
     /// Triggered from the corresponding workflow after retrieving the
@@ -128,11 +133,11 @@ pub enum Action {
     ///
     RemoveEntry((Address, Address)),
     ///
-    GetEntryTimeout(Address),
+    GetEntryTimeout(GetEntryKey),
 
     /// get links from entry address and tag name
-    GetLinks((Address, String)),
-    GetLinksTimeout((Address, String)),
+    GetLinks(GetLinksKey),
+    GetLinksTimeout(GetLinksKey),
     RespondGetLinks((GetDhtMetaData, Vec<Address>)),
     HandleGetLinksResult((DhtMetaData, String)),
 
@@ -196,6 +201,13 @@ pub enum Action {
             Result<ValidationPackage, HolochainError>,
         ),
     ),
+
+    // ----------------
+    // State actions:
+    // ----------------
+    /// Removes the result a future was waiting for from the state once that future
+    /// consumed it. Dispatched by Context::poll_state().
+    ClearResult(WakerKey),
 }
 
 /// function signature for action handler functions
@@ -225,6 +237,52 @@ pub struct DirectMessageData {
     pub is_response: bool,
 }
 
+/// Identifies a request for an entry to the network.
+/// The id is sent along as msg_id and comes back with the response,
+/// so each request gets its own result and its own timeout.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GetEntryKey {
+    /// The address of the entry to get
+    pub address: Address,
+
+    /// A unique ID that is used to pair the eventual result to this request
+    pub id: String,
+}
+
+impl GetEntryKey {
+    /// A key for a new request of the given entry
+    pub fn new(address: Address) -> Self {
+        GetEntryKey {
+            address,
+            id: snowflake::ProcessUniqueId::new().to_string(),
+        }
+    }
+}
+
+/// Identifies a request for links to the network, see GetEntryKey.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GetLinksKey {
+    /// The address of the link base
+    pub base_address: Address,
+
+    /// The link tag
+    pub tag: String,
+
+    /// A unique ID that is used to pair the eventual result to this request
+    pub id: String,
+}
+
+impl GetLinksKey {
+    /// A key for a new request of the links with the given base and tag
+    pub fn new(base_address: Address, tag: String) -> Self {
+        GetLinksKey {
+            base_address,
+            tag,
+            id: snowflake::ProcessUniqueId::new().to_string(),
+        }
+    }
+}
+
 /// Everything the network needs to initialize
 #[derive(Clone, PartialEq, Debug)]
 pub struct NetworkSettings {
@@ -245,7 +303,7 @@ pub struct NetworkSettings {
 pub mod tests {
 
     use crate::{
-        action::{Action, ActionWrapper},
+        action::{Action, ActionWrapper, GetEntryKey},
         nucleus::tests::test_call_response,
     };
     use holochain_core_types::entry::{expected_entry_address, test_entry};
@@ -253,7 +311,10 @@ pub mod tests {
 
     /// dummy action
     pub fn test_action() -> Action {
-        Action::GetEntry(expected_entry_address())
+        Action::GetEntry(GetEntryKey {
+            address: expected_entry_address(),
+            id: String::from("test-id"),
+        })
     }
 
     /// dummy action wrapper with test_action()
@@ -268,7 +329,7 @@ pub mod tests {
 
     /// dummy action for a get of test_hash()
     pub fn test_action_wrapper_get() -> ActionWrapper {
-        ActionWrapper::new(Action::GetEntry(expected_entry_address()))
+        ActionWrapper::new(Action::GetEntry(GetEntryKey::new(expected_entry_address())))
     }
 
     pub fn test_action_wrapper_rzfr() -> ActionWrapper {
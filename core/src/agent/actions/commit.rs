@@ -4,6 +4,7 @@ use crate::{
     agent::state::ActionResponse,
     context::Context,
     instance::dispatch_action,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -42,25 +43,23 @@ impl Future for CommitFuture {
     type Output = Result<Address, HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        match self
-            .context
-            .state()
-            .unwrap()
-            .agent()
-            .actions()
-            .get(&self.action)
-        {
-            Some(ActionResponse::Commit(result)) => match result {
-                Ok(address) => Poll::Ready(Ok(address.clone())),
-                Err(error) => Poll::Ready(Err(error.clone())),
-            },
-            Some(_) => unreachable!(),
-            None => Poll::Pending,
-        }
+        let key = WakerKey::Action(self.action.clone());
+        self.context.poll_state(key, lw, || {
+            match self
+                .context
+                .state()
+                .unwrap()
+                .agent()
+                .actions()
+                .get(&self.action)
+            {
+                Some(ActionResponse::Commit(result)) => match result {
+                    Ok(address) => Poll::Ready(Ok(address.clone())),
+                    Err(error) => Poll::Ready(Err(error.clone())),
+                },
+                Some(_) => unreachable!(),
+                None => Poll::Pending,
+            }
+        })
     }
 }
@@ -0,0 +1,326 @@
+use crate::agent::{chain_store::ChainStore, keystore::verify_chain_header};
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::{HcResult, HolochainError},
+    json::JsonString,
+};
+use std::{
+    convert::TryFrom,
+    fs,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+/// Names the kind of file in every archive so it can't be mistaken for other JSON
+pub const CHAIN_ARCHIVE_FORMAT: &str = "holochain_source_chain";
+/// Version of the archive layout, to be increased with every incompatible change
+pub const CHAIN_ARCHIVE_VERSION: u32 = 1;
+
+/// A whole source chain, headers and entries, in a single self-describing document
+/// that can be moved to another machine and imported into any storage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, DefaultJson)]
+pub struct ChainArchive {
+    format: String,
+    version: u32,
+    /// Address of the header the chain was exported from
+    top_chain_header: Option<Address>,
+    /// The headers with their entries in chain order, starting at the top
+    items: Vec<ChainArchiveItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChainArchiveItem {
+    pub header: ChainHeader,
+    pub entry: Entry,
+}
+
+impl ChainArchive {
+    /// Collects the chain from the given top header down to the first header.
+    /// Fails if a header or an entry of the chain is missing.
+    pub fn export(
+        chain_store: &ChainStore,
+        top_chain_header: &Option<ChainHeader>,
+    ) -> HcResult<Self> {
+        let storage = chain_store.content_storage();
+        let items = chain_store
+            .iter(top_chain_header)
+            .map(|header| -> HcResult<ChainArchiveItem> {
+                let content = storage
+                    .read()?
+                    .fetch(header.entry_address())?
+                    .ok_or_else(|| {
+                        HolochainError::ErrorGeneric(format!(
+                            "Entry {} of header {} is missing",
+                            header.entry_address(),
+                            header.address()
+                        ))
+                    })?;
+                Ok(ChainArchiveItem {
+                    entry: Entry::try_from_content(&content)?,
+                    header,
+                })
+            })
+            .collect::<HcResult<Vec<_>>>()?;
+        // The iterator ends early at a missing header
+        if let Some(link) = items.last().and_then(|item| item.header.link()) {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Header {} is missing",
+                link
+            )));
+        }
+        Ok(ChainArchive {
+            format: CHAIN_ARCHIVE_FORMAT.to_string(),
+            version: CHAIN_ARCHIVE_VERSION,
+            top_chain_header: top_chain_header.as_ref().map(|header| header.address()),
+            items,
+        })
+    }
+
+    pub fn top_chain_header(&self) -> Option<ChainHeader> {
+        self.items.first().map(|item| item.header.clone())
+    }
+
+    pub fn items(&self) -> &Vec<ChainArchiveItem> {
+        &self.items
+    }
+
+    /// The first entry of the archived chain that matches the given predicate
+    pub fn find_entry<F: Fn(&Entry) -> bool>(&self, predicate: F) -> Option<&Entry> {
+        self.items
+            .iter()
+            .rev()
+            .map(|item| &item.entry)
+            .find(|entry| predicate(entry))
+    }
+
+    /// Makes sure the first entry of each of the given entries' types in the archived chain is
+    /// that very entry, e.g. that the chain starts with the DNA and agent of the instance
+    /// it gets imported into.
+    pub fn check_owner(&self, own_entries: &[Entry]) -> HcResult<()> {
+        for own_entry in own_entries {
+            let archived_entry =
+                self.find_entry(|entry| entry.entry_type() == own_entry.entry_type());
+            if archived_entry.map(|entry| entry.address()) != Some(own_entry.address()) {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "The archived chain does not hold the {} it gets imported for",
+                    String::from(own_entry.entry_type())
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes sure this is an archive of a version we can read, that every entry hashes to the
+    /// address its header names, that every header is signed by its sources, and that the
+    /// headers link to each other in archive order.
+    pub fn verify(&self) -> HcResult<()> {
+        if self.format != CHAIN_ARCHIVE_FORMAT || self.version != CHAIN_ARCHIVE_VERSION {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Unsupported chain archive: {} version {}",
+                self.format, self.version
+            )));
+        }
+        let top_address = self.items.first().map(|item| item.header.address());
+        if top_address != self.top_chain_header {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Chain archive starts at header {:?} instead of {:?}",
+                top_address, self.top_chain_header
+            )));
+        }
+        for (index, item) in self.items.iter().enumerate() {
+            let entry_address = item.entry.address();
+            if entry_address != *item.header.entry_address() {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Entry of header {} hashes to {} instead of {}",
+                    item.header.address(),
+                    entry_address,
+                    item.header.entry_address()
+                )));
+            }
+            verify_chain_header(&item.header)?;
+            let previous = self.items.get(index + 1).map(|item| item.header.address());
+            if item.header.link() != previous {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Header {} links to {:?} instead of {:?}",
+                    item.header.address(),
+                    item.header.link(),
+                    previous
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the archive and adds all its headers and entries to the given storage.
+    /// Returns the top header, which the agent's state has to be pointed to.
+    pub fn import(
+        &self,
+        storage: &Arc<RwLock<dyn ContentAddressableStorage>>,
+    ) -> HcResult<Option<ChainHeader>> {
+        self.verify()?;
+        {
+            let mut storage = storage.write()?;
+            for item in self.items.iter().rev() {
+                storage.add(&item.entry)?;
+                storage.add(&item.header)?;
+            }
+        }
+        let top_chain_header = self.top_chain_header();
+        // Also catches wrong links between headers of the same type
+        let inconsistencies = ChainStore::new(storage.clone()).verify(&top_chain_header)?;
+        if let Some(inconsistency) = inconsistencies.first() {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Imported chain is inconsistent: {}",
+                inconsistency
+            )));
+        }
+        Ok(top_chain_header)
+    }
+
+    pub fn save(&self, path: &Path) -> HcResult<()> {
+        fs::write(path, String::from(JsonString::from(self.clone())))?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> HcResult<Self> {
+        Self::try_from(JsonString::from(fs::read_to_string(path)?))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    use self::tempfile::tempdir;
+    use super::*;
+    use crate::agent::{chain_store::tests::test_chain_store, keystore::Keystore};
+    use holochain_cas_implementations::cas::memory::MemoryStorage;
+    use holochain_core_types::{
+        entry::{test_entry, test_entry_b, test_entry_c},
+        time::test_iso_8601,
+    };
+
+    /// commits test_entry, test_entry_b and test_entry again to a new chain store
+    /// and returns it with its top header
+    fn test_chain() -> (ChainStore, Option<ChainHeader>) {
+        let mut keystore = Keystore::new_random();
+        test_chain_signed_by(&keystore.address(), &mut keystore)
+    }
+
+    /// like test_chain() but with headers naming the given source and signed by the keystore
+    fn test_chain_signed_by(
+        source: &Address,
+        keystore: &mut Keystore,
+    ) -> (ChainStore, Option<ChainHeader>) {
+        let chain_store = test_chain_store();
+        let mut top_chain_header: Option<ChainHeader> = None;
+        for entry in vec![test_entry(), test_entry_b(), test_entry()] {
+            let header = ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &vec![source.clone()],
+                &vec![keystore.sign(&entry.address().to_string())],
+                &top_chain_header.as_ref().map(|header| header.address()),
+                &chain_store
+                    .iter_type(&top_chain_header, &entry.entry_type())
+                    .next()
+                    .map(|header| header.address()),
+                &None,
+                &test_iso_8601(),
+            );
+            let storage = chain_store.content_storage();
+            let mut storage = storage.write().unwrap();
+            storage.add(&entry).unwrap();
+            storage.add(&header).unwrap();
+            top_chain_header = Some(header);
+        }
+        (chain_store, top_chain_header)
+    }
+
+    #[test]
+    fn export_import_round_trip_test() {
+        let (chain_store, top_chain_header) = test_chain();
+        let archive = ChainArchive::export(&chain_store, &top_chain_header).unwrap();
+        assert_eq!(archive.items().len(), 3);
+        assert_eq!(archive.top_chain_header(), top_chain_header);
+        assert_eq!(
+            archive.find_entry(|entry| entry.entry_type() == test_entry_b().entry_type()),
+            Some(&test_entry_b())
+        );
+        archive
+            .check_owner(&[test_entry(), test_entry_b()])
+            .unwrap();
+        assert!(archive.check_owner(&[test_entry_c()]).is_err());
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("chain.json");
+        archive.save(&path).unwrap();
+        let loaded = ChainArchive::load(&path).unwrap();
+        assert_eq!(loaded, archive);
+
+        let storage: Arc<RwLock<dyn ContentAddressableStorage>> =
+            Arc::new(RwLock::new(MemoryStorage::new()));
+        assert_eq!(loaded.import(&storage).unwrap(), top_chain_header);
+        let imported = ChainStore::new(storage);
+        assert_eq!(
+            imported.iter(&top_chain_header).collect::<Vec<_>>(),
+            chain_store.iter(&top_chain_header).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ChainArchive::export(&imported, &top_chain_header).unwrap(),
+            archive
+        );
+    }
+
+    #[test]
+    fn export_empty_chain_test() {
+        let archive = ChainArchive::export(&test_chain_store(), &None).unwrap();
+        assert_eq!(archive.top_chain_header(), None);
+        archive.verify().unwrap();
+    }
+
+    #[test]
+    fn import_rejects_tampered_archive_test() {
+        let (chain_store, top_chain_header) = test_chain();
+        let archive = ChainArchive::export(&chain_store, &top_chain_header).unwrap();
+        let storage: Arc<RwLock<dyn ContentAddressableStorage>> =
+            Arc::new(RwLock::new(MemoryStorage::new()));
+
+        let mut wrong_entry = archive.clone();
+        wrong_entry.items[1].entry = test_entry();
+        assert!(wrong_entry.import(&storage).is_err());
+
+        let mut missing_header = archive.clone();
+        missing_header.items.remove(1);
+        assert!(missing_header.import(&storage).is_err());
+
+        let mut wrong_top = archive.clone();
+        wrong_top.items.remove(0);
+        assert!(wrong_top.import(&storage).is_err());
+
+        let mut wrong_version = archive.clone();
+        wrong_version.version += 1;
+        assert!(wrong_version.import(&storage).is_err());
+
+        // a chain that links up but isn't signed by the agent it names
+        let agent = Keystore::new_random().address();
+        let (forged_store, forged_top) = test_chain_signed_by(&agent, &mut Keystore::new_random());
+        let forged = ChainArchive::export(&forged_store, &forged_top).unwrap();
+        assert!(forged.verify().is_err());
+        assert!(forged.import(&storage).is_err());
+
+        // nothing got imported
+        assert_eq!(
+            storage
+                .read()
+                .unwrap()
+                .fetch(&top_chain_header.unwrap().address())
+                .unwrap(),
+            None
+        );
+    }
+}
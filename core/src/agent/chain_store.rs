@@ -5,10 +5,15 @@ use holochain_core_types::{
         storage::ContentAddressableStorage,
     },
     chain_header::ChainHeader,
-    entry::entry_type::EntryType,
-    error::RibosomeErrorCode::{self, *},
+    entry::{entry_type::EntryType, Entry},
+    error::{
+        HcResult, HolochainError,
+        RibosomeErrorCode::{self, *},
+    },
 };
 use std::{
+    collections::HashMap,
+    fmt,
     str::FromStr,
     sync::{Arc, RwLock},
 };
@@ -57,6 +62,173 @@ impl ChainStore {
         )
     }
 
+    /// Returns the header of the CapTokenGrant entry the given token is the address of,
+    /// unless the grant has been revoked by committing a Deletion entry linking to it
+    /// after the last time it was granted.
+    pub fn get_live_cap_grant_header(
+        &self,
+        start_chain_header: &Option<ChainHeader>,
+        token: &Address,
+    ) -> Option<ChainHeader> {
+        self.iter(start_chain_header)
+            .find(|header| match header.entry_type() {
+                EntryType::CapTokenGrant => header.entry_address() == token,
+                EntryType::Deletion => header.link_crud().as_ref() == Some(token),
+                _ => false,
+            })
+            .filter(|header| *header.entry_type() == EntryType::CapTokenGrant)
+    }
+
+    /// Walks the chain from the given top header down to the first header and reports every
+    /// header that is missing or corrupt, every entry that is missing or does not hash to its
+    /// address, and every wrong link_same_type.
+    /// Errors are only returned if the CAS itself fails.
+    pub fn verify(
+        &self,
+        top_chain_header: &Option<ChainHeader>,
+    ) -> HcResult<Vec<ChainInconsistency>> {
+        let mut inconsistencies = Vec::new();
+        // The header of each type that was reached last, together with its position in the
+        // chain. Its link_same_type has to point to the next header of that type.
+        let mut newer_of_type: HashMap<EntryType, (usize, ChainHeader)> = HashMap::new();
+        let mut complete = true;
+        let mut current = top_chain_header.clone();
+        let mut position = 0;
+        while let Some(header) = current.take() {
+            let address = header.address();
+            if let Some((_, newer)) =
+                newer_of_type.insert(header.entry_type().clone(), (position, header.clone()))
+            {
+                inconsistencies.extend(wrong_link_same_type(&newer, Some(address.clone())));
+            }
+            inconsistencies.extend(self.verify_entry(&header)?);
+            if let Some(link) = header.link() {
+                match self.fetch_header(&address, &link)? {
+                    Ok(linked_header) => current = Some(linked_header),
+                    Err(inconsistency) => {
+                        inconsistencies.push(inconsistency);
+                        complete = false;
+                    }
+                }
+            }
+            position += 1;
+        }
+        // The oldest header of each type must not link to any other,
+        // which we only know if we reached the start of the chain
+        if complete {
+            let mut oldest_of_type: Vec<_> = newer_of_type.into_iter().map(|(_, v)| v).collect();
+            oldest_of_type.sort_by_key(|(position, _)| *position);
+            for (_, header) in oldest_of_type {
+                inconsistencies.extend(wrong_link_same_type(&header, None));
+            }
+        }
+        Ok(inconsistencies)
+    }
+
+    /// Fetches the header the given link of the header at the given address points to
+    fn fetch_header(
+        &self,
+        header: &Address,
+        link: &Address,
+    ) -> HcResult<Result<ChainHeader, ChainInconsistency>> {
+        let content = match self.content_storage.read()?.fetch(link)? {
+            Some(content) => content,
+            None => {
+                return Ok(Err(ChainInconsistency::MissingHeader {
+                    header: header.clone(),
+                    link: link.clone(),
+                }));
+            }
+        };
+        Ok(match ChainHeader::try_from_content(&content) {
+            Ok(ref linked_header) if linked_header.address() != *link => {
+                Err(ChainInconsistency::CorruptHeader {
+                    header: link.clone(),
+                    reason: format!("content hashes to {}", linked_header.address()),
+                })
+            }
+            Ok(linked_header) => Ok(linked_header),
+            Err(error) => Err(ChainInconsistency::CorruptHeader {
+                header: link.clone(),
+                reason: error.to_string(),
+            }),
+        })
+    }
+
+    fn verify_entry(&self, header: &ChainHeader) -> HcResult<Option<ChainInconsistency>> {
+        let entry_address = header.entry_address();
+        let content = match self.content_storage.read()?.fetch(entry_address)? {
+            Some(content) => content,
+            None => {
+                return Ok(Some(ChainInconsistency::MissingEntry {
+                    header: header.address(),
+                    entry: entry_address.clone(),
+                }));
+            }
+        };
+        let reason = match Entry::try_from_content(&content) {
+            Ok(ref entry) if entry.address() != *entry_address => {
+                format!("content hashes to {}", entry.address())
+            }
+            Ok(_) => return Ok(None),
+            Err(error) => error.to_string(),
+        };
+        Ok(Some(ChainInconsistency::CorruptEntry {
+            header: header.address(),
+            entry: entry_address.clone(),
+            reason,
+        }))
+    }
+
+    /// Rewrites the headers of the chain so that every link_same_type points to the previous
+    /// header of the same type, and every link to the rewritten previous header.
+    /// Headers that were correct already keep their address, the ones after the first
+    /// repaired header get new addresses. Signatures stay valid since they only cover the
+    /// entry address.
+    /// Returns the new top header, which the agent's state has to be pointed to.
+    /// Fails if a header of the chain is missing or corrupt.
+    pub fn rebuild_type_links(
+        &self,
+        top_chain_header: &Option<ChainHeader>,
+    ) -> HcResult<Option<ChainHeader>> {
+        let mut headers = Vec::new();
+        let mut current = top_chain_header.clone();
+        while let Some(header) = current.take() {
+            if let Some(link) = header.link() {
+                current = Some(self.fetch_header(&header.address(), &link)?.map_err(
+                    |inconsistency| {
+                        HolochainError::ErrorGeneric(format!(
+                            "Can't rebuild a broken chain: {}",
+                            inconsistency
+                        ))
+                    },
+                )?);
+            }
+            headers.push(header);
+        }
+
+        let mut previous: Option<ChainHeader> = None;
+        let mut previous_of_type: HashMap<EntryType, Address> = HashMap::new();
+        for header in headers.into_iter().rev() {
+            let rebuilt_header = ChainHeader::new(
+                header.entry_type(),
+                header.entry_address(),
+                header.sources(),
+                header.entry_signatures(),
+                &previous.as_ref().map(|previous| previous.address()),
+                &previous_of_type.get(header.entry_type()).cloned(),
+                &header.link_crud(),
+                header.timestamp(),
+            );
+            if rebuilt_header.address() != header.address() {
+                self.content_storage.write()?.add(&rebuilt_header)?;
+            }
+            previous_of_type.insert(header.entry_type().clone(), rebuilt_header.address());
+            previous = Some(rebuilt_header);
+        }
+        Ok(previous)
+    }
+
     pub fn query(
         &self,
         start_chain_header: &Option<ChainHeader>,
@@ -140,6 +312,98 @@ impl ChainStore {
     }
 }
 
+/// An inconsistency ChainStore::verify() found in a source chain.
+/// Every variant names the address of the header the inconsistency was found at.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChainInconsistency {
+    /// The header's link points to a header that is not in the CAS,
+    /// so the rest of the chain could not be checked
+    MissingHeader { header: Address, link: Address },
+    /// The content stored under a header address is no header or does not hash to that address
+    CorruptHeader { header: Address, reason: String },
+    /// The entry of the header is not in the CAS
+    MissingEntry { header: Address, entry: Address },
+    /// The content stored under the header's entry address is no entry
+    /// or does not hash to that address
+    CorruptEntry {
+        header: Address,
+        entry: Address,
+        reason: String,
+    },
+    /// The header's link_same_type does not point to the previous header of the same type
+    WrongLinkSameType {
+        header: Address,
+        expected: Option<Address>,
+        actual: Option<Address>,
+    },
+}
+
+impl ChainInconsistency {
+    pub fn header(&self) -> &Address {
+        match self {
+            ChainInconsistency::MissingHeader { header, .. }
+            | ChainInconsistency::CorruptHeader { header, .. }
+            | ChainInconsistency::MissingEntry { header, .. }
+            | ChainInconsistency::CorruptEntry { header, .. }
+            | ChainInconsistency::WrongLinkSameType { header, .. } => header,
+        }
+    }
+}
+
+impl fmt::Display for ChainInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn display_link(link: &Option<Address>) -> String {
+            link.as_ref()
+                .map(|address| address.to_string())
+                .unwrap_or_else(|| "nothing".to_string())
+        }
+        match self {
+            ChainInconsistency::MissingHeader { header, link } => {
+                write!(f, "{}: linked header {} is missing", header, link)
+            }
+            ChainInconsistency::CorruptHeader { header, reason } => {
+                write!(f, "{}: corrupt header: {}", header, reason)
+            }
+            ChainInconsistency::MissingEntry { header, entry } => {
+                write!(f, "{}: entry {} is missing", header, entry)
+            }
+            ChainInconsistency::CorruptEntry {
+                header,
+                entry,
+                reason,
+            } => write!(f, "{}: corrupt entry {}: {}", header, entry, reason),
+            ChainInconsistency::WrongLinkSameType {
+                header,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}: link_same_type points to {} instead of {}",
+                header,
+                display_link(actual),
+                display_link(expected)
+            ),
+        }
+    }
+}
+
+/// Checks that the given header's link_same_type points to the given address
+fn wrong_link_same_type(
+    header: &ChainHeader,
+    expected: Option<Address>,
+) -> Option<ChainInconsistency> {
+    let actual = header.link_same_type();
+    if actual == expected {
+        None
+    } else {
+        Some(ChainInconsistency::WrongLinkSameType {
+            header: header.address(),
+            expected,
+            actual,
+        })
+    }
+}
+
 /// Access each Entry
 ///
 /// # Remarks
@@ -251,12 +515,15 @@ impl Iterator for ChainStoreTypeIterator {
 pub mod tests {
     extern crate tempfile;
     use self::tempfile::tempdir;
-    use crate::agent::chain_store::ChainStore;
+    use crate::agent::chain_store::{ChainInconsistency, ChainStore};
     use holochain_cas_implementations::cas::file::FilesystemStorage;
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::content::{Address, AddressableContent},
         chain_header::{test_chain_header, test_sources, ChainHeader},
+        dna::capabilities::CapabilityType,
         entry::{
+            cap_entries::CapTokenGrant,
+            deletion_entry::DeletionEntry,
             entry_type::{test_entry_type_b, AppEntryType},
             test_entry, test_entry_b, test_entry_c, Entry,
         },
@@ -745,4 +1012,173 @@ pub mod tests {
         assert_eq!(set.matches("foo.rs"), vec![0, 3]); // but, any number of leading '/' are matched by a '**/...'
     }
 
+    #[test]
+    /// show that grants are found until they get revoked and again after granting them again
+    fn get_live_cap_grant_header_test() {
+        let chain_store = test_chain_store();
+        let grant = Entry::CapTokenGrant(
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "nonce").unwrap(),
+        );
+        let token = grant.address();
+        let revocation = Entry::Deletion(DeletionEntry::new(token.clone()));
+
+        let mut top_header = Some(test_chain_header());
+        assert_eq!(
+            chain_store.get_live_cap_grant_header(&top_header, &token),
+            None
+        );
+        let mut push = |entry: &Entry, link_crud: Option<Address>| {
+            let header = ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &test_sources(),
+                &test_signatures(),
+                &top_header.as_ref().map(|header| header.address()),
+                &None,
+                &link_crud,
+                &test_iso_8601(),
+            );
+            (*chain_store.content_storage.write().unwrap())
+                .add(&header)
+                .expect("could not add header to cas");
+            top_header = Some(header.clone());
+            header
+        };
+
+        let grant_header = push(&grant, None);
+        assert_eq!(
+            chain_store.get_live_cap_grant_header(&Some(grant_header.clone()), &token),
+            Some(grant_header.clone())
+        );
+        let revocation_header = push(&revocation, Some(token.clone()));
+        assert_eq!(
+            chain_store.get_live_cap_grant_header(&Some(revocation_header), &token),
+            None
+        );
+        let regrant_header = push(&grant, None);
+        assert_eq!(
+            chain_store.get_live_cap_grant_header(&Some(regrant_header.clone()), &token),
+            Some(regrant_header)
+        );
+        assert_eq!(
+            chain_store.get_live_cap_grant_header(&Some(grant_header), &test_entry().address()),
+            None
+        );
+    }
+
+    /// builds a chain of test_entry, test_entry_b and test_entry again,
+    /// with the link_same_type of the last header set to the given link
+    fn chain_with_type_link(
+        chain_store: &ChainStore,
+        link_same_type: impl FnOnce(&ChainHeader) -> Option<Address>,
+    ) -> (ChainHeader, ChainHeader, ChainHeader) {
+        let header_a = test_chain_header();
+        let entry_b = test_entry_b();
+        let header_b = ChainHeader::new(
+            &entry_b.entry_type(),
+            &entry_b.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(header_a.address()),
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+        let entry_c = test_entry();
+        let header_c = ChainHeader::new(
+            &entry_c.entry_type(),
+            &entry_c.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(header_b.address()),
+            &link_same_type(&header_a),
+            &None,
+            &test_iso_8601(),
+        );
+        let mut storage = chain_store.content_storage.write().unwrap();
+        for header in vec![&header_a, &header_b, &header_c] {
+            storage.add(header).expect("could not add header to cas");
+        }
+        storage.add(&entry_b).expect("could not add entry to cas");
+        storage.add(&entry_c).expect("could not add entry to cas");
+        (header_a, header_b, header_c)
+    }
+
+    #[test]
+    fn verify_intact_chain_test() {
+        let chain_store = test_chain_store();
+        let (_, _, header_c) = chain_with_type_link(&chain_store, |a| Some(a.address()));
+        assert_eq!(chain_store.verify(&Some(header_c)).unwrap(), vec![]);
+        assert_eq!(chain_store.verify(&None).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn verify_reports_inconsistencies_test() {
+        let chain_store = test_chain_store();
+        let (header_a, _, header_c) = chain_with_type_link(&chain_store, |_| None);
+
+        let entry_d = test_entry_c();
+        let missing_address = Address::from("QmMissingHeader");
+        let header_d = ChainHeader::new(
+            &entry_d.entry_type(),
+            &entry_d.address(),
+            &test_sources(),
+            &test_signatures(),
+            &Some(missing_address.clone()),
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+        assert_eq!(
+            chain_store.verify(&Some(header_d.clone())).unwrap(),
+            vec![
+                ChainInconsistency::MissingEntry {
+                    header: header_d.address(),
+                    entry: entry_d.address(),
+                },
+                ChainInconsistency::MissingHeader {
+                    header: header_d.address(),
+                    link: missing_address,
+                },
+            ]
+        );
+
+        let inconsistencies = chain_store.verify(&Some(header_c.clone())).unwrap();
+        assert_eq!(
+            inconsistencies,
+            vec![ChainInconsistency::WrongLinkSameType {
+                header: header_c.address(),
+                expected: Some(header_a.address()),
+                actual: None,
+            }]
+        );
+        assert_eq!(inconsistencies[0].header(), &header_c.address());
+    }
+
+    #[test]
+    fn rebuild_type_links_test() {
+        let chain_store = test_chain_store();
+        let (header_a, header_b, header_c) = chain_with_type_link(&chain_store, |_| None);
+
+        let rebuilt_top = chain_store
+            .rebuild_type_links(&Some(header_c.clone()))
+            .unwrap()
+            .unwrap();
+        assert_ne!(rebuilt_top.address(), header_c.address());
+        assert_eq!(rebuilt_top.entry_address(), header_c.entry_address());
+        assert_eq!(rebuilt_top.link(), Some(header_b.address()));
+        assert_eq!(rebuilt_top.link_same_type(), Some(header_a.address()));
+        assert_eq!(
+            chain_store.verify(&Some(rebuilt_top.clone())).unwrap(),
+            vec![]
+        );
+
+        // an intact chain stays untouched
+        assert_eq!(
+            chain_store
+                .rebuild_type_links(&Some(rebuilt_top.clone()))
+                .unwrap(),
+            Some(rebuilt_top)
+        );
+    }
 }
@@ -0,0 +1,746 @@
+//! The Keystore holds the private keys of an agent and signs on its behalf.
+//! Everything needed to check such a signature is contained in the agent's address,
+//! so verification does not need a keystore.
+//!
+//! On disk the keystore only stores the agent's seed, encrypted with a key derived from
+//! a passphrase (see KeystoreFile). All keys get derived from the seed again when loading.
+//!
+//! The encryption keys are used to encrypt entries of encrypted entry types for a set of
+//! recipients (see EncryptedEntry).
+
+use holochain_core_types::{
+    agent::{AgentId, KeyBuffer},
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::{
+        encrypted_entry::{EncryptedEntry, SealedKey},
+        entry_type::{AppEntryType, EntryType},
+        Entry,
+    },
+    error::{HcResult, HolochainError},
+    json::JsonString,
+    signature::Signature,
+};
+use holochain_sodium::{aead, error::SodiumError, kx, pwhash, random, secbuf::SecBuf, sign, util};
+use multihash::{encode, Hash};
+use std::{
+    convert::TryFrom,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// The version of the keystore file format written by Keystore::save()
+pub const KEYSTORE_FILE_VERSION: u32 = 1;
+
+/// Keystore files are only readable and writable by their owner
+pub const KEYSTORE_FILE_MODE: u32 = 0o600;
+
+/// SodiumError is foreign to this crate as is HolochainError, so we can't implement From
+fn sodium_to_holochain_error(error: SodiumError) -> HolochainError {
+    HolochainError::ErrorGeneric(format!("Keystore crypto error: {}", error))
+}
+
+/// Holds the seed, signing and encryption keys of an agent together with the public keys
+/// derived from the seed.
+/// Seed and secret keys are kept in protected (mlocked / mprotected) memory for the whole
+/// lifetime of the keystore. The public keys make up the agent's KeyBuffer, i.e. its address.
+pub struct Keystore {
+    seed: SecBuf,
+    sign_secret_key: SecBuf,
+    enc_secret_key: SecBuf,
+    public_keys: KeyBuffer,
+}
+
+impl Keystore {
+    /// derives the signing (ed25519) and encryption (x25519) keypairs from the given seed
+    pub fn new_from_seed(seed: &mut SecBuf) -> HcResult<Keystore> {
+        let seed_len = seed.read_lock().len();
+        if seed_len != sign::SEEDBYTES {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Keystore seed must be {} bytes long but has {}",
+                sign::SEEDBYTES,
+                seed_len
+            )));
+        }
+
+        let mut sign_public_key = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
+        let mut sign_secret_key = SecBuf::with_secure(sign::SECRETKEYBYTES);
+        sign::seed_keypair(&mut sign_public_key, &mut sign_secret_key, seed);
+
+        // crypto_kx hashes the seed differently than crypto_sign does,
+        // so both keypairs can be derived from the same seed
+        let mut enc_public_key = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
+        let mut enc_secret_key = SecBuf::with_secure(kx::SECRETKEYBYTES);
+        kx::seed_keypair(&mut enc_public_key, &mut enc_secret_key, seed);
+
+        let mut public_keys = [0; sign::PUBLICKEYBYTES + kx::PUBLICKEYBYTES];
+        public_keys[..sign::PUBLICKEYBYTES].copy_from_slice(&sign_public_key.read_lock());
+        public_keys[sign::PUBLICKEYBYTES..].copy_from_slice(&enc_public_key.read_lock());
+
+        let mut own_seed = SecBuf::with_secure(sign::SEEDBYTES);
+        own_seed.write_lock().copy_from_slice(&seed.read_lock());
+
+        Ok(Keystore {
+            seed: own_seed,
+            sign_secret_key,
+            enc_secret_key,
+            public_keys: KeyBuffer::with_raw(&public_keys),
+        })
+    }
+
+    /// creates a keystore for a new agent from a random seed
+    pub fn new_random() -> Keystore {
+        let mut seed = SecBuf::with_secure(sign::SEEDBYTES);
+        random::buf(&mut seed);
+        Keystore::new_from_seed(&mut seed).expect("random seed has the correct length")
+    }
+
+    /// creates a keystore whose seed is derived from the given string only.
+    /// Anybody knowing the string can sign in the name of this agent, so this must only
+    /// be used for tests and local development (see AgentConfiguration::test_agent)
+    pub fn generate_fake(seed_string: &str) -> Keystore {
+        let digest = encode(Hash::SHA2256, seed_string.as_bytes()).expect("SHA2-256 is supported");
+        let mut seed = SecBuf::with_secure(sign::SEEDBYTES);
+        // the first two bytes of the multihash are the hash type and length
+        seed.write_lock()
+            .copy_from_slice(&digest[digest.len() - sign::SEEDBYTES..]);
+        Keystore::new_from_seed(&mut seed).expect("SHA2-256 digest has the correct length")
+    }
+
+    /// reads the keystore file at the given path and decrypts it with the given passphrase
+    pub fn load(path: &Path, passphrase: &str) -> HcResult<Keystore> {
+        Keystore::decrypt(&KeystoreFile::load(path)?, passphrase)
+    }
+
+    /// encrypts this keystore with the given passphrase and writes it to the given path
+    pub fn save(&mut self, path: &Path, passphrase: &str) -> HcResult<()> {
+        self.encrypt(passphrase)?.save(path)
+    }
+
+    /// encrypts the seed of this keystore with a key derived from the given passphrase
+    pub fn encrypt(&mut self, passphrase: &str) -> HcResult<KeystoreFile> {
+        let mut salt = SecBuf::with_insecure(pwhash::SALTBYTES);
+        random::buf(&mut salt);
+        let mut nonce = SecBuf::with_insecure(aead::NONCEBYTES);
+        random::buf(&mut nonce);
+        let mut key = passphrase_key(passphrase, &mut salt)?;
+        let public_address = self.address().to_string();
+        let mut adata = insecure_buf(public_address.as_bytes());
+        let mut cipher = SecBuf::with_insecure(sign::SEEDBYTES + aead::ABYTES);
+        aead::enc(
+            &mut self.seed,
+            &mut key,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .map_err(sodium_to_holochain_error)?;
+
+        Ok(KeystoreFile {
+            version: KEYSTORE_FILE_VERSION,
+            public_address,
+            salt: encode_buf(&mut salt),
+            nonce: encode_buf(&mut nonce),
+            encrypted_seed: encode_buf(&mut cipher),
+        })
+    }
+
+    /// decrypts the seed of the given file with the given passphrase and derives the keys
+    /// from it. Fails if the passphrase is wrong, the file has been tampered with or the
+    /// derived keys don't belong to the public address stated in the file.
+    pub fn decrypt(file: &KeystoreFile, passphrase: &str) -> HcResult<Keystore> {
+        if file.version != KEYSTORE_FILE_VERSION {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Unsupported keystore file version {}",
+                file.version
+            )));
+        }
+        let mut salt = decode_buf(&file.salt, pwhash::SALTBYTES, "Keystore file salt")?;
+        let mut nonce = decode_buf(&file.nonce, aead::NONCEBYTES, "Keystore file nonce")?;
+        let mut cipher = decode_buf(
+            &file.encrypted_seed,
+            sign::SEEDBYTES + aead::ABYTES,
+            "Keystore file encrypted seed",
+        )?;
+
+        let mut key = passphrase_key(passphrase, &mut salt)?;
+        let mut adata = insecure_buf(file.public_address.as_bytes());
+        let mut seed = SecBuf::with_secure(sign::SEEDBYTES);
+        aead::dec(
+            &mut seed,
+            &mut key,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .map_err(|_| {
+            HolochainError::ErrorGeneric(
+                "Could not decrypt keystore file, wrong passphrase?".to_string(),
+            )
+        })?;
+
+        let keystore = Keystore::new_from_seed(&mut seed)?;
+        if keystore.address().to_string() != file.public_address {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Keystore file claims to belong to {} but holds the keys of {}",
+                file.public_address,
+                keystore.address()
+            )));
+        }
+        Ok(keystore)
+    }
+
+    /// the public signing and encryption keys of this keystore
+    pub fn public_keys(&self) -> KeyBuffer {
+        self.public_keys.clone()
+    }
+
+    /// the address of the agent this keystore belongs to
+    pub fn address(&self) -> Address {
+        Address::from(self.public_keys.render())
+    }
+
+    /// builds the AgentId matching the keys of this keystore
+    pub fn agent_id(&self, nick: &str) -> AgentId {
+        AgentId::new(nick, &self.public_keys)
+    }
+
+    /// signs the given data with the signing key of this keystore
+    /// and returns the base64 encoded signature
+    pub fn sign(&mut self, data: &str) -> Signature {
+        let mut message = SecBuf::with_insecure(data.len());
+        message.write_lock().copy_from_slice(data.as_bytes());
+        let mut signature = SecBuf::with_insecure(sign::BYTES);
+        sign::sign(&mut message, &mut self.sign_secret_key, &mut signature);
+        let signature = signature.read_lock();
+        Signature::from(base64::encode(&**signature))
+    }
+
+    /// encrypts the given app entry for the given recipients (and this keystore's agent,
+    /// who always stays able to read its own entries)
+    pub fn encrypt_entry(
+        &mut self,
+        entry: &Entry,
+        recipients: &[Address],
+    ) -> HcResult<EncryptedEntry> {
+        let entry_type = match entry {
+            Entry::App(entry_type, _) => entry_type.clone(),
+            _ => {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Only app entries can be encrypted, not {}",
+                    entry.entry_type()
+                )));
+            }
+        };
+        let author = self.address();
+
+        let mut entry_key = SecBuf::with_secure(aead::KEYBYTES);
+        random::buf(&mut entry_key);
+        let mut nonce = SecBuf::with_insecure(aead::NONCEBYTES);
+        random::buf(&mut nonce);
+        let content = String::from(entry.content());
+        let mut message = insecure_buf(content.as_bytes());
+        let mut adata = encryption_adata(&entry_type, &author);
+        let mut cipher = SecBuf::with_insecure(content.len() + aead::ABYTES);
+        aead::enc(
+            &mut message,
+            &mut entry_key,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .map_err(sodium_to_holochain_error)?;
+
+        let mut all_recipients = vec![author.clone()];
+        for recipient in recipients {
+            if !all_recipients.contains(recipient) {
+                all_recipients.push(recipient.clone());
+            }
+        }
+        let mut sealed_keys = Vec::new();
+        for recipient in all_recipients {
+            let (_, mut tx) = self.session_keys(&recipient, true)?;
+            let mut key_nonce = SecBuf::with_insecure(aead::NONCEBYTES);
+            random::buf(&mut key_nonce);
+            let mut sealed_key = SecBuf::with_insecure(aead::KEYBYTES + aead::ABYTES);
+            aead::enc(
+                &mut entry_key,
+                &mut tx,
+                Some(&mut adata),
+                &mut key_nonce,
+                &mut sealed_key,
+            )
+            .map_err(sodium_to_holochain_error)?;
+            sealed_keys.push(SealedKey {
+                recipient,
+                nonce: encode_buf(&mut key_nonce),
+                key: encode_buf(&mut sealed_key),
+            });
+        }
+
+        Ok(EncryptedEntry::new(
+            entry_type,
+            author,
+            encode_buf(&mut nonce),
+            encode_buf(&mut cipher),
+            sealed_keys,
+        ))
+    }
+
+    /// decrypts an entry that has been encrypted for this keystore's agent.
+    /// Returns None if the agent is not one of its recipients and fails if anything about
+    /// the entry has been tampered with.
+    pub fn decrypt_entry(&mut self, encrypted_entry: &EncryptedEntry) -> HcResult<Option<Entry>> {
+        let sealed_key = match encrypted_entry.sealed_key_for(&self.address()) {
+            Some(sealed_key) => sealed_key.clone(),
+            None => return Ok(None),
+        };
+        let tampered = || {
+            HolochainError::ErrorGeneric(format!(
+                "Could not decrypt entry {}, it has been tampered with",
+                Entry::Encrypted(encrypted_entry.clone()).address()
+            ))
+        };
+        let mut adata = encryption_adata(encrypted_entry.entry_type(), encrypted_entry.author());
+
+        let (mut rx, _) = self.session_keys(encrypted_entry.author(), false)?;
+        let mut key_nonce = decode_buf(&sealed_key.nonce, aead::NONCEBYTES, "Sealed key nonce")?;
+        let mut key_cipher =
+            decode_buf(&sealed_key.key, aead::KEYBYTES + aead::ABYTES, "Sealed key")?;
+        let mut entry_key = SecBuf::with_secure(aead::KEYBYTES);
+        aead::dec(
+            &mut entry_key,
+            &mut rx,
+            Some(&mut adata),
+            &mut key_nonce,
+            &mut key_cipher,
+        )
+        .map_err(|_| tampered())?;
+
+        let mut nonce = decode_buf(encrypted_entry.nonce(), aead::NONCEBYTES, "Entry nonce")?;
+        let cipher_bytes = base64::decode(encrypted_entry.cipher())?;
+        if cipher_bytes.len() < aead::ABYTES {
+            return Err(tampered());
+        }
+        let mut cipher = insecure_buf(&cipher_bytes);
+        let mut message = SecBuf::with_insecure(cipher_bytes.len() - aead::ABYTES);
+        aead::dec(
+            &mut message,
+            &mut entry_key,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .map_err(|_| tampered())?;
+
+        let json = String::from_utf8(message.read_lock().to_vec()).map_err(|_| tampered())?;
+        let entry = Entry::try_from(JsonString::from(json))?;
+        if entry.entry_type() != EntryType::App(encrypted_entry.entry_type().clone()) {
+            return Err(tampered());
+        }
+        Ok(Some(entry))
+    }
+
+    /// computes the session keys of a key exchange with the encryption key of the given agent.
+    /// The author of an encrypted entry takes the client side, so the author's tx key is the
+    /// recipient's rx key. That also holds if author and recipient are the same agent.
+    fn session_keys(&mut self, other: &Address, as_client: bool) -> HcResult<(SecBuf, SecBuf)> {
+        let other_keys = KeyBuffer::with_corrected(&other.to_string())?;
+        let mut other_public_key = insecure_buf(other_keys.get_enc());
+        let mut own_public_key = insecure_buf(self.public_keys.get_enc());
+        let mut rx = SecBuf::with_secure(kx::SESSIONKEYBYTES);
+        let mut tx = SecBuf::with_secure(kx::SESSIONKEYBYTES);
+        if as_client {
+            kx::client_session_keys(
+                &mut rx,
+                &mut tx,
+                &mut own_public_key,
+                &mut self.enc_secret_key,
+                &mut other_public_key,
+            )
+        } else {
+            kx::server_session_keys(
+                &mut rx,
+                &mut tx,
+                &mut own_public_key,
+                &mut self.enc_secret_key,
+                &mut other_public_key,
+            )
+        }
+        .map_err(sodium_to_holochain_error)?;
+        Ok((rx, tx))
+    }
+}
+
+/// The serialized form of a keystore. Only the seed is stored, encrypted with
+/// xchacha20poly1305 under a key that argon2id derives from the passphrase and salt.
+/// The public address is authenticated as additional data, so it can't be swapped
+/// without decryption failing.
+/// All binary fields are base64 encoded.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct KeystoreFile {
+    pub version: u32,
+    pub public_address: String,
+    pub salt: String,
+    pub nonce: String,
+    pub encrypted_seed: String,
+}
+
+impl KeystoreFile {
+    /// reads the keystore file at the given path without decrypting it
+    pub fn load(path: &Path) -> HcResult<KeystoreFile> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// writes the keystore file to the given path with KEYSTORE_FILE_MODE
+    pub fn save(&self, path: &Path) -> HcResult<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        create_private_file(path)?.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Creates (or truncates) the file at the given path, restricted to KEYSTORE_FILE_MODE
+/// before anything gets written to it.
+#[cfg(unix)]
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(KEYSTORE_FILE_MODE)
+        .open(path)?;
+    // the mode only applies when the file gets created
+    file.set_permissions(fs::Permissions::from_mode(KEYSTORE_FILE_MODE))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &Path) -> io::Result<fs::File> {
+    fs::File::create(path)
+}
+
+fn insecure_buf(bytes: &[u8]) -> SecBuf {
+    let mut buf = SecBuf::with_insecure(bytes.len());
+    buf.write_lock().copy_from_slice(bytes);
+    buf
+}
+
+/// the additional data authenticated with an entry's cipher and sealed keys,
+/// so its type and author can't be swapped
+fn encryption_adata(entry_type: &AppEntryType, author: &Address) -> SecBuf {
+    insecure_buf(format!("{}:{}", String::from(entry_type.clone()), author).as_bytes())
+}
+
+fn encode_buf(buf: &mut SecBuf) -> String {
+    base64::encode(&**buf.read_lock())
+}
+
+/// decodes base64 encoded binary data that has to be exactly len bytes long
+fn decode_buf(encoded: &str, len: usize, name: &str) -> HcResult<SecBuf> {
+    let bytes = base64::decode(encoded)?;
+    if bytes.len() != len {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "{} must be {} bytes long but has {}",
+            name,
+            len,
+            bytes.len()
+        )));
+    }
+    Ok(insecure_buf(&bytes))
+}
+
+/// derives the symmetric key a keystore file gets encrypted with
+fn passphrase_key(passphrase: &str, salt: &mut SecBuf) -> HcResult<SecBuf> {
+    let mut passphrase = insecure_buf(passphrase.as_bytes());
+    let mut key = SecBuf::with_secure(aead::KEYBYTES);
+    let result = pwhash::hash(
+        &mut passphrase,
+        pwhash::OPSLIMIT_INTERACTIVE,
+        pwhash::MEMLIMIT_INTERACTIVE,
+        salt,
+        &mut key,
+    );
+    util::zero(&mut passphrase);
+    result.map_err(sodium_to_holochain_error)?;
+    Ok(key)
+}
+
+/// checks that signature is a valid signature of data made by the agent with the given address
+pub fn verify(source: &Address, data: &str, signature: &Signature) -> HcResult<bool> {
+    let public_keys = KeyBuffer::with_corrected(&source.to_string())?;
+    let signature = match base64::decode(&String::from(signature.clone())) {
+        Ok(ref bytes) if bytes.len() == sign::BYTES => bytes.clone(),
+        _ => return Ok(false),
+    };
+
+    let mut signature_buf = SecBuf::with_insecure(sign::BYTES);
+    signature_buf.write_lock().copy_from_slice(&signature);
+    let mut message = SecBuf::with_insecure(data.len());
+    message.write_lock().copy_from_slice(data.as_bytes());
+    let mut public_key = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
+    public_key
+        .write_lock()
+        .copy_from_slice(public_keys.get_sig());
+
+    Ok(sign::verify(
+        &mut signature_buf,
+        &mut message,
+        &mut public_key,
+    ))
+}
+
+/// checks that every source of the given header signed the address of its entry
+pub fn verify_chain_header(header: &ChainHeader) -> HcResult<()> {
+    let sources = header.sources();
+    let signatures = header.entry_signatures();
+    if sources.is_empty() || sources.len() != signatures.len() {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Chain header of entry {} has {} sources but {} signatures",
+            header.entry_address(),
+            sources.len(),
+            signatures.len()
+        )));
+    }
+    for (source, signature) in sources.iter().zip(signatures.iter()) {
+        let valid = verify(source, &header.entry_address().to_string(), signature).unwrap_or(false);
+        if !valid {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Invalid signature of {} on entry {}",
+                source,
+                header.entry_address()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    use self::tempfile::tempdir;
+    use super::*;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        chain_header::test_chain_header,
+        entry::{entry_type::test_app_entry_type_b, test_entry, test_entry_b, Entry},
+        time::test_iso_8601,
+    };
+
+    /// builds a header for the given entry signed by the given keystore
+    pub fn signed_header(entry: &Entry, keystore: &mut Keystore) -> ChainHeader {
+        ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &vec![keystore.address()],
+            &vec![keystore.sign(&entry.address().to_string())],
+            &None,
+            &None,
+            &None,
+            &test_iso_8601(),
+        )
+    }
+
+    #[test]
+    fn keystore_derives_the_same_keys_from_the_same_seed() {
+        let mut seed = SecBuf::with_secure(sign::SEEDBYTES);
+        random::buf(&mut seed);
+        let keystore_a = Keystore::new_from_seed(&mut seed).unwrap();
+        let keystore_b = Keystore::new_from_seed(&mut seed).unwrap();
+        assert_eq!(keystore_a.address(), keystore_b.address());
+        assert_ne!(keystore_a.address(), Keystore::new_random().address());
+    }
+
+    #[test]
+    fn keystore_rejects_seeds_of_the_wrong_size() {
+        let mut seed = SecBuf::with_secure(sign::SEEDBYTES * 2);
+        assert!(Keystore::new_from_seed(&mut seed).is_err());
+    }
+
+    #[test]
+    fn fake_keystores_are_derived_from_the_nick() {
+        assert_eq!(
+            Keystore::generate_fake("alice").address(),
+            Keystore::generate_fake("alice").address()
+        );
+        assert_ne!(
+            Keystore::generate_fake("alice").address(),
+            Keystore::generate_fake("bob").address()
+        );
+    }
+
+    #[test]
+    fn keystore_files_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("agent.keystore");
+        let mut keystore = Keystore::new_random();
+        keystore.save(&path, "correct horse").unwrap();
+
+        let mut loaded = Keystore::load(&path, "correct horse").unwrap();
+        assert_eq!(keystore.address(), loaded.address());
+        assert_eq!(keystore.sign("some data"), loaded.sign("some data"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn keystore_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("agent.keystore");
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        Keystore::new_random().save(&path, "correct horse").unwrap();
+        assert_eq!(mode(&path), KEYSTORE_FILE_MODE);
+
+        // also when overwriting a file that others could read
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        Keystore::new_random().save(&path, "correct horse").unwrap();
+        assert_eq!(mode(&path), KEYSTORE_FILE_MODE);
+    }
+
+    #[test]
+    fn keystore_files_need_the_right_passphrase() {
+        let mut keystore = Keystore::new_random();
+        let file = keystore.encrypt("correct horse").unwrap();
+        assert!(Keystore::decrypt(&file, "battery staple").is_err());
+    }
+
+    #[test]
+    fn keystore_files_reject_other_addresses() {
+        let mut keystore = Keystore::new_random();
+        let mut file = keystore.encrypt("correct horse").unwrap();
+        file.public_address = Keystore::new_random().address().to_string();
+        assert!(Keystore::decrypt(&file, "correct horse").is_err());
+    }
+
+    #[test]
+    fn keystore_address_matches_agent_id() {
+        let keystore = Keystore::new_random();
+        let agent_id = keystore.agent_id("alice");
+        assert_eq!(agent_id.nick, "alice");
+        assert_eq!(Address::from(agent_id.key), keystore.address());
+    }
+
+    #[test]
+    fn signatures_can_be_verified_with_the_address() {
+        let mut keystore = Keystore::new_random();
+        let signature = keystore.sign("some data");
+        assert_eq!(
+            Ok(true),
+            verify(&keystore.address(), "some data", &signature)
+        );
+        assert_eq!(
+            Ok(false),
+            verify(&keystore.address(), "other data", &signature)
+        );
+        assert_eq!(
+            Ok(false),
+            verify(&Keystore::new_random().address(), "some data", &signature)
+        );
+        assert_eq!(
+            Ok(false),
+            verify(&keystore.address(), "some data", &Signature::from("fake"))
+        );
+    }
+
+    #[test]
+    fn verify_chain_header_accepts_signed_headers() {
+        let mut keystore = Keystore::new_random();
+        assert_eq!(
+            Ok(()),
+            verify_chain_header(&signed_header(&test_entry(), &mut keystore))
+        );
+    }
+
+    #[test]
+    fn verify_chain_header_rejects_forged_headers() {
+        let mut keystore = Keystore::new_random();
+        let header = signed_header(&test_entry(), &mut keystore);
+        let forged = ChainHeader::new(
+            header.entry_type(),
+            header.entry_address(),
+            &vec![Keystore::new_random().address()],
+            header.entry_signatures(),
+            &None,
+            &None,
+            &None,
+            header.timestamp(),
+        );
+        assert!(verify_chain_header(&forged).is_err());
+
+        assert!(verify_chain_header(&test_chain_header()).is_err());
+
+        let unsigned = ChainHeader::new(
+            header.entry_type(),
+            header.entry_address(),
+            header.sources(),
+            &Vec::new(),
+            &None,
+            &None,
+            &None,
+            header.timestamp(),
+        );
+        assert!(verify_chain_header(&unsigned).is_err());
+    }
+
+    #[test]
+    fn encrypted_entries_can_only_be_read_by_their_recipients() {
+        let mut alice = Keystore::new_random();
+        let mut bob = Keystore::new_random();
+        let mut eve = Keystore::new_random();
+        let entry = test_entry();
+
+        let encrypted_entry = alice.encrypt_entry(&entry, &[bob.address()]).unwrap();
+        assert_eq!(encrypted_entry.author(), &alice.address());
+        assert_eq!(
+            encrypted_entry.recipients(),
+            vec![alice.address(), bob.address()]
+        );
+        assert!(!encrypted_entry
+            .cipher()
+            .contains(&String::from(entry.content())));
+
+        assert_eq!(
+            alice.decrypt_entry(&encrypted_entry),
+            Ok(Some(entry.clone()))
+        );
+        assert_eq!(bob.decrypt_entry(&encrypted_entry), Ok(Some(entry)));
+        assert_eq!(eve.decrypt_entry(&encrypted_entry), Ok(None));
+    }
+
+    #[test]
+    fn only_app_entries_can_be_encrypted() {
+        let mut keystore = Keystore::new_random();
+        let agent_entry = Entry::AgentId(keystore.agent_id("alice"));
+        assert!(keystore.encrypt_entry(&agent_entry, &[]).is_err());
+    }
+
+    #[test]
+    fn tampered_encrypted_entries_are_rejected() {
+        let mut alice = Keystore::new_random();
+        let encrypted_entry = alice.encrypt_entry(&test_entry(), &[]).unwrap();
+        let other_entry = alice.encrypt_entry(&test_entry_b(), &[]).unwrap();
+
+        // the cipher of another entry, keeping the address and keys of this one
+        let swapped_cipher = EncryptedEntry::new(
+            encrypted_entry.entry_type().clone(),
+            encrypted_entry.author().clone(),
+            other_entry.nonce().to_string(),
+            other_entry.cipher().to_string(),
+            encrypted_entry.sealed_keys().clone(),
+        );
+        assert!(alice.decrypt_entry(&swapped_cipher).is_err());
+
+        // everything of this entry, but claiming another type
+        let swapped_type = EncryptedEntry::new(
+            test_app_entry_type_b(),
+            encrypted_entry.author().clone(),
+            encrypted_entry.nonce().to_string(),
+            encrypted_entry.cipher().to_string(),
+            encrypted_entry.sealed_keys().clone(),
+        );
+        assert!(alice.decrypt_entry(&swapped_type).is_err());
+    }
+}
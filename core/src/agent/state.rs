@@ -2,17 +2,20 @@ use crate::{
     action::{Action, ActionWrapper, AgentReduceFn},
     agent::chain_store::ChainStore,
     context::Context,
+    retention::ResultMap,
     state::State,
+    wakers::WakerKey,
     workflows::get_entry_result::get_entry_result_workflow,
 };
+use chrono::Utc;
 use holochain_core_types::{
     agent::AgentId,
     cas::content::{Address, AddressableContent, Content},
     chain_header::ChainHeader,
+    chain_migrate::{ChainMigrate, MigrationType},
     entry::{entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
     json::*,
-    signature::Signature,
     time::Iso8601,
 };
 use holochain_wasm_utils::api_serialization::get_entry::*;
@@ -24,9 +27,7 @@ use std::{collections::HashMap, convert::TryFrom, sync::Arc};
 #[derive(Clone, Debug, PartialEq)]
 pub struct AgentState {
     /// every action and the result of that action
-    // @TODO this will blow up memory, implement as some kind of dropping/FIFO with a limit?
-    // @see https://github.com/holochain/holochain-rust/issues/166
-    actions: HashMap<ActionWrapper, ActionResponse>,
+    actions: ResultMap<ActionWrapper, ActionResponse>,
     chain: ChainStore,
     top_chain_header: Option<ChainHeader>,
 }
@@ -35,7 +36,7 @@ impl AgentState {
     /// builds a new, empty AgentState
     pub fn new(chain: ChainStore) -> AgentState {
         AgentState {
-            actions: HashMap::new(),
+            actions: ResultMap::new(),
             chain,
             top_chain_header: None,
         }
@@ -43,7 +44,7 @@ impl AgentState {
 
     pub fn new_with_top_chain_header(chain: ChainStore, chain_header: ChainHeader) -> AgentState {
         AgentState {
-            actions: HashMap::new(),
+            actions: ResultMap::new(),
             chain,
             top_chain_header: Some(chain_header),
         }
@@ -52,7 +53,15 @@ impl AgentState {
     /// getter for a copy of self.actions
     /// uniquely maps action executions to the result of the action
     pub fn actions(&self) -> HashMap<ActionWrapper, ActionResponse> {
-        self.actions.clone()
+        self.actions
+            .iter()
+            .map(|(action_wrapper, response)| (action_wrapper.clone(), response.clone()))
+            .collect()
+    }
+
+    /// Number of action responses held in this state
+    pub fn stored_results(&self) -> usize {
+        self.actions.len()
     }
 
     pub fn chain(&self) -> ChainStore {
@@ -95,6 +104,35 @@ impl AgentState {
             .iter_type(&self.top_chain_header(), &entry.entry_type())
             .find(|h| h.entry_address() == &entry.address())
     }
+
+    /// The Close migration this chain got closed with, together with its header,
+    /// or None if the chain is still open.
+    /// Since nothing can be committed after a Close migration, only the top needs to be checked.
+    pub fn chain_closure(&self) -> HcResult<Option<(ChainHeader, ChainMigrate)>> {
+        let header = match self.top_chain_header {
+            Some(ref header) if *header.entry_type() == EntryType::ChainMigrate => header.clone(),
+            _ => return Ok(None),
+        };
+        let content = self
+            .chain
+            .content_storage()
+            .read()?
+            .fetch(header.entry_address())?
+            .ok_or_else(|| {
+                HolochainError::ErrorGeneric(format!(
+                    "ChainMigrate entry {} not found",
+                    header.entry_address()
+                ))
+            })?;
+        Ok(match Entry::try_from_content(&content)? {
+            Entry::ChainMigrate(ref migration)
+                if *migration.migration_type() == MigrationType::Close =>
+            {
+                Some((header, migration.clone()))
+            }
+            _ => None,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, DefaultJson)]
@@ -156,11 +194,16 @@ pub enum ActionResponse {
     LinkEntries(Result<Entry, HolochainError>),
 }
 
+/// Builds the header for the given entry on top of the current chain.
+/// The entry address gets signed with the agent's keys so that DHT nodes can check
+/// the header was actually created by its source.
+/// Headers are stamped with the current UTC time, which must not be earlier than the
+/// timestamp of the previous header or validators reasoning about time would reject the chain.
 pub fn create_new_chain_header(
     entry: &Entry,
     context: Arc<Context>,
     crud_link: &Option<Address>,
-) -> ChainHeader {
+) -> HcResult<ChainHeader> {
     let agent_state = context
         .state()
         .expect("create_new_chain_header called without state")
@@ -168,12 +211,22 @@ pub fn create_new_chain_header(
     let agent_address = agent_state
         .get_agent_address()
         .unwrap_or(context.agent_id.address());
-    ChainHeader::new(
+    let timestamp = Iso8601::from(Utc::now());
+    if let Some(ref top_chain_header) = agent_state.top_chain_header {
+        if &timestamp < top_chain_header.timestamp() {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Current time {} is earlier than the timestamp {} of the previous chain header",
+                timestamp,
+                top_chain_header.timestamp()
+            )));
+        }
+    }
+    let signature = context.sign(&entry.address().to_string())?;
+    Ok(ChainHeader::new(
         &entry.entry_type(),
         &entry.address(),
         &vec![agent_address],
-        // @TODO signatures
-        &vec![Signature::from("")],
+        &vec![signature],
         &agent_state
             .top_chain_header
             .clone()
@@ -184,9 +237,8 @@ pub fn create_new_chain_header(
             .nth(0)
             .and_then(|chain_header| Some(chain_header.address())),
         crud_link,
-        // @TODO timestamp
-        &Iso8601::from(""),
-    )
+        &timestamp,
+    ))
 }
 
 /// Do a Commit Action against an agent state.
@@ -195,8 +247,6 @@ pub fn create_new_chain_header(
 /// action reduction to hang
 /// @TODO is there a way to reduce that doesn't block indefinitely on callback fns?
 /// @see https://github.com/holochain/holochain-rust/issues/222
-/// @TODO Better error handling in the state persister section
-/// https://github.com/holochain/holochain-rust/issues/555
 fn reduce_commit_entry(
     context: Arc<Context>,
     state: &mut AgentState,
@@ -204,7 +254,31 @@ fn reduce_commit_entry(
 ) {
     let action = action_wrapper.action();
     let (entry, maybe_crud_link) = unwrap_to!(action => Action::Commit);
-    let chain_header = create_new_chain_header(&entry, context.clone(), &maybe_crud_link);
+    // Validation rejects entries for closed chains already,
+    // this catches commits that don't get validated
+    let closure = match state.chain_closure() {
+        Ok(Some((closing_header, _))) => Err(HolochainError::ErrorGeneric(format!(
+            "Source chain is closed by migration {}",
+            closing_header.address()
+        ))),
+        Ok(None) => Ok(()),
+        Err(error) => Err(error),
+    };
+    if let Err(error) = closure {
+        state
+            .actions
+            .insert(action_wrapper.clone(), ActionResponse::Commit(Err(error)));
+        return;
+    }
+    let chain_header = match create_new_chain_header(&entry, context.clone(), &maybe_crud_link) {
+        Ok(chain_header) => chain_header,
+        Err(error) => {
+            state
+                .actions
+                .insert(action_wrapper.clone(), ActionResponse::Commit(Err(error)));
+            return;
+        }
+    };
 
     fn response(
         state: &mut AgentState,
@@ -218,24 +292,29 @@ fn reduce_commit_entry(
     }
     let result = response(state, &entry, &chain_header);
     state.top_chain_header = Some(chain_header);
-    let con = context.clone();
-
-    #[allow(unused_must_use)]
-    con.state().map(|global_state_lock| {
-        let persis_lock = context.clone().persister.clone();
-        let persister = &mut *persis_lock.lock().unwrap();
-        persister.save(global_state_lock.clone());
-    });
 
     state
         .actions
         .insert(action_wrapper.clone(), ActionResponse::Commit(result));
 }
 
+/// Drops the response to an action once the future waiting for it consumed it
+fn reduce_clear_result(
+    _context: Arc<Context>,
+    state: &mut AgentState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    if let WakerKey::Action(consumed_action) = unwrap_to!(action => Action::ClearResult) {
+        state.actions.remove(consumed_action);
+    }
+}
+
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<AgentReduceFn> {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_commit_entry),
+        Action::ClearResult(WakerKey::Action(_)) => Some(reduce_clear_result),
         _ => None,
     }
 }
@@ -250,7 +329,8 @@ pub fn reduce(
     match handler {
         Some(f) => {
             let mut new_state: AgentState = (*old_state).clone();
-            f(context, &mut new_state, &action_wrapper);
+            f(context.clone(), &mut new_state, &action_wrapper);
+            new_state.actions.prune_expired(&context.result_retention);
             Arc::new(new_state)
         }
         None => old_state,
@@ -260,17 +340,24 @@ pub fn reduce(
 #[cfg(test)]
 pub mod tests {
     extern crate tempfile;
-    use super::{reduce_commit_entry, ActionResponse, AgentState, AgentStateSnapshot};
+    use super::{
+        create_new_chain_header, reduce_commit_entry, ActionResponse, AgentState,
+        AgentStateSnapshot,
+    };
     use crate::{
-        action::tests::test_action_wrapper_commit, agent::chain_store::tests::test_chain_store,
-        instance::tests::test_context, state::State,
+        action::tests::test_action_wrapper_commit,
+        agent::{chain_store::tests::test_chain_store, keystore::verify_chain_header},
+        instance::tests::test_context,
+        state::State,
     };
+    use chrono::{Duration, Utc};
     use holochain_core_types::{
         cas::content::AddressableContent,
-        chain_header::test_chain_header,
+        chain_header::{test_chain_header, ChainHeader},
         entry::{expected_entry_address, test_entry, Entry},
         error::HolochainError,
         json::JsonString,
+        time::Iso8601,
     };
     use serde_json;
     use std::{
@@ -320,6 +407,67 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// test that new headers carry a valid signature of the agent
+    fn test_create_new_chain_header_signs_entry() {
+        let agent_state = test_agent_state();
+        let context = test_context("bob");
+        let state = State::new_with_agent(context, Arc::new(agent_state));
+        let mut context = test_context("bob");
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+
+        let header = create_new_chain_header(&test_entry(), context.clone(), &None)
+            .expect("header should be created");
+
+        assert_eq!(header.sources(), &vec![context.agent_id.address()]);
+        assert_eq!(Ok(()), verify_chain_header(&header));
+    }
+
+    #[test]
+    fn test_create_new_chain_header_stamps_current_time() {
+        let context = test_context("bob");
+        let state = State::new_with_agent(context, Arc::new(test_agent_state()));
+        let mut context = test_context("bob");
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+
+        let before = Iso8601::from(Utc::now());
+        let header = create_new_chain_header(&test_entry(), context.clone(), &None)
+            .expect("header should be created");
+        let after = Iso8601::from(Utc::now());
+
+        assert!(&before <= header.timestamp());
+        assert!(header.timestamp() <= &after);
+    }
+
+    #[test]
+    fn test_create_new_chain_header_rejects_clock_going_backwards() {
+        let future = Iso8601::from(Utc::now() + Duration::days(1));
+        let top_chain_header = ChainHeader::new(
+            &test_entry().entry_type(),
+            &test_entry().address(),
+            &Vec::new(),
+            &Vec::new(),
+            &None,
+            &None,
+            &None,
+            &future,
+        );
+        let agent_state =
+            AgentState::new_with_top_chain_header(test_chain_store(), top_chain_header);
+        let context = test_context("bob");
+        let state = State::new_with_agent(context, Arc::new(agent_state));
+        let mut context = test_context("bob");
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+
+        assert!(create_new_chain_header(&test_entry(), context.clone(), &None).is_err());
+    }
+
     #[test]
     /// test response to json
     fn test_commit_response_to_json() {
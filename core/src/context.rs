@@ -1,10 +1,14 @@
 use crate::{
-    action::ActionWrapper,
+    action::{Action, ActionWrapper},
+    agent::keystore::Keystore,
     instance::Observer,
-    logger::Logger,
+    logger::{LogLevel, Logger},
+    network::NetworkTimeouts,
     persister::Persister,
+    retention::ResultRetention,
     signal::{Signal, SignalSender},
     state::State,
+    wakers::{WakerKey, WakerRegistry},
 };
 use holochain_core_types::{
     agent::AgentId,
@@ -12,14 +16,20 @@ use holochain_core_types::{
         content::{Address, AddressableContent},
         storage::ContentAddressableStorage,
     },
-    dna::{wasm::DnaWasm, Dna},
+    dna::{entry_types::Sharing, wasm::DnaWasm, Dna},
     eav::EntityAttributeValueStorage,
+    entry::{encrypted_entry::EncryptedEntry, entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
     json::JsonString,
+    signature::Signature,
 };
+use futures::task::{LocalWaker, Poll};
+use holochain_cas_implementations::cas::memory::MemoryStorage;
 use holochain_net::p2p_config::P2pConfig;
 use jsonrpc_ws_server::jsonrpc_core::IoHandler;
+use serde_json::Value;
 use std::{
+    convert::TryFrom,
     sync::{mpsc::SyncSender, Arc, Mutex, RwLock, RwLockReadGuard},
     thread::sleep,
     time::Duration,
@@ -32,6 +42,7 @@ use std::{
 #[derive(Clone)]
 pub struct Context {
     pub agent_id: AgentId,
+    pub keystore: Arc<Mutex<Keystore>>,
     pub logger: Arc<Mutex<Logger>>,
     pub persister: Arc<Mutex<Persister>>,
     state: Option<Arc<RwLock<State>>>,
@@ -39,10 +50,20 @@ pub struct Context {
     pub observer_channel: Option<SyncSender<Observer>>,
     pub chain_storage: Arc<RwLock<ContentAddressableStorage>>,
     pub dht_storage: Arc<RwLock<ContentAddressableStorage>>,
+    /// Holds the encrypted entries of the DHT shard apart from the chain and DHT storages.
+    /// Defaults to a memory storage.
+    pub encrypted_storage: Arc<RwLock<ContentAddressableStorage>>,
     pub eav_storage: Arc<RwLock<EntityAttributeValueStorage>>,
     pub network_config: JsonString,
+    pub network_timeouts: NetworkTimeouts,
+    pub result_retention: ResultRetention,
+    /// Overrides of the DNA's properties for this instance only, merged into them when zomes
+    /// read properties. They don't change the DNA itself and therefore not its address.
+    /// Validation callbacks don't see them, as all nodes have to validate alike.
+    pub dna_property_overrides: Option<Value>,
     pub container_api: Option<Arc<RwLock<IoHandler>>>,
     pub signal_tx: Option<SyncSender<Signal>>,
+    pub wakers: Arc<Mutex<WakerRegistry>>,
 }
 
 impl Context {
@@ -52,6 +73,7 @@ impl Context {
 
     pub fn new(
         agent_id: AgentId,
+        keystore: Arc<Mutex<Keystore>>,
         logger: Arc<Mutex<Logger>>,
         persister: Arc<Mutex<Persister>>,
         chain_storage: Arc<RwLock<ContentAddressableStorage>>,
@@ -63,6 +85,7 @@ impl Context {
     ) -> Self {
         Context {
             agent_id,
+            keystore,
             logger,
             persister,
             state: None,
@@ -71,14 +94,20 @@ impl Context {
             observer_channel: None,
             chain_storage,
             dht_storage,
+            encrypted_storage: Arc::new(RwLock::new(MemoryStorage::new())),
             eav_storage: eav,
             network_config,
+            network_timeouts: NetworkTimeouts::default(),
+            result_retention: ResultRetention::default(),
+            dna_property_overrides: None,
             container_api,
+            wakers: Arc::new(Mutex::new(WakerRegistry::new())),
         }
     }
 
     pub fn new_with_channels(
         agent_id: AgentId,
+        keystore: Arc<Mutex<Keystore>>,
         logger: Arc<Mutex<Logger>>,
         persister: Arc<Mutex<Persister>>,
         action_channel: Option<SyncSender<ActionWrapper>>,
@@ -90,6 +119,7 @@ impl Context {
     ) -> Result<Context, HolochainError> {
         Ok(Context {
             agent_id,
+            keystore,
             logger,
             persister,
             state: None,
@@ -98,20 +128,52 @@ impl Context {
             observer_channel,
             chain_storage: cas.clone(),
             dht_storage: cas,
+            encrypted_storage: Arc::new(RwLock::new(MemoryStorage::new())),
             eav_storage: eav,
             network_config,
+            network_timeouts: NetworkTimeouts::default(),
+            result_retention: ResultRetention::default(),
+            dna_property_overrides: None,
             container_api: None,
+            wakers: Arc::new(Mutex::new(WakerRegistry::new())),
         })
     }
 
     // helper function to make it easier to call the logger
-    pub fn log<T: Into<String>>(&self, msg: T) {
+    pub fn log<T: Into<String>>(&self, level: LogLevel, msg: T) {
         let mut logger = self
             .logger
             .lock()
             .or(Err(HolochainError::LoggingError))
             .expect("Logger should work");;
-        logger.log(msg.into());
+        logger.log(level, msg.into());
+    }
+
+    /// signs the given data with the keys of this context's agent
+    pub fn sign(&self, data: &str) -> HcResult<Signature> {
+        let mut keystore = self
+            .keystore
+            .lock()
+            .map_err(|_| HolochainError::ErrorGeneric("Keystore lock poisoned".to_string()))?;
+        Ok(keystore.sign(data))
+    }
+
+    /// encrypts the given app entry for this context's agent and the given recipients
+    pub fn encrypt_entry(&self, entry: &Entry, recipients: &[Address]) -> HcResult<EncryptedEntry> {
+        let mut keystore = self
+            .keystore
+            .lock()
+            .map_err(|_| HolochainError::ErrorGeneric("Keystore lock poisoned".to_string()))?;
+        keystore.encrypt_entry(entry, recipients)
+    }
+
+    /// decrypts the given entry if this context's agent is one of its recipients
+    pub fn decrypt_entry(&self, encrypted_entry: &EncryptedEntry) -> HcResult<Option<Entry>> {
+        let mut keystore = self
+            .keystore
+            .lock()
+            .map_err(|_| HolochainError::ErrorGeneric("Keystore lock poisoned".to_string()))?;
+        keystore.decrypt_entry(encrypted_entry)
     }
 
     pub fn set_state(&mut self, state: Arc<RwLock<State>>) {
@@ -125,6 +187,37 @@ impl Context {
         }
     }
 
+    /// Polls for the part of the state a future is waiting for.
+    /// If check() does not find it yet, the future's waker gets registered under the given key
+    /// so the future gets polled again once an action changed that part of the state.
+    /// Once check() is ready the result got consumed and gets cleared from the state.
+    pub fn poll_state<T, F>(&self, key: WakerKey, lw: &LocalWaker, check: F) -> Poll<T>
+    where
+        F: Fn() -> Poll<T>,
+    {
+        if let Poll::Ready(result) = check() {
+            self.clear_result(key);
+            return Poll::Ready(result);
+        }
+        self.wakers.lock().unwrap().register(key.clone(), lw);
+        // The state might have changed before the waker got registered
+        let poll = check();
+        if poll.is_ready() {
+            self.wakers.lock().unwrap().deregister(&key, lw);
+            self.clear_result(key);
+        }
+        poll
+    }
+
+    /// Makes the reducers drop the result stored under the given key.
+    /// Never blocks: if the action channel is full or gone, the result stays until
+    /// the state's ResultRetention prunes it.
+    fn clear_result(&self, key: WakerKey) {
+        if let Some(ref action_channel) = self.action_channel {
+            let _ = action_channel.try_send(ActionWrapper::new(Action::ClearResult(key)));
+        }
+    }
+
     pub fn get_dna(&self) -> Option<Dna> {
         // In the case of genesis we encounter race conditions with regards to setting the DNA.
         // Genesis gets called asynchronously right after dispatching an action that sets the DNA in
@@ -156,6 +249,58 @@ impl Context {
         dna
     }
 
+    /// how the DNA in the state shares entries of the given type, None if the state holds
+    /// no DNA (yet) or the type is no app entry type defined by it.
+    /// Does not wait for the DNA like get_dna(), so it can be used from reducers.
+    pub fn entry_type_sharing(&self, entry_type: &EntryType) -> Option<Sharing> {
+        self.state()?
+            .nucleus()
+            .dna()?
+            .get_entry_type_sharing(entry_type)
+    }
+
+    /// Whether the given entry may leave this agent's source chain, i.e. be published to and
+    /// served from the DHT. The sharing the DNA defines for its type decides, and so do the
+    /// entries that links and deletions point to: publishing those would tell the network
+    /// about private entries.
+    /// Without a DNA in the state only system entries can be published.
+    /// Does not wait for the DNA like get_dna(), so it can be used from reducers.
+    pub fn can_publish(&self, entry: &Entry) -> bool {
+        match entry {
+            Entry::LinkAdd(link_add) => {
+                let link = link_add.link();
+                !self.is_private_entry(link.base()) && !self.is_private_entry(link.target())
+            }
+            Entry::Deletion(deletion_entry) => {
+                !self.is_private_entry(&deletion_entry.clone().deleted_entry_address())
+            }
+            _ => self.can_publish_entry_type(&entry.entry_type()),
+        }
+    }
+
+    /// Whether entries of the given type may be published, see can_publish()
+    pub fn can_publish_entry_type(&self, entry_type: &EntryType) -> bool {
+        match self.state().and_then(|state| state.nucleus().dna()) {
+            Some(dna) => dna.can_publish_entry_type(entry_type),
+            None => entry_type.is_sys() && entry_type.can_publish(),
+        }
+    }
+
+    /// Whether the given address is that of an entry on this agent's source chain
+    /// that must not be published. Entries of other agents are no concern here,
+    /// their private entries never reach us.
+    pub fn is_private_entry(&self, address: &Address) -> bool {
+        let maybe_entry = self.state().and_then(|state| {
+            let storage = state.agent().chain().content_storage();
+            let maybe_json = storage.read().unwrap().fetch(address).ok()?;
+            maybe_json.and_then(|json| Entry::try_from(json).ok())
+        });
+        match maybe_entry {
+            Some(entry) => !self.can_publish_entry_type(&entry.entry_type()),
+            None => false,
+        }
+    }
+
     pub fn get_wasm(&self, zome: &str) -> Option<DnaWasm> {
         let dna = self.get_dna().expect("Callback called without DNA set!");
         dna.get_wasm_from_zome_name(zome)
@@ -215,8 +360,8 @@ pub mod tests {
     use self::tempfile::tempdir;
     use super::*;
     use crate::{
-        context::mock_network_config, instance::tests::test_logger, persister::SimplePersister,
-        state::State,
+        agent::keystore::verify, context::mock_network_config, instance::tests::test_logger,
+        persister::SimplePersister, state::State,
     };
     use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
     use holochain_core_types::agent::AgentId;
@@ -227,6 +372,36 @@ pub mod tests {
         assert_eq!(Context::default_channel_buffer_size(), 100);
     }
 
+    #[test]
+    fn sign_uses_the_keystore_of_the_agent() {
+        let keystore = Keystore::new_random();
+        let agent_id = keystore.agent_id("Terence");
+        let file_storage = Arc::new(RwLock::new(
+            FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
+        ));
+        let context = Context::new(
+            agent_id.clone(),
+            Arc::new(Mutex::new(keystore)),
+            test_logger(),
+            Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
+            file_storage.clone(),
+            file_storage.clone(),
+            Arc::new(RwLock::new(
+                EavFileStorage::new(tempdir().unwrap().path().to_str().unwrap().to_string())
+                    .unwrap(),
+            )),
+            mock_network_config(),
+            None,
+            None,
+        );
+
+        let signature = context.sign("some data").unwrap();
+        assert_eq!(
+            Ok(true),
+            verify(&agent_id.address(), "some data", &signature)
+        );
+    }
+
     #[test]
     fn state_test() {
         let file_storage = Arc::new(RwLock::new(
@@ -234,6 +409,7 @@ pub mod tests {
         ));
         let mut maybe_context = Context::new(
             AgentId::generate_fake("Terence"),
+            Arc::new(Mutex::new(Keystore::new_random())),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
@@ -267,6 +443,7 @@ pub mod tests {
         ));
         let mut context = Context::new(
             AgentId::generate_fake("Terence"),
+            Arc::new(Mutex::new(Keystore::new_random())),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
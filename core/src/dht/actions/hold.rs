@@ -4,6 +4,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -25,12 +26,14 @@ pub async fn hold_entry<'a>(
 
     await!(HoldEntryFuture {
         context: context.clone(),
+        action: action_wrapper,
         address: entry.address(),
     })
 }
 
 pub struct HoldEntryFuture {
     context: Arc<Context>,
+    action: ActionWrapper,
     address: Address,
 }
 
@@ -38,26 +41,17 @@ impl Future for HoldEntryFuture {
     type Output = Result<Address, HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        if let Some(state) = self.context.state() {
-            if state
-                .dht()
-                .content_storage()
-                .read()
-                .unwrap()
-                .contains(&self.address)
-                .unwrap()
-            {
-                Poll::Ready(Ok(self.address.clone()))
+        let key = WakerKey::Action(self.action.clone());
+        self.context.poll_state(key, lw, || {
+            if let Some(state) = self.context.state() {
+                if state.dht().contains(&self.address).unwrap() {
+                    Poll::Ready(Ok(self.address.clone()))
+                } else {
+                    Poll::Pending
+                }
             } else {
                 Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
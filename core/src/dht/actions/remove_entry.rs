@@ -4,6 +4,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -44,19 +45,17 @@ impl Future for RemoveEntryFuture {
     type Output = Result<(), HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        if let Some(state) = self.context.state() {
-            match state.dht().actions().get(&self.action) {
-                Some(Ok(_)) => Poll::Ready(Ok(())),
-                Some(Err(e)) => Poll::Ready(Err(e.clone())),
-                None => Poll::Pending,
+        let key = WakerKey::Action(self.action.clone());
+        self.context.poll_state(key, lw, || {
+            if let Some(state) = self.context.state() {
+                match state.dht().actions().get(&self.action) {
+                    Some(Ok(_)) => Poll::Ready(Ok(())),
+                    Some(Err(e)) => Poll::Ready(Err(e.clone())),
+                    None => Poll::Pending,
+                }
+            } else {
+                Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
@@ -4,10 +4,12 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     dht::dht_store::DhtStore,
+    wakers::WakerKey,
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
     crud_status::{create_crud_link_eav, create_crud_status_eav, CrudStatus, STATUS_NAME},
+    dna::entry_types::Sharing,
     eav::EntityAttributeValue,
     entry::Entry,
     error::HolochainError,
@@ -33,10 +35,15 @@ pub fn reduce(
     let reducer = maybe_reducer.unwrap();
     // Reduce
     let store = old_store.clone();
-    let maybe_new_store = reducer(context, &store, &action_wrapper);
+    let maybe_new_store = reducer(context.clone(), &store, &action_wrapper);
     match maybe_new_store {
         None => old_store,
-        Some(new_store) => Arc::new(new_store),
+        Some(mut new_store) => {
+            new_store
+                .actions_mut()
+                .prune_expired(&context.result_retention);
+            Arc::new(new_store)
+        }
     }
 }
 
@@ -48,13 +55,14 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<DhtReducer> {
         Action::UpdateEntry(_) => Some(reduce_update_entry),
         Action::RemoveEntry(_) => Some(reduce_remove_entry),
         Action::AddLink(_) => Some(reduce_add_link),
+        Action::ClearResult(WakerKey::Action(_)) => Some(reduce_clear_result),
         _ => None,
     }
 }
 
 //
 pub(crate) fn reduce_hold_entry(
-    _context: Arc<Context>,
+    context: Arc<Context>,
     old_store: &DhtStore,
     action_wrapper: &ActionWrapper,
 ) -> Option<DhtStore> {
@@ -66,9 +74,19 @@ pub(crate) fn reduce_hold_entry(
         _ => unreachable!(),
     };
 
+    // Our DHT shard answers get requests of other nodes, so entries of encrypted types
+    // must only get in there encrypted (see author_entry)
+    let encrypted = match entry {
+        Entry::Encrypted(_) => true,
+        _ => false,
+    };
+    if !encrypted && context.entry_type_sharing(&entry.entry_type()) == Some(Sharing::Encrypted) {
+        return None;
+    }
+
     // Add it to local storage
     let new_store = (*old_store).clone();
-    let content_storage = &new_store.content_storage().clone();
+    let content_storage = &new_store.storage_for(entry);
     let res = (*content_storage.write().unwrap()).add(entry).ok();
     if res.is_some() {
         let meta_storage = &new_store.meta_storage().clone();
@@ -92,6 +110,24 @@ pub(crate) fn reduce_hold_entry(
     }
 }
 
+/// Drops the result of an action once the future waiting for it consumed it
+pub(crate) fn reduce_clear_result(
+    _context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let key = unwrap_to!(action => Action::ClearResult);
+    match key {
+        WakerKey::Action(consumed_action) if old_store.actions().contains_key(consumed_action) => {
+            let mut new_store = (*old_store).clone();
+            new_store.actions_mut().remove(consumed_action);
+            Some(new_store)
+        }
+        _ => None,
+    }
+}
+
 //
 pub(crate) fn reduce_add_link(
     _context: Arc<Context>,
@@ -103,8 +139,7 @@ pub(crate) fn reduce_add_link(
     let link = unwrap_to!(action => Action::AddLink);
 
     let mut new_store = (*old_store).clone();
-    let storage = &old_store.content_storage().clone();
-    if !(*storage.read().unwrap()).contains(link.base()).unwrap() {
+    if !old_store.contains(link.base()).unwrap() {
         new_store.actions_mut().insert(
             action_wrapper.clone(),
             Err(HolochainError::ErrorGeneric(String::from(
@@ -210,12 +245,7 @@ fn reduce_remove_entry_inner(
     deletion_address: &Address,
 ) -> Result<Address, HolochainError> {
     // pre-condition: Must already have entry in local content_storage
-    let content_storage = &new_store.content_storage().clone();
-    let maybe_json_entry = content_storage
-        .read()
-        .unwrap()
-        .fetch(latest_deleted_address)
-        .unwrap();
+    let maybe_json_entry = new_store.fetch(latest_deleted_address).unwrap();
     let json_entry = maybe_json_entry.ok_or_else(|| {
         HolochainError::ErrorGeneric(String::from("trying to remove a missing entry"))
     })?;
@@ -295,8 +325,11 @@ pub mod tests {
         state::test_store,
     };
     use holochain_core_types::{
-        cas::content::AddressableContent,
-        entry::{test_entry, test_sys_entry, Entry},
+        cas::content::{Address, AddressableContent},
+        entry::{
+            encrypted_entry::EncryptedEntry, entry_type::test_app_entry_type, test_entry,
+            test_sys_entry, Entry,
+        },
         link::Link,
     };
     use std::{
@@ -339,6 +372,46 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn reduce_hold_entry_keeps_encrypted_entries_apart() {
+        let context = test_context("bob");
+        let store = test_store(context.clone());
+        let entry = Entry::Encrypted(EncryptedEntry::new(
+            test_app_entry_type(),
+            Address::from("alice"),
+            String::from("nonce"),
+            String::from("cipher"),
+            Vec::new(),
+        ));
+
+        let new_dht_store = reduce_hold_entry(
+            Arc::clone(&context),
+            &store.dht(),
+            &ActionWrapper::new(Action::Hold(entry.clone())),
+        )
+        .expect("there should be a new store for holding an encrypted entry");
+
+        assert!(!new_dht_store
+            .content_storage()
+            .read()
+            .unwrap()
+            .contains(&entry.address())
+            .unwrap());
+        assert!(new_dht_store
+            .encrypted_storage()
+            .read()
+            .unwrap()
+            .contains(&entry.address())
+            .unwrap());
+        assert_eq!(
+            new_dht_store
+                .fetch(&entry.address())
+                .unwrap()
+                .map(|content| Entry::try_from_content(&content).unwrap()),
+            Some(entry)
+        );
+    }
+
     #[test]
     fn can_add_links() {
         let context = test_context("bob");
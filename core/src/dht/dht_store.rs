@@ -1,11 +1,15 @@
-use crate::action::ActionWrapper;
+use crate::{action::ActionWrapper, retention::ResultMap};
 use holochain_core_types::{
-    cas::{content::Address, storage::ContentAddressableStorage},
+    cas::{
+        content::{Address, Content},
+        storage::ContentAddressableStorage,
+    },
     eav::{EntityAttributeValue, EntityAttributeValueStorage},
+    entry::Entry,
     error::HolochainError,
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     sync::{Arc, RwLock},
 };
 
@@ -15,9 +19,11 @@ use std::{
 pub struct DhtStore {
     // Storages holding local shard data
     content_storage: Arc<RwLock<ContentAddressableStorage>>,
+    // Encrypted entries are kept apart from the content storage, which is shared with the chain
+    encrypted_storage: Arc<RwLock<ContentAddressableStorage>>,
     meta_storage: Arc<RwLock<EntityAttributeValueStorage>>,
 
-    actions: HashMap<ActionWrapper, Result<Address, HolochainError>>,
+    actions: ResultMap<ActionWrapper, Result<Address, HolochainError>>,
 }
 
 impl PartialEq for DhtStore {
@@ -38,12 +44,14 @@ impl DhtStore {
     // =========
     pub fn new(
         content_storage: Arc<RwLock<ContentAddressableStorage>>,
+        encrypted_storage: Arc<RwLock<ContentAddressableStorage>>,
         meta_storage: Arc<RwLock<EntityAttributeValueStorage>>,
     ) -> Self {
         DhtStore {
             content_storage,
+            encrypted_storage,
             meta_storage,
-            actions: HashMap::new(),
+            actions: ResultMap::new(),
         }
     }
 
@@ -62,15 +70,37 @@ impl DhtStore {
     pub(crate) fn content_storage(&self) -> Arc<RwLock<ContentAddressableStorage>> {
         self.content_storage.clone()
     }
+    pub(crate) fn encrypted_storage(&self) -> Arc<RwLock<ContentAddressableStorage>> {
+        self.encrypted_storage.clone()
+    }
+    /// the storage the given entry gets held in
+    pub(crate) fn storage_for(&self, entry: &Entry) -> Arc<RwLock<ContentAddressableStorage>> {
+        match entry {
+            Entry::Encrypted(_) => self.encrypted_storage(),
+            _ => self.content_storage(),
+        }
+    }
+    /// tells if the content or the encrypted storage holds the given address
+    pub(crate) fn contains(&self, address: &Address) -> Result<bool, HolochainError> {
+        Ok(self.content_storage.read()?.contains(address)?
+            || self.encrypted_storage.read()?.contains(address)?)
+    }
+    /// fetches the content at the given address from the content or the encrypted storage
+    pub(crate) fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
+        match self.content_storage.read()?.fetch(address)? {
+            Some(content) => Ok(Some(content)),
+            None => self.encrypted_storage.read()?.fetch(address),
+        }
+    }
     pub(crate) fn meta_storage(&self) -> Arc<RwLock<EntityAttributeValueStorage>> {
         self.meta_storage.clone()
     }
-    pub fn actions(&self) -> &HashMap<ActionWrapper, Result<Address, HolochainError>> {
+    pub fn actions(&self) -> &ResultMap<ActionWrapper, Result<Address, HolochainError>> {
         &self.actions
     }
     pub(crate) fn actions_mut(
         &mut self,
-    ) -> &mut HashMap<ActionWrapper, Result<Address, HolochainError>> {
+    ) -> &mut ResultMap<ActionWrapper, Result<Address, HolochainError>> {
         &mut self.actions
     }
 }
@@ -1,20 +1,28 @@
 use crate::{
     action::{Action, ActionWrapper},
     context::Context,
+    logger::LogLevel,
+    retention::ResultMetrics,
     signal::Signal,
     state::State,
+    wakers::{keys_for, WakerRegistry},
 };
+use holochain_core_types::error::HcResult;
 use std::{
     sync::{
-        mpsc::{sync_channel, Receiver, SyncSender},
-        Arc, RwLock, RwLockReadGuard,
+        mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender},
+        Arc, Mutex, RwLock, RwLockReadGuard,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub const RECV_DEFAULT_TIMEOUT_MS: Duration = Duration::from_millis(10000);
 
+/// How often the action loop hands a changed state to the context's persister.
+/// Commits and initialization get persisted right away, see changes_persisted_state().
+pub const PERSIST_INTERVAL: Duration = Duration::from_millis(10000);
+
 /// Object representing a Holochain instance, i.e. a running holochain (DNA + DHT + source-chain)
 /// Holds the Event loop and processes it with the redux pattern.
 #[derive(Clone)]
@@ -23,6 +31,10 @@ pub struct Instance {
     state: Arc<RwLock<State>>,
     action_channel: Option<SyncSender<ActionWrapper>>,
     observer_channel: Option<SyncSender<Observer>>,
+    /// Wakers of the futures that wait for the state to change
+    wakers: Arc<Mutex<WakerRegistry>>,
+    /// Ends the action loop, see stop_action_loop()
+    kill_switch: Option<SyncSender<()>>,
 }
 
 type ClosureType = Box<FnMut(&State) -> bool + Send>;
@@ -112,29 +124,78 @@ impl Instance {
         sub_context.set_state(self.state.clone());
         sub_context.action_channel = self.action_channel.clone();
         sub_context.observer_channel = self.observer_channel.clone();
+        sub_context.wakers = self.wakers.clone();
         Arc::new(sub_context)
     }
 
     /// Start the Event Loop on a separate thread
     pub fn start_action_loop(&mut self, context: Arc<Context>) {
         let (rx_action, rx_observer) = self.initialize_channels();
+        let (kill_switch_tx, kill_switch_rx) = sync_channel::<()>(1);
+        self.kill_switch = Some(kill_switch_tx);
 
         let sync_self = self.clone();
         let sub_context = self.initialize_context(context);
 
         thread::spawn(move || {
             let mut state_observers: Vec<Observer> = Vec::new();
-            for action_wrapper in rx_action {
-                state_observers = sync_self.process_action(
-                    action_wrapper,
-                    state_observers,
-                    &rx_observer,
-                    &sub_context,
-                );
+            let mut unsaved_changes = false;
+            let mut last_save = Instant::now();
+            loop {
+                let received = rx_action.recv_timeout(PERSIST_INTERVAL);
+                if kill_switch_rx.try_recv().is_ok() {
+                    break;
+                }
+                match received {
+                    Ok(action_wrapper) => {
+                        state_observers = sync_self.process_action(
+                            action_wrapper,
+                            state_observers,
+                            &rx_observer,
+                            &sub_context,
+                        );
+                        unsaved_changes = true;
+                    }
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if unsaved_changes && last_save.elapsed() >= PERSIST_INTERVAL {
+                    sync_self.save_state_or_log(&sub_context);
+                    unsaved_changes = false;
+                    last_save = Instant::now();
+                }
             }
         });
     }
 
+    /// Lets the action loop end the next time it wakes up, without persisting the state again.
+    /// For when another instance takes over the storage of this one.
+    pub fn stop_action_loop(&self) {
+        if let Some(ref kill_switch) = self.kill_switch {
+            kill_switch.try_send(()).unwrap_or(());
+        }
+    }
+
+    /// Hands the current state to the context's persister.
+    /// Called by the action loop, and should be called before an instance gets shut down.
+    pub fn save_state(&self, context: &Arc<Context>) -> HcResult<()> {
+        let state = self.state().clone();
+        context
+            .persister
+            .lock()
+            .expect("owners of the persister shouldn't panic")
+            .save(state)
+    }
+
+    fn save_state_or_log(&self, context: &Arc<Context>) {
+        if let Err(error) = self.save_state(context) {
+            context.log(
+                LogLevel::Error,
+                format!("Error persisting state: {}", error),
+            );
+        }
+    }
+
     /// Calls the reducers for an action and calls the observers with the new state
     /// returns the new vector of observers
     pub(crate) fn process_action(
@@ -169,9 +230,23 @@ impl Instance {
             *state = new_state;
         }
 
+        // Persist changes of the chain and of the initialization right away,
+        // before anybody who waits for them gets woken
+        if changes_persisted_state(&action_wrapper) {
+            self.save_state_or_log(context);
+        }
+
         // @TODO: add a big fat debug logger here
         self.maybe_emit_action_signal(context, action_wrapper.action().clone());
 
+        // Wake the futures waiting for what the action changed
+        {
+            let mut wakers = self.wakers.lock().expect("owners of the wakers shouldn't panic");
+            for key in keys_for(&action_wrapper) {
+                wakers.wake(&key);
+            }
+        }
+
         // Add new observers
         state_observers.extend(rx_observer.try_iter());
 
@@ -211,6 +286,8 @@ impl Instance {
             state: Arc::new(RwLock::new(State::new(context))),
             action_channel: None,
             observer_channel: None,
+            wakers: Arc::new(Mutex::new(WakerRegistry::new())),
+            kill_switch: None,
         }
     }
 
@@ -219,6 +296,8 @@ impl Instance {
             state: Arc::new(RwLock::new(state)),
             action_channel: None,
             observer_channel: None,
+            wakers: Arc::new(Mutex::new(WakerRegistry::new())),
+            kill_switch: None,
         }
     }
 
@@ -227,6 +306,34 @@ impl Instance {
             .read()
             .expect("owners of the state RwLock shouldn't panic")
     }
+
+    /// Counts the results held in the state and the futures waiting for them,
+    /// for keeping an eye on the memory a long running instance uses
+    pub fn result_metrics(&self) -> ResultMetrics {
+        let state = self.state();
+        ResultMetrics {
+            stored_results: state.agent().stored_results()
+                + state.dht().actions().len()
+                + state.network().stored_results(),
+            pending_requests: state.network().pending_requests(),
+            waiting_futures: self
+                .wakers
+                .lock()
+                .expect("owners of the wakers shouldn't panic")
+                .pending(),
+        }
+    }
+}
+
+/// Whether reducing the given action changes the parts of the state that the persister saves,
+/// see StateSnapshot
+fn changes_persisted_state(action_wrapper: &ActionWrapper) -> bool {
+    match action_wrapper.action() {
+        Action::Commit(_) | Action::InitApplication(_) | Action::ReturnInitializationResult(_) => {
+            true
+        }
+        _ => false,
+    }
 }
 
 /*impl Default for Instance {
@@ -313,14 +420,15 @@ pub mod tests {
         action::{tests::test_action_wrapper_commit, Action, ActionWrapper},
         agent::{
             chain_store::ChainStore,
+            keystore::Keystore,
             state::{ActionResponse, AgentState},
         },
         context::{mock_network_config, Context},
+        wakers::WakerKey,
     };
     use futures::executor::block_on;
     use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
     use holochain_core_types::{
-        agent::AgentId,
         cas::content::AddressableContent,
         chain_header::test_chain_header,
         dna::{zome::Zome, Dna},
@@ -356,7 +464,7 @@ pub mod tests {
     }
 
     impl Logger for TestLogger {
-        fn log(&mut self, msg: String) {
+        fn log(&mut self, _level: LogLevel, msg: String) {
             self.log.push(msg);
         }
         fn dump(&self) -> String {
@@ -373,7 +481,8 @@ pub mod tests {
     /// create a test context and TestLogger pair so we can use the logger in assertions
     #[cfg_attr(tarpaulin, skip)]
     pub fn test_context_and_logger(agent_name: &str) -> (Arc<Context>, Arc<Mutex<TestLogger>>) {
-        let agent = AgentId::generate_fake(agent_name);
+        let keystore = Keystore::new_random();
+        let agent = keystore.agent_id(agent_name);
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
         ));
@@ -381,6 +490,7 @@ pub mod tests {
         (
             Arc::new(Context::new(
                 agent,
+                Arc::new(Mutex::new(keystore)),
                 logger.clone(),
                 Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
                 file_storage.clone(),
@@ -411,7 +521,8 @@ pub mod tests {
         action_channel: &SyncSender<ActionWrapper>,
         observer_channel: &SyncSender<Observer>,
     ) -> Arc<Context> {
-        let agent = AgentId::generate_fake(agent_name);
+        let keystore = Keystore::new_random();
+        let agent = keystore.agent_id(agent_name);
         let logger = test_logger();
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
@@ -419,6 +530,7 @@ pub mod tests {
         Arc::new(
             Context::new_with_channels(
                 agent,
+                Arc::new(Mutex::new(keystore)),
                 logger.clone(),
                 Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
                 Some(action_channel.clone()),
@@ -440,8 +552,10 @@ pub mod tests {
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
         ));
+        let keystore = Keystore::new_random();
         let mut context = Context::new(
-            AgentId::generate_fake("Florence"),
+            keystore.agent_id("Florence"),
+            Arc::new(Mutex::new(keystore)),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
@@ -464,8 +578,10 @@ pub mod tests {
         let file_system =
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap();
         let cas = Arc::new(RwLock::new(file_system.clone()));
+        let keystore = Keystore::new_random();
         let mut context = Context::new(
-            AgentId::generate_fake("Florence"),
+            keystore.agent_id("Florence"),
+            Arc::new(Mutex::new(keystore)),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(cas.clone()))),
             cas.clone(),
@@ -639,6 +755,26 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// tests that consumed results get cleared from the state
+    fn can_clear_results() {
+        let instance = Instance::new(test_context("jason"));
+        let context = instance.initialize_context(test_context("jane"));
+        let (_, rx_observer) = channel::<Observer>();
+
+        let action_wrapper = test_action_wrapper_commit();
+        instance.process_action(action_wrapper.clone(), Vec::new(), &rx_observer, &context);
+        assert_eq!(instance.result_metrics().stored_results, 1);
+
+        instance.process_action(
+            ActionWrapper::new(Action::ClearResult(WakerKey::Action(action_wrapper))),
+            Vec::new(),
+            &rx_observer,
+            &context,
+        );
+        assert_eq!(instance.result_metrics(), ResultMetrics::default());
+    }
+
     #[test]
     /// This test shows how to call dispatch with a closure that should run
     /// when the action results in a state change.  Note that the observer closure
@@ -33,6 +33,7 @@ extern crate holochain_core_types_derive;
 extern crate base64;
 extern crate globset;
 extern crate holochain_net_connection;
+extern crate holochain_sodium;
 #[macro_use]
 extern crate lazy_static;
 
@@ -47,6 +48,8 @@ pub mod logger;
 pub mod network;
 pub mod nucleus;
 pub mod persister;
+pub mod retention;
 pub mod signal;
 pub mod state;
+pub mod wakers;
 pub mod workflows;
@@ -3,11 +3,18 @@
 //! gets emitted globaly from the container.
 
 use chrono::Local;
+use holochain_core_types::error::HolochainError;
+use regex::Regex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 /// trait that defines the logging functionality that holochain_core requires
 pub trait Logger: Send {
-    // Add log message to logger
-    fn log(&mut self, msg: String);
+    // Add log message of the given level to logger
+    fn log(&mut self, level: LogLevel, msg: String);
 
     // Dump all held logs
     fn dump(&self) -> String {
@@ -23,11 +30,354 @@ pub struct SimpleLogger {
 // ignore this in test coverage as it is only side effects
 #[cfg_attr(tarpaulin, skip)]
 impl Logger for SimpleLogger {
-    fn log(&mut self, msg: String) {
+    fn log(&mut self, level: LogLevel, msg: String) {
         let date = Local::now();
-        println!("{}:{}", date.format("%Y-%m-%d %H:%M:%S"), msg);
+        println!(
+            "{}:{}: {}",
+            date.format("%Y-%m-%d %H:%M:%S"),
+            level.as_str(),
+            msg
+        );
     }
     // fn new() -> SimpleLogger {
     //      SimpleLogger {}
     // }
 }
+
+/// Severity of a log message, from the most verbose to the most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Default for LogLevel {
+    fn default() -> Self {
+        LogLevel::Info
+    }
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    /// ANSI escape code of the colour the level gets printed in on terminals
+    fn colour(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "\x1b[90m",
+            LogLevel::Debug => "\x1b[36m",
+            LogLevel::Info => "\x1b[32m",
+            LogLevel::Warn => "\x1b[33m",
+            LogLevel::Error => "\x1b[31m",
+        }
+    }
+}
+
+/// How loggers write a message: as plain text line or as a JSON object per line
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+/// Decides which messages a logger writes: those of at least the given level
+/// that match one of the include patterns, if there are any, and none of the exclude patterns.
+#[derive(Clone, Debug)]
+pub struct LogFilter {
+    level: LogLevel,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl LogFilter {
+    pub fn new(
+        level: LogLevel,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, HolochainError> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Regex::new(pattern).map_err(|error| {
+                        HolochainError::ConfigError(format!(
+                            "Invalid log pattern \"{}\": {}",
+                            pattern, error
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(LogFilter {
+            level,
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn accepts(&self, level: LogLevel, msg: &str) -> bool {
+        level >= self.level
+            && (self.include.is_empty() || self.include.iter().any(|regex| regex.is_match(msg)))
+            && !self.exclude.iter().any(|regex| regex.is_match(msg))
+    }
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        LogFilter {
+            level: LogLevel::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Formats a message of the named source (e.g. an instance) as a line, without line break
+fn format_line(format: LogFormat, name: &str, level: LogLevel, msg: &str, colour: bool) -> String {
+    let date = Local::now();
+    match format {
+        LogFormat::Text => {
+            let level = if colour {
+                format!("{}{:5}\x1b[0m", level.colour(), level.as_str())
+            } else {
+                format!("{:5}", level.as_str())
+            };
+            format!(
+                "{} {} [{}] {}",
+                date.format("%Y-%m-%d %H:%M:%S"),
+                level,
+                name,
+                msg
+            )
+        }
+        LogFormat::Json => json!({
+            "time": date.to_rfc3339(),
+            "level": level.as_str().to_lowercase(),
+            "source": name,
+            "message": msg,
+        })
+        .to_string(),
+    }
+}
+
+/// Logger that prints the messages its filter accepts to stdout,
+/// coloured by level if printed as text with colour enabled.
+pub struct StdoutLogger {
+    name: String,
+    filter: LogFilter,
+    format: LogFormat,
+    colour: bool,
+}
+
+impl StdoutLogger {
+    pub fn new(name: String, filter: LogFilter, format: LogFormat, colour: bool) -> Self {
+        StdoutLogger {
+            name,
+            filter,
+            format,
+            colour,
+        }
+    }
+}
+
+// ignore this in test coverage as it is only side effects
+#[cfg_attr(tarpaulin, skip)]
+impl Logger for StdoutLogger {
+    fn log(&mut self, level: LogLevel, msg: String) {
+        if self.filter.accepts(level, &msg) {
+            println!(
+                "{}",
+                format_line(self.format, &self.name, level, &msg, self.colour)
+            );
+        }
+    }
+}
+
+/// Logger that appends the messages its filter accepts to a file.
+/// Once the file would grow beyond max_file_size, it gets rotated: the file becomes
+/// `<file>.1`, the former `<file>.1` becomes `<file>.2` and so on up to `<file>.<max_files>`,
+/// and logging continues in an empty file.
+pub struct FileLogger {
+    name: String,
+    filter: LogFilter,
+    format: LogFormat,
+    path: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl FileLogger {
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+    pub const DEFAULT_MAX_FILES: usize = 5;
+
+    pub fn new(
+        name: String,
+        path: &Path,
+        filter: LogFilter,
+        format: LogFormat,
+        max_file_size: u64,
+        max_files: usize,
+    ) -> Result<Self, HolochainError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(FileLogger {
+            name,
+            filter,
+            format,
+            path: path.to_path_buf(),
+            max_file_size,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> Result<(), HolochainError> {
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: String) -> Result<(), HolochainError> {
+        let line = line + "\n";
+        let length = line.len() as u64;
+        if self.size > 0 && self.size + length > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += length;
+        Ok(())
+    }
+}
+
+impl Logger for FileLogger {
+    fn log(&mut self, level: LogLevel, msg: String) {
+        if self.filter.accepts(level, &msg) {
+            let line = format_line(self.format, &self.name, level, &msg, false);
+            // there is nowhere left to report a failing log file to
+            let _ = self.write_line(line);
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    use self::tempfile::tempdir;
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn log_filter_test() {
+        let filter = LogFilter::new(
+            LogLevel::Debug,
+            &["^Authoring".to_string(), "^HANDLE".to_string()],
+            &["GetDht".to_string()],
+        )
+        .unwrap();
+        assert!(filter.accepts(LogLevel::Info, "Authoring entry Qm...: committed"));
+        assert!(filter.accepts(LogLevel::Debug, "HANDLE StoreDht: Qm..."));
+        assert!(!filter.accepts(LogLevel::Debug, "HANDLE GetDht: Qm..."));
+        assert!(!filter.accepts(LogLevel::Info, "Zome Function 'main' returned: 1"));
+        assert!(!filter.accepts(LogLevel::Trace, "Authoring entry Qm...: committing..."));
+
+        assert!(LogFilter::default().accepts(LogLevel::Info, "anything"));
+        assert!(!LogFilter::default().accepts(LogLevel::Debug, "anything"));
+        assert!(LogFilter::new(LogLevel::Info, &["(".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn file_logger_writes_json_lines_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("instance.log");
+        let mut logger = FileLogger::new(
+            "test-instance".to_string(),
+            &path,
+            LogFilter::default(),
+            LogFormat::Json,
+            FileLogger::DEFAULT_MAX_FILE_SIZE,
+            FileLogger::DEFAULT_MAX_FILES,
+        )
+        .unwrap();
+        logger.log(LogLevel::Trace, "REDUCE: Commit".to_string());
+        // the level is the one given, whatever the message says
+        logger.log(LogLevel::Info, "Error: from the app".to_string());
+        logger.log(
+            LogLevel::Error,
+            "Persisting the state failed: disk full".to_string(),
+        );
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let line: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["level"], "info");
+        assert_eq!(line["message"], "Error: from the app");
+        let line: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(line["level"], "error");
+        assert_eq!(line["source"], "test-instance");
+        assert_eq!(line["message"], "Persisting the state failed: disk full");
+    }
+
+    #[test]
+    fn file_logger_rotates_test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("instance.log");
+        let mut logger = FileLogger::new(
+            "test-instance".to_string(),
+            &path,
+            LogFilter::default(),
+            LogFormat::Text,
+            100,
+            2,
+        )
+        .unwrap();
+        for i in 0..10 {
+            logger.log(LogLevel::Info, format!("message number {}", i));
+        }
+
+        let current = fs::read_to_string(&path).unwrap();
+        assert!(current.contains("message number 9"));
+        assert!(current.len() <= 100);
+        let first_rotated = fs::read_to_string(logger.rotated_path(1)).unwrap();
+        assert!(first_rotated.contains("message number 8"));
+        let second_rotated = fs::read_to_string(logger.rotated_path(2)).unwrap();
+        assert!(second_rotated.contains("message number 7"));
+        assert!(!logger.rotated_path(3).exists());
+    }
+}
@@ -3,7 +3,11 @@ use crate::{
     action::{Action, ActionWrapper, DirectMessageData},
     context::Context,
     instance::dispatch_action,
-    network::direct_message::{CustomDirectMessage, DirectMessage},
+    network::{
+        actions::dispatch_timeout,
+        direct_message::{CustomDirectMessage, DirectMessage},
+    },
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -16,6 +20,7 @@ use std::{pin::Pin, sync::Arc};
 /// SendDirectMessage Action Creator for custom (=app) messages
 /// This triggers the network module to open a synchronous node-to-node connection
 /// by sending the given CustomDirectMessage and preparing to receive a response.
+/// Fails with HolochainError::Timeout if there is no response within the configured timeout.
 pub async fn custom_send(
     to_agent: Address,
     custom_direct_message: CustomDirectMessage,
@@ -32,11 +37,11 @@ pub async fn custom_send(
     let action_wrapper = ActionWrapper::new(Action::SendDirectMessage(direct_message_data));
     dispatch_action(context.action_channel(), action_wrapper);
 
-    /* async {
-        sleep(Duration::from_secs(60));
-        let action_wrapper = ActionWrapper::new(Action::SendDirectMessageTimeout(id.clone()));
-        dispatch_action(context.action_channel(), action_wrapper.clone());
-    };*/
+    dispatch_timeout(
+        context.network_timeouts.send(),
+        ActionWrapper::new(Action::SendDirectMessageTimeout(id.clone())),
+        context,
+    );
 
     await!(SendResponseFuture {
         context: context.clone(),
@@ -54,18 +59,16 @@ impl Future for SendResponseFuture {
     type Output = Result<String, HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        let state = self.context.state().unwrap().network();
-        if let Err(error) = state.initialized() {
-            return Poll::Ready(Err(HolochainError::ErrorGeneric(error.to_string())));
-        }
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        match state.custom_direct_message_replys.get(&self.id) {
-            Some(result) => Poll::Ready(result.clone()),
-            _ => Poll::Pending,
-        }
+        let key = WakerKey::DirectMessage(self.id.clone());
+        self.context.poll_state(key, lw, || {
+            let state = self.context.state().unwrap().network();
+            if let Err(error) = state.initialized() {
+                return Poll::Ready(Err(HolochainError::ErrorGeneric(error.to_string())));
+            }
+            match state.custom_direct_message_replys.get(&self.id) {
+                Some(result) => Poll::Ready(result.clone()),
+                _ => Poll::Pending,
+            }
+        })
     }
 }
@@ -1,37 +1,41 @@
 extern crate futures;
 use crate::{
-    action::{Action, ActionWrapper},
+    action::{Action, ActionWrapper, GetEntryKey},
     context::Context,
     instance::dispatch_action,
+    network::actions::dispatch_timeout,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{cas::content::Address, entry::EntryWithMeta, error::HcResult};
-use std::{pin::Pin, sync::Arc, thread::sleep, time::Duration};
+use std::{pin::Pin, sync::Arc};
 
 /// GetEntry Action Creator
 /// This is the network version of get_entry that makes the network module start
 /// a look-up process.
 ///
-/// Returns a future that resolves to an ActionResponse.
+/// Returns a future that resolves to an ActionResponse, or to HolochainError::Timeout
+/// if the network does not answer within the configured timeout.
 pub async fn get_entry<'a>(
     context: &'a Arc<Context>,
     address: &'a Address,
 ) -> HcResult<Option<EntryWithMeta>> {
-    let action_wrapper = ActionWrapper::new(Action::GetEntry(address.clone()));
+    let key = GetEntryKey::new(address.clone());
+    let action_wrapper = ActionWrapper::new(Action::GetEntry(key.clone()));
     dispatch_action(context.action_channel(), action_wrapper.clone());
 
-    let _ = async {
-        sleep(Duration::from_secs(60));
-        let action_wrapper = ActionWrapper::new(Action::GetEntryTimeout(address.clone()));
-        dispatch_action(context.action_channel(), action_wrapper.clone());
-    };
+    dispatch_timeout(
+        context.network_timeouts.get_entry(),
+        ActionWrapper::new(Action::GetEntryTimeout(key.clone())),
+        context,
+    );
 
     await!(GetEntryFuture {
         context: context.clone(),
-        address: address.clone(),
+        key,
     })
 }
 
@@ -39,25 +43,23 @@ pub async fn get_entry<'a>(
 /// Tracks the state of the network module
 pub struct GetEntryFuture {
     context: Arc<Context>,
-    address: Address,
+    key: GetEntryKey,
 }
 
 impl Future for GetEntryFuture {
     type Output = HcResult<Option<EntryWithMeta>>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        let state = self.context.state().unwrap().network();
-        if let Err(error) = state.initialized() {
-            return Poll::Ready(Err(error));
-        }
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        match state.get_entry_with_meta_results.get(&self.address) {
-            Some(Some(result)) => Poll::Ready(result.clone()),
-            _ => Poll::Pending,
-        }
+        let key = WakerKey::GetEntry(self.key.clone());
+        self.context.poll_state(key, lw, || {
+            let state = self.context.state().unwrap().network();
+            if let Err(error) = state.initialized() {
+                return Poll::Ready(Err(error));
+            }
+            match state.get_entry_with_meta_results.get(&self.key) {
+                Some(Some(result)) => Poll::Ready(result.clone()),
+                _ => Poll::Pending,
+            }
+        })
     }
 }
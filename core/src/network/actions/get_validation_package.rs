@@ -3,6 +3,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -45,18 +46,16 @@ impl Future for GetValidationPackageFuture {
     type Output = HcResult<Option<ValidationPackage>>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        let state = self.context.state().unwrap().network();
-        if let Err(error) = state.initialized() {
-            return Poll::Ready(Err(error));
-        }
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        match state.get_validation_package_results.get(&self.address) {
-            Some(Some(result)) => Poll::Ready(result.clone()),
-            _ => Poll::Pending,
-        }
+        let key = WakerKey::GetValidationPackage(self.address.clone());
+        self.context.poll_state(key, lw, || {
+            let state = self.context.state().unwrap().network();
+            if let Err(error) = state.initialized() {
+                return Poll::Ready(Err(error));
+            }
+            match state.get_validation_package_results.get(&self.address) {
+                Some(Some(result)) => Poll::Ready(result.clone()),
+                _ => Poll::Pending,
+            }
+        })
     }
 }
@@ -4,6 +4,7 @@ use crate::{
     action::{Action, ActionWrapper, NetworkSettings},
     context::{get_dna_and_agent, Context},
     instance::dispatch_action,
+    wakers::WakerKey,
 };
 use futures::{
     task::{LocalWaker, Poll},
@@ -27,6 +28,7 @@ pub async fn initialize_network(context: &Arc<Context>) -> HcResult<()> {
 
     await!(InitNetworkFuture {
         context: context.clone(),
+        action: action_wrapper,
     })
 }
 
@@ -46,33 +48,33 @@ pub async fn initialize_network_with_spoofed_dna(
 
     await!(InitNetworkFuture {
         context: context.clone(),
+        action: action_wrapper,
     })
 }
 
 pub struct InitNetworkFuture {
     context: Arc<Context>,
+    action: ActionWrapper,
 }
 
 impl Future for InitNetworkFuture {
     type Output = HcResult<()>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        if let Some(state) = self.context.state() {
-            if state.network().network.is_some()
-                || state.network().dna_address.is_some()
-                || state.network().agent_id.is_some()
-            {
-                Poll::Ready(Ok(()))
+        let key = WakerKey::Action(self.action.clone());
+        self.context.poll_state(key, lw, || {
+            if let Some(state) = self.context.state() {
+                if state.network().network.is_some()
+                    || state.network().dna_address.is_some()
+                    || state.network().agent_id.is_some()
+                {
+                    Poll::Ready(Ok(()))
+                } else {
+                    Poll::Pending
+                }
             } else {
                 Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
@@ -5,7 +5,18 @@ pub mod get_validation_package;
 pub mod initialize_network;
 pub mod publish;
 
+use crate::{action::ActionWrapper, context::Context};
 use holochain_core_types::{cas::content::Address, error::HcResult};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    sync::{
+        mpsc::{SyncSender, TrySendError},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 #[derive(Clone, Debug)]
 pub enum ActionResponse {
@@ -13,3 +24,179 @@ pub enum ActionResponse {
     RespondGet(HcResult<()>),
     RespondGetLinks(HcResult<()>),
 }
+
+lazy_static! {
+    /// The timeouts of all requests of all instances, dispatched by a single thread
+    static ref TIMEOUTS: Arc<TimeoutQueue> = TimeoutQueue::start();
+}
+
+/// How long to wait before trying again to dispatch a timeout into a full action channel
+const FULL_CHANNEL_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Dispatches the given timeout action once the given time has passed.
+/// The timeout reducers only act on requests that are still waiting for an answer,
+/// so requests that got resolved in the meantime are left alone.
+/// The actions have to name their request by its id, so that they don't time out
+/// later requests of the same thing.
+pub(crate) fn dispatch_timeout(
+    timeout: Duration,
+    action_wrapper: ActionWrapper,
+    context: &Arc<Context>,
+) {
+    TIMEOUTS.schedule(timeout, action_wrapper, context.action_channel().clone());
+}
+
+/// A timeout action that is due at the given instant
+struct ScheduledTimeout {
+    due: Instant,
+    /// breaks ties between timeouts that are due at the same instant
+    sequence: u64,
+    action_wrapper: ActionWrapper,
+    action_channel: SyncSender<ActionWrapper>,
+}
+
+impl ScheduledTimeout {
+    fn order_key(&self) -> (Instant, u64) {
+        (self.due, self.sequence)
+    }
+}
+
+impl PartialEq for ScheduledTimeout {
+    fn eq(&self, other: &ScheduledTimeout) -> bool {
+        self.order_key() == other.order_key()
+    }
+}
+
+impl Eq for ScheduledTimeout {}
+
+impl PartialOrd for ScheduledTimeout {
+    fn partial_cmp(&self, other: &ScheduledTimeout) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledTimeout {
+    /// Reversed, so that the BinaryHeap pops the earliest timeout first
+    fn cmp(&self, other: &ScheduledTimeout) -> Ordering {
+        other.order_key().cmp(&self.order_key())
+    }
+}
+
+#[derive(Default)]
+struct Timeouts {
+    scheduled: BinaryHeap<ScheduledTimeout>,
+    next_sequence: u64,
+}
+
+/// Holds the scheduled timeouts for the thread that dispatches them when they are due.
+/// The thread sleeps until the earliest timeout is due or a new one gets scheduled.
+#[derive(Default)]
+struct TimeoutQueue {
+    timeouts: Mutex<Timeouts>,
+    changed: Condvar,
+}
+
+impl TimeoutQueue {
+    fn start() -> Arc<TimeoutQueue> {
+        let queue = Arc::new(TimeoutQueue::default());
+        let thread_queue = queue.clone();
+        thread::Builder::new()
+            .name(String::from("network timeouts"))
+            .spawn(move || thread_queue.run())
+            .expect("Could not spawn the thread for network timeouts");
+        queue
+    }
+
+    fn schedule(
+        &self,
+        timeout: Duration,
+        action_wrapper: ActionWrapper,
+        action_channel: SyncSender<ActionWrapper>,
+    ) {
+        let mut timeouts = self.timeouts.lock().unwrap();
+        let sequence = timeouts.next_sequence;
+        timeouts.next_sequence += 1;
+        timeouts.scheduled.push(ScheduledTimeout {
+            due: Instant::now() + timeout,
+            sequence,
+            action_wrapper,
+            action_channel,
+        });
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        loop {
+            let due_timeout = {
+                let mut timeouts = self.timeouts.lock().unwrap();
+                loop {
+                    let now = Instant::now();
+                    match timeouts.scheduled.peek().map(|timeout| timeout.due) {
+                        Some(due) if due <= now => break timeouts.scheduled.pop().unwrap(),
+                        Some(due) => {
+                            timeouts = self.changed.wait_timeout(timeouts, due - now).unwrap().0
+                        }
+                        None => timeouts = self.changed.wait(timeouts).unwrap(),
+                    }
+                }
+            };
+            // Blocking on a full channel would hold up the timeouts of all other instances,
+            // so those get tried again a bit later.
+            // The instance might have been stopped by now, nobody is waiting anymore then.
+            match due_timeout
+                .action_channel
+                .try_send(due_timeout.action_wrapper)
+            {
+                Err(TrySendError::Full(action_wrapper)) => self.schedule(
+                    FULL_CHANNEL_RETRY_INTERVAL,
+                    action_wrapper,
+                    due_timeout.action_channel,
+                ),
+                Ok(()) | Err(TrySendError::Disconnected(_)) => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::action::tests::test_action_wrapper;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn timeouts_get_dispatched_in_the_order_they_are_due() {
+        let queue = TimeoutQueue::start();
+        let (sender, receiver) = sync_channel(10);
+        let late = test_action_wrapper();
+        let early = test_action_wrapper();
+        let start = Instant::now();
+        queue.schedule(Duration::from_millis(300), late.clone(), sender.clone());
+        queue.schedule(Duration::from_millis(100), early.clone(), sender);
+
+        let timeout = Duration::from_millis(2000);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(early));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(receiver.recv_timeout(timeout), Ok(late));
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn full_channels_do_not_hold_up_other_timeouts() {
+        let queue = TimeoutQueue::start();
+        let (full_sender, full_receiver) = sync_channel(1);
+        let (sender, receiver) = sync_channel(1);
+        let blocking = test_action_wrapper();
+        full_sender.send(blocking.clone()).unwrap();
+        let waiting = test_action_wrapper();
+        let other = test_action_wrapper();
+        queue.schedule(Duration::from_millis(10), waiting.clone(), full_sender);
+        queue.schedule(Duration::from_millis(50), other.clone(), sender);
+
+        let timeout = Duration::from_millis(2000);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(other));
+        // the timeout for the full channel arrives once there is room again
+        assert_eq!(full_receiver.recv_timeout(timeout), Ok(blocking));
+        assert_eq!(full_receiver.recv_timeout(timeout), Ok(waiting));
+    }
+}
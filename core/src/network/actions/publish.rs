@@ -4,6 +4,7 @@ use crate::{
     context::Context,
     instance::dispatch_action,
     network::actions::ActionResponse,
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -37,21 +38,19 @@ impl Future for PublishFuture {
     type Output = HcResult<Address>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        let state = self.context.state().unwrap().network();
-        if let Err(error) = state.initialized() {
-            return Poll::Ready(Err(error));
-        }
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        match state.actions().get(&self.action) {
-            Some(ActionResponse::Publish(result)) => match result {
-                Ok(address) => Poll::Ready(Ok(address.to_owned())),
-                Err(error) => Poll::Ready(Err(error.clone())),
-            },
-            _ => Poll::Pending,
-        }
+        let key = WakerKey::Action(self.action.clone());
+        self.context.poll_state(key, lw, || {
+            let state = self.context.state().unwrap().network();
+            if let Err(error) = state.initialized() {
+                return Poll::Ready(Err(error));
+            }
+            match state.actions().get(&self.action) {
+                Some(ActionResponse::Publish(result)) => match result {
+                    Ok(address) => Poll::Ready(Ok(address.to_owned())),
+                    Err(error) => Poll::Ready(Err(error.clone())),
+                },
+                _ => Poll::Pending,
+            }
+        })
     }
 }
@@ -1,6 +1,12 @@
-use crate::{agent::find_chain_header, context::Context};
+use crate::{
+    agent::{find_chain_header, keystore::verify_chain_header},
+    context::Context,
+};
 use holochain_core_types::{
-    cas::content::Address, chain_header::ChainHeader, entry::Entry, error::HolochainError,
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::HolochainError,
 };
 use std::{convert::TryInto, sync::Arc};
 
@@ -14,6 +20,18 @@ impl EntryWithHeader {
     pub fn new(entry: Entry, header: ChainHeader) -> EntryWithHeader {
         EntryWithHeader { entry, header }
     }
+
+    /// Checks that the header belongs to the entry and was signed by all of its sources.
+    pub fn verify(&self) -> Result<(), HolochainError> {
+        if *self.header.entry_address() != self.entry.address() {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Header for {} does not match entry {}",
+                self.header.entry_address(),
+                self.entry.address()
+            )));
+        }
+        verify_chain_header(&self.header)
+    }
 }
 
 fn fetch_entry_from_cas(
@@ -39,3 +57,44 @@ pub fn fetch_entry_with_header(
 
     Ok(EntryWithHeader::new(entry, header))
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::agent::keystore::{tests::signed_header, Keystore};
+    use holochain_core_types::entry::{test_entry, test_entry_b};
+
+    #[test]
+    fn verify_accepts_signed_entry() {
+        let mut keystore = Keystore::new_random();
+        let header = signed_header(&test_entry(), &mut keystore);
+        assert_eq!(Ok(()), EntryWithHeader::new(test_entry(), header).verify());
+    }
+
+    #[test]
+    fn verify_rejects_entry_not_matching_header() {
+        let mut keystore = Keystore::new_random();
+        let header = signed_header(&test_entry(), &mut keystore);
+        assert!(EntryWithHeader::new(test_entry_b(), header)
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn verify_rejects_encrypted_entry_under_another_address() {
+        let mut keystore = Keystore::new_random();
+        let encrypted_entry = Entry::Encrypted(keystore.encrypt_entry(&test_entry(), &[]).unwrap());
+
+        // claiming the address of the entry it holds
+        let header = signed_header(&test_entry(), &mut keystore);
+        assert!(EntryWithHeader::new(encrypted_entry.clone(), header)
+            .verify()
+            .is_err());
+
+        let header = signed_header(&encrypted_entry, &mut keystore);
+        assert_eq!(
+            Ok(()),
+            EntryWithHeader::new(encrypted_entry, header).verify()
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    logger::LogLevel,
     nucleus,
 };
 use holochain_core_types::cas::content::Address;
@@ -24,9 +25,14 @@ pub fn handle_get_dht(get_dht_data: GetDhtData, context: Arc<Context>) {
         Address::from(get_dht_data.address.clone()),
     )
     .unwrap_or_else(|error| {
-        context.log(format!("Error trying to find entry {:?}", error));
+        context.log(
+            LogLevel::Error,
+            format!("Error trying to find entry {:?}", error),
+        );
         None
-    });
+    })
+    // Private entries are not supposed to be in the DHT, act as if we don't have them
+    .filter(|entry_with_meta| context.can_publish(&entry_with_meta.entry));
 
     let action_wrapper =
         ActionWrapper::new(Action::RespondGet((get_dht_data, maybe_entry_with_meta)));
@@ -48,18 +54,22 @@ pub fn handle_get_dht_meta(get_dht_meta_data: GetDhtMetaData, context: Arc<Conte
             .unwrap()
             .as_str()
             .to_string();
-        let links = context
-            .state()
-            .unwrap()
-            .dht()
-            .get_links(
-                Address::from(get_dht_meta_data.address.clone()),
-                tag.clone(),
-            )
-            .unwrap_or(HashSet::new())
-            .into_iter()
-            .map(|eav| eav.value())
-            .collect::<Vec<_>>();
+        let base = Address::from(get_dht_meta_data.address.clone());
+        // Links of private entries are as private as the entries themselves
+        let links = if context.is_private_entry(&base) {
+            Vec::new()
+        } else {
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_links(base, tag.clone())
+                .unwrap_or(HashSet::new())
+                .into_iter()
+                .map(|eav| eav.value())
+                .filter(|target| !context.is_private_entry(target))
+                .collect::<Vec<_>>()
+        };
         let action_wrapper =
             ActionWrapper::new(Action::RespondGetLinks((get_dht_meta_data, links)));
         dispatch_action(context.action_channel(), action_wrapper.clone());
@@ -4,6 +4,7 @@ pub mod store;
 
 use crate::{
     context::Context,
+    logger::LogLevel,
     network::{
         actions::publish::publish,
         handler::{get::*, send::*, store::*},
@@ -12,7 +13,7 @@ use crate::{
 use futures::executor::block_on;
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
-    hash::HashString,
+    entry::Entry,
 };
 use holochain_net_connection::{net_connection::NetHandler, protocol_wrapper::ProtocolWrapper};
 use std::{convert::TryFrom, sync::Arc};
@@ -36,7 +37,7 @@ fn is_me(c: &Arc<Context>, dna_address: &Address, agent_id: &str) -> bool {
         return false;
     }
     if (my_dna_address != *dna_address) || (agent_id != "" && c.agent_id.key != agent_id) {
-        c.log("HANDLE: ignoring, wasn't for me");
+        c.log(LogLevel::Debug, "HANDLE: ignoring, wasn't for me");
         false
     } else {
         true
@@ -58,13 +59,13 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
                 if !is_me(&context, &dht_data.dna_address, "") {
                     return Ok(());
                 }
-                context.log(format!("HANDLE StoreDht: {:?}", dht_data));
+                context.log(LogLevel::Debug, format!("HANDLE StoreDht: {:?}", dht_data));
                 handle_store_dht(dht_data, context.clone())
             }
             Ok(ProtocolWrapper::StoreDhtMeta(dht_meta_data)) => {
-                context.log(format!("HANDLE StoreDhtMeta: {:?}", dht_meta_data));
+                context.log(LogLevel::Debug, format!("HANDLE StoreDhtMeta: {:?}", dht_meta_data));
                 if !is_me(&context, &dht_meta_data.dna_address, "") {
-                    context.log(format!(
+                    context.log(LogLevel::Debug, format!(
                         "HANDLE StoreDhtMeta: ignoring, not for me. {:?}",
                         dht_meta_data
                     ));
@@ -77,19 +78,19 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
                 if !is_me(&context, &get_dht_data.dna_address, "") {
                     return Ok(());
                 }
-                context.log(format!("HANDLE GetDht: {:?}", get_dht_data));
+                context.log(LogLevel::Debug, format!("HANDLE GetDht: {:?}", get_dht_data));
                 handle_get_dht(get_dht_data, context.clone())
             }
             Ok(ProtocolWrapper::GetDhtResult(dht_data)) => {
                 if !is_me(&context, &dht_data.dna_address, &dht_data.agent_id) {
                     return Ok(());
                 }
-                context.log(format!("HANDLE GetDhtResult: {:?}", dht_data));
+                context.log(LogLevel::Debug, format!("HANDLE GetDhtResult: {:?}", dht_data));
                 handle_get_dht_result(dht_data, context.clone())
             }
             Ok(ProtocolWrapper::GetDhtMeta(get_dht_meta_data)) => {
                 if is_me(&context, &get_dht_meta_data.dna_address, "") {
-                    context.log(format!("HANDLE GetDhtMeta: {:?}", get_dht_meta_data));
+                    context.log(LogLevel::Debug, format!("HANDLE GetDhtMeta: {:?}", get_dht_meta_data));
                     handle_get_dht_meta(get_dht_meta_data, context.clone())
                 }
             }
@@ -114,7 +115,7 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
                     //    context.log("HANDLE: Got DHT meta result from myself. Ignoring.");
                     //    return Ok(());
                     //} else {
-                    context.log(format!("HANDLE: GetDhtMetaResult: {:?}", get_dht_meta_data));
+                    context.log(LogLevel::Debug, format!("HANDLE: GetDhtMetaResult: {:?}", get_dht_meta_data));
                     handle_get_dht_meta_result(get_dht_meta_data, context.clone())
                     //}
                 }
@@ -161,13 +162,18 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
 fn republish_all_public_chain_entries(context: &Arc<Context>) {
     let chain = context.state().unwrap().agent().chain();
     let top_header = context.state().unwrap().agent().top_chain_header();
+    let storage = chain.content_storage();
     chain
         .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
-        .for_each(|chain_header| {
-            let hash = HashString::from(chain_header.entry_address().to_string());
+        .filter_map(|chain_header| {
+            let maybe_json = (*storage.read().unwrap()).fetch(chain_header.entry_address());
+            maybe_json.ok()?.and_then(|json| Entry::try_from(json).ok())
+        })
+        .filter(|entry| context.can_publish(entry))
+        .for_each(|entry| {
+            let hash = entry.address();
             match block_on(publish(hash.clone(), context)) {
-                Err(e) => context.log(format!("unable to publish {:?}, got error: {:?}", hash, e)),
+                Err(e) => context.log(LogLevel::Error, format!("unable to publish {:?}, got error: {:?}", hash, e)),
                 _ => {}
             }
         });
@@ -2,6 +2,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
+    logger::LogLevel,
     network::direct_message::DirectMessage,
     workflows::{
         handle_custom_direct_message::handle_custom_direct_message,
@@ -29,7 +30,10 @@ pub fn handle_send(message_data: MessageData, context: Arc<Context>) {
                     custom_direct_message,
                     context.clone(),
                 )) {
-                    context.log(format!("Error handling custom direct message: {:?}", error));
+                    context.log(
+                        LogLevel::Error,
+                        format!("Error handling custom direct message: {:?}", error),
+                    );
                 }
             });
         }
@@ -48,6 +52,7 @@ pub fn handle_send(message_data: MessageData, context: Arc<Context>) {
             });
         }
         DirectMessage::ValidationPackage(_) => context.log(
+            LogLevel::Warn,
             "Got DirectMessage::ValidationPackage as initial message. This should not happen.",
         ),
     };
@@ -71,7 +76,7 @@ pub fn handle_send_result(message_data: MessageData, context: Arc<Context>) {
     match response {
         DirectMessage::Custom(custom_direct_message) => {
             if initial_message.is_none() {
-                context.log("Received a custom direct message response but could not find message ID in history. Not able to process.");
+                context.log(LogLevel::Warn, "Received a custom direct message response but could not find message ID in history. Not able to process.");
                 return;
             }
 
@@ -86,11 +91,12 @@ pub fn handle_send_result(message_data: MessageData, context: Arc<Context>) {
             dispatch_action(context.action_channel(), action_wrapper.clone());
         }
         DirectMessage::RequestValidationPackage(_) => context.log(
+            LogLevel::Warn,
             "Got DirectMessage::RequestValidationPackage as a response. This should not happen.",
         ),
         DirectMessage::ValidationPackage(maybe_validation_package) => {
             if initial_message.is_none() {
-                context.log("Received a validation package but could not find message ID in history. Not able to process.");
+                context.log(LogLevel::Warn, "Received a validation package but could not find message ID in history. Not able to process.");
                 return;
             }
 
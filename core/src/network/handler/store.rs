@@ -1,5 +1,6 @@
 use crate::{
     context::Context,
+    logger::LogLevel,
     network::entry_with_header::EntryWithHeader,
     workflows::{hold_entry::hold_entry_workflow, hold_link::hold_link_workflow},
 };
@@ -17,7 +18,7 @@ pub fn handle_store_dht(dht_data: DhtData, context: Arc<Context>) {
         serde_json::from_str(&serde_json::to_string(&dht_data.content).unwrap()).unwrap();
     thread::spawn(move || {
         match block_on(hold_entry_workflow(&entry_with_header, &context.clone())) {
-            Err(error) => context.log(error),
+            Err(error) => context.log(LogLevel::Error, error),
             _ => (),
         }
     });
@@ -28,7 +29,10 @@ pub fn handle_store_dht(dht_data: DhtData, context: Arc<Context>) {
 pub fn handle_store_dht_meta(dht_meta_data: DhtMetaData, context: Arc<Context>) {
     match dht_meta_data.attribute.as_ref() {
         "link" => {
-            context.log("HANDLE: StoreDhtMeta: got LINK. processing...");
+            context.log(
+                LogLevel::Debug,
+                "HANDLE: StoreDhtMeta: got LINK. processing...",
+            );
             let entry_with_header: EntryWithHeader = serde_json::from_str(
                 &serde_json::to_string(&dht_meta_data.content)
                     .expect("dht_meta_data should be EntryWithHader"),
@@ -36,13 +40,16 @@ pub fn handle_store_dht_meta(dht_meta_data: DhtMetaData, context: Arc<Context>)
             .expect("dht_meta_data should be EntryWithHader");
             thread::spawn(move || {
                 match block_on(hold_link_workflow(&entry_with_header, &context.clone())) {
-                    Err(error) => context.log(error),
+                    Err(error) => context.log(LogLevel::Error, error),
                     _ => (),
                 }
             });
         }
         STATUS_NAME => {
-            context.log("HANDLE: StoreDhtMeta: got CRUD status. processing...");
+            context.log(
+                LogLevel::Debug,
+                "HANDLE: StoreDhtMeta: got CRUD status. processing...",
+            );
             let _crud_status: CrudStatus = serde_json::from_str(
                 &serde_json::to_string(&dht_meta_data.content)
                     .expect("dht_meta_data should be crud_status"),
@@ -51,7 +58,10 @@ pub fn handle_store_dht_meta(dht_meta_data: DhtMetaData, context: Arc<Context>)
             // FIXME: block_on hold crud_status metadata in DHT?
         }
         LINK_NAME => {
-            context.log("HANDLE: StoreDhtMeta: got CRUD LINK. processing...");
+            context.log(
+                LogLevel::Debug,
+                "HANDLE: StoreDhtMeta: got CRUD LINK. processing...",
+            );
             let _crud_link: Address = serde_json::from_str(
                 &serde_json::to_string(&dht_meta_data.content)
                     .expect("dht_meta_data should be crud_link"),
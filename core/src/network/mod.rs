@@ -7,6 +7,44 @@ pub mod state;
 #[cfg(test)]
 pub mod test_utils;
 
+use std::time::Duration;
+
+/// How long requests to the network wait for an answer before they fail with
+/// HolochainError::Timeout. All values are in milliseconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkTimeouts {
+    pub get_entry: u64,
+    pub get_links: u64,
+    pub send: u64,
+}
+
+impl NetworkTimeouts {
+    pub const DEFAULT_TIMEOUT_MS: u64 = 60000;
+
+    pub fn get_entry(&self) -> Duration {
+        Duration::from_millis(self.get_entry)
+    }
+
+    pub fn get_links(&self) -> Duration {
+        Duration::from_millis(self.get_links)
+    }
+
+    pub fn send(&self) -> Duration {
+        Duration::from_millis(self.send)
+    }
+}
+
+impl Default for NetworkTimeouts {
+    fn default() -> Self {
+        NetworkTimeouts {
+            get_entry: Self::DEFAULT_TIMEOUT_MS,
+            get_links: Self::DEFAULT_TIMEOUT_MS,
+            send: Self::DEFAULT_TIMEOUT_MS,
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::{
@@ -15,9 +53,10 @@ pub mod tests {
         instance::tests::test_instance_and_context_by_name,
         network::{
             actions::{
-                get_entry::get_entry, get_links::get_links,
+                custom_send::custom_send, get_entry::get_entry, get_links::get_links,
                 get_validation_package::get_validation_package,
             },
+            direct_message::CustomDirectMessage,
             test_utils::test_wat_always_valid,
         },
         workflows::author_entry::author_entry,
@@ -27,8 +66,10 @@ pub mod tests {
         cas::content::{Address, AddressableContent},
         crud_status::{create_crud_status_eav, CrudStatus},
         entry::{entry_type::test_app_entry_type, test_entry, Entry},
+        error::HolochainError,
         link::Link,
     };
+    use std::{sync::Arc, thread, time::Duration};
     use test_utils::*;
 
     #[test]
@@ -150,4 +191,60 @@ pub mod tests {
                 && (links[1] == entry_addresses[1] || links[1] == entry_addresses[2])
         );
     }
+
+    #[test]
+    fn send_to_unreachable_agent_times_out() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.uuid = String::from("send_to_unreachable_agent_times_out");
+        let (_, context) = test_instance_and_context_by_name(dna, "alice4").unwrap();
+        let mut context = (*context).clone();
+        context.network_timeouts.send = 100;
+        let context = Arc::new(context);
+
+        let message = CustomDirectMessage {
+            zome: String::from("test_zome"),
+            payload: Ok(String::from("anybody there?")),
+        };
+        let result = block_on(custom_send(Address::from("nobody"), message, &context));
+        assert_eq!(result, Err(HolochainError::Timeout));
+        assert!(context
+            .state()
+            .unwrap()
+            .network()
+            .direct_message_connections
+            .is_empty());
+    }
+
+    #[test]
+    fn pending_requests_sleep_until_woken() {
+        let mut dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.uuid = String::from("pending_requests_sleep_until_woken");
+        let (_, context) = test_instance_and_context_by_name(dna, "alice5").unwrap();
+        let mut context = (*context).clone();
+        context.network_timeouts.send = 1000;
+        let context = Arc::new(context);
+        assert_eq!(context.wakers.lock().unwrap().pending(), 0);
+
+        let request = {
+            let context = context.clone();
+            thread::spawn(move || {
+                let message = CustomDirectMessage {
+                    zome: String::from("test_zome"),
+                    payload: Ok(String::from("anybody there?")),
+                };
+                block_on(custom_send(Address::from("nobody"), message, &context))
+            })
+        };
+        // the request registers its waker instead of getting polled over and over
+        let mut tries = 0;
+        while context.wakers.lock().unwrap().pending() == 0 && tries < 50 {
+            thread::sleep(Duration::from_millis(10));
+            tries += 1;
+        }
+        assert_eq!(context.wakers.lock().unwrap().pending(), 1);
+
+        // and the timeout wakes it
+        assert_eq!(request.join().unwrap(), Err(HolochainError::Timeout));
+        assert_eq!(context.wakers.lock().unwrap().pending(), 0);
+    }
 }
@@ -0,0 +1,75 @@
+use crate::{
+    action::ActionWrapper, context::Context, network::state::NetworkState, wakers::WakerKey,
+};
+use std::sync::Arc;
+
+/// Drops the result a future was waiting for once that future consumed it
+pub fn reduce_clear_result(
+    _context: Arc<Context>,
+    network_state: &mut NetworkState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let key = unwrap_to!(action => crate::action::Action::ClearResult);
+
+    match key {
+        WakerKey::Action(consumed_action) => {
+            network_state.actions.remove(consumed_action);
+        }
+        WakerKey::GetEntry(key) => {
+            network_state.get_entry_with_meta_results.remove(key);
+        }
+        WakerKey::GetLinks(key) => {
+            network_state.get_links_results.remove(key);
+        }
+        WakerKey::GetValidationPackage(address) => {
+            network_state.get_validation_package_results.remove(address);
+        }
+        WakerKey::DirectMessage(id) => {
+            network_state.custom_direct_message_replys.remove(id);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        action::{Action, ActionWrapper, GetEntryKey},
+        instance::tests::test_context,
+        state::test_store,
+        wakers::WakerKey,
+    };
+    use holochain_core_types::{cas::content::AddressableContent, entry::test_entry};
+
+    #[test]
+    pub fn reduce_clear_result_test() {
+        let context = test_context("alice");
+        let mut store = test_store(context.clone());
+
+        let key = GetEntryKey::new(test_entry().address());
+        let other_key = GetEntryKey::new(test_entry().address());
+        for key in vec![key.clone(), other_key.clone()] {
+            let action_wrapper = ActionWrapper::new(Action::GetEntry(key));
+            store = store.reduce(context.clone(), action_wrapper);
+        }
+        assert!(store
+            .network()
+            .get_entry_with_meta_results
+            .contains_key(&key));
+
+        let action_wrapper =
+            ActionWrapper::new(Action::ClearResult(WakerKey::GetEntry(key.clone())));
+        let store = store.reduce(context.clone(), action_wrapper);
+        assert!(!store
+            .network()
+            .get_entry_with_meta_results
+            .contains_key(&key));
+        // other requests for the same address keep their results
+        assert!(store
+            .network()
+            .get_entry_with_meta_results
+            .contains_key(&other_key));
+    }
+}
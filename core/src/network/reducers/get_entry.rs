@@ -1,22 +1,22 @@
 use crate::{
-    action::ActionWrapper,
+    action::{ActionWrapper, GetEntryKey},
     context::Context,
     network::{reducers::send, state::NetworkState},
 };
-use holochain_core_types::{cas::content::Address, error::HolochainError};
+use holochain_core_types::error::HolochainError;
 use holochain_net_connection::protocol_wrapper::{GetDhtData, ProtocolWrapper};
 use std::sync::Arc;
 
-fn inner(network_state: &mut NetworkState, address: &Address) -> Result<(), HolochainError> {
+fn inner(network_state: &mut NetworkState, key: &GetEntryKey) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
     send(
         network_state,
         ProtocolWrapper::GetDht(GetDhtData {
-            msg_id: "?".to_string(),
+            msg_id: key.id.clone(),
             dna_address: network_state.dna_address.clone().unwrap(),
             from_agent_id: network_state.agent_id.clone().unwrap(),
-            address: address.to_string(),
+            address: key.address.to_string(),
         }),
     )
 }
@@ -27,16 +27,16 @@ pub fn reduce_get_entry(
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let address = unwrap_to!(action => crate::action::Action::GetEntry);
+    let key = unwrap_to!(action => crate::action::Action::GetEntry);
 
-    let result = match inner(network_state, &address) {
+    let result = match inner(network_state, &key) {
         Ok(()) => None,
         Err(err) => Some(Err(err)),
     };
 
     network_state
         .get_entry_with_meta_results
-        .insert(address.clone(), result);
+        .insert(key.clone(), result);
 }
 
 pub fn reduce_get_entry_timeout(
@@ -45,25 +45,14 @@ pub fn reduce_get_entry_timeout(
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let address = unwrap_to!(action => crate::action::Action::GetEntryTimeout);
+    let key = unwrap_to!(action => crate::action::Action::GetEntryTimeout);
 
-    if network_state
-        .get_entry_with_meta_results
-        .get(address)
-        .is_none()
-    {
-        return;
-    }
-
-    if network_state
-        .get_entry_with_meta_results
-        .get(address)
-        .unwrap()
-        .is_none()
-    {
+    // Only requests that are still waiting time out, results that got consumed
+    // already are gone from the map
+    if let Some(None) = network_state.get_entry_with_meta_results.get(key) {
         network_state
             .get_entry_with_meta_results
-            .insert(address.clone(), Some(Err(HolochainError::Timeout)));
+            .insert(key.clone(), Some(Err(HolochainError::Timeout)));
     }
 }
 
@@ -71,10 +60,10 @@ pub fn reduce_get_entry_timeout(
 mod tests {
 
     use crate::{
-        action::{Action, ActionWrapper, NetworkSettings},
+        action::{Action, ActionWrapper, GetEntryKey, NetworkSettings},
         context::mock_network_config,
         instance::tests::test_context,
-        state::test_store,
+        state::{test_store, State},
     };
     use holochain_core_types::{
         crud_status::CrudStatus, entry::EntryWithMeta, error::HolochainError,
@@ -88,13 +77,14 @@ mod tests {
         let store = test_store(context.clone());
 
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
+        let key = GetEntryKey::new(entry.address());
+        let action_wrapper = ActionWrapper::new(Action::GetEntry(key.clone()));
 
         let store = store.reduce(context.clone(), action_wrapper);
         let maybe_get_entry_result = store
             .network()
             .get_entry_with_meta_results
-            .get(&entry.address())
+            .get(&key)
             .map(|result| result.clone());
         assert_eq!(
             maybe_get_entry_result,
@@ -119,15 +109,22 @@ mod tests {
         let store = store.reduce(context.clone(), action_wrapper);
 
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
+        let key = GetEntryKey::new(entry.address());
+        let action_wrapper = ActionWrapper::new(Action::GetEntry(key.clone()));
 
         let store = store.reduce(context.clone(), action_wrapper);
         let maybe_get_entry_result = store
             .network()
             .get_entry_with_meta_results
-            .get(&entry.address())
+            .get(&key)
             .map(|result| result.clone());
         assert_eq!(maybe_get_entry_result, Some(None));
+
+        // every request gets its own result
+        let other_key = GetEntryKey::new(entry.address());
+        let action_wrapper = ActionWrapper::new(Action::GetEntry(other_key.clone()));
+        let store = store.reduce(context.clone(), action_wrapper);
+        assert_eq!(store.network().get_entry_with_meta_results.len(), 2);
     }
 
     #[test]
@@ -138,57 +135,44 @@ mod tests {
 
         Arc::get_mut(&mut context).unwrap().set_state(store.clone());
 
-        let action_wrapper = ActionWrapper::new(Action::InitNetwork(NetworkSettings {
+        let reduce = |action| {
+            let mut new_store = store.write().unwrap();
+            *new_store = new_store.reduce(context.clone(), ActionWrapper::new(action));
+        };
+        let get_result = |key: &GetEntryKey, state: &Arc<RwLock<State>>| {
+            state
+                .read()
+                .unwrap()
+                .network()
+                .get_entry_with_meta_results
+                .get(key)
+                .map(|result| result.clone())
+        };
+
+        reduce(Action::InitNetwork(NetworkSettings {
             config: mock_network_config(),
             dna_address: "abcd".into(),
             agent_id: String::from("abcd"),
         }));
 
-        {
-            let mut new_store = store.write().unwrap();
-            *new_store = new_store.reduce(context.clone(), action_wrapper);
-        }
-
         let entry = test_entry();
-        let action_wrapper = ActionWrapper::new(Action::GetEntry(entry.address()));
-
-        {
-            let mut new_store = store.write().unwrap();
-            *new_store = new_store.reduce(context.clone(), action_wrapper);
-        }
-        let maybe_get_entry_result = store
-            .read()
-            .unwrap()
-            .network()
-            .get_entry_with_meta_results
-            .get(&entry.address())
-            .map(|result| result.clone());
-        assert_eq!(maybe_get_entry_result, Some(None));
+        let key = GetEntryKey::new(entry.address());
+        reduce(Action::GetEntry(key.clone()));
+        assert_eq!(get_result(&key, &store), Some(None));
 
-        let action_wrapper = ActionWrapper::new(Action::GetEntryTimeout(entry.address()));
-        {
-            let mut new_store = store.write().unwrap();
-            *new_store = new_store.reduce(context.clone(), action_wrapper);
-        }
-        let maybe_get_entry_result = store
-            .read()
-            .unwrap()
-            .network()
-            .get_entry_with_meta_results
-            .get(&entry.address())
-            .map(|result| result.clone());
+        reduce(Action::GetEntryTimeout(key.clone()));
         assert_eq!(
-            maybe_get_entry_result,
+            get_result(&key, &store),
             Some(Some(Err(HolochainError::Timeout)))
         );
 
-        // test that an existing result does not get overwritten by timeout signal
         let entry_with_meta = EntryWithMeta {
             entry: entry.clone(),
             crud_status: CrudStatus::Live,
             maybe_crud_link: None,
         };
-        let dht_data = DhtData {
+        let dht_data = |key: &GetEntryKey| DhtData {
+            msg_id: key.id.clone(),
             address: entry.address().to_string(),
             content: serde_json::from_str(
                 &serde_json::to_string(&Some(entry_with_meta.clone())).unwrap(),
@@ -197,40 +181,33 @@ mod tests {
             ..Default::default()
         };
 
-        let action_wrapper = ActionWrapper::new(Action::HandleGetResult(dht_data));
-        {
-            let mut new_store = store.write().unwrap();
-            *new_store = new_store.reduce(context.clone(), action_wrapper);
-        }
-        let maybe_entry_with_meta_result = store
-            .read()
-            .unwrap()
-            .network()
-            .get_entry_with_meta_results
-            .get(&entry.address())
-            .map(|result| result.clone());
-        assert!(maybe_entry_with_meta_result.is_some());
-        let maybe_entry_with_meta = maybe_entry_with_meta_result.unwrap().unwrap();
-        let entry_with_meta = maybe_entry_with_meta.unwrap().unwrap();
-        assert_eq!(entry_with_meta.entry, entry.clone());
+        // an answer that comes in after the timeout does not change the result
+        reduce(Action::HandleGetResult(dht_data(&key)));
+        assert_eq!(
+            get_result(&key, &store),
+            Some(Some(Err(HolochainError::Timeout)))
+        );
+
+        // test that an existing result does not get overwritten by timeout signal
+        let key = GetEntryKey::new(entry.address());
+        reduce(Action::GetEntry(key.clone()));
+        reduce(Action::HandleGetResult(dht_data(&key)));
+        let maybe_entry_with_meta = get_result(&key, &store).unwrap().unwrap();
+        let got_entry_with_meta = maybe_entry_with_meta.unwrap().unwrap();
+        assert_eq!(got_entry_with_meta.entry, entry.clone());
 
         // Ok we got a positive result in the state
-        let action_wrapper = ActionWrapper::new(Action::GetEntryTimeout(entry.address()));
-        {
-            let mut new_store = store.write().unwrap();
-            *new_store = new_store.reduce(context.clone(), action_wrapper);
-        }
-        let maybe_entry_with_meta_result = store
-            .read()
-            .unwrap()
-            .network()
-            .get_entry_with_meta_results
-            .get(&entry.address())
-            .map(|result| result.clone());
+        reduce(Action::GetEntryTimeout(key.clone()));
         // The timeout should not have overwritten the entry
-        assert!(maybe_entry_with_meta_result.is_some());
-        let maybe_entry_with_meta = maybe_entry_with_meta_result.unwrap().unwrap();
-        let entry_with_meta = maybe_entry_with_meta.unwrap().unwrap();
-        assert_eq!(entry_with_meta.entry, entry);
+        let maybe_entry_with_meta = get_result(&key, &store).unwrap().unwrap();
+        let got_entry_with_meta = maybe_entry_with_meta.unwrap().unwrap();
+        assert_eq!(got_entry_with_meta.entry, entry);
+
+        // the timeout of an earlier request for the same address does not touch later ones
+        let earlier_key = key;
+        let key = GetEntryKey::new(entry.address());
+        reduce(Action::GetEntry(key.clone()));
+        reduce(Action::GetEntryTimeout(earlier_key));
+        assert_eq!(get_result(&key, &store), Some(None));
     }
 }
@@ -1,27 +1,23 @@
 use crate::{
-    action::ActionWrapper,
+    action::{ActionWrapper, GetLinksKey},
     context::Context,
     network::{reducers::send, state::NetworkState},
 };
-use holochain_core_types::{cas::content::Address, error::HolochainError};
+use holochain_core_types::error::HolochainError;
 use holochain_net_connection::protocol_wrapper::{GetDhtMetaData, ProtocolWrapper};
 use std::sync::Arc;
 
-fn inner(
-    network_state: &mut NetworkState,
-    address: &Address,
-    tag: &String,
-) -> Result<(), HolochainError> {
+fn inner(network_state: &mut NetworkState, key: &GetLinksKey) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
     send(
         network_state,
         ProtocolWrapper::GetDhtMeta(GetDhtMetaData {
-            msg_id: "?".to_string(),
+            msg_id: key.id.clone(),
             dna_address: network_state.dna_address.clone().unwrap(),
             from_agent_id: network_state.agent_id.clone().unwrap(),
-            address: address.to_string(),
-            attribute: format!("link__{}", tag),
+            address: key.base_address.to_string(),
+            attribute: format!("link__{}", key.tag),
         }),
     )
 }
@@ -32,16 +28,14 @@ pub fn reduce_get_links(
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let (address, tag) = unwrap_to!(action => crate::action::Action::GetLinks);
+    let key = unwrap_to!(action => crate::action::Action::GetLinks);
 
-    let result = match inner(network_state, &address, tag) {
+    let result = match inner(network_state, &key) {
         Ok(()) => None,
         Err(err) => Some(Err(err)),
     };
 
-    network_state
-        .get_links_results
-        .insert((address.clone(), tag.clone()), result);
+    network_state.get_links_results.insert(key.clone(), result);
 }
 
 pub fn reduce_get_links_timeout(
@@ -52,11 +46,8 @@ pub fn reduce_get_links_timeout(
     let action = action_wrapper.action();
     let key = unwrap_to!(action => crate::action::Action::GetLinksTimeout);
 
-    if network_state.get_links_results.get(key).is_none() {
-        return;
-    }
-
-    if network_state.get_links_results.get(key).unwrap().is_none() {
+    // Only requests that are still waiting time out, see reduce_get_entry_timeout()
+    if let Some(None) = network_state.get_links_results.get(key) {
         network_state
             .get_links_results
             .insert(key.clone(), Some(Err(HolochainError::Timeout)));
@@ -67,7 +58,7 @@ pub fn reduce_get_links_timeout(
 mod tests {
 
     use crate::{
-        action::{Action, ActionWrapper, NetworkSettings},
+        action::{Action, ActionWrapper, GetLinksKey, NetworkSettings},
         context::mock_network_config,
         instance::tests::test_context,
         state::test_store,
@@ -82,7 +73,7 @@ mod tests {
 
         let entry = test_entry();
         let tag = String::from("test-tag");
-        let key = (entry.address(), tag.clone());
+        let key = GetLinksKey::new(entry.address(), tag.clone());
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
         let store = store.reduce(context.clone(), action_wrapper);
@@ -115,7 +106,7 @@ mod tests {
 
         let entry = test_entry();
         let tag = String::from("test-tag");
-        let key = (entry.address(), tag.clone());
+        let key = GetLinksKey::new(entry.address(), tag.clone());
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
         let store = store.reduce(context.clone(), action_wrapper);
@@ -148,7 +139,7 @@ mod tests {
 
         let entry = test_entry();
         let tag = String::from("test-tag");
-        let key = (entry.address(), tag.clone());
+        let key = GetLinksKey::new(entry.address(), tag.clone());
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
         {
@@ -1,4 +1,8 @@
-use crate::{action::ActionWrapper, context::Context, network::state::NetworkState};
+use crate::{
+    action::{ActionWrapper, GetLinksKey},
+    context::Context,
+    network::state::NetworkState,
+};
 use holochain_core_types::{cas::content::Address, error::HolochainError};
 use holochain_net_connection::protocol_wrapper::DhtMetaData;
 use std::sync::Arc;
@@ -27,10 +31,15 @@ pub fn reduce_handle_get_links_result(
     let action = action_wrapper.action();
     let (dht_meta_data, tag) = unwrap_to!(action => crate::action::Action::HandleGetLinksResult);
 
-    let result = inner(network_state, dht_meta_data);
+    let key = GetLinksKey {
+        base_address: Address::from(dht_meta_data.address.clone()),
+        tag: tag.clone(),
+        id: dht_meta_data.msg_id.clone(),
+    };
 
-    network_state.get_links_results.insert(
-        (Address::from(dht_meta_data.address.clone()), tag.clone()),
-        Some(result),
-    );
+    // Answers to requests that timed out or that we never made are of no use
+    if let Some(None) = network_state.get_links_results.get(&key) {
+        let result = inner(network_state, dht_meta_data);
+        network_state.get_links_results.insert(key, Some(result));
+    }
 }
@@ -1,4 +1,8 @@
-use crate::{action::ActionWrapper, context::Context, network::state::NetworkState};
+use crate::{
+    action::{ActionWrapper, GetEntryKey},
+    context::Context,
+    network::state::NetworkState,
+};
 use holochain_core_types::{cas::content::Address, entry::EntryWithMeta, error::HolochainError};
 use holochain_net_connection::protocol_wrapper::DhtData;
 use std::sync::Arc;
@@ -26,9 +30,16 @@ pub fn reduce_handle_get_result(
     let action = action_wrapper.action();
     let dht_data = unwrap_to!(action => crate::action::Action::HandleGetResult);
 
-    let result = inner(network_state, dht_data);
+    let key = GetEntryKey {
+        address: Address::from(dht_data.address.clone()),
+        id: dht_data.msg_id.clone(),
+    };
 
-    network_state
-        .get_entry_with_meta_results
-        .insert(Address::from(dht_data.address.clone()), Some(result));
+    // Answers to requests that timed out or that we never made are of no use
+    if let Some(None) = network_state.get_entry_with_meta_results.get(&key) {
+        let result = inner(network_state, dht_data);
+        network_state
+            .get_entry_with_meta_results
+            .insert(key, Some(result));
+    }
 }
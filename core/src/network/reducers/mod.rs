@@ -1,3 +1,4 @@
+pub mod clear_result;
 pub mod get_entry;
 pub mod get_links;
 pub mod get_validation_package;
@@ -11,6 +12,7 @@ pub mod resolve_direct_connection;
 pub mod respond_get;
 pub mod respond_get_links;
 pub mod send_direct_message;
+pub mod shutdown;
 
 use crate::{
     action::{Action, ActionWrapper, NetworkReduceFn},
@@ -18,6 +20,7 @@ use crate::{
     network::{
         direct_message::DirectMessage,
         reducers::{
+            clear_result::reduce_clear_result,
             get_entry::{reduce_get_entry, reduce_get_entry_timeout},
             get_links::{reduce_get_links, reduce_get_links_timeout},
             get_validation_package::reduce_get_validation_package,
@@ -31,6 +34,7 @@ use crate::{
             respond_get::reduce_respond_get,
             respond_get_links::reduce_respond_get_links,
             send_direct_message::{reduce_send_direct_message, reduce_send_direct_message_timeout},
+            shutdown::reduce_shutdown,
         },
         state::NetworkState,
     },
@@ -46,6 +50,7 @@ use std::sync::Arc;
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NetworkReduceFn> {
     match action_wrapper.action() {
+        Action::ClearResult(_) => Some(reduce_clear_result),
         Action::GetEntry(_) => Some(reduce_get_entry),
         Action::GetEntryTimeout(_) => Some(reduce_get_entry_timeout),
         Action::GetLinks(_) => Some(reduce_get_links),
@@ -62,6 +67,7 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NetworkReduceFn> {
         Action::RespondGetLinks(_) => Some(reduce_respond_get_links),
         Action::SendDirectMessage(_) => Some(reduce_send_direct_message),
         Action::SendDirectMessageTimeout(_) => Some(reduce_send_direct_message_timeout),
+        Action::ShutdownNetwork => Some(reduce_shutdown),
         _ => None,
     }
 }
@@ -75,7 +81,8 @@ pub fn reduce(
     match handler {
         Some(f) => {
             let mut new_state: NetworkState = (*old_state).clone();
-            f(context, &mut new_state, &action_wrapper);
+            f(context.clone(), &mut new_state, &action_wrapper);
+            new_state.prune(&context.result_retention);
             Arc::new(new_state)
         }
         None => old_state,
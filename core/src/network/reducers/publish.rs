@@ -1,6 +1,7 @@
 use crate::{
     action::ActionWrapper,
     context::Context,
+    logger::LogLevel,
     network::{
         actions::ActionResponse,
         entry_with_header::{fetch_entry_with_header, EntryWithHeader},
@@ -93,7 +94,10 @@ fn publish_link_meta(
     };
     let link = link_add_entry.link().clone();
 
-    context.log(format!("Publishing link meta for link: {:?}", link));
+    context.log(
+        LogLevel::Info,
+        format!("Publishing link meta for link: {:?}", link),
+    );
 
     send(
         network_state,
@@ -118,6 +122,12 @@ fn reduce_publish_inner(
     network_state.initialized()?;
 
     let entry_with_header = fetch_entry_with_header(&address, &context)?;
+    if !context.can_publish(&entry_with_header.entry) {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Entry {} is private and must not be published",
+            address
+        )));
+    }
     let (crud_status, maybe_crud_link) = get_entry_crud_meta_from_dht(context, address.clone())?
         .expect("Entry should have crud-status metadata in DHT.");
     match entry_with_header.entry.entry_type() {
@@ -1,6 +1,7 @@
 use crate::{
     action::ActionWrapper,
     context::Context,
+    logger::LogLevel,
     network::{actions::ActionResponse, reducers::send, state::NetworkState},
 };
 use holochain_core_types::{cas::content::Address, error::HolochainError};
@@ -37,10 +38,13 @@ pub fn reduce_respond_get_links(
     let (get_dht_meta_data, links) = unwrap_to!(action => crate::action::Action::RespondGetLinks);
     let result = reduce_respond_get_links_inner(network_state, get_dht_meta_data, links);
 
-    context.log(format!(
-        "Responding to GET LINKS request from {} with {:?}",
-        get_dht_meta_data.from_agent_id, links
-    ));
+    context.log(
+        LogLevel::Info,
+        format!(
+            "Responding to GET LINKS request from {} with {:?}",
+            get_dht_meta_data.from_agent_id, links
+        ),
+    );
 
     network_state.actions.insert(
         action_wrapper.clone(),
@@ -1,6 +1,7 @@
 use crate::{
     action::{ActionWrapper, DirectMessageData},
     context::Context,
+    logger::LogLevel,
     network::{reducers::send, state::NetworkState},
 };
 use holochain_core_types::error::HolochainError;
@@ -42,7 +43,10 @@ pub fn reduce_send_direct_message(
     let action = action_wrapper.action();
     let dm_data = unwrap_to!(action => crate::action::Action::SendDirectMessage);
     if let Err(error) = inner(network_state, dm_data) {
-        context.log(format!("Error sending direct message: {:?}", error));
+        context.log(
+            LogLevel::Error,
+            format!("Error sending direct message: {:?}", error),
+        );
     }
 }
 
@@ -58,6 +62,8 @@ pub fn reduce_send_direct_message_timeout(
         return;
     }
 
+    // A response that comes in after this would not find anybody waiting for it
+    network_state.direct_message_connections.remove(id);
     network_state
         .custom_direct_message_replys
         .insert(id.clone(), Err(HolochainError::Timeout));
@@ -120,6 +126,12 @@ mod tests {
             .get(&msg_id)
             .cloned();
         assert_eq!(maybe_reply, None);
+        assert!(store
+            .read()
+            .unwrap()
+            .network()
+            .direct_message_connections
+            .contains_key(&msg_id));
 
         let action_wrapper = ActionWrapper::new(Action::SendDirectMessageTimeout(msg_id.clone()));
         {
@@ -134,5 +146,11 @@ mod tests {
             .get(&msg_id.clone())
             .cloned();
         assert_eq!(maybe_reply, Some(Err(HolochainError::Timeout)));
+        assert!(!store
+            .read()
+            .unwrap()
+            .network()
+            .direct_message_connections
+            .contains_key(&msg_id));
     }
 }
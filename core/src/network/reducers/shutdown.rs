@@ -0,0 +1,52 @@
+use crate::{
+    action::ActionWrapper, context::Context, logger::LogLevel, network::state::NetworkState,
+};
+use std::sync::Arc;
+
+pub fn reduce_shutdown(
+    context: Arc<Context>,
+    state: &mut NetworkState,
+    _action_wrapper: &ActionWrapper,
+) {
+    if let Some(network) = state.network.take() {
+        if let Err(error) = network.lock().unwrap().stop() {
+            context.log(
+                LogLevel::Error,
+                format!("Error while stopping the network: {}", error),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        action::{Action, ActionWrapper, NetworkSettings},
+        context::mock_network_config,
+        instance::tests::test_context,
+        state::test_store,
+    };
+
+    #[test]
+    pub fn reduce_shutdown_test() {
+        let context = test_context("alice");
+        let store = test_store(context.clone());
+
+        let action_wrapper = ActionWrapper::new(Action::InitNetwork(NetworkSettings {
+            config: mock_network_config(),
+            dna_address: "abcd".into(),
+            agent_id: String::from("abcd"),
+        }));
+        let store = store.reduce(context.clone(), action_wrapper);
+        assert!(store.network().initialized().is_ok());
+
+        let store = store.reduce(context.clone(), ActionWrapper::new(Action::ShutdownNetwork));
+        assert!(store.network().network.is_none());
+        assert!(store.network().initialized().is_err());
+
+        // shutting down again does nothing
+        let store = store.reduce(context.clone(), ActionWrapper::new(Action::ShutdownNetwork));
+        assert!(store.network().network.is_none());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    action::ActionWrapper,
+    action::{ActionWrapper, GetEntryKey, GetLinksKey},
     network::{actions::ActionResponse, direct_message::DirectMessage},
+    retention::{ResultMap, ResultRetention},
 };
 use boolinator::*;
 use holochain_core_types::{
@@ -14,7 +15,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-type Actions = HashMap<ActionWrapper, ActionResponse>;
+type Actions = ResultMap<ActionWrapper, ActionResponse>;
 
 /// This represents the state of a get_entry network process:
 /// None: process started, but no response yet from the network
@@ -40,32 +41,30 @@ type GetValidationPackageResult = Option<Result<Option<ValidationPackage>, Holoc
 #[derive(Clone, Debug)]
 pub struct NetworkState {
     /// every action and the result of that action
-    // @TODO this will blow up memory, implement as some kind of dropping/FIFO with a limit?
-    // @see https://github.com/holochain/holochain-rust/issues/166
     pub actions: Actions,
     pub network: Option<Arc<Mutex<P2pNetwork>>>,
     pub dna_address: Option<Address>,
     pub agent_id: Option<String>,
 
-    /// Here we store the results of GET entry processes.
+    /// Here we store the results of GET entry processes, one for each request.
     /// None means that we are still waiting for a result from the network.
-    pub get_entry_with_meta_results: HashMap<Address, GetEntryWithMetaResult>,
+    pub get_entry_with_meta_results: ResultMap<GetEntryKey, GetEntryWithMetaResult>,
 
-    /// Here we store the results of GET links processes.
-    /// The key of this map is the base address and the tag name for which the links
+    /// Here we store the results of GET links processes, one for each request.
+    /// The key of this map holds the base address and the tag name for which the links
     /// are requested.
     /// None means that we are still waiting for a result from the network.
-    pub get_links_results: HashMap<(Address, String), GetLinksResult>,
+    pub get_links_results: ResultMap<GetLinksKey, GetLinksResult>,
 
     /// Here we store the results of get validation package processes.
     /// None means that we are still waiting for a result from the network.
-    pub get_validation_package_results: HashMap<Address, GetValidationPackageResult>,
+    pub get_validation_package_results: ResultMap<Address, GetValidationPackageResult>,
 
     /// This stores every open (= waiting for response) node-to-node messages.
     /// Entries get removed when we receive an answer through Action::ResolveDirectConnection.
     pub direct_message_connections: HashMap<String, DirectMessage>,
 
-    pub custom_direct_message_replys: HashMap<String, Result<String, HolochainError>>,
+    pub custom_direct_message_replys: ResultMap<String, Result<String, HolochainError>>,
 
     id: snowflake::ProcessUniqueId,
 }
@@ -79,23 +78,63 @@ impl PartialEq for NetworkState {
 impl NetworkState {
     pub fn new() -> Self {
         NetworkState {
-            actions: HashMap::new(),
+            actions: ResultMap::new(),
             network: None,
             dna_address: None,
             agent_id: None,
 
-            get_entry_with_meta_results: HashMap::new(),
-            get_links_results: HashMap::new(),
-            get_validation_package_results: HashMap::new(),
+            get_entry_with_meta_results: ResultMap::new(),
+            get_links_results: ResultMap::new(),
+            get_validation_package_results: ResultMap::new(),
             direct_message_connections: HashMap::new(),
-            custom_direct_message_replys: HashMap::new(),
+            custom_direct_message_replys: ResultMap::new(),
 
             id: snowflake::ProcessUniqueId::new(),
         }
     }
 
-    pub fn actions(&self) -> Actions {
-        self.actions.clone()
+    pub fn actions(&self) -> &Actions {
+        &self.actions
+    }
+
+    /// Drops results nobody consumed within the given retention, see ResultMap::prune().
+    /// Requests that still wait for an answer are kept, see ResultMap::prune_resolved(),
+    /// and action results only expire, see ResultMap::prune_expired().
+    pub fn prune(&mut self, retention: &ResultRetention) {
+        self.actions.prune_expired(retention);
+        self.get_entry_with_meta_results.prune_resolved(retention);
+        self.get_links_results.prune_resolved(retention);
+        self.get_validation_package_results
+            .prune_resolved(retention);
+        self.custom_direct_message_replys.prune(retention);
+    }
+
+    /// Number of results held in the maps of this state
+    pub fn stored_results(&self) -> usize {
+        self.actions.len()
+            + self.get_entry_with_meta_results.len()
+            + self.get_links_results.len()
+            + self.get_validation_package_results.len()
+            + self.custom_direct_message_replys.len()
+    }
+
+    /// Number of requests that still wait for an answer from the network
+    pub fn pending_requests(&self) -> usize {
+        self.get_entry_with_meta_results
+            .iter()
+            .filter(|(_, result)| result.is_none())
+            .count()
+            + self
+                .get_links_results
+                .iter()
+                .filter(|(_, result)| result.is_none())
+                .count()
+            + self
+                .get_validation_package_results
+                .iter()
+                .filter(|(_, result)| result.is_none())
+                .count()
+            + self.direct_message_connections.len()
     }
 
     pub fn initialized(&self) -> Result<(), HolochainError> {
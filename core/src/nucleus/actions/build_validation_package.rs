@@ -7,6 +7,7 @@ use crate::{
     nucleus::ribosome::callback::{
         validation_package::get_validation_package_definition, CallbackResult,
     },
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
@@ -14,6 +15,7 @@ use futures::{
 };
 use holochain_core_types::{
     chain_header::ChainHeader,
+    dna::entry_types::Sharing,
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
     validation::{ValidationPackage, ValidationPackageDefinition::*},
@@ -61,6 +63,11 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
         EntryType::AgentId => {
             // FIXME
         }
+
+        EntryType::ChainMigrate => {
+            // Migrations only get validated against the author's own chain
+        }
+
         _ => {
             return ValidationPackageFuture {
                 context: context.clone(),
@@ -77,7 +84,8 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
         let id = id.clone();
         let entry = entry.clone();
         let context = context.clone();
-        let entry_header = find_chain_header(&entry.clone(), &context).unwrap_or(
+        let maybe_entry_header = match find_chain_header(&entry.clone(), &context) {
+            Some(entry_header) => Ok(entry_header),
             // TODO: make sure that we don't run into race conditions with respect to the chain
             // We need the source chain header as part of the validation package.
             // For an already committed entry (when asked to deliver the validation package to
@@ -90,8 +98,18 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
             // and just used for the validation, I don't see why it would be a problem.
             // If it was a problem, we would have to make sure that the whole commit process
             // (including validtion) is atomic.
-            agent::state::create_new_chain_header(&entry, context.clone(), &None),
-        );
+            None => agent::state::create_new_chain_header(&entry, context.clone(), &None),
+        };
+        let entry_header = match maybe_entry_header {
+            Ok(entry_header) => entry_header,
+            Err(error) => {
+                return ValidationPackageFuture {
+                    context: context.clone(),
+                    key: id,
+                    error: Some(error),
+                };
+            }
+        };
 
         thread::spawn(move || {
             let maybe_callback_result = get_validation_package_definition(&entry, context.clone());
@@ -151,12 +169,16 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
     }
 }
 
+/// Entries of encrypted types are left out as validators would get to see their content
 fn all_public_chain_entries(context: &Arc<Context>) -> Vec<Entry> {
     let chain = context.state().unwrap().agent().chain();
     let top_header = context.state().unwrap().agent().top_chain_header();
     chain
         .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
+        .filter(|ref chain_header| context.can_publish_entry_type(chain_header.entry_type()))
+        .filter(|ref chain_header| {
+            context.entry_type_sharing(chain_header.entry_type()) != Some(Sharing::Encrypted)
+        })
         .map(|chain_header| {
             let storage = chain.content_storage().clone();
             let json = (*storage.read().unwrap())
@@ -192,20 +214,18 @@ impl Future for ValidationPackageFuture {
         if let Some(ref error) = self.error {
             return Poll::Ready(Err(error.clone()));
         }
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        if let Some(state) = self.context.state() {
-            match state.nucleus().validation_packages.get(&self.key) {
-                Some(Ok(validation_package)) => Poll::Ready(Ok(validation_package.clone())),
-                Some(Err(error)) => Poll::Ready(Err(error.clone())),
-                None => Poll::Pending,
+        let key = WakerKey::ValidationPackage(self.key.clone());
+        self.context.poll_state(key, lw, || {
+            if let Some(state) = self.context.state() {
+                match state.nucleus().validation_packages.get(&self.key) {
+                    Some(Ok(validation_package)) => Poll::Ready(Ok(validation_package.clone())),
+                    Some(Err(error)) => Poll::Ready(Err(error.clone())),
+                    None => Poll::Pending,
+                }
+            } else {
+                Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
 
@@ -14,9 +14,7 @@ pub(crate) fn get_entry_from_dht(
     context: &Arc<Context>,
     address: Address,
 ) -> Result<Option<Entry>, HolochainError> {
-    let dht = context.state().unwrap().dht().content_storage();
-    let storage = &dht.clone();
-    let json = (*storage.read().unwrap()).fetch(&address)?;
+    let json = context.state().unwrap().dht().fetch(&address)?;
     let entry: Option<Entry> = json
         .and_then(|js| js.try_into().ok())
         .map(|s: Entry| s.into());
@@ -8,13 +8,14 @@ use crate::{
         ribosome::callback::{genesis::genesis, CallbackParams, CallbackResult},
         state::NucleusStatus,
     },
+    wakers::WakerKey,
 };
 use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{dna::Dna, entry::Entry, error::HolochainError};
-use std::{pin::Pin, sync::Arc, time::*};
+use std::{pin::Pin, sync::Arc, thread, time::*};
 
 /// Timeout in seconds for initialization process.
 /// Future will resolve to an error after this duration.
@@ -107,9 +108,18 @@ pub async fn initialize_application(
         )))
         .expect("Action channel not usable in initialize_application()");
 
+    // Nothing changes in the state when initialization times out,
+    // so wake the future for it to notice
+    let created_at = Instant::now();
+    let wakers = context.wakers.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(INITIALIZATION_TIMEOUT));
+        wakers.lock().unwrap().wake(&WakerKey::Initialization);
+    });
+
     await!(InitializationFuture {
         context: context.clone(),
-        created_at: Instant::now(),
+        created_at,
     })
 }
 
@@ -124,30 +134,26 @@ impl Future for InitializationFuture {
     type Output = Result<NucleusStatus, HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-
-        if Instant::now().duration_since(self.created_at)
-            > Duration::from_secs(INITIALIZATION_TIMEOUT)
-        {
-            return Poll::Ready(Err(HolochainError::ErrorGeneric(
-                "Timeout while initializing".to_string(),
-            )));
-        }
-        if let Some(state) = self.context.state() {
-            match state.nucleus().status {
-                NucleusStatus::New => Poll::Pending,
-                NucleusStatus::Initializing => Poll::Pending,
-                NucleusStatus::Initialized => Poll::Ready(Ok(NucleusStatus::Initialized)),
-                NucleusStatus::InitializationFailed(ref error) => {
-                    Poll::Ready(Err(HolochainError::ErrorGeneric(error.clone())))
+        self.context.poll_state(WakerKey::Initialization, lw, || {
+            if Instant::now().duration_since(self.created_at)
+                >= Duration::from_secs(INITIALIZATION_TIMEOUT)
+            {
+                return Poll::Ready(Err(HolochainError::ErrorGeneric(
+                    "Timeout while initializing".to_string(),
+                )));
+            }
+            if let Some(state) = self.context.state() {
+                match state.nucleus().status {
+                    NucleusStatus::New => Poll::Pending,
+                    NucleusStatus::Initializing => Poll::Pending,
+                    NucleusStatus::Initialized => Poll::Ready(Ok(NucleusStatus::Initialized)),
+                    NucleusStatus::InitializationFailed(ref error) => {
+                        Poll::Ready(Err(HolochainError::ErrorGeneric(error.clone())))
+                    }
                 }
+            } else {
+                Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
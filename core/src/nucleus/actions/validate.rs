@@ -2,19 +2,22 @@ extern crate futures;
 extern crate serde_json;
 use crate::{
     action::{Action, ActionWrapper},
+    agent::keystore::verify_chain_header,
     context::Context,
     nucleus::ribosome::callback::{self, CallbackResult},
+    wakers::WakerKey,
 };
 use futures::{
     future::{self, Future, FutureObj},
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{
-    cas::content::AddressableContent,
+    cas::content::{Address, AddressableContent},
+    chain_migrate::{ChainMigrate, MigrationType},
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
     hash::HashString,
-    validation::ValidationData,
+    validation::{EntryLifecycle, ValidationData},
 };
 use snowflake;
 use std::{pin::Pin, sync::Arc, thread};
@@ -32,6 +35,10 @@ pub fn validate_entry<'a>(
     let id = snowflake::ProcessUniqueId::new();
     let address = entry.address();
 
+    if let Err(error) = validate_migration_rules(&entry, &validation_data, context) {
+        return FutureObj::new(Box::new(future::err(error)));
+    }
+
     match entry.entry_type() {
         EntryType::App(app_entry_type) => {
             if context
@@ -61,12 +68,19 @@ pub fn validate_entry<'a>(
         }
 
         EntryType::CapTokenGrant => {
-            // FIXME
+            // The shape of a grant always matches its capability type (which is derived from
+            // its assignees) and grants only ever get looked up on the chain of the agent
+            // who committed them when a call is checked, so there is nothing more to validate.
         }
 
         EntryType::AgentId => {
             // FIXME
         }
+
+        EntryType::ChainMigrate => {
+            // Checked by validate_migration_rules() above
+        }
+
         _ => {
             return FutureObj::new(Box::new(future::err(HolochainError::ValidationFailed(
                 format!(
@@ -118,6 +132,103 @@ pub fn validate_entry<'a>(
     }))
 }
 
+/// Enforces the rules of chain migrations.
+/// Wherever a migration gets validated it can't name the DNA of the chain it is committed to,
+/// and an Open migration has to carry the header of a Close migration in favour of this DNA,
+/// signed by the agents who open the chain.
+/// For entries that get committed to the agent's own chain also: nothing can be committed
+/// after a Close migration, and an Open migration has to come right after genesis.
+fn validate_migration_rules(
+    entry: &Entry,
+    validation_data: &ValidationData,
+    context: &Arc<Context>,
+) -> Result<(), HolochainError> {
+    let state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Context has no state".to_string()))?;
+    let agent = state.agent();
+    let on_own_chain = match validation_data.lifecycle {
+        EntryLifecycle::Chain => true,
+        _ => false,
+    };
+    if on_own_chain {
+        if let Some((closing_header, _)) = agent.chain_closure()? {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Source chain is closed by migration {}, nothing can be committed to it anymore",
+                closing_header.address()
+            )));
+        }
+    }
+
+    if let Entry::ChainMigrate(migration) = entry {
+        let dna_address = state.nucleus().dna().map(|dna| dna.address());
+        if dna_address.as_ref() == Some(migration.dna_address()) {
+            return Err(HolochainError::ValidationFailed(
+                "A chain can't migrate to or from its own DNA".to_string(),
+            ));
+        }
+        if *migration.migration_type() == MigrationType::Open {
+            let authors = if on_own_chain {
+                vec![context.agent_id.address()]
+            } else {
+                validation_data.sources.clone()
+            };
+            validate_closing_header(migration, dna_address, &authors)?;
+
+            if on_own_chain {
+                let after_genesis = agent.chain().iter(&agent.top_chain_header()).any(|header| {
+                    match header.entry_type() {
+                        EntryType::Dna | EntryType::AgentId => false,
+                        _ => true,
+                    }
+                });
+                if after_genesis {
+                    return Err(HolochainError::ValidationFailed(
+                        "An Open migration has to be the first entry after genesis".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the closing header of an Open migration is the header of a Close migration
+/// in favour of the given DNA, signed by all of the given authors of the Open migration.
+fn validate_closing_header(
+    migration: &ChainMigrate,
+    dna_address: Option<Address>,
+    authors: &Vec<Address>,
+) -> Result<(), HolochainError> {
+    let closing_header = migration.closing_header().ok_or_else(|| {
+        HolochainError::ValidationFailed(
+            "An Open migration has to name the header that closed the old chain".to_string(),
+        )
+    })?;
+    let expected_close =
+        dna_address.map(|address| Entry::ChainMigrate(ChainMigrate::close(address)).address());
+    if *closing_header.entry_type() != EntryType::ChainMigrate
+        || expected_close.as_ref() != Some(closing_header.entry_address())
+    {
+        return Err(HolochainError::ValidationFailed(
+            "The closing header of an Open migration has to be the header of a Close migration \
+             in favour of this DNA"
+                .to_string(),
+        ));
+    }
+    verify_chain_header(closing_header)?;
+    if authors.is_empty()
+        || !authors
+            .iter()
+            .all(|author| closing_header.sources().contains(author))
+    {
+        return Err(HolochainError::ValidationFailed(
+            "The old chain of an Open migration has to be closed by the same agents".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// ValidationFuture resolves to an Ok(ActionWrapper) or an Err(error_message:String).
 /// Tracks the state for ValidationResults.
 pub struct ValidationFuture {
@@ -129,19 +240,17 @@ impl Future for ValidationFuture {
     type Output = Result<HashString, HolochainError>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
-        //
-        // TODO: connect the waker to state updates for performance reasons
-        // See: https://github.com/holochain/holochain-rust/issues/314
-        //
-        lw.wake();
-        if let Some(state) = self.context.state() {
-            match state.nucleus().validation_results.get(&self.key) {
-                Some(Ok(())) => Poll::Ready(Ok(self.key.1.clone())),
-                Some(Err(e)) => Poll::Ready(Err(HolochainError::ValidationFailed(e.clone()))),
-                None => Poll::Pending,
+        let key = WakerKey::ValidationResult(self.key.clone());
+        self.context.poll_state(key, lw, || {
+            if let Some(state) = self.context.state() {
+                match state.nucleus().validation_results.get(&self.key) {
+                    Some(Ok(())) => Poll::Ready(Ok(self.key.1.clone())),
+                    Some(Err(e)) => Poll::Ready(Err(HolochainError::ValidationFailed(e.clone()))),
+                    None => Poll::Pending,
+                }
+            } else {
+                Poll::Pending
             }
-        } else {
-            Poll::Pending
-        }
+        })
     }
 }
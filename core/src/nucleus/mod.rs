@@ -40,6 +40,9 @@ pub struct ZomeFnCall {
     pub cap: Option<CapabilityCall>,
     pub fn_name: String,
     pub parameters: JsonString,
+    /// Whether the call comes from zome code of this instance (see hc_call) rather than
+    /// from an interface or a bridge. Only those calls may use the agent's address as token.
+    internal: bool,
 }
 
 impl ZomeFnCall {
@@ -57,9 +60,29 @@ impl ZomeFnCall {
             cap: cap,
             fn_name: function.to_string(),
             parameters: parameters.into(),
+            internal: false,
         }
     }
 
+    /// A call made by the instance's agent: by zome code of this instance, or by the host
+    /// the instance is embedded in (see `Holochain::call_as_agent()`), which is trusted
+    /// with the agent's rights. Never use this for calls that come in from the outside.
+    pub fn new_internal<J: Into<JsonString>>(
+        zome: &str,
+        cap: Option<CapabilityCall>,
+        function: &str,
+        parameters: J,
+    ) -> Self {
+        ZomeFnCall {
+            internal: true,
+            ..ZomeFnCall::new(zome, cap, function, parameters)
+        }
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.internal
+    }
+
     pub fn same_fn_as(&self, fn_call: &ZomeFnCall) -> bool {
         self.zome_name == fn_call.zome_name
             && self.cap == fn_call.cap
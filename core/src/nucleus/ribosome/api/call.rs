@@ -1,5 +1,6 @@
 use crate::{
     action::{Action, ActionWrapper},
+    agent::keystore::verify,
     context::Context,
     instance::RECV_DEFAULT_TIMEOUT_MS,
     nucleus::{
@@ -9,11 +10,20 @@ use crate::{
         ZomeFnCall,
     },
 };
+use chrono::{Duration, Utc};
 use holochain_core_types::{
-    dna::{capabilities::CapabilityCall, Dna},
-    entry::cap_entries::CapTokenGrant,
+    cas::content::{Address, AddressableContent},
+    dna::{
+        capabilities::{
+            CapabilityCall, CapabilityRequestParams, CapabilityType,
+            CALL_SIGNATURE_MAX_VALIDITY_SECONDS, CAP_REQUEST_PARAMS_KEY,
+        },
+        Dna,
+    },
+    entry::{cap_entries::CapTokenGrant, Entry},
     error::{DnaError, HolochainError},
     json::JsonString,
+    time::Iso8601,
 };
 use holochain_wasm_utils::api_serialization::{ZomeFnCallArgs, THIS_INSTANCE};
 use jsonrpc_lite::JsonRpc;
@@ -27,7 +37,7 @@ use wasmi::{RuntimeArgs, RuntimeValue};
 // ZomeFnCallArgs to ZomeFnCall
 impl ZomeFnCall {
     fn from_args(args: ZomeFnCallArgs) -> Self {
-        ZomeFnCall::new(&args.zome_name, args.cap, &args.fn_name, args.fn_args)
+        ZomeFnCall::new_internal(&args.zome_name, args.cap, &args.fn_name, args.fn_args)
     }
 }
 
@@ -42,7 +52,7 @@ pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
     let args_str = runtime.load_json_string_from_args(&args);
 
-    let input = match ZomeFnCallArgs::try_from(args_str.clone()) {
+    let mut input = match ZomeFnCallArgs::try_from(args_str.clone()) {
         Ok(input) => input,
         // Exit on error
         Err(_) => {
@@ -51,6 +61,14 @@ pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
         }
     };
 
+    // Zome code must not be able to call in the name of somebody else,
+    // so the caller is always the agent running it
+    let agent_address = runtime.context.agent_id.address();
+    input.cap = input.cap.map(|mut cap_call| {
+        cap_call.caller = Some(agent_address);
+        cap_call
+    });
+
     let result = if input.instance_handle == String::from(THIS_INSTANCE) {
         // ZomeFnCallArgs to ZomeFnCall
         let zome_call = ZomeFnCall::from_args(input.clone());
@@ -115,9 +133,30 @@ fn bridge_call(runtime: &mut Runtime, input: ZomeFnCallArgs) -> Result<JsonStrin
                 "No container API in context".to_string(),
             ))?;
 
-    let cap_name = match input.cap {
-        Some(cap_call) => cap_call.cap_name,
-        None => String::from(""),
+    // The callee can't tell who is calling, so we sign the call as the caller
+    let (cap_name, params) = match input.cap {
+        Some(cap_call) => {
+            let expires =
+                Iso8601::from(Utc::now() + Duration::seconds(CALL_SIGNATURE_MAX_VALIDITY_SECONDS));
+            let signature = runtime.context.sign(&cap_call.signing_payload(
+                &input.fn_name,
+                &input.fn_args,
+                &expires,
+            ))?;
+            (
+                cap_call.cap_name,
+                add_cap_request_params(
+                    &input.fn_args,
+                    CapabilityRequestParams {
+                        token: cap_call.cap_token,
+                        caller: cap_call.caller,
+                        signature: Some(signature),
+                        expires: Some(expires),
+                    },
+                )?,
+            )
+        }
+        None => (String::from(""), input.fn_args),
     };
 
     let method = format!(
@@ -130,7 +169,7 @@ fn bridge_call(runtime: &mut Runtime, input: ZomeFnCallArgs) -> Result<JsonStrin
     let id = ProcessUniqueId::new();
     let request = format!(
         r#"{{"jsonrpc": "2.0", "method": "{}", "params": {}, "id": "{}"}}"#,
-        method, input.fn_args, id
+        method, params, id
     );
 
     let response = handler
@@ -152,6 +191,23 @@ fn bridge_call(runtime: &mut Runtime, input: ZomeFnCallArgs) -> Result<JsonStrin
     }
 }
 
+/// Adds the token and caller to the arguments of a bridge call so the callee's container
+/// can check them against the callee's grants (see CAP_REQUEST_PARAMS_KEY).
+/// Only arguments that are JSON objects can carry them.
+fn add_cap_request_params(
+    fn_args: &str,
+    cap_params: CapabilityRequestParams,
+) -> Result<String, HolochainError> {
+    let mut params: serde_json::Value = serde_json::from_str(fn_args)?;
+    if let Some(object) = params.as_object_mut() {
+        object.insert(
+            CAP_REQUEST_PARAMS_KEY.to_string(),
+            serde_json::to_value(cap_params)?,
+        );
+    }
+    Ok(params.to_string())
+}
+
 pub fn validate_call(
     context: Arc<Context>,
     state: &NucleusState,
@@ -236,7 +292,6 @@ pub(crate) fn reduce_call(
     launch_zome_fn_call(context, fn_call, &code, state.dna.clone().unwrap().name);
 }
 
-// TODO: check the signature too
 fn is_token_the_agent(context: Arc<Context>, cap: &Option<CapabilityCall>) -> bool {
     match cap {
         None => false,
@@ -244,25 +299,71 @@ fn is_token_the_agent(context: Arc<Context>, cap: &Option<CapabilityCall>) -> bo
     }
 }
 
+/// The caller of the given call if it signed the call of the given function with the given
+/// parameters with a signature that did not expire yet, None otherwise.
+/// Callers from outside the instance only claim who they are, this makes sure they are.
+fn verified_caller(call: &CapabilityCall, fn_name: &str, parameters: &str) -> Option<Address> {
+    let caller = call.caller.clone()?;
+    let signature = call.signature.signature.clone()?;
+    let expires = call.signature.expires.clone()?;
+    let now = Utc::now();
+    if expires < Iso8601::from(now)
+        || expires > Iso8601::from(now + Duration::seconds(CALL_SIGNATURE_MAX_VALIDITY_SECONDS))
+    {
+        return None;
+    }
+    let payload = call.signing_payload(fn_name, parameters, &expires);
+    if verify(&caller, &payload, &signature).unwrap_or(false) {
+        Some(caller)
+    } else {
+        None
+    }
+}
+
 /// checks to see if a given function call is allowable according to the capabilities
 /// that have been registered to callers in the chain.
 fn check_capability(context: Arc<Context>, fn_call: &ZomeFnCall) -> bool {
-    // the agent can always do everything
-    if is_token_the_agent(context.clone(), &fn_call.cap) {
+    // the agent can always do everything, but the agent's address is public, so only
+    // zome code running as the agent can use it as token
+    if fn_call.is_internal() && is_token_the_agent(context.clone(), &fn_call.cap) {
         return true;
     }
 
     match fn_call.cap.clone() {
         None => false,
-        Some(call) => {
-            let chain = &context.chain_storage;
-            let maybe_json = chain.read().unwrap().fetch(&call.cap_token).unwrap();
-            let grant = match maybe_json {
-                Some(content) => CapTokenGrant::try_from(content).unwrap(),
-                None => return false,
-            };
-            grant.verify(call.cap_token.clone(), call.caller, &call.signature)
-        }
+        Some(call) => match get_grant(&context, &call.cap_token) {
+            Some(grant) => {
+                // assignees are checked against the caller, which callers from outside
+                // the instance have to prove by signing the call
+                let caller = match grant.cap_type() {
+                    CapabilityType::Assigned if !fn_call.is_internal() => verified_caller(
+                        &call,
+                        &fn_call.fn_name,
+                        &String::from(fn_call.parameters.clone()),
+                    ),
+                    _ => call.caller.clone(),
+                };
+                grant.cap_name() == call.cap_name
+                    && grant.verify(call.cap_token.clone(), caller, &call.signature)
+            }
+            None => false,
+        },
+    }
+}
+
+/// fetches the grant the given token refers to from the agent's own chain.
+/// Tokens of anything that isn't a CapTokenGrant entry of this agent, or of a grant that
+/// has been revoked, don't grant anything.
+fn get_grant(context: &Arc<Context>, token: &Address) -> Option<CapTokenGrant> {
+    let state = context.state()?;
+    let agent = state.agent();
+    agent
+        .chain()
+        .get_live_cap_grant_header(&agent.top_chain_header(), token)?;
+    let content = context.chain_storage.read().ok()?.fetch(token).ok()??;
+    match Entry::try_from(content).ok()? {
+        Entry::CapTokenGrant(grant) => Some(grant),
+        _ => None,
     }
 }
 
@@ -273,12 +374,15 @@ pub mod tests {
     extern crate wabt;
 
     use crate::{
+        agent::keystore::Keystore,
         context::Context,
         instance::{tests::test_instance_and_context, Instance, Observer, RECV_DEFAULT_TIMEOUT_MS},
         nucleus::{
             ribosome::{
                 api::{
                     call::{Action, ActionWrapper, ZomeFnCall},
+                    grant_capability::grant_capability,
+                    revoke_capability::revoke_capability,
                     tests::{
                         test_function_name, test_parameters, test_zome_api_function_wasm,
                         test_zome_name,
@@ -297,12 +401,17 @@ pub mod tests {
             capabilities::{Capability, CapabilityCall, CapabilityType, FnDeclaration},
             Dna,
         },
-        entry::Entry,
+        entry::{test_entry, Entry},
         error::{DnaError, HolochainError},
         json::JsonString,
+        signature::Signature,
+        time::test_iso_8601,
+    };
+    use holochain_wasm_utils::api_serialization::{
+        capabilities::GrantCapabilityArgs, ZomeFnCallArgs,
     };
-    use holochain_wasm_utils::api_serialization::ZomeFnCallArgs;
 
+    use chrono::DateTime;
     use futures::executor::block_on;
     use serde_json;
     use std::sync::{
@@ -358,23 +467,57 @@ pub mod tests {
         }
     }
 
+    fn test_cap_call(token_str: &str, caller: Address) -> CapabilityCall {
+        CapabilityCall::new(
+            "test_cap".to_string(),
+            Address::from(token_str),
+            Some(caller),
+        )
+    }
+
+    /// a call from outside the instance, signed by the given keystore's agent
+    fn signed_zome_call(token_str: &str, keystore: &mut Keystore) -> ZomeFnCall {
+        signed_zome_call_expiring(token_str, keystore, Utc::now() + Duration::seconds(60))
+    }
+
+    fn signed_zome_call_expiring(
+        token_str: &str,
+        keystore: &mut Keystore,
+        expires: DateTime<Utc>,
+    ) -> ZomeFnCall {
+        let cap_call = test_cap_call(token_str, keystore.address());
+        let expires = Iso8601::from(expires);
+        let signature = keystore.sign(&cap_call.signing_payload("test", "{}", &expires));
+        ZomeFnCall::new(
+            "test_zome",
+            Some(cap_call.with_signature(signature, expires)),
+            "test",
+            "{}",
+        )
+    }
+
     #[cfg_attr(tarpaulin, skip)]
     fn test_reduce_call(
         test_setup: &TestSetup,
         token_str: &str,
-        _caller: Address,
+        caller: Address,
         expected: Result<Result<JsonString, HolochainError>, RecvTimeoutError>,
     ) {
         let zome_call = ZomeFnCall::new(
             "test_zome",
-            Some(CapabilityCall::new(
-                "test_cap".to_string(),
-                Address::from(token_str),
-                None,
-            )),
+            Some(test_cap_call(token_str, caller)),
             "test",
             "{}",
         );
+        test_reduce_zome_call(test_setup, zome_call, expected);
+    }
+
+    #[cfg_attr(tarpaulin, skip)]
+    fn test_reduce_zome_call(
+        test_setup: &TestSetup,
+        zome_call: ZomeFnCall,
+        expected: Result<Result<JsonString, HolochainError>, RecvTimeoutError>,
+    ) {
         let zome_call_action = ActionWrapper::new(Action::Call(zome_call.clone()));
 
         // process the action
@@ -452,19 +595,32 @@ pub mod tests {
         let dna = setup_dna_for_cap_test(CapabilityType::Transferable);
         let test_setup = setup_test(dna);
         let expected_failure = Ok(Err(HolochainError::CapabilityCheckFailed));
-        test_reduce_call(&test_setup, "", Address::from("caller"), expected_failure);
+        test_reduce_call(
+            &test_setup,
+            "",
+            Address::from("caller"),
+            expected_failure.clone(),
+        );
 
         // Expecting timeout since there is no function in wasm to call
         let expected = Err(RecvTimeoutError::Disconnected);
         let agent_token_str = test_setup.context.agent_id.key.clone();
+        let agent_call = test_cap_call(&agent_token_str, Address::from(agent_token_str.clone()));
+        test_reduce_zome_call(
+            &test_setup,
+            ZomeFnCall::new_internal("test_zome", Some(agent_call), "test", "{}"),
+            expected.clone(),
+        );
+        // the agent's address is no token for calls from outside the instance
         test_reduce_call(
             &test_setup,
             &agent_token_str,
             Address::from(agent_token_str.clone()),
-            expected.clone(),
+            expected_failure,
         );
 
-        let grant = CapTokenGrant::create(CapabilityType::Transferable, None).unwrap();
+        let grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "nonce").unwrap();
         let grant_entry = Entry::CapTokenGrant(grant);
         let addr = block_on(author_entry(&grant_entry, None, &test_setup.context)).unwrap();
         test_reduce_call(
@@ -490,29 +646,183 @@ pub mod tests {
         // Expecting timeout since there is no function in wasm to call
         let expected = Err(RecvTimeoutError::Disconnected);
         let agent_token_str = test_setup.context.agent_id.key.clone();
+        let agent_call = test_cap_call(&agent_token_str, Address::from(agent_token_str.clone()));
+        test_reduce_zome_call(
+            &test_setup,
+            ZomeFnCall::new_internal("test_zome", Some(agent_call), "test", "{}"),
+            expected.clone(),
+        );
         test_reduce_call(
             &test_setup,
             &agent_token_str,
             Address::from(agent_token_str.clone()),
-            expected.clone(),
+            expected_failure.clone(),
         );
 
-        let someone = Address::from("somoeone");
-        let grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![someone.clone()])).unwrap();
+        let mut someone = Keystore::new_random();
+        let grant = CapTokenGrant::create(
+            "test_cap",
+            CapabilityType::Assigned,
+            Some(vec![someone.address()]),
+            "nonce",
+        )
+        .unwrap();
         let grant_entry = Entry::CapTokenGrant(grant);
         let addr = block_on(author_entry(&grant_entry, None, &test_setup.context)).unwrap();
+        test_reduce_zome_call(
+            &test_setup,
+            signed_zome_call(&String::from(addr.clone()), &mut someone),
+            expected.clone(),
+        );
+
+        // assignees have to sign their calls
         test_reduce_call(
             &test_setup,
             &String::from(addr.clone()),
-            someone,
-            expected.clone(),
+            someone.address(),
+            expected_failure.clone(),
+        );
+        // signed calls can't be replayed with other arguments
+        let mut forged_call = signed_zome_call(&String::from(addr.clone()), &mut someone);
+        forged_call.parameters = JsonString::from(r#"{"other":"arguments"}"#);
+        test_reduce_zome_call(&test_setup, forged_call, expected_failure.clone());
+        // or once their signature expired
+        test_reduce_zome_call(
+            &test_setup,
+            signed_zome_call_expiring(
+                &String::from(addr.clone()),
+                &mut someone,
+                Utc::now() - Duration::seconds(1),
+            ),
+            expected_failure.clone(),
+        );
+        // and signatures can't be made to last for long
+        test_reduce_zome_call(
+            &test_setup,
+            signed_zome_call_expiring(
+                &String::from(addr.clone()),
+                &mut someone,
+                Utc::now() + Duration::days(1),
+            ),
+            expected_failure.clone(),
+        );
+
+        let mut someone_else = Keystore::new_random();
+        test_reduce_zome_call(
+            &test_setup,
+            signed_zome_call(&String::from(addr), &mut someone_else),
+            expected_failure,
+        );
+    }
+
+    #[test]
+    fn test_call_with_token_of_other_entry() {
+        let dna = setup_dna_for_cap_test(CapabilityType::Transferable);
+        let test_setup = setup_test(dna);
+        let addr = block_on(author_entry(&test_entry(), None, &test_setup.context)).unwrap();
+        let expected_failure = Ok(Err(HolochainError::CapabilityCheckFailed));
+        test_reduce_call(
+            &test_setup,
+            &String::from(addr),
+            Address::from("any caller"),
+            expected_failure,
+        );
+    }
+
+    #[test]
+    fn test_call_with_granted_and_revoked_token() {
+        let dna = setup_dna_for_cap_test(CapabilityType::Transferable);
+        let test_setup = setup_test(dna);
+        let expected_failure = Ok(Err(HolochainError::CapabilityCheckFailed));
+
+        assert!(grant_capability(
+            &test_setup.context,
+            &test_zome_name(),
+            GrantCapabilityArgs {
+                cap_name: "other_cap".to_string(),
+                assignees: Vec::new(),
+            },
+        )
+        .is_err());
+        assert!(revoke_capability(&test_setup.context, Address::from("not a grant")).is_err());
+
+        let token = grant_capability(
+            &test_setup.context,
+            &test_zome_name(),
+            GrantCapabilityArgs {
+                cap_name: "test_cap".to_string(),
+                assignees: Vec::new(),
+            },
+        )
+        .unwrap();
+        // tokens can't be predicted from the capability and assignees
+        let other_token = grant_capability(
+            &test_setup.context,
+            &test_zome_name(),
+            GrantCapabilityArgs {
+                cap_name: "test_cap".to_string(),
+                assignees: Vec::new(),
+            },
+        )
+        .unwrap();
+        assert_ne!(token, other_token);
+        // Expecting timeout since there is no function in wasm to call
+        let expected = Err(RecvTimeoutError::Disconnected);
+        test_reduce_call(
+            &test_setup,
+            &String::from(token.clone()),
+            Address::from("any caller"),
+            expected,
+        );
+
+        revoke_capability(&test_setup.context, token.clone()).unwrap();
+        test_reduce_call(
+            &test_setup,
+            &String::from(token.clone()),
+            Address::from("any caller"),
+            expected_failure.clone(),
         );
+        assert!(revoke_capability(&test_setup.context, token).is_err());
 
-        /* function call doesn't know who the caller is yet so can't do the check in reduce
-                let someone_else = Address::from("somoeone_else");
-                test_reduce_call(&test_setup,&String::from(addr),someone_else, expected_failure.clone());
-        */
+        // grants of other capabilities don't give access
+        let other_grant = Entry::CapTokenGrant(
+            CapTokenGrant::create("other_cap", CapabilityType::Transferable, None, "nonce")
+                .unwrap(),
+        );
+        let addr = block_on(author_entry(&other_grant, None, &test_setup.context)).unwrap();
+        test_reduce_call(
+            &test_setup,
+            &String::from(addr),
+            Address::from("any caller"),
+            expected_failure,
+        );
+    }
+
+    #[test]
+    fn test_add_cap_request_params() {
+        let cap_params = CapabilityRequestParams {
+            token: Address::from("token"),
+            caller: Some(Address::from("caller")),
+            signature: Some(Signature::from("signature")),
+            expires: Some(test_iso_8601()),
+        };
+        let params: serde_json::Value = serde_json::from_str(
+            &add_cap_request_params(r#"{"a":1}"#, cap_params.clone()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(params["a"], 1);
+        assert_eq!(
+            serde_json::from_value::<CapabilityRequestParams>(
+                params[CAP_REQUEST_PARAMS_KEY].clone()
+            )
+            .unwrap(),
+            cap_params
+        );
+        assert_eq!(
+            add_cap_request_params("[1]", cap_params.clone()).unwrap(),
+            "[1]"
+        );
+        assert!(add_cap_request_params("not json", cap_params).is_err());
     }
 
     #[test]
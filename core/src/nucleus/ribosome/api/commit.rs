@@ -1,33 +1,41 @@
 use crate::{
     nucleus::ribosome::{api::ZomeApiResult, Runtime},
-    workflows::author_entry::author_entry,
+    workflows::author_entry::author_entry_for,
 };
 use futures::executor::block_on;
 use holochain_core_types::{cas::content::Address, entry::Entry, error::HolochainError};
+use holochain_wasm_utils::api_serialization::commit_entry::CommitEntryArgs;
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::CommitAppEntry function code
 /// args: [0] encoded MemoryAllocation as u32
-/// Expected complex argument: CommitArgs
+/// Expected complex argument: the Entry, or CommitEntryArgs for encrypted entries with recipients
 /// Returns an HcApiReturnCode as I32
 pub fn invoke_commit_app_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
     let args_str = runtime.load_json_string_from_args(&args);
-    let entry = match Entry::try_from(args_str.clone()) {
-        Ok(entry_input) => entry_input,
-        // Exit on error
-        Err(_) => {
-            println!(
-                "invoke_commit_app_entry failed to deserialize Entry: {:?}",
-                args_str
-            );
-            return ribosome_error_code!(ArgumentDeserializationFailed);
-        }
+    let (entry, recipients) = match Entry::try_from(args_str.clone()) {
+        Ok(entry_input) => (entry_input, Vec::new()),
+        Err(_) => match CommitEntryArgs::try_from(args_str.clone()) {
+            Ok(commit_args) => (commit_args.entry, commit_args.recipients),
+            // Exit on error
+            Err(_) => {
+                println!(
+                    "invoke_commit_app_entry failed to deserialize Entry: {:?}",
+                    args_str
+                );
+                return ribosome_error_code!(ArgumentDeserializationFailed);
+            }
+        },
     };
     // Wait for future to be resolved
-    let task_result: Result<Address, HolochainError> =
-        block_on(author_entry(&entry, None, &runtime.context));
+    let task_result: Result<Address, HolochainError> = block_on(author_entry_for(
+        &entry,
+        None,
+        &recipients,
+        &runtime.context,
+    ));
 
     runtime.store_result(task_result)
 }
@@ -1,4 +1,7 @@
-use crate::nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use crate::{
+    logger::LogLevel,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+};
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::Debug function code
@@ -11,7 +14,7 @@ pub fn invoke_debug(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult
     // TODO #502 - log in logger as DEBUG log-level
     runtime
         .context
-        .log(format!("zome_log:DEBUG: '{}'", payload));
+        .log(LogLevel::Debug, format!("zome_log:DEBUG: '{}'", payload));
     // Done
     ribosome_success!()
 }
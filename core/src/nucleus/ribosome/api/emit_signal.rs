@@ -0,0 +1,117 @@
+use crate::{
+    context::Context,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    signal::{Signal, UserSignal},
+};
+use holochain_core_types::{error::HolochainError, json::JsonString};
+use holochain_wasm_utils::api_serialization::emit_signal::EmitSignalArgs;
+use serde_json::Value;
+use std::{convert::TryFrom, sync::Arc};
+use wasmi::RuntimeArgs;
+
+/// ZomeApiFunction::EmitSignal function code
+/// args: [0] encoded MemoryAllocation as u32
+/// Expected complex argument: EmitSignalArgs
+/// Returns an HcApiReturnCode as I32
+pub fn invoke_emit_signal(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let emit_signal_args = match EmitSignalArgs::try_from(args_str.clone()) {
+        Ok(emit_signal_args) => emit_signal_args,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_emit_signal failed to deserialize EmitSignalArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = emit_signal(
+        &runtime.context,
+        emit_signal_args.name,
+        JsonString::from(emit_signal_args.arguments),
+    );
+
+    runtime.store_result(result)
+}
+
+/// Sends a user signal to the signal channel of the context, from where the container
+/// passes it on to the clients of the instance. Without a signal channel nobody listens
+/// and the signal gets dropped.
+pub fn emit_signal(
+    context: &Arc<Context>,
+    name: String,
+    arguments: JsonString,
+) -> Result<(), HolochainError> {
+    serde_json::from_str::<Value>(&String::from(arguments.clone())).map_err(|_| {
+        HolochainError::ErrorGeneric(format!("Arguments of signal {} are not JSON", name))
+    })?;
+    if let Some(ref signal_tx) = context.signal_tx {
+        signal_tx
+            .send(Signal::User(UserSignal { name, arguments }))
+            .map_err(|_| HolochainError::ErrorGeneric("Signal channel is closed".to_string()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        instance::tests::test_context,
+        nucleus::ribosome::{
+            api::{tests::test_zome_api_function, ZomeApiFunction},
+            Defn,
+        },
+        signal::signal_channel,
+    };
+
+    #[test]
+    fn emit_signal_test() {
+        let (signal_tx, signal_rx) = signal_channel();
+        let mut context = (*test_context("alice")).clone();
+        context.signal_tx = Some(signal_tx);
+        let context = Arc::new(context);
+
+        emit_signal(
+            &context,
+            "new_post".to_string(),
+            JsonString::from(json!({"title": "hello"})),
+        )
+        .unwrap();
+        match signal_rx.try_recv() {
+            Ok(Signal::User(user_signal)) => assert_eq!(
+                user_signal,
+                UserSignal {
+                    name: "new_post".to_string(),
+                    arguments: JsonString::from(json!({"title": "hello"})),
+                }
+            ),
+            other => panic!("Expected a user signal, got {:?}", other),
+        }
+
+        assert!(emit_signal(
+            &context,
+            "new_post".to_string(),
+            JsonString::from("no json".to_string())
+        )
+        .is_err());
+        assert!(signal_rx.try_recv().is_err());
+    }
+
+    #[test]
+    /// test that zomes can emit signals without anybody listening
+    fn test_zome_api_function_emit_signal() {
+        let args = EmitSignalArgs {
+            name: "new_post".to_string(),
+            arguments: json!({"title": "hello"}).to_string(),
+        };
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::EmitSignal.as_str(),
+            JsonString::from(args).into_bytes(),
+        );
+        assert!(String::from(call_result).starts_with(r#"{"ok":true,"#));
+    }
+}
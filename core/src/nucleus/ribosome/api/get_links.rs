@@ -1,4 +1,5 @@
 use crate::{
+    logger::LogLevel,
     network::actions::get_links::get_links,
     nucleus::ribosome::{api::ZomeApiResult, Runtime},
 };
@@ -28,16 +29,18 @@ pub fn invoke_get_links(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiRes
     };
 
     if input.options.status_request != LinksStatusRequestKind::Live {
-        runtime
-            .context
-            .log("get links status request other than Live not implemented!");
+        runtime.context.log(
+            LogLevel::Error,
+            "get links status request other than Live not implemented!",
+        );
         return ribosome_error_code!(Unspecified);
     }
 
     if input.options.sources {
-        runtime
-            .context
-            .log("get links retrieve sources not implemented!");
+        runtime.context.log(
+            LogLevel::Error,
+            "get links retrieve sources not implemented!",
+        );
         return ribosome_error_code!(Unspecified);
     }
 
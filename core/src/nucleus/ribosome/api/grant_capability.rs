@@ -0,0 +1,81 @@
+use crate::{
+    context::Context,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::author_entry::author_entry,
+};
+use futures::executor::block_on;
+use holochain_core_types::{
+    cas::content::Address,
+    dna::capabilities::CapabilityType,
+    entry::{cap_entries::CapTokenGrant, Entry},
+    error::HolochainError,
+};
+use holochain_sodium::{random, secbuf::SecBuf};
+use holochain_wasm_utils::api_serialization::capabilities::GrantCapabilityArgs;
+use std::{convert::TryFrom, sync::Arc};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// Number of random bytes in the nonce of a CapTokenGrant
+const GRANT_NONCE_BYTES: usize = 32;
+
+/// ZomeApiFunction::GrantCapability function code
+/// args: [0] encoded MemoryAllocation as u32
+/// Expected complex argument: GrantCapabilityArgs
+/// Returns an HcApiReturnCode as I32
+pub fn invoke_grant_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let args = match GrantCapabilityArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_grant_capability failed to deserialize GrantCapabilityArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = grant_capability(&runtime.context, &runtime.zome_call.zome_name, args);
+
+    runtime.store_result(result)
+}
+
+/// Commits a CapTokenGrant for a capability of the given zome and returns its address,
+/// which is the token callers have to present.
+/// The type of the capability in the DNA decides who can use the token: anybody holding it
+/// for transferable capabilities, only the given assignees for assigned ones.
+pub fn grant_capability(
+    context: &Arc<Context>,
+    zome_name: &str,
+    args: GrantCapabilityArgs,
+) -> Result<Address, HolochainError> {
+    let dna = context.get_dna().ok_or(HolochainError::DnaMissing)?;
+    let cap_type = dna
+        .get_capability_with_zome_name(zome_name, &args.cap_name)
+        .map_err(HolochainError::Dna)?
+        .cap_type
+        .clone();
+    if cap_type == CapabilityType::Public {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Capability '{}' is public and can be called without a token",
+            args.cap_name
+        )));
+    }
+    let grant = CapTokenGrant::create(
+        &args.cap_name,
+        cap_type,
+        Some(args.assignees),
+        &grant_nonce(),
+    )?;
+    block_on(author_entry(&Entry::CapTokenGrant(grant), None, context))
+}
+
+/// A fresh random nonce for a CapTokenGrant, so that its token can't be guessed
+fn grant_nonce() -> String {
+    let mut nonce = SecBuf::with_insecure(GRANT_NONCE_BYTES);
+    random::buf(&mut nonce);
+    let nonce = nonce.read_lock();
+    base64::encode(&**nonce)
+}
@@ -4,22 +4,28 @@
 pub mod call;
 pub mod commit;
 pub mod debug;
+pub mod emit_signal;
 pub mod entry_address;
 pub mod get_entry;
 pub mod get_links;
+pub mod grant_capability;
 pub mod init_globals;
 pub mod link_entries;
+pub mod property;
 pub mod query;
 pub mod remove_entry;
+pub mod revoke_capability;
 pub mod send;
 pub mod update_entry;
 
 use crate::nucleus::ribosome::{
     api::{
         call::invoke_call, commit::invoke_commit_app_entry, debug::invoke_debug,
-        entry_address::invoke_entry_address, get_entry::invoke_get_entry,
-        get_links::invoke_get_links, init_globals::invoke_init_globals,
-        link_entries::invoke_link_entries, query::invoke_query, remove_entry::invoke_remove_entry,
+        emit_signal::invoke_emit_signal, entry_address::invoke_entry_address,
+        get_entry::invoke_get_entry, get_links::invoke_get_links,
+        grant_capability::invoke_grant_capability, init_globals::invoke_init_globals,
+        link_entries::invoke_link_entries, property::invoke_property, query::invoke_query,
+        remove_entry::invoke_remove_entry, revoke_capability::invoke_revoke_capability,
         send::invoke_send, update_entry::invoke_update_entry,
     },
     runtime::Runtime,
@@ -88,6 +94,22 @@ pub enum ZomeApiFunction {
     EntryAddress,
 
     Send,
+
+    /// Commit a grant of a capability of the calling zome and return its token
+    /// hc_grant_capability(cap_name: String, assignees: Vec<Address>) -> Address
+    GrantCapability,
+
+    /// Revoke a grant so calls with its token fail
+    /// hc_revoke_capability(token: Address)
+    RevokeCapability,
+
+    /// Look up a property of the instance's DNA by its dotted path
+    /// hc_property(path: String) -> JsonString
+    Property,
+
+    /// Send a named signal with JSON arguments to the clients of the instance
+    /// hc_emit_signal(name: String, arguments: JsonString)
+    EmitSignal,
 }
 
 impl Defn for ZomeApiFunction {
@@ -107,6 +129,10 @@ impl Defn for ZomeApiFunction {
             ZomeApiFunction::Query => "hc_query",
             ZomeApiFunction::EntryAddress => "hc_entry_address",
             ZomeApiFunction::Send => "hc_send",
+            ZomeApiFunction::GrantCapability => "hc_grant_capability",
+            ZomeApiFunction::RevokeCapability => "hc_revoke_capability",
+            ZomeApiFunction::Property => "hc_property",
+            ZomeApiFunction::EmitSignal => "hc_emit_signal",
         }
     }
 
@@ -145,6 +171,10 @@ impl FromStr for ZomeApiFunction {
             "hc_query" => Ok(ZomeApiFunction::Query),
             "hc_entry_address" => Ok(ZomeApiFunction::EntryAddress),
             "hc_send" => Ok(ZomeApiFunction::Send),
+            "hc_grant_capability" => Ok(ZomeApiFunction::GrantCapability),
+            "hc_revoke_capability" => Ok(ZomeApiFunction::RevokeCapability),
+            "hc_property" => Ok(ZomeApiFunction::Property),
+            "hc_emit_signal" => Ok(ZomeApiFunction::EmitSignal),
             _ => Err("Cannot convert string to ZomeApiFunction"),
         }
     }
@@ -177,6 +207,10 @@ impl ZomeApiFunction {
             ZomeApiFunction::Query => invoke_query,
             ZomeApiFunction::EntryAddress => invoke_entry_address,
             ZomeApiFunction::Send => invoke_send,
+            ZomeApiFunction::GrantCapability => invoke_grant_capability,
+            ZomeApiFunction::RevokeCapability => invoke_revoke_capability,
+            ZomeApiFunction::Property => invoke_property,
+            ZomeApiFunction::EmitSignal => invoke_emit_signal,
         }
     }
 }
@@ -424,6 +458,10 @@ pub mod tests {
             ("hc_query", ZomeApiFunction::Query),
             ("hc_entry_address", ZomeApiFunction::EntryAddress),
             ("hc_send", ZomeApiFunction::Send),
+            ("hc_grant_capability", ZomeApiFunction::GrantCapability),
+            ("hc_revoke_capability", ZomeApiFunction::RevokeCapability),
+            ("hc_property", ZomeApiFunction::Property),
+            ("hc_emit_signal", ZomeApiFunction::EmitSignal),
         ] {
             assert_eq!(ZomeApiFunction::from_str(input).unwrap(), output);
         }
@@ -453,6 +491,10 @@ pub mod tests {
             (ZomeApiFunction::Query, "hc_query"),
             (ZomeApiFunction::EntryAddress, "hc_entry_address"),
             (ZomeApiFunction::Send, "hc_send"),
+            (ZomeApiFunction::GrantCapability, "hc_grant_capability"),
+            (ZomeApiFunction::RevokeCapability, "hc_revoke_capability"),
+            (ZomeApiFunction::Property, "hc_property"),
+            (ZomeApiFunction::EmitSignal, "hc_emit_signal"),
         ] {
             assert_eq!(output, input.as_str());
         }
@@ -473,6 +515,10 @@ pub mod tests {
             ("hc_query", 11),
             ("hc_entry_address", 12),
             ("hc_send", 13),
+            ("hc_grant_capability", 14),
+            ("hc_revoke_capability", 15),
+            ("hc_property", 16),
+            ("hc_emit_signal", 17),
         ] {
             assert_eq!(output, ZomeApiFunction::str_to_index(input));
         }
@@ -493,6 +539,10 @@ pub mod tests {
             (11, ZomeApiFunction::Query),
             (12, ZomeApiFunction::EntryAddress),
             (13, ZomeApiFunction::Send),
+            (14, ZomeApiFunction::GrantCapability),
+            (15, ZomeApiFunction::RevokeCapability),
+            (16, ZomeApiFunction::Property),
+            (17, ZomeApiFunction::EmitSignal),
         ] {
             assert_eq!(output, ZomeApiFunction::from_index(input));
         }
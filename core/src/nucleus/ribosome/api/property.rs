@@ -0,0 +1,148 @@
+use crate::{
+    context::Context,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+};
+use holochain_core_types::{
+    dna::dna::{merge_properties, property_at_path},
+    error::HolochainError,
+    json::JsonString,
+};
+use std::sync::Arc;
+use wasmi::RuntimeArgs;
+
+/// ZomeApiFunction::Property function code
+/// args: [0] encoded MemoryAllocation as u32
+/// Expected argument: the dotted path of the property as a JSON string, e.g. "settings.max_posts"
+/// Returns the property's JSON value in a ZomeApiInternalResult
+pub fn invoke_property(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let path = match serde_json::from_str::<String>(&String::from(args_str.clone())) {
+        Ok(path) => path,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_property failed to deserialize property path: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let with_overrides = !is_validation_callback(&runtime.zome_call.fn_name);
+    let result = get_property(&runtime.context, &path, with_overrides);
+
+    runtime.store_result(result)
+}
+
+/// Whether the given zome function is one of the callbacks that validate entries and links
+/// or define what their validation needs.
+/// All nodes of a DHT have to come to the same result in those, so they only see
+/// the DNA's own properties, never the property overrides of an instance.
+fn is_validation_callback(fn_name: &str) -> bool {
+    fn_name.starts_with("__hdk_validate") || fn_name.starts_with("__hdk_get_validation_package")
+}
+
+/// Looks up the property at the given dotted path in the properties of the instance's DNA,
+/// with the instance's property overrides merged into them if with_overrides is set.
+pub fn get_property(
+    context: &Arc<Context>,
+    path: &str,
+    with_overrides: bool,
+) -> Result<JsonString, HolochainError> {
+    let dna = context
+        .get_dna()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Instance has no DNA".to_string()))?;
+    let from_dna = property_at_path(&dna.properties, path);
+    let overridden = context
+        .dna_property_overrides
+        .as_ref()
+        .filter(|_| with_overrides)
+        .and_then(|overrides| property_at_path(overrides, path));
+    let value = match (from_dna, overridden) {
+        (Some(from_dna), Some(overridden)) => {
+            let mut value = from_dna.clone();
+            merge_properties(&mut value, overridden);
+            value
+        }
+        (_, Some(overridden)) => overridden.clone(),
+        (Some(from_dna), None) => from_dna.clone(),
+        (None, None) => {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "DNA has no property {}",
+                path
+            )));
+        }
+    };
+    Ok(JsonString::from(value))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        instance::tests::test_instance_and_context,
+        nucleus::ribosome::{
+            api::{tests::test_zome_api_function, ZomeApiFunction},
+            Defn,
+        },
+    };
+    use holochain_core_types::dna::{zome::Zome, Dna};
+
+    #[test]
+    fn get_property_with_overrides_test() {
+        let mut dna = Dna::new();
+        dna.zomes.insert("".to_string(), Zome::default());
+        dna.properties = json!({"name": "posts", "limits": {"posts": 10, "likes": 5}});
+        let (_instance, context) = test_instance_and_context(dna.clone()).unwrap();
+
+        assert_eq!(
+            get_property(&context, "limits.posts", true).unwrap(),
+            JsonString::from(json!(10))
+        );
+        assert!(get_property(&context, "limits.comments", true).is_err());
+
+        let mut overriding_context = (*context).clone();
+        overriding_context.dna_property_overrides =
+            Some(json!({"limits": {"posts": 20, "comments": 3}}));
+        let overriding_context = Arc::new(overriding_context);
+        assert_eq!(
+            get_property(&overriding_context, "limits", true).unwrap(),
+            JsonString::from(json!({"posts": 20, "likes": 5, "comments": 3}))
+        );
+        assert_eq!(
+            get_property(&overriding_context, "limits.comments", true).unwrap(),
+            JsonString::from(json!(3))
+        );
+        assert_eq!(
+            get_property(&overriding_context, "name", true).unwrap(),
+            JsonString::from(json!("posts"))
+        );
+
+        // validation callbacks only see the DNA's properties
+        assert_eq!(
+            get_property(&overriding_context, "limits", false).unwrap(),
+            JsonString::from(json!({"posts": 10, "likes": 5}))
+        );
+        assert!(get_property(&overriding_context, "limits.comments", false).is_err());
+        assert!(is_validation_callback("__hdk_validate_app_entry"));
+        assert!(is_validation_callback("__hdk_validate_link"));
+        assert!(is_validation_callback(
+            "__hdk_get_validation_package_for_entry_type"
+        ));
+        assert!(!is_validation_callback("handle_create_post"));
+
+        // the overrides don't touch the DNA
+        assert_eq!(overriding_context.get_dna(), Some(dna));
+    }
+
+    #[test]
+    /// test that zomes get an error for properties the DNA does not have
+    fn test_zome_api_function_property_missing() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::Property.as_str(),
+            JsonString::from(json!("foo")).into_bytes(),
+        );
+        assert!(String::from(call_result).starts_with(r#"{"ok":false,"#));
+    }
+}
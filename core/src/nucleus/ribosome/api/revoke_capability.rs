@@ -0,0 +1,88 @@
+use crate::{
+    agent::actions::commit::commit_entry,
+    context::Context,
+    nucleus::{
+        actions::{build_validation_package::*, validate::*},
+        ribosome::{api::ZomeApiResult, Runtime},
+    },
+};
+use futures::{
+    executor::block_on,
+    future::{self, TryFutureExt},
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{deletion_entry::DeletionEntry, Entry},
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use std::{convert::TryFrom, sync::Arc};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::RevokeCapability function code
+/// args: [0] encoded MemoryAllocation as u32
+/// Expected Address argument (the token returned by grant_capability)
+/// Returns an HcApiReturnCode as I32
+pub fn invoke_revoke_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let token = match Address::try_from(args_str.clone()) {
+        Ok(token) => token,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_revoke_capability failed to deserialize Address: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = revoke_capability(&runtime.context, token);
+
+    runtime.store_result(result)
+}
+
+/// Revokes the grant the given token is the address of by committing a Deletion entry
+/// linking to it, after which calls with that token fail the capability check.
+/// Like the grants themselves, revocations only go to the agent's own chain and
+/// don't get published.
+pub fn revoke_capability(context: &Arc<Context>, token: Address) -> Result<(), HolochainError> {
+    let is_live_grant = {
+        let state = context
+            .state()
+            .ok_or_else(|| HolochainError::ErrorGeneric("Context has no state".to_string()))?;
+        let agent = state.agent();
+        agent
+            .chain()
+            .get_live_cap_grant_header(&agent.top_chain_header(), &token)
+            .is_some()
+    };
+    if !is_live_grant {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "No capability grant to revoke for token {}",
+            token
+        )));
+    }
+
+    let revocation = Entry::Deletion(DeletionEntry::new(token.clone()));
+    block_on(
+        // 1. Build the context needed for validation of the entry
+        build_validation_package(&revocation, context)
+            .and_then(|validation_package| {
+                future::ready(Ok(ValidationData {
+                    package: validation_package,
+                    sources: vec![context.agent_id.address()],
+                    lifecycle: EntryLifecycle::Chain,
+                    action: EntryAction::Delete,
+                }))
+            })
+            // 2. Validate the entry
+            .and_then(|validation_data| {
+                validate_entry(revocation.clone(), validation_data, context)
+            })
+            // 3. Commit the entry to the chain only
+            .and_then(|_| commit_entry(revocation.clone(), Some(token.clone()), context)),
+    )
+    .map(|_| ())
+}
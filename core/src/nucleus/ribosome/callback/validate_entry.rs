@@ -61,6 +61,10 @@ pub fn validate_entry(
         // a grant should always be private, so it should always pass
         EntryType::CapTokenGrant => Ok(CallbackResult::Pass),
 
+        // the rules of migrations are the same for every DNA
+        // and get checked before, in nucleus::actions::validate
+        EntryType::ChainMigrate => Ok(CallbackResult::Pass),
+
         // TODO: actually check agent against app specific membrane validation rule
         // like for instance: validate_agent_id(
         //                      entry.clone(),
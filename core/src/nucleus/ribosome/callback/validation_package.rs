@@ -90,6 +90,7 @@ pub fn get_validation_package_definition(
         EntryType::Deletion => JsonString::from(ValidationPackageDefinition::ChainFull),
         EntryType::CapTokenGrant => JsonString::from(ValidationPackageDefinition::Entry),
         EntryType::AgentId => JsonString::from(ValidationPackageDefinition::Entry),
+        EntryType::ChainMigrate => JsonString::from(ValidationPackageDefinition::Entry),
         _ => Err(HolochainError::NotImplemented)?,
     };
 
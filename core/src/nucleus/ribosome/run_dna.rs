@@ -1,5 +1,6 @@
 use crate::{
     context::Context,
+    logger::LogLevel,
     nucleus::{
         ribosome::{api::ZomeApiFunction, memory::SinglePageManager, Runtime},
         ZomeFnCall, ZomeFnResult,
@@ -168,9 +169,12 @@ pub fn run_dna(
         }
     };
     // Log & done
-    runtime.context.log(format!(
-        "Zome Function '{}' returned: {}",
-        zome_call.fn_name, return_log_msg,
-    ));
+    runtime.context.log(
+        LogLevel::Info,
+        format!(
+            "Zome Function '{}' returned: {}",
+            zome_call.fn_name, return_log_msg,
+        ),
+    );
     return return_result;
 }
@@ -5,7 +5,7 @@ use holochain_core_types::{
 };
 use snowflake;
 use std::collections::HashMap;
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum NucleusStatus {
     New,
     Initializing,
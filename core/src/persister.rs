@@ -1,19 +1,17 @@
 use crate::{
     agent::state::{AgentStateSnapshot, AGENT_SNAPSHOT_ADDRESS},
     context::Context,
-    state::State,
+    state::{State, StateSnapshot, STATE_SNAPSHOT_ADDRESS},
 };
 use holochain_core_types::{
     cas::{
         content::{Address, AddressableContent, Content},
         storage::ContentAddressableStorage,
     },
+    chain_header::ChainHeader,
     error::HolochainError,
 };
-use std::{
-    convert::TryFrom,
-    sync::{Arc, RwLock},
-};
+use std::sync::{Arc, RwLock};
 
 /// trait that defines the persistence functionality that holochain_core requires
 pub trait Persister: Send {
@@ -25,9 +23,13 @@ pub trait Persister: Send {
     fn load(&self, context: Arc<Context>) -> Result<Option<State>, HolochainError>;
 }
 
+/// Persists a StateSnapshot of the state in a CAS, overwriting the previous one.
+/// States saved by older versions only hold an AgentStateSnapshot which load() falls back to.
 #[derive(Clone)]
 pub struct SimplePersister {
     storage: Arc<RwLock<ContentAddressableStorage>>,
+    /// what the last save() wrote, so that saving an unchanged state is a no-op
+    last_snapshot: Option<StateSnapshot>,
 }
 
 impl PartialEq for SimplePersister {
@@ -38,14 +40,24 @@ impl PartialEq for SimplePersister {
 
 impl Persister for SimplePersister {
     fn save(&mut self, state: State) -> Result<(), HolochainError> {
+        let snapshot = StateSnapshot::from(&state);
+        if self.last_snapshot.as_ref() == Some(&snapshot) {
+            return Ok(());
+        }
         let lock = &*self.storage.clone();
         let mut store = lock.write().unwrap();
-        let snapshot = AgentStateSnapshot::try_from(state)?;
-        Ok(store.add(&snapshot)?)
+        store.add(&snapshot)?;
+        self.last_snapshot = Some(snapshot);
+        Ok(())
     }
     fn load(&self, context: Arc<Context>) -> Result<Option<State>, HolochainError> {
         let lock = &*self.storage.clone();
         let store = lock.write().unwrap();
+        let address = Address::from(STATE_SNAPSHOT_ADDRESS);
+        if let Some(content) = store.fetch(&address)? {
+            let snapshot = StateSnapshot::try_from_content(&content)?;
+            return State::try_from_snapshot(context, snapshot).map(Some);
+        }
         let address = Address::from(AGENT_SNAPSHOT_ADDRESS);
         let snapshot: Option<AgentStateSnapshot> = store.fetch(&address)?.map(|s: Content| {
             AgentStateSnapshot::try_from_content(&s)
@@ -58,7 +70,42 @@ impl Persister for SimplePersister {
 
 impl SimplePersister {
     pub fn new(storage: Arc<RwLock<ContentAddressableStorage>>) -> Self {
-        SimplePersister { storage: storage }
+        SimplePersister {
+            storage: storage,
+            last_snapshot: None,
+        }
+    }
+
+    /// The top of the source chain according to the persisted state
+    pub fn load_top_chain_header(&self) -> Result<Option<ChainHeader>, HolochainError> {
+        let store = self.storage.read()?;
+        if let Some(content) = store.fetch(&Address::from(STATE_SNAPSHOT_ADDRESS))? {
+            return Ok(StateSnapshot::try_from_content(&content)?.top_chain_header);
+        }
+        Ok(match store.fetch(&Address::from(AGENT_SNAPSHOT_ADDRESS))? {
+            Some(content) => Some(
+                AgentStateSnapshot::try_from_content(&content)?
+                    .top_chain_header()
+                    .clone(),
+            ),
+            None => None,
+        })
+    }
+
+    /// Points the persisted state to a new top of the source chain,
+    /// e.g. after ChainStore::rebuild_type_links() rewrote the chain
+    pub fn save_top_chain_header(&mut self, header: ChainHeader) -> Result<(), HolochainError> {
+        let mut store = self.storage.write()?;
+        match store.fetch(&Address::from(STATE_SNAPSHOT_ADDRESS))? {
+            Some(content) => {
+                let mut snapshot = StateSnapshot::try_from_content(&content)?;
+                snapshot.top_chain_header = Some(header);
+                store.add(&snapshot)?;
+                self.last_snapshot = Some(snapshot);
+            }
+            None => store.add(&AgentStateSnapshot::new(header))?,
+        }
+        Ok(())
     }
 }
 
@@ -69,7 +116,12 @@ mod tests {
     use self::tempfile::tempdir;
     use crate::{
         instance::tests::test_context_with_agent_state,
+        nucleus::state::NucleusStatus,
         persister::{Persister, SimplePersister},
+        state::{State, StateSnapshot},
+    };
+    use holochain_core_types::{
+        cas::content::AddressableContent, chain_header::test_chain_header, dna::Dna, entry::Entry,
     };
     use std::fs::File;
 
@@ -92,4 +144,32 @@ mod tests {
         // need to fix this so `persitance.load()` takes a networks or something
         assert_ne!(state.network(), state_from_file.network());
     }
+
+    #[test]
+    fn persists_nucleus_with_agent() {
+        let context = test_context_with_agent_state();
+        let mut dna = Dna::new();
+        dna.name = "persisted".to_string();
+        let snapshot = StateSnapshot {
+            top_chain_header: Some(test_chain_header()),
+            dna_address: Some(dna.address()),
+            status: NucleusStatus::Initialized,
+        };
+        // the DNA is not part of the snapshot, it has to be in the chain's storage
+        assert!(State::try_from_snapshot(context.clone(), snapshot.clone()).is_err());
+        context
+            .chain_storage
+            .write()
+            .unwrap()
+            .add(&Entry::Dna(dna.clone()))
+            .unwrap();
+        let state = State::try_from_snapshot(context.clone(), snapshot.clone()).unwrap();
+        assert_eq!(state.nucleus().dna(), Some(dna));
+
+        let mut persistance = SimplePersister::new(context.dht_storage.clone());
+        persistance.save(state).unwrap();
+        let state_from_storage = persistance.load(context).unwrap().unwrap();
+        assert_eq!(StateSnapshot::from(&state_from_storage), snapshot);
+        assert!(state_from_storage.nucleus().has_initialized());
+    }
 }
@@ -0,0 +1,298 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// How long results of actions and network requests stay in the state if no future
+/// consumes them, and how many of them each map of network request results holds at most.
+/// The TTL is in milliseconds. Requests that are still waiting for an answer don't
+/// expire, their timeouts resolve them.
+/// The maps of action results only expire by TTL, as a burst of actions must not drop
+/// results that futures are still going to pick up.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResultRetention {
+    pub ttl: u64,
+    pub max_results: usize,
+}
+
+impl ResultRetention {
+    pub const DEFAULT_TTL_MS: u64 = 300000;
+    pub const DEFAULT_MAX_RESULTS: usize = 1000;
+
+    pub fn ttl(&self) -> Duration {
+        Duration::from_millis(self.ttl)
+    }
+}
+
+impl Default for ResultRetention {
+    fn default() -> Self {
+        ResultRetention {
+            ttl: Self::DEFAULT_TTL_MS,
+            max_results: Self::DEFAULT_MAX_RESULTS,
+        }
+    }
+}
+
+/// Number of results the state of an instance holds, see Instance::result_metrics()
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ResultMetrics {
+    /// entries in all actions and result maps of the state
+    pub stored_results: usize,
+    /// requests to the network that did not get an answer yet
+    pub pending_requests: usize,
+    /// futures that wait for the state to change
+    pub waiting_futures: usize,
+}
+
+/// A HashMap for the results that reducers store for futures to pick up.
+/// Results get removed by Action::ClearResult once a future consumed them.
+/// prune() drops the ones nobody consumed after the TTL of a ResultRetention,
+/// and the oldest ones if there are more than its max_results.
+/// Maps of requests use prune_resolved() instead, which keeps the ones still waiting
+/// for an answer, and maps of action results use prune_expired(), which only goes by TTL.
+#[derive(Clone, Debug)]
+pub struct ResultMap<K: Clone + Eq + Hash, V> {
+    results: HashMap<K, (V, u64)>,
+    /// Every insertion with its time, oldest first.
+    /// Keys that got inserted again or removed leave outdated insertions behind
+    /// which prune() skips.
+    insertions: VecDeque<(K, u64, Instant)>,
+    next_insertion: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> ResultMap<K, V> {
+    pub fn new() -> Self {
+        ResultMap {
+            results: HashMap::new(),
+            insertions: VecDeque::new(),
+            next_insertion: 0,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.results.get(key).map(|(value, _)| value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.results.contains_key(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.results.iter().map(|(key, (value, _))| (key, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Inserts (or replaces) the result for the given key, which restarts its TTL
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let insertion = self.next_insertion;
+        self.next_insertion += 1;
+        self.insertions
+            .push_back((key.clone(), insertion, Instant::now()));
+        self.results
+            .insert(key, (value, insertion))
+            .map(|(value, _)| value)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.results.remove(key).map(|(value, _)| value)
+    }
+
+    fn is_current(&self, key: &K, insertion: u64) -> bool {
+        self.results
+            .get(key)
+            .map(|(_, current)| *current == insertion)
+            .unwrap_or(false)
+    }
+
+    /// Drops expired results and the oldest ones beyond retention.max_results
+    pub fn prune(&mut self, retention: &ResultRetention) {
+        self.prune_where(retention.ttl(), retention.max_results, |_| true)
+    }
+
+    /// Only drops expired results, however many there are
+    pub fn prune_expired(&mut self, retention: &ResultRetention) {
+        self.prune_where(retention.ttl(), usize::MAX, |_| true)
+    }
+
+    /// Like prune(), but only drops the results for which can_drop returns true.
+    /// The others stay (and keep their place in the order of insertions).
+    fn prune_where<F: Fn(&V) -> bool>(&mut self, ttl: Duration, max_results: usize, can_drop: F) {
+        let now = Instant::now();
+        let mut kept = Vec::new();
+        while let Some((key, insertion, inserted_at)) = self.insertions.pop_front() {
+            if !self.is_current(&key, insertion) {
+                continue;
+            }
+            let due = self.results.len() > max_results || now.duration_since(inserted_at) >= ttl;
+            if !due {
+                self.insertions.push_front((key, insertion, inserted_at));
+                break;
+            }
+            if self.get(&key).map(&can_drop).unwrap_or(false) {
+                self.results.remove(&key);
+            } else {
+                kept.push((key, insertion, inserted_at));
+            }
+        }
+        for insertion in kept.into_iter().rev() {
+            self.insertions.push_front(insertion);
+        }
+
+        // Results that get consumed leave their insertion behind until it reaches the front,
+        // so forget outdated insertions once they outnumber the results
+        if self.insertions.len() > 2 * self.results.len() + 16 {
+            let results = &self.results;
+            self.insertions.retain(|(key, insertion, _)| {
+                results
+                    .get(key)
+                    .map(|(_, current)| current == insertion)
+                    .unwrap_or(false)
+            });
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> ResultMap<K, Option<V>> {
+    /// Like prune(), but never drops results that are still None.
+    /// In maps of network requests None means the request is still waiting for an answer,
+    /// its future would wait forever if it got dropped. The request's timeout resolves it.
+    pub fn prune_resolved(&mut self, retention: &ResultRetention) {
+        self.prune_where(retention.ttl(), retention.max_results, Option::is_some)
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> Default for ResultMap<K, V> {
+    fn default() -> Self {
+        ResultMap::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: PartialEq> PartialEq for ResultMap<K, V> {
+    fn eq(&self, other: &ResultMap<K, V>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn results_can_be_replaced_and_removed() {
+        let mut map = ResultMap::new();
+        assert_eq!(map.insert("a", 1), None);
+        assert_eq!(map.insert("a", 2), Some(1));
+        assert_eq!(map.get(&"a"), Some(&2));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.remove(&"a"), Some(2));
+        assert!(map.is_empty());
+        assert!(!map.contains_key(&"a"));
+    }
+
+    #[test]
+    fn expired_results_get_pruned() {
+        let retention = ResultRetention {
+            ttl: 50,
+            max_results: 10,
+        };
+        let mut map = ResultMap::new();
+        map.insert("old", 1);
+        map.insert("replaced", 2);
+        sleep(Duration::from_millis(100));
+        map.insert("replaced", 3);
+        map.insert("new", 4);
+
+        map.prune(&retention);
+        assert_eq!(map.get(&"old"), None);
+        assert_eq!(map.get(&"replaced"), Some(&3));
+        assert_eq!(map.get(&"new"), Some(&4));
+    }
+
+    #[test]
+    fn oldest_results_get_pruned_beyond_max_results() {
+        let retention = ResultRetention {
+            ttl: ResultRetention::DEFAULT_TTL_MS,
+            max_results: 2,
+        };
+        let mut map = ResultMap::new();
+        map.insert(1, ());
+        map.insert(2, ());
+        map.insert(1, ());
+        map.insert(3, ());
+
+        map.prune(&retention);
+        assert_eq!(map.len(), 2);
+        assert!(map.contains_key(&1));
+        assert!(!map.contains_key(&2));
+        assert!(map.contains_key(&3));
+    }
+
+    #[test]
+    fn results_only_expire_if_pruned_by_ttl() {
+        let retention = ResultRetention {
+            ttl: 50,
+            max_results: 2,
+        };
+        let mut map = ResultMap::new();
+        for i in 0..10 {
+            map.insert(i, ());
+        }
+        map.prune_expired(&retention);
+        assert_eq!(map.len(), 10);
+
+        sleep(Duration::from_millis(100));
+        map.insert(10, ());
+        map.prune_expired(&retention);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key(&10));
+    }
+
+    #[test]
+    fn pending_results_do_not_get_pruned() {
+        let retention = ResultRetention {
+            ttl: 50,
+            max_results: 1,
+        };
+        let mut map = ResultMap::new();
+        map.insert("pending", None);
+        map.insert("resolved", Some(1));
+        sleep(Duration::from_millis(100));
+        map.insert("new", None);
+
+        map.prune_resolved(&retention);
+        assert_eq!(map.get(&"pending"), Some(&None));
+        assert_eq!(map.get(&"resolved"), None);
+        assert_eq!(map.get(&"new"), Some(&None));
+
+        // once resolved they expire like any other result
+        map.insert("pending", Some(2));
+        sleep(Duration::from_millis(100));
+        map.prune_resolved(&retention);
+        assert_eq!(map.get(&"pending"), None);
+        assert_eq!(map.get(&"new"), Some(&None));
+    }
+
+    #[test]
+    fn outdated_insertions_get_forgotten() {
+        let mut map = ResultMap::new();
+        for i in 0..1000 {
+            map.insert(i, ());
+            map.remove(&i);
+            map.prune(&ResultRetention::default());
+        }
+        assert!(map.insertions.len() <= 16);
+    }
+}
@@ -4,11 +4,19 @@ use std::{
 };
 
 use crate::action::Action;
+use holochain_core_types::json::JsonString;
 
 #[derive(Debug)]
 pub enum Signal {
     Internal(Action),
-    User,
+    User(UserSignal),
+}
+
+/// A signal a zome emitted through `hdk::emit_signal()` for the clients of its instance
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSignal {
+    pub name: String,
+    pub arguments: JsonString,
 }
 
 pub type SignalSender = SyncSender<Signal>;
@@ -6,18 +6,24 @@ use crate::{
     },
     context::Context,
     dht::dht_store::DhtStore,
+    logger::LogLevel,
     network::state::NetworkState,
-    nucleus::state::NucleusState,
+    nucleus::state::{NucleusState, NucleusStatus},
 };
 use holochain_core_types::{
-    cas::storage::ContentAddressableStorage,
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
     dna::Dna,
     entry::{entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
+    json::JsonString,
 };
 use std::{
     collections::HashSet,
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     sync::{Arc, RwLock},
 };
 
@@ -46,7 +52,11 @@ impl State {
         State {
             nucleus: Arc::new(NucleusState::new()),
             agent: Arc::new(AgentState::new(ChainStore::new(chain_cas.clone()))),
-            dht: Arc::new(DhtStore::new(dht_cas.clone(), eav)),
+            dht: Arc::new(DhtStore::new(
+                dht_cas.clone(),
+                context.encrypted_storage.clone(),
+                eav,
+            )),
             network: Arc::new(NetworkState::new()),
             history: HashSet::new(),
         }
@@ -90,14 +100,18 @@ impl State {
         State {
             nucleus: Arc::new(nucleus_state),
             agent: agent_state,
-            dht: Arc::new(DhtStore::new(cas.clone(), eav.clone())),
+            dht: Arc::new(DhtStore::new(
+                cas.clone(),
+                context.encrypted_storage.clone(),
+                eav.clone(),
+            )),
             network: Arc::new(NetworkState::new()),
             history: HashSet::new(),
         }
     }
 
     pub fn reduce(&self, context: Arc<Context>, action_wrapper: ActionWrapper) -> Self {
-        context.log(format!("REDUCE: {:?}", action_wrapper));
+        context.log(LogLevel::Trace, format!("REDUCE: {:?}", action_wrapper));
         let mut new_state = State {
             nucleus: crate::nucleus::reduce(
                 Arc::clone(&context),
@@ -155,6 +169,89 @@ impl State {
             Arc::new(agent_state),
         ))
     }
+
+    /// Restores the state a StateSnapshot was taken from.
+    /// The DHT store gets built on top of the context's CAS and EAV storage which hold
+    /// the DHT's content and metadata across restarts.
+    /// The DNA gets fetched from the chain's storage, which holds it since genesis.
+    pub fn try_from_snapshot(context: Arc<Context>, snapshot: StateSnapshot) -> HcResult<State> {
+        let dna = match snapshot.dna_address {
+            Some(dna_address) => Some(fetch_dna(&context.chain_storage, &dna_address)?),
+            None => None,
+        };
+        let chain_store = ChainStore::new(context.chain_storage.clone());
+        let agent_state = match snapshot.top_chain_header {
+            Some(top_chain_header) => {
+                AgentState::new_with_top_chain_header(chain_store, top_chain_header)
+            }
+            None => AgentState::new(chain_store),
+        };
+        let mut nucleus_state = NucleusState::new();
+        nucleus_state.dna = dna;
+        nucleus_state.status = snapshot.status;
+        Ok(State {
+            nucleus: Arc::new(nucleus_state),
+            agent: Arc::new(agent_state),
+            dht: Arc::new(DhtStore::new(
+                context.dht_storage.clone(),
+                context.encrypted_storage.clone(),
+                context.eav_storage.clone(),
+            )),
+            network: Arc::new(NetworkState::new()),
+            history: HashSet::new(),
+        })
+    }
+}
+
+/// The parts of the state that need to survive a restart of the instance:
+/// the top of the source chain and the DNA with its initialization status.
+/// The DNA is an entry of the source chain, so only its address gets saved.
+/// Everything else is either transient (results of actions, the network) or lives in the
+/// instance's storages already (the chain and the DHT's content and metadata).
+/// Taken in one go so that restoring never mixes slices from different points in time.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, DefaultJson)]
+pub struct StateSnapshot {
+    pub top_chain_header: Option<ChainHeader>,
+    pub dna_address: Option<Address>,
+    pub status: NucleusStatus,
+}
+
+impl<'a> From<&'a State> for StateSnapshot {
+    fn from(state: &State) -> Self {
+        StateSnapshot {
+            top_chain_header: state.agent().top_chain_header(),
+            dna_address: state.nucleus().dna.as_ref().map(|dna| dna.address()),
+            status: state.nucleus().status(),
+        }
+    }
+}
+
+pub static STATE_SNAPSHOT_ADDRESS: &'static str = "StateSnapshot";
+impl AddressableContent for StateSnapshot {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn try_from_content(content: &Content) -> Result<Self, HolochainError> {
+        Self::try_from(content.to_owned())
+    }
+
+    fn address(&self) -> Address {
+        STATE_SNAPSHOT_ADDRESS.into()
+    }
+}
+
+/// The DNA with the given address from the given storage
+fn fetch_dna(cas: &Arc<RwLock<ContentAddressableStorage>>, address: &Address) -> HcResult<Dna> {
+    let content = cas.read()?.fetch(address)?.ok_or_else(|| {
+        HolochainError::ErrorGeneric(format!("No DNA entry found in storage at {}", address))
+    })?;
+    match Entry::try_from_content(&content)? {
+        Entry::Dna(dna) => Ok(dna),
+        _ => Err(HolochainError::SerializationError(
+            "Tried to get Dna from non-Dna Entry".into(),
+        )),
+    }
 }
 
 pub fn test_store(context: Arc<Context>) -> State {
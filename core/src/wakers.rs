@@ -0,0 +1,199 @@
+use crate::action::{Action, ActionWrapper, GetEntryKey, GetLinksKey};
+use futures::task::{LocalWaker, Waker};
+use holochain_core_types::cas::content::Address;
+use snowflake;
+use std::collections::HashMap;
+
+/// Names the slice of the state a pending future is waiting on.
+/// Futures register their waker under such a key when they return Poll::Pending
+/// and get woken by the action loop once an action was reduced that changes that slice,
+/// see keys_for().
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WakerKey {
+    /// The response to the given action in the actions map of a sub-state
+    /// or any other effect the reduction of that very action has
+    Action(ActionWrapper),
+    /// NetworkState::get_entry_with_meta_results for the given request
+    GetEntry(GetEntryKey),
+    /// NetworkState::get_links_results for the given request
+    GetLinks(GetLinksKey),
+    /// NetworkState::get_validation_package_results for the given entry address
+    GetValidationPackage(Address),
+    /// NetworkState::custom_direct_message_replys for the given message id
+    DirectMessage(String),
+    /// NucleusState::validation_results for the given validation
+    ValidationResult((snowflake::ProcessUniqueId, Address)),
+    /// NucleusState::validation_packages for the given id
+    ValidationPackage(snowflake::ProcessUniqueId),
+    /// NucleusState::status
+    Initialization,
+}
+
+/// All keys whose slice of the state changes by reducing the given action.
+pub fn keys_for(action_wrapper: &ActionWrapper) -> Vec<WakerKey> {
+    let key = match action_wrapper.action() {
+        Action::GetEntry(key) | Action::GetEntryTimeout(key) => {
+            Some(WakerKey::GetEntry(key.clone()))
+        }
+        Action::HandleGetResult(dht_data) => Some(WakerKey::GetEntry(GetEntryKey {
+            address: Address::from(dht_data.address.clone()),
+            id: dht_data.msg_id.clone(),
+        })),
+        Action::GetLinks(key) | Action::GetLinksTimeout(key) => {
+            Some(WakerKey::GetLinks(key.clone()))
+        }
+        Action::HandleGetLinksResult((dht_meta_data, tag)) => {
+            Some(WakerKey::GetLinks(GetLinksKey {
+                base_address: Address::from(dht_meta_data.address.clone()),
+                tag: tag.clone(),
+                id: dht_meta_data.msg_id.clone(),
+            }))
+        }
+        Action::GetValidationPackage(header) => Some(WakerKey::GetValidationPackage(
+            header.entry_address().clone(),
+        )),
+        Action::HandleGetValidationPackage((address, _)) => {
+            Some(WakerKey::GetValidationPackage(address.clone()))
+        }
+        Action::HandleCustomSendResponse((id, _)) | Action::SendDirectMessageTimeout(id) => {
+            Some(WakerKey::DirectMessage(id.clone()))
+        }
+        Action::ReturnValidationResult((key, _)) => Some(WakerKey::ValidationResult(key.clone())),
+        Action::ReturnValidationPackage((id, _)) => Some(WakerKey::ValidationPackage(id.clone())),
+        Action::InitApplication(_) | Action::ReturnInitializationResult(_) => {
+            Some(WakerKey::Initialization)
+        }
+        _ => None,
+    };
+    let mut keys = vec![WakerKey::Action(action_wrapper.clone())];
+    keys.extend(key);
+    keys
+}
+
+/// Holds the wakers of all pending futures of an instance.
+/// The action loop wakes them after reducing an action, see Context::poll_state()
+/// for how futures use it.
+#[derive(Default)]
+pub struct WakerRegistry {
+    wakers: HashMap<WakerKey, Vec<Waker>>,
+}
+
+impl WakerRegistry {
+    pub fn new() -> Self {
+        WakerRegistry::default()
+    }
+
+    /// Makes the next wake() of the given key wake the task behind the given waker
+    pub fn register(&mut self, key: WakerKey, lw: &LocalWaker) {
+        let wakers = self.wakers.entry(key).or_insert_with(Vec::new);
+        if !wakers.iter().any(|waker| lw.will_wake_nonlocal(waker)) {
+            wakers.push(lw.clone().into_waker());
+        }
+    }
+
+    /// Forgets the given waker again, for futures that got ready before they were woken
+    pub fn deregister(&mut self, key: &WakerKey, lw: &LocalWaker) {
+        let now_empty = match self.wakers.get_mut(key) {
+            Some(wakers) => {
+                wakers.retain(|waker| !lw.will_wake_nonlocal(waker));
+                wakers.is_empty()
+            }
+            None => false,
+        };
+        if now_empty {
+            self.wakers.remove(key);
+        }
+    }
+
+    /// Wakes (and forgets) all wakers registered for the given key
+    pub fn wake(&mut self, key: &WakerKey) {
+        if let Some(wakers) = self.wakers.remove(key) {
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Number of wakers that wait for a change of the state
+    pub fn pending(&self) -> usize {
+        self.wakers.values().map(|wakers| wakers.len()).sum()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::action::tests::test_action_wrapper_commit;
+    use futures::{executor::block_on, future::poll_fn, task::Poll};
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn keys_for_test() {
+        let action_wrapper = test_action_wrapper_commit();
+        assert_eq!(
+            keys_for(&action_wrapper),
+            vec![WakerKey::Action(action_wrapper.clone())]
+        );
+
+        let key = GetEntryKey::new(Address::from("QmAddress"));
+        let action_wrapper = ActionWrapper::new(Action::GetEntryTimeout(key.clone()));
+        assert_eq!(
+            keys_for(&action_wrapper),
+            vec![
+                WakerKey::Action(action_wrapper.clone()),
+                WakerKey::GetEntry(key)
+            ]
+        );
+    }
+
+    #[test]
+    fn registered_wakers_get_woken() {
+        let registry = Arc::new(Mutex::new(WakerRegistry::new()));
+        let woken = Arc::new(Mutex::new(false));
+        let key = WakerKey::Initialization;
+
+        let waker = {
+            let registry = registry.clone();
+            let woken = woken.clone();
+            let key = key.clone();
+            thread::spawn(move || {
+                while registry.lock().unwrap().pending() == 0 {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                *woken.lock().unwrap() = true;
+                registry.lock().unwrap().wake(&key);
+            })
+        };
+
+        block_on(poll_fn(|lw| {
+            if *woken.lock().unwrap() {
+                Poll::Ready(())
+            } else {
+                // polling again before getting woken must not register the task twice
+                let mut registry = registry.lock().unwrap();
+                registry.register(key.clone(), lw);
+                registry.register(key.clone(), lw);
+                Poll::Pending
+            }
+        }));
+        waker.join().unwrap();
+        assert_eq!(registry.lock().unwrap().pending(), 0);
+    }
+
+    #[test]
+    fn deregistered_wakers_are_forgotten() {
+        let mut registry = WakerRegistry::new();
+        block_on(poll_fn(|lw| {
+            registry.register(WakerKey::Initialization, lw);
+            assert_eq!(registry.pending(), 1);
+            registry.deregister(&WakerKey::Initialization, lw);
+            Poll::Ready(())
+        }));
+        assert_eq!(registry.pending(), 0);
+        assert!(registry.wakers.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     agent::actions::commit::commit_entry,
     context::Context,
+    logger::LogLevel,
     network::actions::publish::publish,
     nucleus::actions::{
         build_validation_package::build_validation_package, validate::validate_entry,
@@ -9,6 +10,7 @@ use crate::{
 
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
+    dna::entry_types::Sharing,
     entry::Entry,
     error::HolochainError,
     validation::{EntryAction, EntryLifecycle, ValidationData},
@@ -19,12 +21,32 @@ pub async fn author_entry<'a>(
     entry: &'a Entry,
     maybe_crud_link: Option<Address>,
     context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    await!(author_entry_for(entry, maybe_crud_link, &[], context))
+}
+
+/// Authors an entry like author_entry() does. Entries of encrypted entry types get encrypted
+/// for the given recipients too, so they can read them (besides the author).
+/// Encrypted entries get validated as they are, but committed and published encrypted, so the
+/// returned address is the one of the encrypted entry.
+pub async fn author_entry_for<'a>(
+    entry: &'a Entry,
+    maybe_crud_link: Option<Address>,
+    recipients: &'a [Address],
+    context: &'a Arc<Context>,
 ) -> Result<Address, HolochainError> {
     let address = entry.address();
-    context.log(format!(
-        "Authoring entry: {} with content: {:?}",
-        address, entry
-    ));
+    let encrypted = context.entry_type_sharing(&entry.entry_type()) == Some(Sharing::Encrypted);
+    if !recipients.is_empty() && !encrypted {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Entry {} can't have recipients, its entry type is not encrypted",
+            address
+        )));
+    }
+    context.log(
+        LogLevel::Info,
+        format!("Authoring entry: {} with content: {:?}", address, entry),
+    );
     // 1. Build the context needed for validation of the entry
     let validation_package = await!(build_validation_package(&entry, &context))?;
     let validation_data = ValidationData {
@@ -35,38 +57,103 @@ pub async fn author_entry<'a>(
     };
 
     // 2. Validate the entry
-    context.log(format!("Authoring entry {}: validating...", address));
+    context.log(
+        LogLevel::Info,
+        format!("Authoring entry {}: validating...", address),
+    );
     await!(validate_entry(entry.clone(), validation_data, &context))?;
-    context.log(format!("Authoring entry {}: is valid!", address));
+    context.log(
+        LogLevel::Info,
+        format!("Authoring entry {}: is valid!", address),
+    );
 
-    // 3. Commit the entry
-    context.log(format!("Authoring entry {}: committing...", address));
+    // 3. Encrypt entries of encrypted types, they neither get committed nor held as they are
+    let entry = if encrypted {
+        context.log(
+            LogLevel::Info,
+            format!("Authoring entry {}: encrypting...", address),
+        );
+        Entry::Encrypted(context.encrypt_entry(entry, recipients)?)
+    } else {
+        entry.clone()
+    };
+
+    // 4. Commit the entry
+    context.log(
+        LogLevel::Info,
+        format!("Authoring entry {}: committing...", address),
+    );
     let addr = await!(commit_entry(entry.clone(), maybe_crud_link, &context))?;
-    context.log(format!("Authoring entry {}: committed", address));
-
-    // 4. Publish the valid entry to DHT. This will call Hold to itself
-    //TODO: missing a general public/private sharing check here, for now just
-    // using the entry_type can_publish() function which isn't enough
-    if entry.entry_type().can_publish() {
-        context.log(format!("Authoring entry {}: publishing...", address));
-        await!(publish(entry.address(), &context))?;
-        context.log(format!("Authoring entry {}: published!", address));
+    context.log(
+        LogLevel::Info,
+        format!("Authoring entry {}: committed as {}", address, addr),
+    );
+
+    // 5. Publish the valid entry to DHT. This will call Hold to itself
+    if context.can_publish(&entry) {
+        context.log(
+            LogLevel::Info,
+            format!("Authoring entry {}: publishing...", address),
+        );
+        await!(publish(addr.clone(), &context))?;
+        context.log(
+            LogLevel::Info,
+            format!("Authoring entry {}: published!", address),
+        );
     } else {
-        context.log(format!(
-            "Authoring entry {}: entry is private, no publishing",
-            address
-        ));
+        context.log(
+            LogLevel::Info,
+            format!(
+                "Authoring entry {}: entry is private, no publishing",
+                address
+            ),
+        );
     }
     Ok(addr)
 }
 
 #[cfg(test)]
 pub mod tests {
-    use super::author_entry;
-    use crate::nucleus::actions::tests::*;
+    use super::{author_entry, author_entry_for};
+    use crate::{
+        agent::{actions::commit::commit_entry, keystore::Keystore},
+        context::Context,
+        network::actions::publish::publish,
+        nucleus::actions::{tests::*, validate::validate_entry},
+        workflows::get_entry_result::get_entry_with_meta_workflow,
+    };
     use futures::executor::block_on;
-    use holochain_core_types::{entry::test_entry, json::JsonString};
-    use std::{thread, time};
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        chain_header::ChainHeader,
+        chain_migrate::ChainMigrate,
+        dna::entry_types::Sharing,
+        entry::{test_entry, Entry},
+        error::HolochainError,
+        json::JsonString,
+        link::link_add::LinkAdd,
+        signature::Signature,
+        time::test_iso_8601,
+        validation::{EntryAction, EntryLifecycle, ValidationData, ValidationPackage},
+    };
+    use std::{collections::HashSet, convert::TryFrom, sync::Arc, thread, time};
+
+    /// waits for the DHT shard of the given context to hold the entry at the given address
+    fn wait_for_dht_entry(context: &Arc<Context>, address: &Address) -> JsonString {
+        for _ in 0..120 {
+            let json = context
+                .state()
+                .unwrap()
+                .dht()
+                .fetch(address)
+                .expect("could not fetch from CAS");
+            if let Some(json) = json {
+                return json;
+            }
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        panic!("entry {} never reached the DHT", address);
+    }
 
     #[test]
     #[cfg(not(windows))]
@@ -106,4 +193,228 @@ pub mod tests {
             "{\"App\":[\"testEntryType\",\"\\\"test entry value\\\"\"]}".to_string(),
         );
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    /// test that entries of encrypted types only reach the DHT encrypted and can only
+    /// be read by their recipients
+    fn test_commit_encrypted_entry() {
+        let mut dna = test_dna();
+        dna.uuid = "test_commit_encrypted_entry".to_string();
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&test_entry().entry_type())
+            .unwrap()
+            .sharing = Sharing::Encrypted;
+        let (_instance1, context1) = instance_by_name("jill", dna.clone());
+        let (_instance2, context2) = instance_by_name("jack", dna.clone());
+        let (_instance3, context3) = instance_by_name("eve", dna);
+
+        let entry = test_entry();
+        let recipients = vec![context2.agent_id.address()];
+        let entry_address =
+            block_on(author_entry_for(&entry, None, &recipients, &context1)).unwrap();
+        // the encrypted entry is what gets committed and it has an address of its own
+        assert_ne!(entry_address, entry.address());
+        let committed = context1
+            .state()
+            .unwrap()
+            .agent()
+            .top_chain_header()
+            .unwrap();
+        assert_eq!(committed.entry_address(), &entry_address);
+
+        for context in vec![&context1, &context2, &context3] {
+            let json = wait_for_dht_entry(context, &entry_address);
+            assert!(!String::from(json.clone()).contains("test entry value"));
+            assert_eq!(
+                Entry::try_from(json.clone()).unwrap().address(),
+                entry_address
+            );
+            assert!(!context
+                .state()
+                .unwrap()
+                .dht()
+                .contains(&entry.address())
+                .unwrap());
+            match Entry::try_from(json).unwrap() {
+                Entry::Encrypted(encrypted_entry) => assert_eq!(
+                    encrypted_entry.recipients(),
+                    vec![context1.agent_id.address(), context2.agent_id.address()]
+                ),
+                other => panic!("expected an encrypted entry, got {:?}", other),
+            }
+        }
+
+        for context in vec![&context1, &context2] {
+            let entry_with_meta = block_on(get_entry_with_meta_workflow(context, &entry_address))
+                .unwrap()
+                .expect("recipients can get the entry");
+            assert_eq!(
+                JsonString::from(entry_with_meta.entry),
+                JsonString::from(entry.clone())
+            );
+        }
+        assert_eq!(
+            block_on(get_entry_with_meta_workflow(&context3, &entry_address)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    /// test that neither private entries nor links to them reach the network,
+    /// be it by publishing, by republishing to new peers or by answering get requests
+    fn test_private_entries_and_their_links_are_not_published() {
+        let mut dna = test_dna();
+        dna.uuid = "test_private_entries_and_their_links_are_not_published".to_string();
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&test_entry().entry_type())
+            .unwrap()
+            .sharing = Sharing::Private;
+        let (_instance1, context1) = instance_by_name("jill", dna.clone());
+
+        let private_entry = test_entry();
+        let private_address = block_on(author_entry(&private_entry, None, &context1)).unwrap();
+        let public_entry = test_entry_package_entry();
+        block_on(commit_entry(public_entry.clone(), None, &context1)).unwrap();
+        let link = Entry::LinkAdd(LinkAdd::new(
+            &public_entry.address(),
+            &private_address,
+            "secret",
+        ));
+        block_on(commit_entry(link.clone(), None, &context1)).unwrap();
+
+        assert!(!context1.can_publish(&private_entry));
+        assert!(!context1.can_publish(&link));
+        assert!(context1.can_publish(&public_entry));
+        assert!(block_on(publish(private_address.clone(), &context1)).is_err());
+        assert!(block_on(publish(link.address(), &context1)).is_err());
+
+        // jill republishes her chain once jack comes online,
+        // which is the only way the public entry can reach him
+        let (_instance2, context2) = instance_by_name("jack", dna);
+        wait_for_dht_entry(&context2, &public_entry.address());
+
+        let dht = context2.state().unwrap().dht();
+        let storage = dht.content_storage();
+        assert_eq!(storage.read().unwrap().fetch(&private_address), Ok(None));
+        assert_eq!(storage.read().unwrap().fetch(&link.address()), Ok(None));
+        assert_eq!(
+            dht.get_links(public_entry.address(), String::from("secret")),
+            Ok(HashSet::new())
+        );
+        assert_eq!(
+            block_on(get_entry_with_meta_workflow(&context2, &private_address)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    /// test that recipients can only be given for entries of encrypted types
+    fn test_recipients_need_encrypted_entry_type() {
+        let mut dna = test_dna();
+        dna.uuid = "test_recipients_need_encrypted_entry_type".to_string();
+        let (_instance, context) = instance_by_name("jill", dna);
+        let recipients = vec![Address::from("jack")];
+        assert!(block_on(author_entry_for(&test_entry(), None, &recipients, &context)).is_err());
+    }
+
+    #[test]
+    /// test that chains can only be closed and opened the way migrations are meant to,
+    /// and that nothing can be committed after a chain got closed
+    fn test_chain_migrate_rules() {
+        let dna = test_dna();
+        let dna_address = dna.address();
+        let (_instance, context) = instance_by_name("jill", dna);
+        let author = |migration: ChainMigrate| {
+            block_on(author_entry(
+                &Entry::ChainMigrate(migration),
+                None,
+                &context,
+            ))
+        };
+        let old_dna_address = Address::from("QmOldDna");
+        let new_dna_address = Address::from("QmNewDna");
+        let header = |migration: ChainMigrate, source: Address, signature: Signature| {
+            let entry = Entry::ChainMigrate(migration);
+            ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &vec![source],
+                &vec![signature],
+                &None,
+                &None,
+                &None,
+                &test_iso_8601(),
+            )
+        };
+        let signed_by_jill = |migration: ChainMigrate| {
+            let address = Entry::ChainMigrate(migration.clone()).address();
+            let signature = context.sign(&address.to_string()).unwrap();
+            header(migration, context.agent_id.address(), signature)
+        };
+        let closing_header = signed_by_jill(ChainMigrate::close(dna_address.clone()));
+
+        // the old chain has to be closed in favour of this DNA, by jill
+        let closed_elsewhere = signed_by_jill(ChainMigrate::close(new_dna_address.clone()));
+        let mut stranger = Keystore::new_random();
+        let close_this = ChainMigrate::close(dna_address.clone());
+        let close_this_address = Entry::ChainMigrate(close_this.clone()).address();
+        let stranger_signature = stranger.sign(&close_this_address.to_string());
+        let closed_by_stranger = header(close_this.clone(), stranger.address(), stranger_signature);
+        let forged = header(
+            close_this,
+            context.agent_id.address(),
+            Signature::from("forged"),
+        );
+        let open_own_dna = ChainMigrate::open(dna_address.clone(), closing_header.clone());
+        assert!(author(open_own_dna).is_err());
+        for invalid_header in vec![closed_elsewhere, closed_by_stranger, forged] {
+            assert!(author(ChainMigrate::open(old_dna_address.clone(), invalid_header)).is_err());
+        }
+
+        // the DHT checks the closing header against the sources of the Open migration
+        let open = ChainMigrate::open(old_dna_address.clone(), closing_header.clone());
+        let validate_on_dht = |sources: Vec<Address>| {
+            let validation_data = ValidationData {
+                package: ValidationPackage::only_header(closing_header.clone()),
+                sources,
+                lifecycle: EntryLifecycle::Dht,
+                action: EntryAction::Create,
+            };
+            block_on(validate_entry(
+                Entry::ChainMigrate(open.clone()),
+                validation_data,
+                &context,
+            ))
+        };
+        assert!(validate_on_dht(vec![stranger.address()]).is_err());
+        assert!(validate_on_dht(vec![context.agent_id.address()]).is_ok());
+
+        author(open.clone()).unwrap();
+        block_on(author_entry(&test_entry(), None, &context)).unwrap();
+        assert!(author(open).is_err());
+        assert!(author(ChainMigrate::close(dna_address)).is_err());
+
+        let close = ChainMigrate::close(new_dna_address);
+        author(close.clone()).unwrap();
+        let (_, closure) = context
+            .state()
+            .unwrap()
+            .agent()
+            .chain_closure()
+            .unwrap()
+            .expect("chain should be closed");
+        assert_eq!(closure, close);
+        match block_on(author_entry(&test_entry(), None, &context)) {
+            Err(HolochainError::ValidationFailed(message)) => assert!(message.contains("closed")),
+            other => panic!("expected the commit to fail validation, got {:?}", other),
+        }
+    }
 }
@@ -1,7 +1,10 @@
 use crate::{context::Context, network, nucleus};
 
 use holochain_core_types::{
-    cas::content::Address, crud_status::CrudStatus, entry::EntryWithMeta, error::HolochainError,
+    cas::content::Address,
+    crud_status::CrudStatus,
+    entry::{Entry, EntryWithMeta},
+    error::HolochainError,
 };
 use holochain_wasm_utils::api_serialization::get_entry::{
     GetEntryArgs, GetEntryResult, StatusRequestKind,
@@ -14,13 +17,33 @@ pub async fn get_entry_with_meta_workflow<'a>(
     address: &'a Address,
 ) -> Result<Option<EntryWithMeta>, HolochainError> {
     // 1. Try to get the entry locally (i.e. local DHT shard)
-    let maybe_entry_with_meta =
+    let mut maybe_entry_with_meta =
         nucleus::actions::get_entry::get_entry_with_meta(context, address.clone())?;
-    if maybe_entry_with_meta.is_some() {
-        return Ok(maybe_entry_with_meta);
-    }
     // 2. No result, so try on the network
-    await!(network::actions::get_entry::get_entry(context, &address))
+    if maybe_entry_with_meta.is_none() {
+        maybe_entry_with_meta = await!(network::actions::get_entry::get_entry(context, &address))?;
+    }
+    // 3. Decrypt entries of encrypted types, they don't exist for agents that can't
+    match maybe_entry_with_meta {
+        Some(entry_with_meta) => decrypt_entry_with_meta(context, entry_with_meta),
+        None => Ok(None),
+    }
+}
+
+fn decrypt_entry_with_meta(
+    context: &Arc<Context>,
+    entry_with_meta: EntryWithMeta,
+) -> Result<Option<EntryWithMeta>, HolochainError> {
+    let encrypted_entry = match entry_with_meta.entry {
+        Entry::Encrypted(ref encrypted_entry) => encrypted_entry.clone(),
+        _ => return Ok(Some(entry_with_meta)),
+    };
+    Ok(context
+        .decrypt_entry(&encrypted_entry)?
+        .map(|entry| EntryWithMeta {
+            entry,
+            ..entry_with_meta
+        }))
 }
 
 /// Get GetEntryResult workflow
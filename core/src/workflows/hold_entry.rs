@@ -8,7 +8,9 @@ use crate::{
 };
 
 use holochain_core_types::{
-    cas::content::Address,
+    cas::content::{Address, AddressableContent},
+    dna::entry_types::Sharing,
+    entry::Entry,
     error::HolochainError,
     validation::{EntryAction, EntryLifecycle, ValidationData},
 };
@@ -20,12 +22,38 @@ pub async fn hold_entry_workflow<'a>(
 ) -> Result<Address, HolochainError> {
     let EntryWithHeader { entry, header } = &entry_with_header;
 
-    // 1. Get validation package from source
+    // 1. Check that the header belongs to the entry and was signed by its sources.
+    // Entries are addressed by their content, encrypted ones by their cipher, so this also
+    // rejects entries published under an address that is not their own.
+    entry_with_header.verify()?;
+
+    // Encrypted entries can't be validated by anybody but their recipients.
+    // We only make sure they are of an encrypted entry type, so this can't be used to
+    // sneak entries of other types past validation, and hold them apart from other content.
+    if let Entry::Encrypted(encrypted_entry) = entry {
+        if context.entry_type_sharing(&entry.entry_type()) != Some(Sharing::Encrypted) {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Entry {} is encrypted but entries of type {} are not",
+                entry.address(),
+                entry.entry_type()
+            )));
+        }
+        if !header.sources().contains(encrypted_entry.author()) {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Entry {} was encrypted by {} who is not one of its sources",
+                entry.address(),
+                encrypted_entry.author()
+            )));
+        }
+        return await!(hold_entry(entry, &context));
+    }
+
+    // 2. Get validation package from source
     let maybe_validation_package = await!(get_validation_package(header.clone(), &context))?;
     let validation_package = maybe_validation_package
         .ok_or("Could not get validation package from source".to_string())?;
 
-    // 2. Create validation data struct
+    // 3. Create validation data struct
     let validation_data = ValidationData {
         package: validation_package,
         sources: header.sources().clone(),
@@ -33,10 +61,10 @@ pub async fn hold_entry_workflow<'a>(
         action: EntryAction::Create,
     };
 
-    // 3. Validate the entry
+    // 4. Validate the entry
     await!(validate_entry(entry.clone(), validation_data, &context))?;
 
-    // 3. If valid store the entry in the local DHT shard
+    // 5. If valid store the entry in the local DHT shard
     await!(hold_entry(entry, &context))
 }
 
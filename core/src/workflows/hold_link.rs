@@ -1,6 +1,7 @@
 use crate::{
     context::Context,
     dht::actions::add_link::add_link,
+    logger::LogLevel,
     network::{
         actions::get_validation_package::get_validation_package, entry_with_header::EntryWithHeader,
     },
@@ -28,15 +29,24 @@ pub async fn hold_link_workflow<'a>(
     };
     let link = link_add.link().clone();
 
-    context.log(format!("Hold link: {:?}", link));
-    // 1. Get validation package from source
-    context.log(format!("Hold link: getting validation package..."));
+    context.log(LogLevel::Info, format!("Hold link: {:?}", link));
+    // 1. Check that the header belongs to the link and was signed by its sources
+    entry_with_header.verify()?;
+
+    // 2. Get validation package from source
+    context.log(
+        LogLevel::Info,
+        format!("Hold link: getting validation package..."),
+    );
     let maybe_validation_package = await!(get_validation_package(header.clone(), &context))?;
     let validation_package = maybe_validation_package
         .ok_or("Could not get validation package from source".to_string())?;
-    context.log(format!("Hold link: got validation package!"));
+    context.log(
+        LogLevel::Info,
+        format!("Hold link: got validation package!"),
+    );
 
-    // 2. Create validation data struct
+    // 3. Create validation data struct
     let validation_data = ValidationData {
         package: validation_package,
         sources: header.sources().clone(),
@@ -44,17 +54,17 @@ pub async fn hold_link_workflow<'a>(
         action: EntryAction::Create,
     };
 
-    // 3. Validate the entry
-    context.log(format!("Hold link: validate..."));
+    // 4. Validate the entry
+    context.log(LogLevel::Info, format!("Hold link: validate..."));
     await!(validate_entry(entry.clone(), validation_data, &context)).map_err(|err| {
-        context.log(format!("Hold link: invalid! {:?}", err));
+        context.log(LogLevel::Warn, format!("Hold link: invalid! {:?}", err));
         err
     })?;
-    context.log(format!("Hold link: is valid!"));
+    context.log(LogLevel::Info, format!("Hold link: is valid!"));
 
-    // 3. If valid store the entry in the local DHT shard
+    // 5. If valid store the entry in the local DHT shard
     await!(add_link(&link, &context))?;
-    context.log(format!("Hold link: added! {:?}", link));
+    context.log(LogLevel::Info, format!("Hold link: added! {:?}", link));
     Ok(())
 }
 
@@ -6,13 +6,13 @@ extern crate holochain_core_types;
 extern crate holochain_net;
 
 use holochain_container_api::{context_builder::ContextBuilder, Holochain};
-use holochain_core::context::Context;
+use holochain_core::{agent::keystore::Keystore, context::Context};
 use holochain_core_types::{cas::content::Address, dna::Dna, error::HolochainError};
 
 use std::sync::Arc;
 
-use holochain_core::logger::Logger;
-use holochain_core_types::{agent::AgentId, dna::capabilities::CapabilityCall};
+use holochain_core::logger::{LogLevel, Logger};
+use holochain_core_types::dna::capabilities::CapabilityCall;
 use std::{
     ffi::{CStr, CString},
     os::raw::c_char,
@@ -22,7 +22,7 @@ use std::{
 struct NullLogger {}
 
 impl Logger for NullLogger {
-    fn log(&mut self, _msg: String) {}
+    fn log(&mut self, _level: LogLevel, _msg: String) {}
 }
 
 #[no_mangle]
@@ -56,10 +56,11 @@ pub unsafe extern "C" fn holochain_load(storage_path: CStrPtr) -> *mut Holochain
     }
 }
 
+/// Always the same agent, so instances can be loaded again from their storage
 fn get_context(path: &String) -> Result<Context, HolochainError> {
-    let agent = AgentId::generate_fake("c_bob");
+    let keystore = Keystore::generate_fake("c_bob");
     Ok(ContextBuilder::new()
-        .with_agent(agent)
+        .with_agent(keystore.agent_id("c_bob"), keystore)?
         .with_file_storage(path.clone())?
         .spawn())
 }
@@ -435,6 +435,83 @@ impl EavTestSuite {
             );
         }
     }
+
+    pub fn test_attribute_prefix<A, S>(mut eav_storage: S)
+    where
+        A: AddressableContent + Clone,
+        S: EntityAttributeValueStorage,
+    {
+        let one = A::try_from_content(&Content::from(RawString::from("foo")))
+            .expect("could not create AddressableContent from Content");
+        let two = A::try_from_content(&Content::from(RawString::from("bar")))
+            .expect("could not create AddressableContent from Content");
+        let three = A::try_from_content(&Content::from(RawString::from("baz")))
+            .expect("could not create AddressableContent from Content");
+
+        let mut expected = HashSet::new();
+        for (attribute, value) in vec![("link__a", two.clone()), ("link__b", three.clone())] {
+            let eav =
+                EntityAttributeValue::new(&one.address(), &attribute.to_string(), &value.address())
+                    .expect("could not create EAV");
+            eav_storage.add_eav(&eav).expect("could not add eav");
+            expected.insert(eav);
+        }
+
+        // same entity but an attribute not matching the prefix
+        let other_attribute =
+            EntityAttributeValue::new(&one.address(), &"crud-status".to_string(), &two.address())
+                .expect("could not create EAV");
+        eav_storage
+            .add_eav(&other_attribute)
+            .expect("could not add eav");
+
+        // matching attribute but another entity
+        let other_entity =
+            EntityAttributeValue::new(&two.address(), &"link__a".to_string(), &three.address())
+                .expect("could not create EAV");
+        eav_storage
+            .add_eav(&other_entity)
+            .expect("could not add eav");
+
+        // all links of one
+        assert_eq!(
+            expected,
+            eav_storage
+                .fetch_eav_with_attribute_prefix(Some(one.address()), "link__", None)
+                .expect("could not fetch eav"),
+        );
+
+        // all links pointing to three
+        let mut expected_to_three = HashSet::new();
+        expected_to_three.insert(
+            EntityAttributeValue::new(&one.address(), &"link__b".to_string(), &three.address())
+                .expect("could not create EAV"),
+        );
+        expected_to_three.insert(other_entity.clone());
+        assert_eq!(
+            expected_to_three,
+            eav_storage
+                .fetch_eav_with_attribute_prefix(None, "link__", Some(three.address()))
+                .expect("could not fetch eav"),
+        );
+
+        // all links of anything
+        expected.insert(other_entity);
+        assert_eq!(
+            expected,
+            eav_storage
+                .fetch_eav_with_attribute_prefix(None, "link__", None)
+                .expect("could not fetch eav"),
+        );
+
+        // a prefix nothing starts with
+        assert_eq!(
+            HashSet::new(),
+            eav_storage
+                .fetch_eav_with_attribute_prefix(Some(one.address()), "nothing", None)
+                .expect("could not fetch eav"),
+        );
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +158,7 @@ pub mod tests {
             entry_type::{test_entry_type, test_entry_type_a, test_entry_type_b},
             test_entry, test_entry_a, test_entry_b,
         },
+        json::JsonString,
         signature::{test_signature_b, test_signatures},
         time::test_iso_8601,
     };
@@ -268,6 +269,18 @@ pub mod tests {
         assert_eq!(test_chain_header().timestamp(), &test_iso_8601());
     }
 
+    #[test]
+    /// headers stored with an empty timestamp still load and keep their address
+    fn legacy_empty_timestamp() {
+        let content =
+            String::from(test_chain_header().content()).replace(&test_iso_8601().to_string(), "");
+        let chain_header = ChainHeader::try_from_content(&JsonString::from(content.clone()))
+            .expect("headers with empty timestamps should deserialize");
+        assert_eq!(chain_header.timestamp().to_string(), "");
+        assert_eq!(chain_header.content(), JsonString::from(content));
+        assert_ne!(chain_header.address(), test_chain_header().address());
+    }
+
     #[test]
     fn link_test() {
         let chain_header_a = test_chain_header();
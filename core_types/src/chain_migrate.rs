@@ -1,4 +1,91 @@
-//! NOT YET AVAILABLE
+//! A ChainMigrate entry moves an agent from one DNA to another.
+//! The agent closes their chain on the old DNA with a Close migration naming the new DNA,
+//! and opens their chain on the new DNA with an Open migration naming the old DNA and
+//! carrying the header of the Close migration, so anybody can check that it is signed
+//! by the agent and points to a Close in favour of the new DNA.
+//! Nothing can be committed to a chain after it got closed.
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct ChainMigrate {}
+use crate::{
+    cas::content::Address, chain_header::ChainHeader, error::HolochainError, json::JsonString,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum MigrationType {
+    Close,
+    Open,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, DefaultJson)]
+pub struct ChainMigrate {
+    migration_type: MigrationType,
+    /// The DNA of the other chain: the new one for Close, the old one for Open
+    dna_address: Address,
+    /// The header of the Close migration on the old chain, only set for Open
+    closing_header: Option<ChainHeader>,
+}
+
+impl ChainMigrate {
+    /// Closes a chain in favour of a chain running the given DNA
+    pub fn close(new_dna_address: Address) -> Self {
+        ChainMigrate {
+            migration_type: MigrationType::Close,
+            dna_address: new_dna_address,
+            closing_header: None,
+        }
+    }
+
+    /// Opens a chain as the continuation of the chain running the given DNA,
+    /// which got closed by the Close migration with the given header
+    pub fn open(old_dna_address: Address, closing_header: ChainHeader) -> Self {
+        ChainMigrate {
+            migration_type: MigrationType::Open,
+            dna_address: old_dna_address,
+            closing_header: Some(closing_header),
+        }
+    }
+
+    pub fn migration_type(&self) -> &MigrationType {
+        &self.migration_type
+    }
+
+    pub fn dna_address(&self) -> &Address {
+        &self.dna_address
+    }
+
+    pub fn closing_header(&self) -> Option<&ChainHeader> {
+        self.closing_header.as_ref()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::chain_header::test_chain_header;
+    use std::convert::TryFrom;
+
+    pub fn test_chain_migrate_close() -> ChainMigrate {
+        ChainMigrate::close(Address::from("QmNewDna"))
+    }
+
+    #[test]
+    fn chain_migrate_smoke_test() {
+        let close = test_chain_migrate_close();
+        assert_eq!(close.migration_type(), &MigrationType::Close);
+        assert_eq!(close.dna_address(), &Address::from("QmNewDna"));
+        assert_eq!(close.closing_header(), None);
+
+        let open = ChainMigrate::open(Address::from("QmOldDna"), test_chain_header());
+        assert_eq!(open.migration_type(), &MigrationType::Open);
+        assert_eq!(open.dna_address(), &Address::from("QmOldDna"));
+        assert_eq!(open.closing_header(), Some(&test_chain_header()));
+    }
+
+    #[test]
+    fn chain_migrate_json_round_trip_test() {
+        let open = ChainMigrate::open(Address::from("QmOldDna"), test_chain_header());
+        assert_eq!(
+            ChainMigrate::try_from(JsonString::from(open.clone())).unwrap(),
+            open
+        );
+    }
+}
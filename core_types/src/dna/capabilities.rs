@@ -1,6 +1,7 @@
 //! File holding all the structs for handling capabilities defined in DNA.
 
-use crate::cas::content::Address;
+use crate::{cas::content::Address, signature::Signature, time::Iso8601};
+use serde_json;
 use std::str::FromStr;
 
 //--------------------------------------------------------------------------------------------------
@@ -47,9 +48,21 @@ impl ReservedCapabilityNames {
 //--------------------------------------------------------------------------------------------------
 // CapabilityCall
 //--------------------------------------------------------------------------------------------------
-/// a struct to hold the signature of the call
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
-pub struct CallSignature {}
+/// a struct to hold the signature of the call, made by its caller over the call's
+/// signing payload (see CapabilityCall::signing_payload), and the time it expires.
+/// It proves that calls from outside the instance come from the agent they claim as their
+/// caller, which callers only accept until the signature expires.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CallSignature {
+    #[serde(default)]
+    pub signature: Option<Signature>,
+    #[serde(default)]
+    pub expires: Option<Iso8601>,
+}
+
+/// Signatures of calls that expire later than this many seconds from now don't get accepted,
+/// so a captured signed call can't be replayed for long
+pub const CALL_SIGNATURE_MAX_VALIDITY_SECONDS: i64 = 300;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct CapabilityCall {
@@ -65,9 +78,51 @@ impl CapabilityCall {
             cap_name: name,
             cap_token: token,
             caller: caller,
-            signature: CallSignature {}, // FIXME
+            signature: CallSignature::default(),
         }
     }
+
+    /// the same call, signed by its caller with a signature that expires at the given time
+    pub fn with_signature(mut self, signature: Signature, expires: Iso8601) -> Self {
+        self.signature = CallSignature {
+            signature: Some(signature),
+            expires: Some(expires),
+        };
+        self
+    }
+
+    /// what the caller signs when calling the given function with this call's capability
+    /// and token and the given parameters, for a signature that expires at the given time.
+    /// Parameters that are JSON get signed in their compact form, as they don't necessarily
+    /// reach the callee formatted the way the caller wrote them.
+    pub fn signing_payload(&self, fn_name: &str, parameters: &str, expires: &Iso8601) -> String {
+        let parameters = serde_json::from_str::<serde_json::Value>(parameters)
+            .map(|value| value.to_string())
+            .unwrap_or_else(|_| parameters.to_string());
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.cap_name, self.cap_token, fn_name, expires, parameters
+        )
+    }
+}
+
+/// Zome functions that are called over JSON-RPC (through interfaces and bridges) get their
+/// arguments as the request params. Callers of non-public capabilities add the token, their
+/// address, their signature of the call (see CapabilityCall::signing_payload) and the time it
+/// expires under this key, which gets removed before the arguments reach the function:
+/// `{"__cap": {"token": "Qm...", "caller": "Hc...", "signature": "...", "expires": "..."}, ...arguments}`
+pub const CAP_REQUEST_PARAMS_KEY: &str = "__cap";
+
+/// The capability token, caller, signature and its expiry passed under CAP_REQUEST_PARAMS_KEY
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CapabilityRequestParams {
+    pub token: Address,
+    #[serde(default)]
+    pub caller: Option<Address>,
+    #[serde(default)]
+    pub signature: Option<Signature>,
+    #[serde(default)]
+    pub expires: Option<Iso8601>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -169,7 +224,9 @@ impl Capability {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::time::test_iso_8601;
     use serde_json;
+    use std::convert::TryFrom;
 
     #[test]
     /// test that a canonical string can be created from ReservedCapabilityNames
@@ -182,6 +239,18 @@ mod tests {
         assert_eq!(cap.cap_type, CapabilityType::Transferable);
     }
 
+    #[test]
+    fn test_signing_payload_covers_parameters_and_expiry() {
+        let call = CapabilityCall::new("cap".to_string(), Address::from("token"), None);
+        let expires = test_iso_8601();
+        let payload = call.signing_payload("fn", r#"{"a": 1}"#, &expires);
+        assert_eq!(payload, format!("cap:token:fn:{}:{{\"a\":1}}", expires));
+        assert_eq!(call.signing_payload("fn", r#"{"a":1}"#, &expires), payload);
+        assert_ne!(call.signing_payload("fn", r#"{"a":2}"#, &expires), payload);
+        let later = Iso8601::try_from("2018-10-11T03:23:39+00:00").unwrap();
+        assert_ne!(call.signing_payload("fn", r#"{"a":1}"#, &later), payload);
+    }
+
     #[test]
     /// test that ReservedCapabilityNames can be created from a canonical string
     fn test_capabilities_from_str() {
@@ -1,6 +1,11 @@
 use crate::{
     cas::content::{AddressableContent, Content},
-    dna::{bridges::Bridge, capabilities::Capability, entry_types::EntryTypeDef, wasm, zome},
+    dna::{
+        bridges::{Bridge, BridgeReference},
+        capabilities::Capability,
+        entry_types::{EntryTypeDef, Sharing},
+        wasm, zome,
+    },
     entry::entry_type::EntryType,
     error::{DnaError, HolochainError},
     json::JsonString,
@@ -198,12 +203,41 @@ impl Dna {
         None
     }
 
+    /// Return how entries of the given type are shared as declared by their entry_type
+    /// definition. None for system entry types and app entry types the DNA doesn't define.
+    pub fn get_entry_type_sharing(&self, entry_type: &EntryType) -> Option<Sharing> {
+        match entry_type {
+            EntryType::App(app_entry_type) => self
+                .get_entry_type_def(&app_entry_type.to_string())
+                .map(|entry_type_def| entry_type_def.sharing.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether entries of the given type may leave the source chain.
+    /// App entry types are publishable if their sharing says so (types the DNA does not
+    /// define never are), system entry types decide for themselves.
+    pub fn can_publish_entry_type(&self, entry_type: &EntryType) -> bool {
+        match entry_type {
+            EntryType::App(_) => self
+                .get_entry_type_sharing(entry_type)
+                .map(|sharing| sharing.can_publish())
+                .unwrap_or(false),
+            _ => entry_type.can_publish(),
+        }
+    }
+
     pub fn multihash(&self) -> Result<Vec<u8>, HolochainError> {
         let s = String::from(JsonString::from(self.to_owned()));
         multihash::encode(multihash::Hash::SHA2256, &s.into_bytes())
             .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))
     }
 
+    /// Looks up an application property by its dotted path, see [property_at_path](fn.property_at_path.html).
+    pub fn get_property(&self, path: &str) -> Option<&Value> {
+        property_at_path(&self.properties, path)
+    }
+
     pub fn get_required_bridges(&self) -> Vec<Bridge> {
         self.zomes
             .values()
@@ -211,6 +245,68 @@ impl Dna {
             .flatten()
             .collect()
     }
+
+    /// All bridges declared by the zomes of this DNA, required and optional ones.
+    pub fn get_bridges(&self) -> Vec<Bridge> {
+        self.zomes
+            .values()
+            .map(|zome| zome.bridges.clone())
+            .flatten()
+            .collect()
+    }
+
+    /// Tells if this DNA can be the callee of a bridge with the given reference.
+    /// Address references have to match the DNA's address. Capability references
+    /// need, for every referenced capability, a zome with a capability of that name
+    /// that declares all of the referenced functions with the same signatures.
+    pub fn satisfies_bridge_reference(&self, reference: &BridgeReference) -> bool {
+        match reference {
+            BridgeReference::Address { dna_address } => self.address() == *dna_address,
+            BridgeReference::Capability { capabilities } => {
+                capabilities.iter().all(|(cap_name, required)| {
+                    self.zomes.values().any(|zome| {
+                        zome.capabilities.get(cap_name).map_or(false, |cap| {
+                            required
+                                .functions
+                                .iter()
+                                .all(|function| cap.functions.contains(function))
+                        })
+                    })
+                })
+            }
+        }
+    }
+}
+
+/// Walks down the given properties along a dotted path like "settings.limits.posts".
+/// Path segments select object members or, if they are numbers, array elements.
+/// The empty path selects the properties themselves.
+pub fn property_at_path<'a>(properties: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(properties);
+    }
+    path.split('.')
+        .try_fold(properties, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.get(index)),
+            _ => None,
+        })
+}
+
+/// Merges the given overrides into the properties. Objects get merged member by member,
+/// any other override value replaces the property it overrides.
+pub fn merge_properties(properties: &mut Value, overrides: &Value) {
+    match (properties, overrides) {
+        (Value::Object(properties), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_properties(properties.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (properties, overrides) => *properties = overrides.clone(),
+    }
 }
 
 impl Hash for Dna {
@@ -14,6 +14,8 @@ pub enum Sharing {
     Public,
     #[serde(rename = "private")]
     Private,
+    /// Entries only reach the DHT encrypted for their author and the recipients given
+    /// when committing them (see EncryptedEntry)
     #[serde(rename = "encrypted")]
     Encrypted,
 }
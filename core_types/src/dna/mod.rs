@@ -37,7 +37,7 @@ pub mod tests {
     use super::*;
     extern crate base64;
     use crate::{
-        cas::content::Address,
+        cas::content::{Address, AddressableContent},
         dna::{
             bridges::{Bridge, BridgePresence, BridgeReference},
             capabilities::{Capability, CapabilityType, FnDeclaration, FnParameter},
@@ -70,6 +70,52 @@ pub mod tests {
         assert_eq!(Some(&entry_type_def), dna.get_entry_type_def("bar"));
     }
 
+    #[test]
+    fn get_entry_type_sharing_test() {
+        let mut dna = test_dna();
+        let mut zome = test_zome();
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.sharing = entry_types::Sharing::Encrypted;
+        zome.entry_types
+            .insert(EntryType::App(AppEntryType::from("bar")), entry_type_def);
+        dna.zomes.insert("zome".to_string(), zome);
+
+        assert_eq!(
+            Some(entry_types::Sharing::Encrypted),
+            dna.get_entry_type_sharing(&EntryType::App(AppEntryType::from("bar")))
+        );
+        assert_eq!(
+            None,
+            dna.get_entry_type_sharing(&EntryType::App(AppEntryType::from("foo")))
+        );
+        assert_eq!(None, dna.get_entry_type_sharing(&EntryType::AgentId));
+    }
+
+    #[test]
+    fn can_publish_entry_type_test() {
+        let mut dna = test_dna();
+        let mut zome = test_zome();
+        let mut private_entry_type_def = EntryTypeDef::new();
+        private_entry_type_def.sharing = entry_types::Sharing::Private;
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("private")),
+            private_entry_type_def,
+        );
+        let mut public_entry_type_def = EntryTypeDef::new();
+        public_entry_type_def.sharing = entry_types::Sharing::Public;
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("public")),
+            public_entry_type_def,
+        );
+        dna.zomes.insert("zome".to_string(), zome);
+
+        assert!(dna.can_publish_entry_type(&EntryType::App(AppEntryType::from("public"))));
+        assert!(!dna.can_publish_entry_type(&EntryType::App(AppEntryType::from("private"))));
+        assert!(!dna.can_publish_entry_type(&EntryType::App(AppEntryType::from("unknown"))));
+        assert!(dna.can_publish_entry_type(&EntryType::AgentId));
+        assert!(!dna.can_publish_entry_type(&EntryType::CapTokenGrant));
+    }
+
     #[test]
     fn can_parse_and_output_json() {
         let dna = test_dna();
@@ -364,6 +410,39 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn get_property_by_dotted_path() {
+        let dna = Dna::try_from(JsonString::from(
+            r#"{
+                "properties": {
+                    "str": "hello",
+                    "obj": {"a": 1, "arr": [{"b": true}]}
+                }
+            }"#,
+        ))
+        .unwrap();
+
+        assert_eq!(dna.get_property("str"), Some(&json!("hello")));
+        assert_eq!(dna.get_property("obj.a"), Some(&json!(1)));
+        assert_eq!(dna.get_property("obj.arr.0.b"), Some(&json!(true)));
+        assert_eq!(dna.get_property(""), Some(&dna.properties));
+        assert_eq!(dna.get_property("obj.b"), None);
+        assert_eq!(dna.get_property("obj.arr.1"), None);
+        assert_eq!(dna.get_property("str.a"), None);
+    }
+
+    #[test]
+    fn merge_properties_test() {
+        let mut properties = json!({"a": 1, "obj": {"b": 2, "c": 3}});
+        dna::merge_properties(&mut properties, &json!({"obj": {"c": 4, "d": 5}, "e": [6]}));
+        assert_eq!(
+            properties,
+            json!({"a": 1, "obj": {"b": 2, "c": 4, "d": 5}, "e": [6]})
+        );
+        dna::merge_properties(&mut properties, &json!({"obj": null}));
+        assert_eq!(properties, json!({"a": 1, "obj": null, "e": [6]}));
+    }
+
     #[test]
     fn get_wasm_from_zome_name() {
         let dna = Dna::try_from(JsonString::from(
@@ -577,4 +656,51 @@ pub mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_satisfies_bridge_reference() {
+        let hello = FnDeclaration {
+            name: String::from("hello"),
+            inputs: vec![],
+            outputs: vec![FnParameter {
+                name: String::from("greeting"),
+                parameter_type: String::from("string"),
+            }],
+        };
+        let mut capability = Capability::new(CapabilityType::Public);
+        capability.functions.push(hello.clone());
+        let mut zome = zome::Zome::default();
+        zome.capabilities
+            .insert(String::from("greetings"), capability.clone());
+        let mut dna = Dna::new();
+        dna.zomes.insert(String::from("greeter"), zome);
+
+        let reference = |cap_name: &str, capability: Capability| BridgeReference::Capability {
+            capabilities: btreemap! { String::from(cap_name) => capability },
+        };
+
+        assert!(dna.satisfies_bridge_reference(&reference("greetings", capability.clone())));
+        assert!(dna.satisfies_bridge_reference(&reference(
+            "greetings",
+            Capability::new(CapabilityType::Public)
+        )));
+        assert!(!dna.satisfies_bridge_reference(&reference("farewells", capability.clone())));
+
+        let mut other_signature = Capability::new(CapabilityType::Public);
+        other_signature.functions.push(FnDeclaration {
+            inputs: vec![FnParameter {
+                name: String::from("name"),
+                parameter_type: String::from("string"),
+            }],
+            ..hello
+        });
+        assert!(!dna.satisfies_bridge_reference(&reference("greetings", other_signature)));
+
+        assert!(dna.satisfies_bridge_reference(&BridgeReference::Address {
+            dna_address: dna.address(),
+        }));
+        assert!(!dna.satisfies_bridge_reference(&BridgeReference::Address {
+            dna_address: Address::from("Qmabcdef1234567890"),
+        }));
+    }
 }
@@ -123,6 +123,23 @@ pub trait EntityAttributeValueStorage: objekt::Clone + Send + Sync + Debug {
         attribute: Option<Attribute>,
         value: Option<Value>,
     ) -> Result<HashSet<EntityAttributeValue>, HolochainError>;
+    /// Fetch the set of EntityAttributeValues whose attribute starts with the given prefix
+    /// (e.g. all links of an entity with the prefix "link__").
+    /// Entity and value constrain the same way they do in fetch_eav().
+    /// The default implementation filters an attribute-open fetch_eav(). Implementations
+    /// that keep their attributes ordered should override this with a range query.
+    fn fetch_eav_with_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        value: Option<Value>,
+    ) -> Result<HashSet<EntityAttributeValue>, HolochainError> {
+        Ok(self
+            .fetch_eav(entity, None, value)?
+            .into_iter()
+            .filter(|eav| eav.attribute().starts_with(attribute_prefix))
+            .collect())
+    }
 }
 
 clone_trait_object!(EntityAttributeValueStorage);
@@ -26,22 +26,34 @@ impl CapToken {
 /// System entry to hold a capabilities granted by the callee
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
 pub struct CapTokenGrant {
+    /// name of the capability (of the zome the grant was made from) the token gives access to
+    cap_name: String,
     assignees: Option<Vec<Address>>,
+    /// random value that makes the token of a grant unpredictable,
+    /// even to somebody knowing the capability and the assignees
+    #[serde(default)]
+    nonce: String,
 }
 
 impl CapTokenGrant {
-    fn new(assignees: Option<Vec<Address>>) -> Self {
+    fn new(cap_name: &str, assignees: Option<Vec<Address>>, nonce: &str) -> Self {
         CapTokenGrant {
+            cap_name: cap_name.to_string(),
             assignees: assignees,
+            nonce: nonce.to_string(),
         }
     }
 
+    /// The nonce has to be random (see CapTokenGrant::nonce), core generates one
+    /// with holochain_sodium::random when zomes grant capabilities.
     pub fn create(
+        cap_name: &str,
         cap_type: CapabilityType,
         assignees: Option<Vec<Address>>,
+        nonce: &str,
     ) -> Result<Self, HolochainError> {
         let assignees = CapTokenGrant::valid(cap_type, assignees)?;
-        Ok(CapTokenGrant::new(assignees))
+        Ok(CapTokenGrant::new(cap_name, assignees, nonce))
     }
 
     // internal check that type and assignees are valid for create
@@ -71,7 +83,7 @@ impl CapTokenGrant {
     }
 
     // the token value is address of the entry, so we can just build it
-    // and take the address.
+    // and take the address. The nonce keeps it from being guessed.
     pub fn token(&self) -> CapTokenValue {
         let addr: Address = Entry::CapTokenGrant((*self).clone()).address();
         addr
@@ -90,10 +102,18 @@ impl CapTokenGrant {
         }
     }
 
+    pub fn cap_name(&self) -> String {
+        self.cap_name.clone()
+    }
+
     pub fn assignees(&self) -> Option<Vec<Address>> {
         self.assignees.clone()
     }
 
+    pub fn nonce(&self) -> String {
+        self.nonce.clone()
+    }
+
     /// verifies that this grant is valid for a given requester and token value
     pub fn verify(
         &self,
@@ -113,7 +133,8 @@ impl CapTokenGrant {
             return false;
         }
 
-        // TODO: CallSignature check against Address
+        // Signatures can't be checked here, callers have to make sure `from` signed the
+        // call before they pass it in (see CallSignature)
 
         match self.cap_type() {
             CapabilityType::Public => true,
@@ -136,13 +157,14 @@ pub mod tests {
 
     #[test]
     fn test_new_cap_token_grant_entry() {
-        let grant = CapTokenGrant::new(None);
+        let grant = CapTokenGrant::new("test_cap", None, "nonce");
         assert_eq!(grant.cap_type(), CapabilityType::Public);
-        let grant = CapTokenGrant::new(Some(Vec::new()));
+        let grant = CapTokenGrant::new("test_cap", Some(Vec::new()), "nonce");
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
         let test_address = Address::new();
-        let grant = CapTokenGrant::new(Some(vec![test_address.clone()]));
+        let grant = CapTokenGrant::new("test_cap", Some(vec![test_address.clone()]), "nonce");
         assert_eq!(grant.cap_type(), CapabilityType::Assigned);
+        assert_eq!(grant.cap_name(), "test_cap");
         assert_eq!(grant.assignees().unwrap()[0], test_address)
     }
 
@@ -163,28 +185,42 @@ pub mod tests {
 
     #[test]
     fn test_create_cap_token_grant_entry() {
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Public, None);
+        let maybe_grant = CapTokenGrant::create("test_cap", CapabilityType::Public, None, "nonce");
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Public);
 
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Transferable, Some(Vec::new()));
+        let maybe_grant = CapTokenGrant::create(
+            "test_cap",
+            CapabilityType::Transferable,
+            Some(Vec::new()),
+            "nonce",
+        );
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
 
         let test_address = Address::new();
 
-        let maybe_grant =
-            CapTokenGrant::create(CapabilityType::Public, Some(vec![test_address.clone()]));
+        let maybe_grant = CapTokenGrant::create(
+            "test_cap",
+            CapabilityType::Public,
+            Some(vec![test_address.clone()]),
+            "nonce",
+        );
         assert!(maybe_grant.is_err());
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Transferable, None);
+        let maybe_grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "nonce");
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
 
-        let maybe_grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![test_address.clone()]));
+        let maybe_grant = CapTokenGrant::create(
+            "test_cap",
+            CapabilityType::Assigned,
+            Some(vec![test_address.clone()]),
+            "nonce",
+        );
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Assigned);
@@ -195,9 +231,10 @@ pub mod tests {
     fn test_cap_grant_verify() {
         let test_address1 = Address::from("some identity");
         let test_address2 = Address::from("some other identity");
-        let test_call_signature = &CallSignature {};
+        let test_call_signature = &CallSignature::default();
 
-        let grant = CapTokenGrant::create(CapabilityType::Public, None).unwrap();
+        let grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Public, None, "nonce").unwrap();
         let token = grant.token();
         assert!(grant.verify(token.clone(), None, test_call_signature));
         assert!(grant.verify(
@@ -207,7 +244,8 @@ pub mod tests {
         ));
         assert!(grant.verify(Address::from("Bad Token"), None, test_call_signature));
 
-        let grant = CapTokenGrant::create(CapabilityType::Transferable, None).unwrap();
+        let grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "nonce").unwrap();
         let token = grant.token();
         assert!(!grant.verify(token.clone(), None, test_call_signature));
         assert!(grant.verify(
@@ -226,9 +264,13 @@ pub mod tests {
             test_call_signature
         ));
 
-        let grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![test_address1.clone()]))
-                .unwrap();
+        let grant = CapTokenGrant::create(
+            "test_cap",
+            CapabilityType::Assigned,
+            Some(vec![test_address1.clone()]),
+            "nonce",
+        )
+        .unwrap();
         let token = grant.token();
         assert!(!grant.verify(token.clone(), None, test_call_signature));
         assert!(grant.verify(
@@ -247,4 +289,14 @@ pub mod tests {
             test_call_signature
         ));
     }
+
+    #[test]
+    fn test_cap_grant_token_depends_on_nonce() {
+        let grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "nonce").unwrap();
+        let other_grant =
+            CapTokenGrant::create("test_cap", CapabilityType::Transferable, None, "other").unwrap();
+        assert_eq!(grant.nonce(), "nonce");
+        assert_ne!(grant.token(), other_grant.token());
+    }
 }
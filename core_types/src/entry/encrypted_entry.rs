@@ -0,0 +1,156 @@
+use crate::{
+    cas::content::Address, entry::entry_type::AppEntryType, error::HolochainError, json::JsonString,
+};
+
+//-------------------------------------------------------------------------------------------------
+// EncryptedEntry
+//-------------------------------------------------------------------------------------------------
+
+/// The form an entry of an encrypted entry type (see Sharing::Encrypted) gets published and
+/// held in the DHT in.
+///
+/// The serialized entry is encrypted with a random entry key. That key is sealed once for
+/// every recipient (always including the author) with the session key of a key exchange between
+/// the encryption keys of the author and that recipient, so only those agents can read the entry.
+///
+/// An encrypted entry is addressed like any other entry, by the hash of its content, i.e. of the
+/// cipher. It is what gets committed to the author's chain in place of the entry, so neither the
+/// header nor the DHT reveal the address of the entry it holds, which would let anybody confirm a
+/// guess of its content.
+/// All binary fields are base64 encoded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+pub struct EncryptedEntry {
+    entry_type: AppEntryType,
+    author: Address,
+    nonce: String,
+    cipher: String,
+    sealed_keys: Vec<SealedKey>,
+}
+
+impl EncryptedEntry {
+    pub fn new(
+        entry_type: AppEntryType,
+        author: Address,
+        nonce: String,
+        cipher: String,
+        sealed_keys: Vec<SealedKey>,
+    ) -> Self {
+        EncryptedEntry {
+            entry_type,
+            author,
+            nonce,
+            cipher,
+            sealed_keys,
+        }
+    }
+
+    pub fn entry_type(&self) -> &AppEntryType {
+        &self.entry_type
+    }
+
+    /// the agent that encrypted the entry, whose encryption key recipients need
+    /// to open their sealed key
+    pub fn author(&self) -> &Address {
+        &self.author
+    }
+
+    pub fn nonce(&self) -> &str {
+        &self.nonce
+    }
+
+    pub fn cipher(&self) -> &str {
+        &self.cipher
+    }
+
+    pub fn sealed_keys(&self) -> &Vec<SealedKey> {
+        &self.sealed_keys
+    }
+
+    /// the entry key sealed for the given agent, if it is one of the recipients
+    pub fn sealed_key_for(&self, recipient: &Address) -> Option<&SealedKey> {
+        self.sealed_keys
+            .iter()
+            .find(|sealed_key| sealed_key.recipient == *recipient)
+    }
+
+    /// addresses of all agents that can decrypt the entry
+    pub fn recipients(&self) -> Vec<Address> {
+        self.sealed_keys
+            .iter()
+            .map(|sealed_key| sealed_key.recipient.clone())
+            .collect()
+    }
+}
+
+/// The entry key of an EncryptedEntry, encrypted for one of its recipients
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SealedKey {
+    pub recipient: Address,
+    pub nonce: String,
+    pub key: String,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        cas::content::AddressableContent,
+        entry::{entry_type::test_app_entry_type, test_entry, Entry},
+    };
+
+    pub fn test_encrypted_entry() -> EncryptedEntry {
+        EncryptedEntry::new(
+            test_app_entry_type(),
+            Address::from("alice"),
+            String::from("nonce"),
+            String::from("cipher"),
+            vec![
+                SealedKey {
+                    recipient: Address::from("alice"),
+                    nonce: String::from("alice nonce"),
+                    key: String::from("alice key"),
+                },
+                SealedKey {
+                    recipient: Address::from("bob"),
+                    nonce: String::from("bob nonce"),
+                    key: String::from("bob key"),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn encrypted_entry_smoke_test() {
+        let encrypted_entry = test_encrypted_entry();
+        assert_eq!(
+            encrypted_entry.recipients(),
+            vec![Address::from("alice"), Address::from("bob")]
+        );
+        assert_eq!(
+            encrypted_entry
+                .sealed_key_for(&Address::from("bob"))
+                .map(|sealed_key| sealed_key.key.clone()),
+            Some(String::from("bob key"))
+        );
+        assert_eq!(encrypted_entry.sealed_key_for(&Address::from("eve")), None);
+    }
+
+    #[test]
+    fn encrypted_entry_is_addressed_by_its_content() {
+        let entry = Entry::Encrypted(test_encrypted_entry());
+        assert_ne!(entry.address(), test_entry().address());
+        assert_eq!(
+            entry.address(),
+            Address::encode_from_str(&String::from(entry.content()), multihash::Hash::SHA2256)
+        );
+        assert_eq!(entry.entry_type(), test_entry().entry_type());
+
+        let round_tripped = Entry::try_from_content(&entry.content()).unwrap();
+        match round_tripped {
+            Entry::Encrypted(encrypted_entry) => {
+                assert_eq!(encrypted_entry, test_encrypted_entry())
+            }
+            _ => panic!("expected an encrypted entry"),
+        }
+    }
+}
@@ -90,26 +90,9 @@ impl EntryType {
         !self.is_app()
     }
 
+    /// Whether entries of this type can be published at all. App entry types are always
+    /// allowed here as only the DNA knows how they are shared, see Dna::can_publish_entry_type
     pub fn can_publish(&self) -> bool {
-        /*
-                let dna = context
-            .state()
-            .expect("context must have a State.")
-            .nucleus()
-            .dna()
-            .expect("context.state must hold DNA in order to commit an app entry.");
-        let maybe_def = dna.get_entry_type_def(&entry.entry_type().to_string());
-        if maybe_def.is_none() {
-            // TODO #439 - Log the error. Once we have better logging.
-            return None;
-        }
-        let entry_type_def = maybe_def.unwrap();
-
-        // app entry type must be publishable
-        if !entry_type_def.sharing.clone().can_publish() {
-            return None;
-        }
-            */
         match self {
             EntryType::Dna => false,
             EntryType::CapTokenGrant => false,
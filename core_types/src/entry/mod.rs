@@ -5,11 +5,13 @@
 
 pub mod cap_entries;
 pub mod deletion_entry;
+pub mod encrypted_entry;
 pub mod entry_type;
 
 use self::{
     cap_entries::{CapToken, CapTokenGrant},
     deletion_entry::DeletionEntry,
+    encrypted_entry::EncryptedEntry,
 };
 use agent::{test_agent_id, AgentId};
 use cas::content::{Address, AddressableContent, Content};
@@ -74,6 +76,10 @@ pub enum Entry {
     ChainMigrate(ChainMigrate),
     CapToken(CapToken),
     CapTokenGrant(CapTokenGrant),
+
+    /// An app entry of an encrypted entry type as it is committed and shared through the DHT,
+    /// standing in for the entry it holds
+    Encrypted(EncryptedEntry),
 }
 
 impl From<Option<Entry>> for JsonString {
@@ -103,6 +109,9 @@ impl Entry {
             Entry::ChainMigrate(_) => EntryType::ChainMigrate,
             Entry::CapToken(_) => EntryType::CapToken,
             Entry::CapTokenGrant(_) => EntryType::CapTokenGrant,
+            Entry::Encrypted(encrypted_entry) => {
+                EntryType::App(encrypted_entry.entry_type().to_owned())
+            }
         }
     }
 }
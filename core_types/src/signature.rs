@@ -5,7 +5,7 @@
 /// Signature is meant in the classic cryptographic sense,
 /// as a string which can be validated as having been signed
 /// by the private key associated with a given public key
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Signature(String);
 
 impl From<&'static str> for Signature {
@@ -14,6 +14,18 @@ impl From<&'static str> for Signature {
     }
 }
 
+impl From<String> for Signature {
+    fn from(s: String) -> Signature {
+        Signature(s)
+    }
+}
+
+impl From<Signature> for String {
+    fn from(s: Signature) -> String {
+        s.0
+    }
+}
+
 pub fn test_signatures() -> Vec<Signature> {
     vec![Signature::from("fake-signature")]
 }
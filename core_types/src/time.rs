@@ -2,18 +2,220 @@
 //! within ChainHeader to enforce that their timestamps
 //! are defined in a useful and consistent way.
 
-/// This struct represents datetime data stored as a string
-/// in the ISO 8601 format.
+use crate::error::HolochainError;
+use chrono::{DateTime, FixedOffset, Offset, TimeZone};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// This struct represents a point in time that gets stored as a string
+/// in the ISO 8601 (RFC 3339) format.
 /// More info on the relevant [wikipedia article](https://en.wikipedia.org/wiki/ISO_8601).
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub struct Iso8601(String);
+/// Only valid timestamps can be constructed. They keep the string they got parsed from
+/// and get serialized verbatim, as it's the string that ends up in the addresses of
+/// chain headers. They order by the instant they represent, but are only equal if they
+/// are also written the same way.
+///
+/// Chain headers used to be stored with an empty timestamp. Such an empty timestamp still
+/// deserializes and gets serialized as it was, so the addresses of old headers don't change,
+/// and it orders before all other timestamps.
+#[derive(Clone, Debug)]
+pub struct Iso8601 {
+    text: String,
+    /// None for legacy empty timestamps
+    date_time: Option<DateTime<FixedOffset>>,
+}
+
+impl fmt::Display for Iso8601 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl PartialEq for Iso8601 {
+    fn eq(&self, other: &Iso8601) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for Iso8601 {}
+
+impl PartialOrd for Iso8601 {
+    fn partial_cmp(&self, other: &Iso8601) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Iso8601 {
+    /// By instant first, timestamps of the same instant that are written differently
+    /// by their string so the order agrees with equality
+    fn cmp(&self, other: &Iso8601) -> Ordering {
+        self.date_time
+            .cmp(&other.date_time)
+            .then_with(|| self.text.cmp(&other.text))
+    }
+}
+
+impl Hash for Iso8601 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text.hash(state)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for Iso8601 {
+    fn from(date_time: DateTime<Tz>) -> Iso8601 {
+        let date_time = date_time.with_timezone(&date_time.offset().fix());
+        Iso8601 {
+            text: date_time.to_rfc3339(),
+            date_time: Some(date_time),
+        }
+    }
+}
+
+impl TryFrom<Iso8601> for DateTime<FixedOffset> {
+    type Error = HolochainError;
+    fn try_from(iso_8601: Iso8601) -> Result<DateTime<FixedOffset>, HolochainError> {
+        iso_8601.date_time.ok_or_else(|| {
+            HolochainError::ErrorGeneric(String::from("Empty timestamps have no date and time"))
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Iso8601 {
+    type Error = HolochainError;
+    fn try_from(s: &str) -> Result<Iso8601, HolochainError> {
+        Iso8601::try_from(String::from(s))
+    }
+}
 
-impl From<&'static str> for Iso8601 {
-    fn from(s: &str) -> Iso8601 {
-        Iso8601(s.to_owned())
+impl TryFrom<String> for Iso8601 {
+    type Error = HolochainError;
+    fn try_from(s: String) -> Result<Iso8601, HolochainError> {
+        match DateTime::parse_from_rfc3339(&s) {
+            Ok(date_time) => Ok(Iso8601 {
+                text: s,
+                date_time: Some(date_time),
+            }),
+            Err(error) => Err(HolochainError::ErrorGeneric(format!(
+                "Invalid ISO 8601 timestamp \"{}\": {}",
+                s, error
+            ))),
+        }
+    }
+}
+
+impl From<Iso8601> for String {
+    fn from(iso_8601: Iso8601) -> String {
+        iso_8601.text
+    }
+}
+
+impl Serialize for Iso8601 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Iso8601 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Iso8601, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.is_empty() {
+            return Ok(Iso8601 {
+                text: s,
+                date_time: None,
+            });
+        }
+        Iso8601::try_from(s).map_err(de::Error::custom)
     }
 }
 
 pub fn test_iso_8601() -> Iso8601 {
-    Iso8601::from("2018-10-11T03:23:38+00:00")
+    Iso8601::try_from("2018-10-11T03:23:38+00:00").expect("test timestamp is valid")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json;
+
+    #[test]
+    fn iso_8601_round_trips_through_strings_and_json() {
+        let timestamp = test_iso_8601();
+        assert_eq!(timestamp.to_string(), "2018-10-11T03:23:38+00:00");
+        assert_eq!(
+            Iso8601::try_from(String::from(timestamp.clone())),
+            Ok(timestamp.clone())
+        );
+
+        let json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(json, "\"2018-10-11T03:23:38+00:00\"");
+        assert_eq!(serde_json::from_str::<Iso8601>(&json).unwrap(), timestamp);
+    }
+
+    #[test]
+    fn iso_8601_rejects_invalid_timestamps() {
+        assert!(Iso8601::try_from("").is_err());
+        assert!(Iso8601::try_from("2018-10-11").is_err());
+        assert!(Iso8601::try_from("2018-13-11T03:23:38+00:00").is_err());
+        assert!(serde_json::from_str::<Iso8601>("\"yesterday\"").is_err());
+    }
+
+    #[test]
+    fn iso_8601_orders_by_instant() {
+        let earlier = test_iso_8601();
+        let later = Iso8601::try_from("2018-10-11T03:23:39+00:00").unwrap();
+        let same_instant = Iso8601::try_from("2018-10-11T05:23:38+02:00").unwrap();
+        assert!(earlier < later);
+        assert!(same_instant < later);
+        assert_eq!(same_instant.to_string(), "2018-10-11T05:23:38+02:00");
+    }
+
+    #[test]
+    fn iso_8601_is_only_equal_if_written_the_same_way() {
+        let same_instant = Iso8601::try_from("2018-10-11T05:23:38+02:00").unwrap();
+        assert_ne!(test_iso_8601(), same_instant);
+        assert_ne!(test_iso_8601().cmp(&same_instant), Ordering::Equal);
+        let zulu = Iso8601::try_from("2018-10-11T03:23:38Z").unwrap();
+        assert_ne!(test_iso_8601(), zulu);
+        assert_eq!(test_iso_8601().cmp(&zulu), Ordering::Less);
+    }
+
+    #[test]
+    fn iso_8601_keeps_the_way_it_is_written() {
+        for text in &[
+            "2018-10-11T03:23:38Z",
+            "2018-10-11T03:23:38.5+00:00",
+            "2018-10-11T03:23:38.500000000+02:00",
+        ] {
+            let json = format!("\"{}\"", text);
+            let timestamp: Iso8601 = serde_json::from_str(&json).unwrap();
+            assert_eq!(timestamp.to_string(), *text);
+            assert_eq!(serde_json::to_string(&timestamp).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn iso_8601_keeps_legacy_empty_timestamps() {
+        let legacy: Iso8601 = serde_json::from_str("\"\"").unwrap();
+        assert_eq!(serde_json::to_string(&legacy).unwrap(), "\"\"");
+        assert_eq!(legacy.to_string(), "");
+        assert!(legacy < test_iso_8601());
+        assert!(DateTime::<FixedOffset>::try_from(legacy).is_err());
+    }
+
+    #[test]
+    fn iso_8601_converts_from_and_to_chrono() {
+        let date_time = Utc.ymd(2018, 10, 11).and_hms(3, 23, 38);
+        let timestamp = Iso8601::from(date_time);
+        assert_eq!(timestamp, test_iso_8601());
+        assert_eq!(
+            DateTime::<FixedOffset>::try_from(timestamp).unwrap(),
+            date_time
+        );
+    }
 }
@@ -15,6 +15,9 @@ use holochain_core_types::{
 pub use holochain_wasm_utils::api_serialization::validation::*;
 use holochain_wasm_utils::{
     api_serialization::{
+        capabilities::GrantCapabilityArgs,
+        commit_entry::CommitEntryArgs,
+        emit_signal::EmitSignalArgs,
         get_entry::{
             EntryHistory, GetEntryArgs, GetEntryOptions, GetEntryResult, GetEntryResultType,
             StatusRequestKind,
@@ -480,6 +483,59 @@ pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
     }
 }
 
+/// Commits an entry of an encrypted entry type (`"sharing": "encrypted"` in the DNA) that can be
+/// read by the given recipients as well.
+/// Entries of encrypted entry types only ever reach the DHT encrypted. Committed with
+/// [commit_entry](fn.commit_entry.html) only their author can decrypt them, here every agent in
+/// `recipients` can too. Other agents can't see the entry's content when they get it.
+/// The entry is committed encrypted as well, so the returned address is the one of the encrypted
+/// entry, which is what recipients get it by.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use hdk::error::ZomeApiResult;
+/// # use holochain_core_types::{cas::content::Address, entry::Entry, json::RawString};
+///
+/// # #[no_mangle]
+/// # pub fn hc_commit_entry(_: u32) -> u32 { 0 }
+///
+/// # fn main() {
+/// fn handle_send_secret(secret: String, friend: Address) -> ZomeApiResult<Address> {
+///     let entry = Entry::App("secret".into(), RawString::from(secret).into());
+///     hdk::commit_encrypted_entry(&entry, vec![friend])
+/// }
+/// # }
+/// ```
+pub fn commit_encrypted_entry(entry: &Entry, recipients: Vec<Address>) -> ZomeApiResult<Address> {
+    let mut mem_stack: SinglePageStack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        CommitEntryArgs {
+            entry: entry.clone(),
+            recipients,
+        },
+    )?;
+
+    let encoded_allocation_of_result: u32 =
+        unsafe { hc_commit_entry(allocation_of_input.encode() as u32) };
+
+    // Deserialize complex result stored in memory
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
 /// Retrieves latest version of an entry from the local chain or the DHT, by looking it up using
 /// the specified address.
 /// Returns None if no entry exists at the specified address or
@@ -663,13 +719,102 @@ pub fn link_entries<S: Into<String>>(
     }
 }
 
-/// NOT YET AVAILABLE
-// Returns a DNA property, which are defined by the DNA developer.
-// They are custom values that are defined in the DNA file
-// that can be used in the zome code for defining configurable behaviors.
-// (e.g. Name, Language, Description, Author, etc.).
-pub fn property<S: Into<String>>(_name: S) -> ZomeApiResult<String> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Returns a DNA property, which are defined by the DNA developer.
+/// They are custom values that are defined in the DNA file
+/// that can be used in the zome code for defining configurable behaviors.
+/// (e.g. Name, Language, Description, Author, etc.).
+/// Nested properties are addressed by a dotted path like `"limits.max_posts"`,
+/// array elements by their index like `"admins.0"`.
+/// Containers can override properties per instance, the returned value includes
+/// those overrides, except in validation callbacks. Those only get the DNA's own properties,
+/// as every node of the DHT has to validate the same way, whatever its local settings are.
+/// # Examples
+/// ```rust
+/// # #![feature(try_from)]
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use hdk::error::ZomeApiResult;
+/// # use std::convert::TryInto;
+/// # fn main() {
+/// pub fn handle_max_posts() -> ZomeApiResult<u32> {
+///     let max_posts: u32 = hdk::property("limits.max_posts")?.try_into()?;
+///     Ok(max_posts)
+/// }
+/// # }
+/// ```
+pub fn property<S: Into<String>>(name: S) -> ZomeApiResult<JsonString> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        JsonString::from(RawString::from(name.into())),
+    )?;
+
+    let encoded_allocation_of_result: u32 =
+        unsafe { hc_property(allocation_of_input.encode() as u32) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value))
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Sends a signal with the given name and JSON arguments to the clients of this instance,
+/// e.g. to tell a UI about new data instead of having it poll zome functions.
+/// Containers push these signals as notifications to the websocket interfaces that expose
+/// the instance.
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_json;
+/// # extern crate hdk;
+/// # use hdk::error::ZomeApiResult;
+/// # fn main() {
+/// fn handle_create_post(title: String) -> ZomeApiResult<()> {
+///     // ...commit the post...
+///     hdk::emit_signal("post_created", json!({ "title": title }))
+/// }
+/// # }
+/// ```
+pub fn emit_signal<S: Into<String>, J: Into<JsonString>>(
+    name: S,
+    arguments: J,
+) -> ZomeApiResult<()> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        EmitSignalArgs {
+            name: name.into(),
+            arguments: String::from(arguments.into()),
+        },
+    )?;
+
+    let encoded_allocation_of_result: u32 =
+        unsafe { hc_emit_signal(allocation_of_input.encode() as u32) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(())
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 /// Reconstructs an address of the given entry data.
@@ -1085,6 +1230,82 @@ pub fn send(to_agent: Address, payload: String) -> ZomeApiResult<String> {
     }
 }
 
+/// Grants access to the functions of a capability of this zome by committing a
+/// capability token grant to your local source chain. Returns the token, i.e. the address
+/// of the grant, which callers have to pass along with their calls.
+/// For transferable capabilities anybody holding the token can use it and `assignees` must
+/// be empty. For assigned capabilities only the given agents can use it.
+/// Public capabilities can be called without a token, so they can't be granted.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use hdk::error::ZomeApiResult;
+/// # use holochain_core_types::cas::content::Address;
+///
+/// # fn main() {
+/// fn handle_share_notes(friend: Address) -> ZomeApiResult<Address> {
+///     hdk::grant_capability("notes", vec![friend])
+/// }
+/// # }
+/// ```
+pub fn grant_capability<S: Into<String>>(
+    cap_name: S,
+    assignees: Vec<Address>,
+) -> ZomeApiResult<Address> {
+    let mut mem_stack: SinglePageStack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        GrantCapabilityArgs {
+            cap_name: cap_name.into(),
+            assignees,
+        },
+    )?;
+
+    let encoded_allocation_of_result: u32 =
+        unsafe { hc_grant_capability(allocation_of_input.encode() as u32) };
+
+    // Deserialize complex result stored in memory
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Revokes a capability token returned by [grant_capability](fn.grant_capability.html).
+/// Calls carrying the token fail from then on.
+pub fn revoke_capability(token: Address) -> ZomeApiResult<()> {
+    let mut mem_stack: SinglePageStack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(&mut mem_stack, token)?;
+
+    let encoded_allocation_of_result: u32 =
+        unsafe { hc_revoke_capability(allocation_of_input.encode() as u32) };
+
+    // Deserialize complex result stored in memory
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(())
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
 /// NOT YET AVAILABLE
 pub fn start_bundle(_timeout: usize, _user_param: serde_json::Value) -> ZomeApiResult<()> {
     Err(ZomeApiError::FunctionNotImplemented)
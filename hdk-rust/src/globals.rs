@@ -17,6 +17,7 @@ lazy_static! {
 #[allow(dead_code)]
 extern "C" {
     pub(crate) fn hc_property(encoded_allocation_of_input: u32) -> u32;
+    pub(crate) fn hc_emit_signal(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_entry_address(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_debug(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_call(encoded_allocation_of_input: u32) -> u32;
@@ -30,6 +31,8 @@ extern "C" {
     pub(crate) fn hc_get_links(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_query(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_send(encoded_allocation_of_input: u32) -> u32;
+    pub(crate) fn hc_grant_capability(encoded_allocation_of_input: u32) -> u32;
+    pub(crate) fn hc_revoke_capability(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_start_bundle(encoded_allocation_of_input: u32) -> u32;
     pub(crate) fn hc_close_bundle(encoded_allocation_of_input: u32) -> u32;
 }
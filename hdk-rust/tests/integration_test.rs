@@ -16,7 +16,7 @@ extern crate holochain_core_types_derive;
 use hdk::error::{ZomeApiError, ZomeApiResult};
 use holochain_container_api::{error::HolochainResult, *};
 use holochain_core_types::{
-    cas::content::Address,
+    cas::content::{Address, AddressableContent},
     crud_status::CrudStatus,
     dna::{
         capabilities::{Capability, CapabilityCall, CapabilityType, FnDeclaration},
@@ -27,7 +27,6 @@ use holochain_core_types::{
         Entry, EntryWithMeta,
     },
     error::{CoreError, HolochainError},
-    hash::HashString,
     json::JsonString,
 };
 use holochain_wasm_utils::{
@@ -232,9 +231,7 @@ fn can_use_globals() {
     let result = make_test_call(&mut hc, "check_global", r#"{}"#);
     assert_eq!(
         result.clone(),
-        Ok(JsonString::from(HashString::from(
-            "alice-----------------------------------------------------------------------------AAAIuDJb4M"
-        ))),
+        Ok(JsonString::from(hc.context().agent_id.address())),
         "result = {:?}",
         result
     );
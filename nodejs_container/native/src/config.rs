@@ -2,7 +2,7 @@ use holochain_container_api::config::{
     AgentConfiguration, Configuration, DnaConfiguration, InstanceConfiguration,
     LoggerConfiguration, StorageConfiguration,
 };
-use holochain_core_types::agent::AgentId;
+use holochain_core::agent::keystore::Keystore;
 use holochain_net::p2p_config::P2pConfig;
 use neon::prelude::*;
 use std::{collections::HashMap, path::PathBuf};
@@ -42,22 +42,20 @@ fn make_config(instance_data: Vec<InstanceData>) -> Configuration {
         let agent_name = instance.agent.name;
         let mut dna_data = instance.dna;
         let agent_config = agent_configs.entry(agent_name.clone()).or_insert_with(|| {
-            let agent_key = AgentId::generate_fake(&agent_name);
+            let keystore = Keystore::generate_fake(&agent_name);
             AgentConfiguration {
                 id: agent_name.clone(),
                 name: agent_name.clone(),
-                public_address: agent_key.key,
+                public_address: keystore.address().to_string(),
                 key_file: format!("fake/key/{}", agent_name),
+                test_agent: Some(true),
             }
         });
         let dna_config = dna_configs
             .entry(dna_data.path.clone())
             .or_insert_with(|| make_dna_config(dna_data).expect("DNA file not found"));
 
-        let logger_mock = LoggerConfiguration {
-            logger_type: String::from("DONTCARE"),
-            file: None,
-        };
+        let logger_mock = LoggerConfiguration::default();
         let network_mock = Some(P2pConfig::DEFAULT_MOCK_CONFIG.to_string());
         let agent_id = agent_config.id.clone();
         let dna_id = dna_config.id.clone();
@@ -68,6 +66,9 @@ fn make_config(instance_data: Vec<InstanceData>) -> Configuration {
             storage: StorageConfiguration::Memory,
             logger: logger_mock,
             network: network_mock,
+            network_timeouts: Default::default(),
+            result_retention: Default::default(),
+            dna_properties: None,
         };
         instance_configs.push(instance);
     }
@@ -93,9 +94,10 @@ fn make_dna_config(dna: DnaData) -> Result<DnaConfiguration, String> {
         id: path.clone(),
         hash: String::from("DONTCARE"),
         file: path,
+        skip_hash_check: Some(true),
     })
     // eventually can get actual file content to calculate hash and stuff,
-    // but for now it doesn't matter so don't care...
+    // but for now it doesn't matter so the check is skipped...
 
     // let temp = DnaConfiguration {id: "", hash: "", file: dna_path};
     // let dna = Dna::try_from(temp).map_err(|e| e.to_string())?;
@@ -3,10 +3,9 @@ use holochain_container_api::{
     container::Container as RustContainer,
 };
 use holochain_core::{
-    logger::Logger,
+    logger::{LogLevel, Logger},
     signal::{signal_channel, SignalReceiver},
 };
-use holochain_core_types::{cas::content::Address, dna::capabilities::CapabilityCall};
 use neon::{context::Context, prelude::*};
 
 use crate::config::*;
@@ -15,7 +14,7 @@ use crate::config::*;
 struct NullLogger {}
 
 impl Logger for NullLogger {
-    fn log(&mut self, _msg: String) {}
+    fn log(&mut self, _level: LogLevel, _msg: String) {}
 }
 
 pub struct NodeContainer {
@@ -91,15 +90,12 @@ declare_types! {
             let call_result = {
                 let guard = cx.lock();
                 let hab = &mut *this.borrow_mut(&guard);
-                let cap = Some(CapabilityCall::new(
-                    cap_name.to_string(),
-                    Address::from(""), //FIXME
-                    None,
-                ));
-                let instance_arc = hab.container.instances().get(&instance_id)
+                let instance_arc = hab.container.instances().get(&instance_id).cloned()
                     .expect(&format!("No instance with id: {}", instance_id));
                 let mut instance = instance_arc.write().unwrap();
-                instance.call(&zome, cap, &fn_name, &params)
+                // the tests embedding the container act as the instance's own agent,
+                // which may call any of its functions
+                instance.call_as_agent(&zome, &cap_name, &fn_name, &params)
             };
 
             let res_string = call_result.or_else(|e| {
@@ -117,9 +113,9 @@ declare_types! {
             let result = {
                 let guard = cx.lock();
                 let hab = this.borrow(&guard);
-                let instance = hab.container.instances().get(&instance_id)
-                    .expect(&format!("No instance with id: {}", instance_id))
-                    .read().unwrap();
+                let instance_arc = hab.container.instances().get(&instance_id).cloned()
+                    .expect(&format!("No instance with id: {}", instance_id));
+                let instance = instance_arc.read().unwrap();
                 let out = instance.context().state().ok_or("No state?".to_string())
                     .and_then(|state| state
                         .agent().get_agent_address()
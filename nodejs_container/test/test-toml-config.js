@@ -7,18 +7,19 @@ const toml = `
 id = "test/agent/1"
 name = "Holo Tester 1"
 key_file = "holo_tester.key"
-public_address = "sandwich--------------------------------------------------------------------------AAAEqzh28L"
+test_agent = true
 
 [[agents]]
 id = "test/agent/2"
 name = "Holo Tester 2"
 key_file = "holo_tester.key"
-public_address = "sandwich--------------------------------------------------------------------------AAAEqzh28L"
+test_agent = true
 
 [[dnas]]
 id = "test/dna"
 file = "../app_spec/dist/app_spec.hcpkg"
 hash = "Qm328wyq38924y"
+skip_hash_check = true
 
 [[instances]]
 id = "test/instance/1"
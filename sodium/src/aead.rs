@@ -0,0 +1,176 @@
+//! This module provides access to libsodium authenticated encryption
+//! (xchacha20poly1305 ietf)
+
+use super::check_init;
+
+use super::{error::SodiumError, secbuf::SecBuf};
+
+pub const NONCEBYTES: usize =
+    rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_NPUBBYTES as usize;
+pub const ABYTES: usize = rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_ABYTES as usize;
+pub const KEYBYTES: usize = rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_KEYBYTES as usize;
+
+/// encrypt a message
+/// @param {SecBuf} message - the data to encrypt
+/// @param {SecBuf} secret - the KEYBYTES symmetric key
+/// @param {Option<SecBuf>} adata - additional data that gets authenticated but not encrypted
+/// @param {SecBuf} nonce - NONCEBYTES of random data, never reuse with the same key
+/// @param {SecBuf} cipher - Empty Buffer of message length + ABYTES to be used as cipher return
+pub fn enc(
+    message: &mut SecBuf,
+    secret: &mut SecBuf,
+    adata: Option<&mut SecBuf>,
+    nonce: &mut SecBuf,
+    cipher: &mut SecBuf,
+) -> Result<(), SodiumError> {
+    check_init();
+    let message = message.read_lock();
+    let secret = secret.read_lock();
+    let nonce = nonce.read_lock();
+    let mut cipher = cipher.write_lock();
+    if cipher.len() != message.len() + ABYTES {
+        return Err(SodiumError::new("aead cipher buffer has the wrong length"));
+    }
+    let adata = adata.map(|adata| adata.read_lock());
+    let (adata_ptr, adata_len) = match adata {
+        Some(ref adata) => (adata.as_ptr(), adata.len()),
+        None => (std::ptr::null(), 0),
+    };
+    unsafe {
+        rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_encrypt(
+            cipher.as_mut_ptr(),
+            std::ptr::null_mut(),
+            message.as_ptr(),
+            message.len() as libc::c_ulonglong,
+            adata_ptr,
+            adata_len as libc::c_ulonglong,
+            std::ptr::null(),
+            nonce.as_ptr(),
+            secret.as_ptr(),
+        );
+    }
+    Ok(())
+}
+
+/// decrypt a cipher, failing if it (or the additional data) has been tampered with
+/// or the key is wrong
+/// @param {SecBuf} decrypted - Empty Buffer of cipher length - ABYTES to be used as message return
+/// @param {SecBuf} secret - the KEYBYTES symmetric key
+/// @param {Option<SecBuf>} adata - the additional data given to enc()
+/// @param {SecBuf} nonce - the nonce given to enc()
+/// @param {SecBuf} cipher - the encrypted data
+pub fn dec(
+    decrypted: &mut SecBuf,
+    secret: &mut SecBuf,
+    adata: Option<&mut SecBuf>,
+    nonce: &mut SecBuf,
+    cipher: &mut SecBuf,
+) -> Result<(), SodiumError> {
+    check_init();
+    let secret = secret.read_lock();
+    let nonce = nonce.read_lock();
+    let cipher = cipher.read_lock();
+    let mut decrypted = decrypted.write_lock();
+    if cipher.len() != decrypted.len() + ABYTES {
+        return Err(SodiumError::new("aead message buffer has the wrong length"));
+    }
+    let adata = adata.map(|adata| adata.read_lock());
+    let (adata_ptr, adata_len) = match adata {
+        Some(ref adata) => (adata.as_ptr(), adata.len()),
+        None => (std::ptr::null(), 0),
+    };
+    let result = unsafe {
+        rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_decrypt(
+            decrypted.as_mut_ptr(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            cipher.as_ptr(),
+            cipher.len() as libc::c_ulonglong,
+            adata_ptr,
+            adata_len as libc::c_ulonglong,
+            nonce.as_ptr(),
+            secret.as_ptr(),
+        )
+    };
+    match result {
+        0 => Ok(()),
+        _ => Err(SodiumError::new("aead decryption failed")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+
+    fn test_buf(content: &[u8]) -> SecBuf {
+        let mut buf = SecBuf::with_insecure(content.len());
+        buf.write_lock().copy_from_slice(content);
+        buf
+    }
+
+    #[test]
+    fn it_should_round_trip() {
+        let mut message = test_buf(b"hello");
+        let mut secret = SecBuf::with_secure(KEYBYTES);
+        random::buf(&mut secret);
+        let mut nonce = SecBuf::with_insecure(NONCEBYTES);
+        random::buf(&mut nonce);
+        let mut adata = test_buf(b"context");
+        let mut cipher = SecBuf::with_insecure(5 + ABYTES);
+
+        enc(
+            &mut message,
+            &mut secret,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .unwrap();
+
+        let mut decrypted = SecBuf::with_insecure(5);
+        dec(
+            &mut decrypted,
+            &mut secret,
+            Some(&mut adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .unwrap();
+        let decrypted = decrypted.read_lock();
+        assert_eq!(b"hello", &**decrypted);
+    }
+
+    #[test]
+    fn it_should_fail_with_wrong_key_or_adata() {
+        let mut message = test_buf(b"hello");
+        let mut secret = SecBuf::with_secure(KEYBYTES);
+        random::buf(&mut secret);
+        let mut nonce = SecBuf::with_insecure(NONCEBYTES);
+        random::buf(&mut nonce);
+        let mut cipher = SecBuf::with_insecure(5 + ABYTES);
+        enc(&mut message, &mut secret, None, &mut nonce, &mut cipher).unwrap();
+
+        let mut decrypted = SecBuf::with_insecure(5);
+        let mut other_adata = test_buf(b"other");
+        assert!(dec(
+            &mut decrypted,
+            &mut secret,
+            Some(&mut other_adata),
+            &mut nonce,
+            &mut cipher,
+        )
+        .is_err());
+
+        let mut other_secret = SecBuf::with_secure(KEYBYTES);
+        random::buf(&mut other_secret);
+        assert!(dec(
+            &mut decrypted,
+            &mut other_secret,
+            None,
+            &mut nonce,
+            &mut cipher,
+        )
+        .is_err());
+    }
+}
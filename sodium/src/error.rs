@@ -0,0 +1,22 @@
+//! This module provides the error type returned by fallible libsodium functions
+
+use std::fmt;
+
+/// libsodium reports failures only as a non-zero return code,
+/// so all we can pass on is a description of what failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SodiumError(String);
+
+impl SodiumError {
+    pub fn new(msg: &str) -> Self {
+        SodiumError(msg.to_string())
+    }
+}
+
+impl fmt::Display for SodiumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SodiumError {}
@@ -0,0 +1,162 @@
+//! This module provides access to libsodium x25519 key exchange keypairs
+
+use super::check_init;
+
+use super::{error::SodiumError, secbuf::SecBuf};
+
+pub const SEEDBYTES: usize = rust_sodium_sys::crypto_kx_SEEDBYTES as usize;
+pub const PUBLICKEYBYTES: usize = rust_sodium_sys::crypto_kx_PUBLICKEYBYTES as usize;
+pub const SECRETKEYBYTES: usize = rust_sodium_sys::crypto_kx_SECRETKEYBYTES as usize;
+pub const SESSIONKEYBYTES: usize = rust_sodium_sys::crypto_kx_SESSIONKEYBYTES as usize;
+
+/// derive an encryption (key exchange) keypair from the provided seed
+/// @param {SecBuf} public_key - Empty Buffer to be used as public key return
+/// @param {SecBuf} secret_key - Empty Buffer to be used as secret key return
+/// @param {SecBuf} seed - the seed to derive the keypair from
+pub fn seed_keypair(public_key: &mut SecBuf, secret_key: &mut SecBuf, seed: &mut SecBuf) {
+    check_init();
+    let seed = seed.read_lock();
+    let mut public_key = public_key.write_lock();
+    let mut secret_key = secret_key.write_lock();
+    unsafe {
+        rust_sodium_sys::crypto_kx_seed_keypair(
+            public_key.as_mut_ptr(),
+            secret_key.as_mut_ptr(),
+            seed.as_ptr(),
+        );
+    }
+}
+
+/// compute the session keys of the client side of a key exchange.
+/// The client's tx key equals the server's rx key and vice versa.
+/// @param {SecBuf} rx - Empty Buffer of SESSIONKEYBYTES to be used as receiving key return
+/// @param {SecBuf} tx - Empty Buffer of SESSIONKEYBYTES to be used as transmitting key return
+/// @param {SecBuf} client_public_key - the public key of the client
+/// @param {SecBuf} client_secret_key - the secret key of the client
+/// @param {SecBuf} server_public_key - the public key of the server
+pub fn client_session_keys(
+    rx: &mut SecBuf,
+    tx: &mut SecBuf,
+    client_public_key: &mut SecBuf,
+    client_secret_key: &mut SecBuf,
+    server_public_key: &mut SecBuf,
+) -> Result<(), SodiumError> {
+    check_init();
+    let client_public_key = client_public_key.read_lock();
+    let client_secret_key = client_secret_key.read_lock();
+    let server_public_key = server_public_key.read_lock();
+    let mut rx = rx.write_lock();
+    let mut tx = tx.write_lock();
+    let result = unsafe {
+        rust_sodium_sys::crypto_kx_client_session_keys(
+            rx.as_mut_ptr(),
+            tx.as_mut_ptr(),
+            client_public_key.as_ptr(),
+            client_secret_key.as_ptr(),
+            server_public_key.as_ptr(),
+        )
+    };
+    match result {
+        0 => Ok(()),
+        _ => Err(SodiumError::new("kx server public key is not acceptable")),
+    }
+}
+
+/// compute the session keys of the server side of a key exchange.
+/// The server's rx key equals the client's tx key and vice versa.
+/// @param {SecBuf} rx - Empty Buffer of SESSIONKEYBYTES to be used as receiving key return
+/// @param {SecBuf} tx - Empty Buffer of SESSIONKEYBYTES to be used as transmitting key return
+/// @param {SecBuf} server_public_key - the public key of the server
+/// @param {SecBuf} server_secret_key - the secret key of the server
+/// @param {SecBuf} client_public_key - the public key of the client
+pub fn server_session_keys(
+    rx: &mut SecBuf,
+    tx: &mut SecBuf,
+    server_public_key: &mut SecBuf,
+    server_secret_key: &mut SecBuf,
+    client_public_key: &mut SecBuf,
+) -> Result<(), SodiumError> {
+    check_init();
+    let server_public_key = server_public_key.read_lock();
+    let server_secret_key = server_secret_key.read_lock();
+    let client_public_key = client_public_key.read_lock();
+    let mut rx = rx.write_lock();
+    let mut tx = tx.write_lock();
+    let result = unsafe {
+        rust_sodium_sys::crypto_kx_server_session_keys(
+            rx.as_mut_ptr(),
+            tx.as_mut_ptr(),
+            server_public_key.as_ptr(),
+            server_secret_key.as_ptr(),
+            client_public_key.as_ptr(),
+        )
+    };
+    match result {
+        0 => Ok(()),
+        _ => Err(SodiumError::new("kx client public key is not acceptable")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+
+    #[test]
+    fn it_should_derive_different_keypairs_from_different_seeds() {
+        let mut seed_a = SecBuf::with_secure(SEEDBYTES);
+        let mut seed_b = SecBuf::with_secure(SEEDBYTES);
+        random::buf(&mut seed_a);
+        random::buf(&mut seed_b);
+        let mut public_key_a = SecBuf::with_insecure(PUBLICKEYBYTES);
+        let mut secret_key_a = SecBuf::with_secure(SECRETKEYBYTES);
+        let mut public_key_b = SecBuf::with_insecure(PUBLICKEYBYTES);
+        let mut secret_key_b = SecBuf::with_secure(SECRETKEYBYTES);
+
+        seed_keypair(&mut public_key_a, &mut secret_key_a, &mut seed_a);
+        seed_keypair(&mut public_key_b, &mut secret_key_b, &mut seed_b);
+
+        let public_key_a = public_key_a.read_lock();
+        let public_key_b = public_key_b.read_lock();
+        assert_ne!(&**public_key_a, &**public_key_b);
+    }
+
+    #[test]
+    fn it_should_agree_on_session_keys() {
+        let keypair = || {
+            let mut seed = SecBuf::with_secure(SEEDBYTES);
+            random::buf(&mut seed);
+            let mut public_key = SecBuf::with_insecure(PUBLICKEYBYTES);
+            let mut secret_key = SecBuf::with_secure(SECRETKEYBYTES);
+            seed_keypair(&mut public_key, &mut secret_key, &mut seed);
+            (public_key, secret_key)
+        };
+        let (mut client_pk, mut client_sk) = keypair();
+        let (mut server_pk, mut server_sk) = keypair();
+
+        let mut client_rx = SecBuf::with_secure(SESSIONKEYBYTES);
+        let mut client_tx = SecBuf::with_secure(SESSIONKEYBYTES);
+        client_session_keys(
+            &mut client_rx,
+            &mut client_tx,
+            &mut client_pk,
+            &mut client_sk,
+            &mut server_pk,
+        )
+        .unwrap();
+        let mut server_rx = SecBuf::with_secure(SESSIONKEYBYTES);
+        let mut server_tx = SecBuf::with_secure(SESSIONKEYBYTES);
+        server_session_keys(
+            &mut server_rx,
+            &mut server_tx,
+            &mut server_pk,
+            &mut server_sk,
+            &mut client_pk,
+        )
+        .unwrap();
+
+        assert_eq!(&**client_tx.read_lock(), &**server_rx.read_lock());
+        assert_eq!(&**client_rx.read_lock(), &**server_tx.read_lock());
+        assert_ne!(&**client_tx.read_lock(), &**client_rx.read_lock());
+    }
+}
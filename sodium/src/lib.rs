@@ -25,6 +25,11 @@ macro_rules! rptr {
     };
 }
 
+pub mod aead;
+pub mod error;
+pub mod kx;
+pub mod pwhash;
 pub mod random;
 pub mod secbuf;
+pub mod sign;
 pub mod util;
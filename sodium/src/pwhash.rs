@@ -0,0 +1,104 @@
+//! This module provides access to libsodium password hashing (argon2id)
+
+use super::check_init;
+
+use super::{error::SodiumError, secbuf::SecBuf};
+
+pub const SALTBYTES: usize = rust_sodium_sys::crypto_pwhash_SALTBYTES as usize;
+pub const OPSLIMIT_INTERACTIVE: u64 = rust_sodium_sys::crypto_pwhash_OPSLIMIT_INTERACTIVE as u64;
+pub const MEMLIMIT_INTERACTIVE: usize =
+    rust_sodium_sys::crypto_pwhash_MEMLIMIT_INTERACTIVE as usize;
+pub const OPSLIMIT_MODERATE: u64 = rust_sodium_sys::crypto_pwhash_OPSLIMIT_MODERATE as u64;
+pub const MEMLIMIT_MODERATE: usize = rust_sodium_sys::crypto_pwhash_MEMLIMIT_MODERATE as usize;
+const ALG_DEFAULT: libc::c_int = rust_sodium_sys::crypto_pwhash_ALG_DEFAULT as libc::c_int;
+
+/// derive a key from a password, filling the whole hash buffer
+/// @param {SecBuf} password - the password to hash
+/// @param {u64} ops_limit - computation cost, e.g. OPSLIMIT_INTERACTIVE
+/// @param {usize} mem_limit - memory cost in bytes, e.g. MEMLIMIT_INTERACTIVE
+/// @param {SecBuf} salt - SALTBYTES of random data
+/// @param {SecBuf} hash - Empty Buffer to be used as hash return
+pub fn hash(
+    password: &mut SecBuf,
+    ops_limit: u64,
+    mem_limit: usize,
+    salt: &mut SecBuf,
+    hash: &mut SecBuf,
+) -> Result<(), SodiumError> {
+    check_init();
+    let password = password.read_lock();
+    let salt = salt.read_lock();
+    if salt.len() != SALTBYTES {
+        return Err(SodiumError::new("pwhash salt has the wrong length"));
+    }
+    let mut hash = hash.write_lock();
+    let result = unsafe {
+        rust_sodium_sys::crypto_pwhash(
+            hash.as_mut_ptr(),
+            hash.len() as libc::c_ulonglong,
+            password.as_ptr() as *const libc::c_char,
+            password.len() as libc::c_ulonglong,
+            salt.as_ptr(),
+            ops_limit as libc::c_ulonglong,
+            mem_limit,
+            ALG_DEFAULT,
+        )
+    };
+    match result {
+        0 => Ok(()),
+        _ => Err(SodiumError::new("pwhash failed, out of memory?")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_password(content: &[u8]) -> SecBuf {
+        let mut password = SecBuf::with_insecure(content.len());
+        password.write_lock().copy_from_slice(content);
+        password
+    }
+
+    fn test_hash(password: &[u8], salt: &mut SecBuf) -> SecBuf {
+        let mut password = test_password(password);
+        let mut hash = SecBuf::with_secure(32);
+        super::hash(
+            &mut password,
+            OPSLIMIT_INTERACTIVE,
+            MEMLIMIT_INTERACTIVE,
+            salt,
+            &mut hash,
+        )
+        .unwrap();
+        hash
+    }
+
+    #[test]
+    fn it_should_derive_the_same_hash_from_the_same_password_and_salt() {
+        let mut salt = SecBuf::with_insecure(SALTBYTES);
+        let mut hash_a = test_hash(b"correct horse", &mut salt);
+        let mut hash_b = test_hash(b"correct horse", &mut salt);
+        let mut hash_c = test_hash(b"battery staple", &mut salt);
+        let hash_a = hash_a.read_lock();
+        let hash_b = hash_b.read_lock();
+        let hash_c = hash_c.read_lock();
+        assert_eq!(&**hash_a, &**hash_b);
+        assert_ne!(&**hash_a, &**hash_c);
+    }
+
+    #[test]
+    fn it_should_reject_bad_salts() {
+        let mut password = test_password(b"correct horse");
+        let mut salt = SecBuf::with_insecure(SALTBYTES + 1);
+        let mut hash = SecBuf::with_secure(32);
+        assert!(super::hash(
+            &mut password,
+            OPSLIMIT_INTERACTIVE,
+            MEMLIMIT_INTERACTIVE,
+            &mut salt,
+            &mut hash,
+        )
+        .is_err());
+    }
+}
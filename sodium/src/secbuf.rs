@@ -6,7 +6,8 @@ use std::ops::{Deref, DerefMut};
 use super::check_init;
 
 /// a trait for structures that can be used as a backing store for SecBuf
-trait Bufferable {
+/// (Send so that keys held in a SecBuf can be shared behind a Mutex)
+trait Bufferable: Send {
     fn new(s: usize) -> Box<Bufferable>
     where
         Self: Sized;
@@ -95,6 +96,10 @@ impl Bufferable for SodiumBuf {
     }
 }
 
+/// the memory behind the pointer is exclusively owned by this SodiumBuf
+/// and not tied to the thread that allocated it
+unsafe impl Send for SodiumBuf {}
+
 impl Drop for SodiumBuf {
     fn drop(&mut self) {
         unsafe {
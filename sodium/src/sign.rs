@@ -0,0 +1,141 @@
+//! This module provides access to libsodium ed25519 signatures
+
+use super::check_init;
+
+use super::secbuf::SecBuf;
+
+pub const SEEDBYTES: usize = rust_sodium_sys::crypto_sign_SEEDBYTES as usize;
+pub const PUBLICKEYBYTES: usize = rust_sodium_sys::crypto_sign_PUBLICKEYBYTES as usize;
+pub const SECRETKEYBYTES: usize = rust_sodium_sys::crypto_sign_SECRETKEYBYTES as usize;
+pub const BYTES: usize = rust_sodium_sys::crypto_sign_BYTES as usize;
+
+/// derive a signing keypair from the provided seed
+/// @param {SecBuf} public_key - Empty Buffer to be used as public key return
+/// @param {SecBuf} secret_key - Empty Buffer to be used as secret key return
+/// @param {SecBuf} seed - the seed to derive the keypair from
+pub fn seed_keypair(public_key: &mut SecBuf, secret_key: &mut SecBuf, seed: &mut SecBuf) {
+    check_init();
+    let seed = seed.read_lock();
+    let mut public_key = public_key.write_lock();
+    let mut secret_key = secret_key.write_lock();
+    unsafe {
+        rust_sodium_sys::crypto_sign_seed_keypair(
+            public_key.as_mut_ptr(),
+            secret_key.as_mut_ptr(),
+            seed.as_ptr(),
+        );
+    }
+}
+
+/// sign a message with the provided secret key
+/// @param {SecBuf} message - the message to sign
+/// @param {SecBuf} secret_key - the secret key to sign with
+/// @param {SecBuf} signature - Empty Buffer to be used as signature return
+pub fn sign(message: &mut SecBuf, secret_key: &mut SecBuf, signature: &mut SecBuf) {
+    check_init();
+    let message = message.read_lock();
+    let secret_key = secret_key.read_lock();
+    let mut signature = signature.write_lock();
+    unsafe {
+        rust_sodium_sys::crypto_sign_detached(
+            signature.as_mut_ptr(),
+            std::ptr::null_mut(),
+            message.as_ptr(),
+            message.len() as libc::c_ulonglong,
+            secret_key.as_ptr(),
+        );
+    }
+}
+
+/// verify a signature of a message against the provided public key
+/// @param {SecBuf} signature - the signature to verify
+/// @param {SecBuf} message - the message that was signed
+/// @param {SecBuf} public_key - the public key of the signer
+/// @return {bool} - true if the signature is valid
+pub fn verify(signature: &mut SecBuf, message: &mut SecBuf, public_key: &mut SecBuf) -> bool {
+    check_init();
+    let signature = signature.read_lock();
+    let message = message.read_lock();
+    let public_key = public_key.read_lock();
+    unsafe {
+        rust_sodium_sys::crypto_sign_verify_detached(
+            signature.as_ptr(),
+            message.as_ptr(),
+            message.len() as libc::c_ulonglong,
+            public_key.as_ptr(),
+        ) == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+
+    fn test_keypair() -> (SecBuf, SecBuf) {
+        let mut seed = SecBuf::with_secure(SEEDBYTES);
+        random::buf(&mut seed);
+        let mut public_key = SecBuf::with_insecure(PUBLICKEYBYTES);
+        let mut secret_key = SecBuf::with_secure(SECRETKEYBYTES);
+        seed_keypair(&mut public_key, &mut secret_key, &mut seed);
+        (public_key, secret_key)
+    }
+
+    fn test_message(content: &[u8]) -> SecBuf {
+        let mut message = SecBuf::with_insecure(content.len());
+        message.write_lock().copy_from_slice(content);
+        message
+    }
+
+    #[test]
+    fn it_should_sign_and_verify() {
+        let (mut public_key, mut secret_key) = test_keypair();
+        let mut message = test_message(b"hello");
+        let mut signature = SecBuf::with_insecure(BYTES);
+
+        sign(&mut message, &mut secret_key, &mut signature);
+
+        assert!(verify(&mut signature, &mut message, &mut public_key));
+    }
+
+    #[test]
+    fn it_should_not_verify_other_messages() {
+        let (mut public_key, mut secret_key) = test_keypair();
+        let mut message = test_message(b"hello");
+        let mut signature = SecBuf::with_insecure(BYTES);
+
+        sign(&mut message, &mut secret_key, &mut signature);
+
+        let mut other_message = test_message(b"hellO");
+        assert!(!verify(&mut signature, &mut other_message, &mut public_key));
+    }
+
+    #[test]
+    fn it_should_not_verify_with_other_keys() {
+        let (_, mut secret_key) = test_keypair();
+        let (mut other_public_key, _) = test_keypair();
+        let mut message = test_message(b"hello");
+        let mut signature = SecBuf::with_insecure(BYTES);
+
+        sign(&mut message, &mut secret_key, &mut signature);
+
+        assert!(!verify(&mut signature, &mut message, &mut other_public_key));
+    }
+
+    #[test]
+    fn it_should_derive_the_same_keypair_from_the_same_seed() {
+        let mut seed = SecBuf::with_secure(SEEDBYTES);
+        random::buf(&mut seed);
+        let mut public_key_a = SecBuf::with_insecure(PUBLICKEYBYTES);
+        let mut secret_key_a = SecBuf::with_secure(SECRETKEYBYTES);
+        let mut public_key_b = SecBuf::with_insecure(PUBLICKEYBYTES);
+        let mut secret_key_b = SecBuf::with_secure(SECRETKEYBYTES);
+
+        seed_keypair(&mut public_key_a, &mut secret_key_a, &mut seed);
+        seed_keypair(&mut public_key_b, &mut secret_key_b, &mut seed);
+
+        let public_key_a = public_key_a.read_lock();
+        let public_key_b = public_key_b.read_lock();
+        assert_eq!(&**public_key_a, &**public_key_b);
+    }
+}
@@ -7,7 +7,8 @@ extern crate serde_json;
 extern crate tempfile;
 
 use holochain_container_api::{context_builder::ContextBuilder, *};
-use holochain_core_types::{agent::AgentId, dna::Dna};
+use holochain_core::agent::keystore::Keystore;
+use holochain_core_types::dna::Dna;
 use std::{env, sync::Arc};
 
 use tempfile::tempdir;
@@ -36,9 +37,10 @@ fn main() {
     // let dna = holochain_core_types::dna::from_package_file("mydna.hcpkg");
     let tempdir = tempdir().unwrap();
     let dna = Dna::new();
-    let agent = AgentId::generate_fake(identity);
+    let keystore = Keystore::generate_fake(identity);
     let context = ContextBuilder::new()
-        .with_agent(agent)
+        .with_agent(keystore.agent_id(identity), keystore)
+        .expect("The agent of a keystore has its keys")
         .with_file_storage(tempdir.path().to_str().unwrap())
         .expect("Tempdir must be accessible")
         .spawn();
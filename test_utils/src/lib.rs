@@ -10,13 +10,13 @@ extern crate wabt;
 use holochain_container_api::{context_builder::ContextBuilder, error::HolochainResult, Holochain};
 use holochain_core::{
     action::Action,
+    agent::keystore::Keystore,
     context::Context,
-    logger::Logger,
+    logger::{LogLevel, Logger},
     signal::Signal,
 };
 use holochain_core_types::{
     cas::content::Address,
-    agent::AgentId,
     dna::{
         capabilities::{Capability, FnDeclaration, CapabilityType, CapabilityCall},
         entry_types::{EntryTypeDef, LinkedFrom, LinksTo},
@@ -169,7 +169,7 @@ pub struct TestLogger {
 }
 
 impl Logger for TestLogger {
-    fn log(&mut self, msg: String) {
+    fn log(&mut self, _level: LogLevel, msg: String) {
         self.log.push(msg);
     }
     fn dump(&self) -> String {
@@ -191,12 +191,14 @@ pub fn test_logger() -> Arc<Mutex<TestLogger>> {
 
 #[cfg_attr(tarpaulin, skip)]
 pub fn test_context_and_logger(agent_name: &str) -> (Arc<Context>, Arc<Mutex<TestLogger>>) {
-    let agent = AgentId::generate_fake(agent_name);
+    let keystore = Keystore::new_random();
+    let agent = keystore.agent_id(agent_name);
     let logger = test_logger();
     (
         Arc::new(
             ContextBuilder::new()
-                .with_agent(agent)
+                .with_agent(agent, keystore)
+                .expect("The agent of a keystore has its keys")
                 .with_logger(logger.clone())
                 .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
                 .expect("Tempdir must be accessible")
@@ -239,10 +241,12 @@ pub fn hc_setup_and_call_zome_fn(wasm_path: &str, fn_name: &str) -> HolochainRes
 
 /// create a test context and TestLogger pair so we can use the logger in assertions
 pub fn create_test_context(agent_name: &str) -> Arc<Context> {
-    let agent = AgentId::generate_fake(agent_name);
+    let keystore = Keystore::new_random();
+    let agent = keystore.agent_id(agent_name);
     Arc::new(
         ContextBuilder::new()
-            .with_agent(agent)
+            .with_agent(agent, keystore)
+            .expect("The agent of a keystore has its keys")
             .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
             .expect("Tempdir must be accessible")
             .spawn()
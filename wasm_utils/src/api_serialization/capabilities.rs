@@ -0,0 +1,10 @@
+use holochain_core_types::{cas::content::Address, error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function grant_capability() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct GrantCapabilityArgs {
+    /// name of a capability of the calling zome
+    pub cap_name: String,
+    /// agents that may use the token, must be empty for transferable capabilities
+    pub assignees: Vec<Address>,
+}
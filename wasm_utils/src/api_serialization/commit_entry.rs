@@ -0,0 +1,11 @@
+use holochain_core_types::{cas::content::Address, entry::Entry, error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function commit_entry() is invoked for an
+/// entry of an encrypted entry type that gets shared with other agents.
+/// Entries that are only committed get passed without this wrapper.
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct CommitEntryArgs {
+    pub entry: Entry,
+    /// agents besides the author that will be able to decrypt the entry
+    pub recipients: Vec<Address>,
+}
@@ -0,0 +1,10 @@
+use holochain_core_types::{error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function emit_signal() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct EmitSignalArgs {
+    /// name clients can tell the signals of the app apart by
+    pub name: String,
+    /// the signal's data as a JSON string
+    pub arguments: String,
+}
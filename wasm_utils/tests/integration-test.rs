@@ -13,7 +13,7 @@ extern crate tempfile;
 extern crate test_utils;
 
 use holochain_container_api::error::{HolochainInstanceError, HolochainResult};
-use holochain_core::logger::Logger;
+use holochain_core::logger::{LogLevel, Logger};
 use holochain_core_types::{
     error::{CoreError, HolochainError, RibosomeErrorCode},
     json::{default_try_from_json, JsonString, RawString},
@@ -28,7 +28,7 @@ pub struct TestLogger {
 }
 
 impl Logger for TestLogger {
-    fn log(&mut self, msg: String) {
+    fn log(&mut self, _level: LogLevel, msg: String) {
         self.log.push(msg);
     }
 